@@ -1,7 +1,7 @@
 /// Procedural macros for generating card themes and theme queries from CSS files.
 use proc_macro::TokenStream;
 use quote::quote;
-use std::{env, fs, path::PathBuf};
+use std::{env, fs, path::Path};
 use syn::LitStr;
 
 /// Builds a `CardTheme` enum from the themes found in assets/css/themes.
@@ -21,7 +21,23 @@ pub fn build_card_themes(_input: TokenStream) -> TokenStream {
     let arms = metas.iter().map(|m| {
         let ident = &m.variant_ident;
         let include = &m.include_lit;
-        quote! { CardTheme::#ident => include_str!(#include) }
+        match &m.base_include_lit {
+            Some(base) => quote! {
+                CardTheme::#ident => concat!(include_str!(#base), "\n", include_str!(#include))
+            },
+            None => quote! { CardTheme::#ident => include_str!(#include) },
+        }
+    });
+
+    let name_arms = metas.iter().map(|m| {
+        let ident = &m.variant_ident;
+        let rename = &m.rename_lit;
+        quote! { CardTheme::#ident => #rename }
+    });
+
+    let all_variants = metas.iter().map(|m| {
+        let ident = &m.variant_ident;
+        quote! { CardTheme::#ident }
     });
 
     let enum_doc = LitStr::new(
@@ -39,6 +55,16 @@ pub fn build_card_themes(_input: TokenStream) -> TokenStream {
             pub fn load_css(&self) -> &'static str {
                 match self { #( #arms, )* }
             }
+
+            #[doc = "Returns the snake_case theme name, as used in the `theme` query parameter."]
+            pub fn name(&self) -> &'static str {
+                match self { #( #name_arms, )* }
+            }
+
+            #[doc = "Returns every available theme."]
+            pub fn all() -> &'static [CardTheme] {
+                &[ #( #all_variants, )* ]
+            }
         }
     };
 
@@ -56,8 +82,7 @@ pub fn build_theme_query(_input: TokenStream) -> TokenStream {
     let variants = metas.iter().map(|m| {
         let ident = &m.variant_ident;
         let doc = &m.doc_lit;
-        let rename = &m.rename_lit;
-        quote! { #[doc = #doc] #[serde(rename = #rename)] #ident }
+        quote! { #[doc = #doc] #ident }
     });
 
     let arms = metas.iter().map(|m| {
@@ -65,16 +90,38 @@ pub fn build_theme_query(_input: TokenStream) -> TokenStream {
         quote! { ThemeQuery::#ident => CardTheme::#ident }
     });
 
+    let parse_arms = metas.iter().map(|m| {
+        let ident = &m.variant_ident;
+        let rename = &m.rename_lit;
+        quote! { #rename => Ok(ThemeQuery::#ident) }
+    });
+
+    let renames = metas.iter().map(|m| &m.rename_lit);
+
     let enum_doc = LitStr::new(
-        "ThemeQuery is generated from CSS files in assets/css/themes; query uses snake_case (kebab-case files).",
+        "ThemeQuery is generated from CSS files in assets/css/themes; query uses snake_case (kebab-case files), matched case-insensitively and ignoring surrounding whitespace.",
         proc_macro2::Span::call_site(),
     );
 
     let expanded = quote! {
         #[doc = #enum_doc]
-        #[derive(Debug, Deserialize)]
+        #[derive(Debug)]
         pub enum ThemeQuery { #( #variants, )* }
 
+        impl<'de> ::serde::Deserialize<'de> for ThemeQuery {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: ::serde::Deserializer<'de>,
+            {
+                const VARIANTS: &[&str] = &[ #( #renames, )* ];
+                let value = <String as ::serde::Deserialize>::deserialize(deserializer)?;
+                match value.trim().to_lowercase().as_str() {
+                    #( #parse_arms, )*
+                    _ => Err(<D::Error as ::serde::de::Error>::unknown_variant(&value, VARIANTS)),
+                }
+            }
+        }
+
         impl From<ThemeQuery> for CardTheme {
             fn from(t: ThemeQuery) -> Self {
                 match t { #( #arms, )* }
@@ -164,6 +211,19 @@ struct ThemeMeta {
     include_lit: LitStr,
     /// snake_case name used for serde(rename)
     rename_lit: LitStr,
+    /// Absolute path literal of the base stylesheet declared with `@extends`, if any
+    base_include_lit: Option<LitStr>,
+}
+
+/// Parses the `/* @extends <name> */` directive of a theme stylesheet, returning the base name.
+fn parse_extends(css: &str) -> Option<&str> {
+    css.split("/*")
+        .skip(1)
+        .filter_map(|comment| comment.split("*/").next())
+        .find_map(|comment| {
+            let (_, rest) = comment.split_once("@extends")?;
+            rest.split_whitespace().next()
+        })
 }
 
 /// Collects [ThemeMeta] from the assets/css/themes directory.
@@ -173,9 +233,53 @@ struct ThemeMeta {
 /// The generated enum variants will be in PascalCase (e.g., `DarkMode`).
 /// The serde rename will be in snake_case (e.g., `dark_mode`).
 /// The doc comments will be generated from the file names, converting kebab-case to title case (e.g., `Dark Mode`).
+///
+/// Files prefixed with `_` (e.g., `_base.css`) are shared base stylesheets rather than themes.
+/// A theme declaring `/* @extends base */` gets `_base.css` prepended to its CSS.
 fn collect_themes() -> Vec<ThemeMeta> {
     let manifest_dir = env::var("CARGO_MANIFEST_DIR").expect("CARGO_MANIFEST_DIR not set");
-    let themes_dir = PathBuf::from(manifest_dir).join("assets/css/themes");
+    let metas = collect_themes_in_dir(Path::new(&manifest_dir));
+    for meta in &metas {
+        check_required_classes(meta);
+    }
+    metas
+}
+
+/// Classes the cards render without any inline fallback, so every theme has to style them.
+const REQUIRED_THEME_CLASSES: [&str; 1] = [".progressBarBackground"];
+
+/// Fails the build when the theme (including its base stylesheet) misses a [REQUIRED_THEME_CLASSES].
+fn check_required_classes(meta: &ThemeMeta) {
+    let read = |lit: &LitStr| {
+        fs::read_to_string(lit.value())
+            .unwrap_or_else(|e| panic!("Failed to read {}: {e}", lit.value()))
+    };
+    let mut css = read(&meta.include_lit);
+    if let Some(base) = &meta.base_include_lit {
+        css.push_str(&read(base));
+    }
+
+    let missing = missing_classes(&css);
+    if !missing.is_empty() {
+        panic!(
+            "Theme {} is missing required classes: {}",
+            meta.include_lit.value(),
+            missing.join(", ")
+        );
+    }
+}
+
+/// Returns the [REQUIRED_THEME_CLASSES] not found in the `css`.
+fn missing_classes(css: &str) -> Vec<&'static str> {
+    REQUIRED_THEME_CLASSES
+        .into_iter()
+        .filter(|class| !css.contains(class))
+        .collect()
+}
+
+/// Collects [ThemeMeta] from the assets/css/themes directory under the given base directory.
+fn collect_themes_in_dir(base: &Path) -> Vec<ThemeMeta> {
+    let themes_dir = base.join("assets/css/themes");
 
     let mut out = Vec::new();
     let entries = fs::read_dir(&themes_dir).expect("Failed to read assets/css/themes");
@@ -185,8 +289,8 @@ fn collect_themes() -> Vec<ThemeMeta> {
             continue;
         }
         let stem = match path.file_stem().and_then(|s| s.to_str()) {
-            Some(s) => s,
-            None => continue,
+            Some(s) if !s.starts_with('_') => s,
+            _ => continue,
         };
 
         let variant = to_pascal_case(stem);
@@ -202,11 +306,26 @@ fn collect_themes() -> Vec<ThemeMeta> {
         let include_path = abs.to_string_lossy().to_string();
         let include_lit = LitStr::new(&include_path, proc_macro2::Span::call_site());
 
+        let css = fs::read_to_string(&path)
+            .unwrap_or_else(|e| panic!("Failed to read {}: {e}", path.display()));
+        let base_include_lit = parse_extends(&css).map(|name| {
+            let base_path = themes_dir.join(format!("_{name}.css"));
+            let abs = base_path.canonicalize().unwrap_or_else(|_| {
+                panic!(
+                    "Theme {} extends missing base {}",
+                    path.display(),
+                    base_path.display()
+                )
+            });
+            LitStr::new(&abs.to_string_lossy(), proc_macro2::Span::call_site())
+        });
+
         out.push(ThemeMeta {
             variant_ident,
             doc_lit,
             include_lit,
             rename_lit,
+            base_include_lit,
         });
     }
 
@@ -222,44 +341,6 @@ mod tests {
     use std::path::Path;
     use tempfile::tempdir;
 
-    // Test-only variant that scans a provided base directory instead of CARGO_MANIFEST_DIR
-    fn collect_themes_in_dir(base: &std::path::Path) -> Vec<ThemeMeta> {
-        let themes_dir = base.join("assets/css/themes");
-        let mut out = Vec::new();
-        let entries = fs::read_dir(&themes_dir).expect("Failed to read assets/css/themes");
-        for entry in entries.flatten() {
-            let path = entry.path();
-            if path.extension().and_then(|e| e.to_str()) != Some("css") {
-                continue;
-            }
-            let stem = match path.file_stem().and_then(|s| s.to_str()) {
-                Some(s) => s,
-                None => continue,
-            };
-
-            let variant = to_pascal_case(stem);
-            let variant_ident = syn::Ident::new(&variant, proc_macro2::Span::call_site());
-
-            let doc_text = to_title_from_stem(stem);
-            let doc_lit = LitStr::new(&doc_text, variant_ident.span());
-
-            let rename = stem.to_ascii_lowercase().replace('-', "_");
-            let rename_lit = LitStr::new(&rename, proc_macro2::Span::call_site());
-
-            let abs = path.canonicalize().unwrap_or(path.clone());
-            let include_path = abs.to_string_lossy().to_string();
-            let include_lit = LitStr::new(&include_path, proc_macro2::Span::call_site());
-
-            out.push(ThemeMeta {
-                variant_ident,
-                doc_lit,
-                include_lit,
-                rename_lit,
-            });
-        }
-        out
-    }
-
     #[test]
     fn fn_to_pascal_case() {
         assert_eq!(to_pascal_case("transparent-blue"), "TransparentBlue");
@@ -303,5 +384,74 @@ mod tests {
             )
         );
         assert_eq!(m.rename_lit.value(), "transparent_blue");
+        assert!(m.base_include_lit.is_none());
+    }
+
+    #[test]
+    fn fn_missing_classes() {
+        assert_eq!(missing_classes(".title {}"), [".progressBarBackground"]);
+        assert!(missing_classes(".progressBarBackground { fill: #eee; }").is_empty());
+    }
+
+    #[test]
+    fn fn_parse_extends() {
+        assert_eq!(
+            parse_extends("/* @extends base */\n.title {}"),
+            Some("base")
+        );
+        assert_eq!(
+            parse_extends("/**\n * Dark theme.\n * @extends shared\n */"),
+            Some("shared")
+        );
+        assert_eq!(parse_extends("/* Dark theme. */\n.title {}"), None);
+        // Only comments count
+        assert_eq!(parse_extends(".title { content: '@extends base'; }"), None);
+    }
+
+    #[test]
+    fn fn_collect_themes_with_extends() {
+        let tmp = tempdir().expect("tempdir");
+        let base = tmp.path();
+        let themes_dir = base.join("assets/css/themes");
+        fs::create_dir_all(&themes_dir).expect("mkdir -p assets/css/themes");
+
+        fs::write(themes_dir.join("_base.css"), ".label { fill: #000; }").unwrap();
+        fs::write(
+            themes_dir.join("ocean.css"),
+            "/* @extends base */\n.title { fill: #00f; }",
+        )
+        .unwrap();
+        fs::write(themes_dir.join("plain.css"), ".title { fill: #fff; }").unwrap();
+
+        let mut metas = collect_themes_in_dir(base);
+        metas.sort_by_key(|m| m.variant_ident.to_string());
+
+        // The base stylesheet is not a theme on its own
+        let names: Vec<_> = metas.iter().map(|m| m.variant_ident.to_string()).collect();
+        assert_eq!(names, ["Ocean", "Plain"]);
+
+        let ocean_base = metas[0]
+            .base_include_lit
+            .as_ref()
+            .expect("ocean extends base");
+        assert!(
+            ocean_base.value().ends_with(
+                Path::new("assets/css/themes/_base.css")
+                    .to_string_lossy()
+                    .as_ref()
+            )
+        );
+        assert!(metas[1].base_include_lit.is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "extends missing base")]
+    fn fn_collect_themes_with_missing_base() {
+        let tmp = tempdir().expect("tempdir");
+        let themes_dir = tmp.path().join("assets/css/themes");
+        fs::create_dir_all(&themes_dir).expect("mkdir -p assets/css/themes");
+        fs::write(themes_dir.join("ocean.css"), "/* @extends base */").unwrap();
+
+        collect_themes_in_dir(tmp.path());
     }
 }
@@ -5,10 +5,10 @@ use std::collections::BTreeMap;
 use std::fs;
 use std::path::Path;
 
-use github_statcrab::cards::card::{CardSettings, CardTheme};
-use github_statcrab::cards::error_card::ErrorCard;
-use github_statcrab::cards::langs_card::{LangsCard, LanguageStat, LayoutType};
-use github_statcrab::cards::stats_card::StatsCard;
+use github_statcrab::cards::card::CardTheme;
+use github_statcrab::cards::examples;
+use github_statcrab::cards::langs_card::LayoutType;
+use github_statcrab::cards::raster;
 
 // Generate the theme parser function dynamically from CSS files
 use card_theme_macros::build_theme_parser;
@@ -18,6 +18,11 @@ build_theme_parser!();
 const README_PATH: &str = "assets/css/themes/README.md";
 const EXAMPLES_DIR: &str = "assets/css/themes/examples";
 
+/// Flag writing a PNG of each example next to its SVG
+const PNG_FLAG: &str = "--png";
+/// Size of the exported PNGs relative to the SVG examples
+const PNG_SCALE: f32 = 2.0;
+
 /// Converts a kebab-case or snake_case string to PascalCase (same logic as the macro).
 fn to_pascal_case(s: &str) -> String {
     let mut out = String::new();
@@ -48,6 +53,9 @@ fn parse_card_theme_from_filename(filename_stem: &str) -> Option<CardTheme> {
 }
 
 fn main() -> Result<()> {
+    // The README only links the SVGs, the PNGs are for a quick look
+    let export_pngs = std::env::args().skip(1).any(|arg| arg == PNG_FLAG);
+
     // Create examples directory
     fs::create_dir_all(EXAMPLES_DIR).context("Failed to create examples directory")?;
 
@@ -68,57 +76,56 @@ fn main() -> Result<()> {
 
     for (theme_name, theme_variant) in &themes {
         // Generate regular Stats Card example
-        let stats_svg = generate_stats_card_example(theme_variant.clone())?;
+        let stats_svg = examples::stats_example(theme_variant.clone())?;
         let stats_file = format!("stats-card-{}.svg", theme_name);
-        fs::write(Path::new(EXAMPLES_DIR).join(&stats_file), &stats_svg)
+        write_example(&stats_file, &stats_svg, export_pngs)
             .context("Failed to write stats card SVG")?;
         stats_examples.insert(theme_name.clone(), stats_file);
 
         // Generate regular Langs Card example (vertical)
-        let langs_svg = generate_langs_card_example(theme_variant.clone())?;
+        let langs_svg = examples::langs_example(theme_variant.clone(), LayoutType::Vertical)?;
         let langs_file = format!("langs-card-{}.svg", theme_name);
-        fs::write(Path::new(EXAMPLES_DIR).join(&langs_file), &langs_svg)
+        write_example(&langs_file, &langs_svg, export_pngs)
             .context("Failed to write langs card SVG")?;
         langs_examples.insert(theme_name.clone(), langs_file);
 
         // Generate regular Langs Card example (horizontal)
-        let langs_horizontal_svg = generate_langs_card_horizontal_example(theme_variant.clone())?;
+        let langs_horizontal_svg =
+            examples::langs_example(theme_variant.clone(), LayoutType::Horizontal)?;
         let langs_horizontal_file = format!("langs-card-{}-horizontal.svg", theme_name);
-        fs::write(
-            Path::new(EXAMPLES_DIR).join(&langs_horizontal_file),
-            &langs_horizontal_svg,
-        )
-        .context("Failed to write langs card horizontal SVG")?;
+        write_example(&langs_horizontal_file, &langs_horizontal_svg, export_pngs)
+            .context("Failed to write langs card horizontal SVG")?;
         langs_horizontal_examples.insert(theme_name.clone(), langs_horizontal_file);
 
         // Generate transparent Stats Card example (hide_background & hide_background_stroke)
-        let stats_transparent_svg = generate_stats_card_example_transparent(theme_variant.clone())?;
+        let stats_transparent_svg =
+            examples::stats_example_with(examples::example_settings(theme_variant.clone(), true))?;
         let stats_transparent_file = format!("stats-card-{}-transparent.svg", theme_name);
-        fs::write(
-            Path::new(EXAMPLES_DIR).join(&stats_transparent_file),
-            &stats_transparent_svg,
-        )
-        .context("Failed to write transparent stats card SVG")?;
+        write_example(&stats_transparent_file, &stats_transparent_svg, export_pngs)
+            .context("Failed to write transparent stats card SVG")?;
         stats_transparent_examples.insert(theme_name.clone(), stats_transparent_file);
 
         // Generate transparent Langs Card example (vertical, hide_background & hide_background_stroke)
-        let langs_transparent_svg = generate_langs_card_example_transparent(theme_variant.clone())?;
+        let langs_transparent_svg = examples::langs_example_with(
+            examples::example_settings(theme_variant.clone(), true),
+            LayoutType::Vertical,
+        )?;
         let langs_transparent_file = format!("langs-card-{}-transparent.svg", theme_name);
-        fs::write(
-            Path::new(EXAMPLES_DIR).join(&langs_transparent_file),
-            &langs_transparent_svg,
-        )
-        .context("Failed to write transparent langs card SVG")?;
+        write_example(&langs_transparent_file, &langs_transparent_svg, export_pngs)
+            .context("Failed to write transparent langs card SVG")?;
         langs_transparent_examples.insert(theme_name.clone(), langs_transparent_file);
 
         // Generate transparent Langs Card example (horizontal, hide_background & hide_background_stroke)
-        let langs_horizontal_transparent_svg =
-            generate_langs_card_horizontal_example_transparent(theme_variant.clone())?;
+        let langs_horizontal_transparent_svg = examples::langs_example_with(
+            examples::example_settings(theme_variant.clone(), true),
+            LayoutType::Horizontal,
+        )?;
         let langs_horizontal_transparent_file =
             format!("langs-card-{}-horizontal-transparent.svg", theme_name);
-        fs::write(
-            Path::new(EXAMPLES_DIR).join(&langs_horizontal_transparent_file),
+        write_example(
+            &langs_horizontal_transparent_file,
             &langs_horizontal_transparent_svg,
+            export_pngs,
         )
         .context("Failed to write transparent langs card horizontal SVG")?;
         langs_horizontal_transparent_examples
@@ -126,21 +133,15 @@ fn main() -> Result<()> {
     }
 
     // Generate Error Card examples (always use default theme)
-    let error_short_svg = generate_error_card_short_example()?;
+    let error_short_svg = examples::error_example(examples::SHORT_ERROR_MESSAGE)?;
     let error_short_file = "error-card-short.svg".to_string();
-    fs::write(
-        Path::new(EXAMPLES_DIR).join(&error_short_file),
-        &error_short_svg,
-    )
-    .context("Failed to write error card short SVG")?;
+    write_example(&error_short_file, &error_short_svg, export_pngs)
+        .context("Failed to write error card short SVG")?;
 
-    let error_long_svg = generate_error_card_long_example()?;
+    let error_long_svg = examples::error_example(examples::LONG_ERROR_MESSAGE)?;
     let error_long_file = "error-card-long.svg".to_string();
-    fs::write(
-        Path::new(EXAMPLES_DIR).join(&error_long_file),
-        &error_long_svg,
-    )
-    .context("Failed to write error card long SVG")?;
+    write_example(&error_long_file, &error_long_svg, export_pngs)
+        .context("Failed to write error card long SVG")?;
 
     // Generate new README content with fixed intro
     let examples = ThemeExamples {
@@ -162,11 +163,30 @@ fn main() -> Result<()> {
         "Generated {} theme examples (stats + langs vertical + langs horizontal, regular + transparent)",
         themes.len() * 3
     );
+    if export_pngs {
+        println!("Exported the PNG of every example to {}", EXAMPLES_DIR);
+    }
     println!("Updated {}", README_PATH);
 
     Ok(())
 }
 
+/// Writes the `svg` example to `file` in the examples directory, and its PNG next to it
+/// when `export_png` is set.
+fn write_example(file: &str, svg: &str, export_png: bool) -> Result<()> {
+    let path = Path::new(EXAMPLES_DIR).join(file);
+    fs::write(&path, svg)?;
+    if export_png {
+        fs::write(path.with_extension("png"), example_png(svg)?)?;
+    }
+    Ok(())
+}
+
+/// Rasterizes an example card to PNG, the way the cards are served with `format=png`.
+fn example_png(svg: &str) -> Result<Vec<u8>> {
+    raster::svg_to_png(svg, PNG_SCALE).context("Failed to rasterize the example to PNG")
+}
+
 /// Discovers themes by scanning the CSS files in assets/css/themes
 fn discover_themes() -> Result<BTreeMap<String, CardTheme>> {
     let themes_dir = Path::new("assets/css/themes");
@@ -187,6 +207,11 @@ fn discover_themes() -> Result<BTreeMap<String, CardTheme>> {
             .and_then(|s| s.to_str())
             .context("Failed to get theme file stem")?;
 
+        // Shared base stylesheets (e.g. `_base.css`) are not themes
+        if stem.starts_with('_') {
+            continue;
+        }
+
         // Convert kebab-case filename to snake_case API name
         let api_name = stem.to_ascii_lowercase().replace('-', "_");
 
@@ -206,276 +231,6 @@ fn discover_themes() -> Result<BTreeMap<String, CardTheme>> {
     Ok(themes)
 }
 
-/// Generates a Stats Card example with dummy data
-fn generate_stats_card_example(theme: CardTheme) -> Result<String> {
-    let settings = CardSettings {
-        offset_x: 12,
-        offset_y: 12,
-        theme,
-        hide_title: false,
-        hide_background: false,
-        hide_background_stroke: false,
-    };
-
-    let stats_card = StatsCard {
-        card_settings: settings,
-        username: "octocat".to_string(),
-        stars_count: Some(1234),
-        commits_ytd_count: Some(567),
-        issues_count: Some(89),
-        pull_requests_count: Some(123),
-        merge_requests_count: Some(45),
-        reviews_count: Some(67),
-        started_discussions_count: Some(12),
-        answered_discussions_count: Some(34),
-    };
-
-    Ok(stats_card.render())
-}
-
-/// Generates a Langs Card example with dummy data (vertical layout)
-fn generate_langs_card_example(theme: CardTheme) -> Result<String> {
-    let settings = CardSettings {
-        offset_x: 12,
-        offset_y: 12,
-        theme,
-        hide_title: false,
-        hide_background: false,
-        hide_background_stroke: false,
-    };
-
-    let dummy_stats = vec![
-        LanguageStat {
-            name: "Rust".to_string(),
-            size_bytes: 45000,
-            repo_count: 15,
-        },
-        LanguageStat {
-            name: "TypeScript".to_string(),
-            size_bytes: 35000,
-            repo_count: 12,
-        },
-        LanguageStat {
-            name: "JavaScript".to_string(),
-            size_bytes: 25000,
-            repo_count: 8,
-        },
-        LanguageStat {
-            name: "Python".to_string(),
-            size_bytes: 15000,
-            repo_count: 6,
-        },
-        LanguageStat {
-            name: "Go".to_string(),
-            size_bytes: 10000,
-            repo_count: 4,
-        },
-    ];
-
-    let langs_card = LangsCard {
-        card_settings: settings,
-        layout: LayoutType::Vertical,
-        stats: dummy_stats,
-        size_weight: Some(1.0),
-        count_weight: Some(0.0),
-        max_languages: Some(5),
-    };
-
-    Ok(langs_card.render())
-}
-
-/// Generates a Langs Card example with dummy data (horizontal layout)
-fn generate_langs_card_horizontal_example(theme: CardTheme) -> Result<String> {
-    let settings = CardSettings {
-        offset_x: 12,
-        offset_y: 12,
-        theme,
-        hide_title: false,
-        hide_background: false,
-        hide_background_stroke: false,
-    };
-
-    let dummy_stats = vec![
-        LanguageStat {
-            name: "Rust".to_string(),
-            size_bytes: 45000,
-            repo_count: 15,
-        },
-        LanguageStat {
-            name: "TypeScript".to_string(),
-            size_bytes: 35000,
-            repo_count: 12,
-        },
-        LanguageStat {
-            name: "JavaScript".to_string(),
-            size_bytes: 25000,
-            repo_count: 8,
-        },
-        LanguageStat {
-            name: "Python".to_string(),
-            size_bytes: 15000,
-            repo_count: 6,
-        },
-        LanguageStat {
-            name: "Go".to_string(),
-            size_bytes: 10000,
-            repo_count: 4,
-        },
-    ];
-
-    let langs_card = LangsCard {
-        card_settings: settings,
-        layout: LayoutType::Horizontal,
-        stats: dummy_stats,
-        size_weight: Some(1.0),
-        count_weight: Some(0.0),
-        max_languages: Some(5),
-    };
-
-    Ok(langs_card.render())
-}
-
-/// Generates a Stats Card example with transparent background (hide_background & hide_background_stroke enabled)
-fn generate_stats_card_example_transparent(theme: CardTheme) -> Result<String> {
-    let settings = CardSettings {
-        offset_x: 12,
-        offset_y: 12,
-        theme,
-        hide_title: false,
-        hide_background: true,
-        hide_background_stroke: true,
-    };
-
-    let stats_card = StatsCard {
-        card_settings: settings,
-        username: "octocat".to_string(),
-        stars_count: Some(1234),
-        commits_ytd_count: Some(567),
-        issues_count: Some(89),
-        pull_requests_count: Some(123),
-        merge_requests_count: Some(45),
-        reviews_count: Some(67),
-        started_discussions_count: Some(12),
-        answered_discussions_count: Some(34),
-    };
-
-    Ok(stats_card.render())
-}
-
-/// Generates a Langs Card example with transparent background (hide_background & hide_background_stroke enabled) - vertical layout
-fn generate_langs_card_example_transparent(theme: CardTheme) -> Result<String> {
-    let settings = CardSettings {
-        offset_x: 12,
-        offset_y: 12,
-        theme,
-        hide_title: false,
-        hide_background: true,
-        hide_background_stroke: true,
-    };
-
-    let dummy_stats = vec![
-        LanguageStat {
-            name: "Rust".to_string(),
-            size_bytes: 45000,
-            repo_count: 15,
-        },
-        LanguageStat {
-            name: "TypeScript".to_string(),
-            size_bytes: 35000,
-            repo_count: 12,
-        },
-        LanguageStat {
-            name: "JavaScript".to_string(),
-            size_bytes: 25000,
-            repo_count: 8,
-        },
-        LanguageStat {
-            name: "Python".to_string(),
-            size_bytes: 15000,
-            repo_count: 6,
-        },
-        LanguageStat {
-            name: "Go".to_string(),
-            size_bytes: 10000,
-            repo_count: 4,
-        },
-    ];
-
-    let langs_card = LangsCard {
-        card_settings: settings,
-        layout: LayoutType::Vertical,
-        stats: dummy_stats,
-        size_weight: Some(1.0),
-        count_weight: Some(0.0),
-        max_languages: Some(5),
-    };
-
-    Ok(langs_card.render())
-}
-
-/// Generates a Langs Card example with transparent background (hide_background & hide_background_stroke enabled) - horizontal layout
-fn generate_langs_card_horizontal_example_transparent(theme: CardTheme) -> Result<String> {
-    let settings = CardSettings {
-        offset_x: 12,
-        offset_y: 12,
-        theme,
-        hide_title: false,
-        hide_background: true,
-        hide_background_stroke: true,
-    };
-
-    let dummy_stats = vec![
-        LanguageStat {
-            name: "Rust".to_string(),
-            size_bytes: 45000,
-            repo_count: 15,
-        },
-        LanguageStat {
-            name: "TypeScript".to_string(),
-            size_bytes: 35000,
-            repo_count: 12,
-        },
-        LanguageStat {
-            name: "JavaScript".to_string(),
-            size_bytes: 25000,
-            repo_count: 8,
-        },
-        LanguageStat {
-            name: "Python".to_string(),
-            size_bytes: 15000,
-            repo_count: 6,
-        },
-        LanguageStat {
-            name: "Go".to_string(),
-            size_bytes: 10000,
-            repo_count: 4,
-        },
-    ];
-
-    let langs_card = LangsCard {
-        card_settings: settings,
-        layout: LayoutType::Horizontal,
-        stats: dummy_stats,
-        size_weight: Some(1.0),
-        count_weight: Some(0.0),
-        max_languages: Some(5),
-    };
-
-    Ok(langs_card.render())
-}
-
-/// Generates an Error Card example with a short message
-fn generate_error_card_short_example() -> Result<String> {
-    let error_card = ErrorCard::new("Invalid username provided".to_string());
-    Ok(error_card.render())
-}
-
-/// Generates an Error Card example with a long message that wraps to multiple lines
-fn generate_error_card_long_example() -> Result<String> {
-    let error_card = ErrorCard::new("The GitHub API returned an error when trying to fetch user statistics. This might be due to rate limiting or an invalid username. Please check your configuration and try again.".to_string());
-    Ok(error_card.render())
-}
-
 /// Structure to hold all theme examples for README generation
 struct ThemeExamples<'a> {
     stats_examples: &'a BTreeMap<String, String>,
@@ -496,6 +251,7 @@ fn generate_readme_content(examples: ThemeExamples) -> Result<String> {
     content.push_str("# How to add new themes?\n\n");
     content.push_str("If you want to contribute a new theme, please add a new CSS file in the `assets/css/themes` directory. The file name should be in kebab-case (e.g., `new-theme.css`). The macro will automatically generate the necessary Rust code for the new theme based on the file name.\n\n");
     content.push_str("The CSS classes defined in the theme file should follow the naming convention used in the existing themes.\n\n");
+    content.push_str("Rules shared by several themes can live in a `_`-prefixed base file (e.g., `_base.css`), which is not a theme on its own. A theme starting with a `/* @extends base */` comment gets `_base.css` prepended to its CSS, so it only needs its own overrides.\n\n");
     content.push_str("> [!NOTE]  \n");
     content.push_str("> While you can use CSS for styling, keep in mind that you are working with SVG elements. This means that some CSS properties may not work as expected.\n\n");
     content.push_str("The **Transparent** column shows theme variants with `hide_background=true` and `hide_background_stroke=true` options enabled, removing the card background for integration into custom layouts.\n\n");
@@ -575,16 +331,11 @@ mod tests {
     use tempfile::tempdir;
 
     #[test]
-    fn test_generate_stats_card_example() {
-        let result = generate_stats_card_example(CardTheme::TransparentBlue);
-        assert!(result.is_ok());
-
-        let svg = result.unwrap();
-        assert!(svg.contains("<svg"));
-        assert!(svg.contains("</svg>"));
-        assert!(svg.contains("octocat"));
-        assert!(svg.contains("1.2k")); // stars count formatted
-        assert!(svg.contains("567")); // commits count
+    fn test_example_png_of_a_theme() {
+        let svg = examples::stats_example(CardTheme::Dark).unwrap();
+        let png = example_png(&svg).unwrap();
+        assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+        assert!(png.len() > 8);
     }
 
     #[test]
@@ -687,6 +438,8 @@ mod tests {
             .expect("Failed to write test file");
         fs::write(themes_dir.join("not-css.txt"), "not a css file")
             .expect("Failed to write test file");
+        fs::write(themes_dir.join("_base.css"), "/* shared rules */")
+            .expect("Failed to write test file");
 
         // Temporarily change working directory for the test
         let original_dir = std::env::current_dir().expect("Failed to get current dir");
@@ -729,19 +482,6 @@ mod tests {
         );
     }
 
-    #[test]
-    fn test_generate_langs_card_example() {
-        let result = generate_langs_card_example(CardTheme::Dark);
-        assert!(result.is_ok());
-
-        let svg = result.unwrap();
-        assert!(svg.contains("<svg"));
-        assert!(svg.contains("</svg>"));
-        assert!(svg.contains("TypeScript"));
-        assert!(svg.contains("JavaScript"));
-        assert!(svg.contains("Rust"));
-    }
-
     #[test]
     fn test_generate_readme_content_structure() {
         let mut stats_examples = BTreeMap::new();
@@ -825,20 +565,4 @@ mod tests {
         assert!(content.contains("| `dark` | ![dark](examples/stats-card-dark.svg) | ![dark transparent](examples/stats-card-dark-transparent.svg) |"));
         assert!(content.contains("| `light` | ![light](examples/langs-card-light.svg) | ![light transparent](examples/langs-card-light-transparent.svg) |"));
     }
-
-    #[test]
-    fn test_langs_card_example_contains_expected_languages() {
-        let svg = generate_langs_card_example(CardTheme::Monokai).unwrap();
-
-        // Should contain all the dummy programming languages
-        assert!(svg.contains("TypeScript"));
-        assert!(svg.contains("JavaScript"));
-        assert!(svg.contains("Rust"));
-        assert!(svg.contains("Python"));
-        assert!(svg.contains("Go"));
-
-        // Should be valid SVG
-        assert!(svg.starts_with("<svg"));
-        assert!(svg.ends_with("</svg>\n"));
-    }
 }
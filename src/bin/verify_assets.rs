@@ -0,0 +1,227 @@
+#![cfg(feature = "verify-assets")]
+
+use anyhow::{Context, Result};
+use quick_xml::Reader;
+use quick_xml::events::Event;
+use std::fs;
+use std::path::Path;
+
+use github_statcrab::cards::card::{CardError, CardTheme};
+use github_statcrab::cards::examples;
+use github_statcrab::cards::langs_card::LayoutType;
+
+// Assets directory inside the repository
+const ASSETS_DIR: &str = "assets";
+
+// Classes every theme has to style
+const REQUIRED_THEME_CLASSES: [&str; 5] = [
+    ".title",
+    ".label",
+    ".icon",
+    ".background",
+    ".progressBarBackground",
+];
+
+fn main() -> Result<()> {
+    let failures = verify(Path::new(ASSETS_DIR))?;
+
+    if failures.is_empty() {
+        println!(
+            "Verified {} themes, stat icons and language colors",
+            CardTheme::all().len()
+        );
+        return Ok(());
+    }
+
+    for failure in &failures {
+        eprintln!("FAIL: {failure}");
+    }
+    anyhow::bail!("{} asset check(s) failed", failures.len())
+}
+
+/// Runs every asset check and returns the list of failures.
+/// An `Err` is only returned when the assets directory itself can't be read.
+fn verify(assets_dir: &Path) -> Result<Vec<String>> {
+    let mut failures = Vec::new();
+
+    for theme in CardTheme::all() {
+        if let Err(e) = check_css(theme.load_css()) {
+            failures.push(format!("theme {}: {e}", theme.name()));
+        }
+        for (card, svg) in render_samples(theme.clone()) {
+            if let Err(e) = svg
+                .map_err(|e| e.to_string())
+                .and_then(|svg| check_svg(&svg))
+            {
+                failures.push(format!("theme {}, {card}: {e}", theme.name()));
+            }
+        }
+    }
+
+    let error_card = examples::error_example(examples::SHORT_ERROR_MESSAGE);
+    if let Err(e) = error_card
+        .map_err(|e| e.to_string())
+        .and_then(|svg| check_svg(&svg))
+    {
+        failures.push(format!("error card: {e}"));
+    }
+
+    let colors_path = assets_dir.join("configs/language-colors.json");
+    let colors = fs::read_to_string(&colors_path)
+        .with_context(|| format!("Reading {}", colors_path.display()))?;
+    failures.extend(
+        check_language_colors(&colors)
+            .into_iter()
+            .map(|e| format!("language colors: {e}")),
+    );
+
+    let icons_dir = assets_dir.join("icons");
+    let entries =
+        fs::read_dir(&icons_dir).with_context(|| format!("Reading {}", icons_dir.display()))?;
+    for entry in entries {
+        let path = entry.context("Failed to read directory entry")?.path();
+        if path.extension().and_then(|s| s.to_str()) != Some("svg") {
+            continue;
+        }
+        let result = fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|svg| check_svg(&svg));
+        if let Err(e) = result {
+            failures.push(format!("icon {}: {e}", path.display()));
+        }
+    }
+
+    Ok(failures)
+}
+
+/// Renders a sample of each card type with dummy data for the given theme
+fn render_samples(theme: CardTheme) -> Vec<(&'static str, Result<String, CardError>)> {
+    vec![
+        ("stats card", examples::stats_example(theme.clone())),
+        (
+            "langs card (vertical)",
+            examples::langs_example(theme.clone(), LayoutType::Vertical),
+        ),
+        (
+            "langs card (horizontal)",
+            examples::langs_example(theme.clone(), LayoutType::Horizontal),
+        ),
+        (
+            "langs card (donut)",
+            examples::langs_example(theme, LayoutType::Donut),
+        ),
+    ]
+}
+
+/// Checks that a theme stylesheet has balanced rules and styles every required class
+fn check_css(css: &str) -> Result<(), String> {
+    let mut depth = 0i32;
+    for ch in css.chars() {
+        match ch {
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            _ => {}
+        }
+        if depth < 0 {
+            return Err("unexpected '}'".to_string());
+        }
+    }
+    if depth != 0 {
+        return Err("unclosed '{'".to_string());
+    }
+
+    let missing: Vec<&str> = REQUIRED_THEME_CLASSES
+        .into_iter()
+        .filter(|class| !css.contains(class))
+        .collect();
+    if !missing.is_empty() {
+        return Err(format!("missing classes: {}", missing.join(", ")));
+    }
+
+    Ok(())
+}
+
+/// Checks that the document is a well-formed SVG
+fn check_svg(svg: &str) -> Result<(), String> {
+    let mut reader = Reader::from_str(svg);
+    let mut found_svg = false;
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(ref e)) if e.name().as_ref() == b"svg" => found_svg = true,
+            Ok(Event::Eof) => break,
+            Ok(_) => (),
+            Err(e) => return Err(format!("invalid XML: {e}")),
+        }
+    }
+
+    if !found_svg {
+        return Err("no <svg> root element".to_string());
+    }
+    Ok(())
+}
+
+/// Checks that the language colors JSON maps names to hex colors
+fn check_language_colors(json: &str) -> Vec<String> {
+    let colors: serde_json::Map<String, serde_json::Value> = match serde_json::from_str(json) {
+        Ok(colors) => colors,
+        Err(e) => return vec![format!("invalid JSON: {e}")],
+    };
+
+    colors
+        .iter()
+        .filter(|(_, color)| !color.as_str().is_some_and(is_hex_color))
+        .map(|(name, color)| format!("{name} has an invalid color {color}"))
+        .collect()
+}
+
+fn is_hex_color(color: &str) -> bool {
+    color
+        .strip_prefix('#')
+        .is_some_and(|hex| matches!(hex.len(), 3 | 6) && hex.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verify_repository_assets() {
+        let assets_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join(ASSETS_DIR);
+        let failures = verify(&assets_dir).expect("assets directory should be readable");
+        assert!(failures.is_empty(), "Asset check failures: {failures:?}");
+    }
+
+    #[test]
+    fn test_check_css() {
+        let css = REQUIRED_THEME_CLASSES
+            .iter()
+            .map(|class| format!("{class} {{ fill: #fff; }}"))
+            .collect::<String>();
+        assert!(check_css(&css).is_ok());
+
+        assert!(check_css(&format!("{css} .title {{ fill: #fff;")).is_err());
+        assert!(check_css(&format!("{css} }}")).is_err());
+        assert!(
+            check_css(".title { fill: #fff; }")
+                .unwrap_err()
+                .contains(".background")
+        );
+    }
+
+    #[test]
+    fn test_check_svg() {
+        assert!(check_svg("<svg xmlns=\"http://www.w3.org/2000/svg\"></svg>").is_ok());
+        assert!(check_svg("<svg><g></svg>").is_err());
+        assert!(check_svg("Failed to render card").is_err());
+    }
+
+    #[test]
+    fn test_check_language_colors() {
+        assert!(check_language_colors(r##"{"Rust": "#dea584", "C": "#555"}"##).is_empty());
+
+        let failures = check_language_colors(r##"{"Rust": "dea584", "Go": null}"##);
+        assert_eq!(failures.len(), 2);
+
+        assert_eq!(check_language_colors("{not json").len(), 1);
+    }
+}
@@ -0,0 +1,187 @@
+//! Accent color of a card derived from the dominant color of an image, e.g. the
+//! avatar of the user.
+
+use std::sync::Arc;
+
+use resvg::{tiny_skia, usvg};
+
+/// Side (in pixels) the image is downscaled to before its colors are counted.
+const SAMPLE_SIZE: u32 = 24;
+
+/// Pixels more transparent than this are left out, e.g. the corners of round avatars.
+const MIN_ALPHA: u8 = 128;
+
+/// Weight of a pixel without saturation, grays still win images with no color at all.
+const GRAY_WEIGHT: f64 = 0.1;
+
+/// Dominant color of an image (PNG, JPEG, GIF or WebP) as `#rrggbb`, `None` when it
+/// can't be decoded or is fully transparent.
+pub fn dominant_color(image: &[u8]) -> Option<String> {
+    let [r, g, b] = dominant_pixel_color(&downscale(image)?)?;
+    Some(format!("#{r:02x}{g:02x}{b:02x}"))
+}
+
+/// CSS giving the title and the icons of a card the accent `color`, appended after the
+/// theme style.
+pub fn accent_css(color: &str) -> String {
+    format!(".title, .icon {{ fill: {color}; }}")
+}
+
+/// Draws the image stretched over a [SAMPLE_SIZE] square, through resvg which does
+/// the decoding of the raster formats.
+fn downscale(image: &[u8]) -> Option<tiny_skia::Pixmap> {
+    let kind = if image.starts_with(b"\x89PNG") {
+        usvg::ImageKind::PNG
+    } else if image.starts_with(b"\xff\xd8\xff") {
+        usvg::ImageKind::JPEG
+    } else if image.starts_with(b"GIF8") {
+        usvg::ImageKind::GIF
+    } else if image.starts_with(b"RIFF") && image.get(8..12) == Some(b"WEBP") {
+        usvg::ImageKind::WEBP
+    } else {
+        return None;
+    };
+    let data = Arc::new(image.to_vec());
+    let options = usvg::Options {
+        image_href_resolver: usvg::ImageHrefResolver {
+            resolve_data: Box::new(|_, _, _| None),
+            resolve_string: Box::new(move |href, _| {
+                (href == "image").then(|| kind(Arc::clone(&data)))
+            }),
+        },
+        ..Default::default()
+    };
+    let svg = format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{SAMPLE_SIZE}" height="{SAMPLE_SIZE}"><image href="image" width="{SAMPLE_SIZE}" height="{SAMPLE_SIZE}" preserveAspectRatio="none"/></svg>"#
+    );
+    let tree = usvg::Tree::from_str(&svg, &options).ok()?;
+
+    let mut pixmap = tiny_skia::Pixmap::new(SAMPLE_SIZE, SAMPLE_SIZE)?;
+    resvg::render(
+        &tree,
+        tiny_skia::Transform::identity(),
+        &mut pixmap.as_mut(),
+    );
+    Some(pixmap)
+}
+
+/// Most common color of the pixmap, its pixels grouped by their 4 high bits per
+/// channel and weighted by their saturation, so a colored subject wins over a white
+/// or gray background. The color is the mean of the pixels of the heaviest group.
+fn dominant_pixel_color(pixmap: &tiny_skia::Pixmap) -> Option<[u8; 3]> {
+    // Total weight and weighted sums of the channels, by group
+    let mut groups = vec![[0.0f64; 4]; 16 * 16 * 16];
+    for pixel in pixmap.pixels() {
+        let color = pixel.demultiply();
+        if color.alpha() < MIN_ALPHA {
+            continue;
+        }
+        let [r, g, b] = [color.red(), color.green(), color.blue()];
+        let (max, min) = (r.max(g).max(b), r.min(g).min(b));
+        let saturation = if max == 0 {
+            0.0
+        } else {
+            f64::from(max - min) / f64::from(max)
+        };
+        let weight = GRAY_WEIGHT + saturation;
+
+        let group = (usize::from(r >> 4) << 8) | (usize::from(g >> 4) << 4) | usize::from(b >> 4);
+        let [total, sum_r, sum_g, sum_b] = &mut groups[group];
+        *total += weight;
+        *sum_r += weight * f64::from(r);
+        *sum_g += weight * f64::from(g);
+        *sum_b += weight * f64::from(b);
+    }
+
+    let [total, sum_r, sum_g, sum_b] = groups
+        .into_iter()
+        .filter(|[total, ..]| *total > 0.0)
+        .max_by(|a, b| a[0].total_cmp(&b[0]))?;
+    Some([sum_r, sum_g, sum_b].map(|sum| (sum / total).round() as u8))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A PNG of `size` pixels split into vertical bands of the given colors and
+    /// relative widths.
+    fn banded_png(size: u32, bands: &[(tiny_skia::Color, u32)]) -> Vec<u8> {
+        let mut pixmap = tiny_skia::Pixmap::new(size, size).unwrap();
+        let total: u32 = bands.iter().map(|(_, width)| width).sum();
+        let mut x = 0.0;
+        for (color, width) in bands {
+            let band_width = (size * width) as f32 / total as f32;
+            let rect = tiny_skia::Rect::from_xywh(x, 0.0, band_width, size as f32).unwrap();
+            let mut paint = tiny_skia::Paint::default();
+            paint.set_color(*color);
+            pixmap.fill_rect(rect, &paint, tiny_skia::Transform::identity(), None);
+            x += band_width;
+        }
+        pixmap.encode_png().unwrap()
+    }
+
+    fn rgb(r: u8, g: u8, b: u8) -> tiny_skia::Color {
+        tiny_skia::Color::from_rgba8(r, g, b, 255)
+    }
+
+    mod fn_dominant_color {
+        use super::*;
+
+        #[test]
+        fn single_color_image() {
+            let png = banded_png(64, &[(rgb(0x2f, 0x81, 0xf7), 1)]);
+            assert_eq!(dominant_color(&png).as_deref(), Some("#2f81f7"));
+        }
+
+        #[test]
+        fn saturated_color_wins_over_a_larger_gray_background() {
+            let png = banded_png(
+                64,
+                &[(rgb(0xf0, 0xf0, 0xf0), 5), (rgb(0xe0, 0x30, 0x30), 3)],
+            );
+            assert_eq!(dominant_color(&png).as_deref(), Some("#e03030"));
+        }
+
+        #[test]
+        fn larger_of_two_colors_wins() {
+            let png = banded_png(
+                64,
+                &[(rgb(0x20, 0xa0, 0x40), 3), (rgb(0x30, 0x30, 0xe0), 1)],
+            );
+            assert_eq!(dominant_color(&png).as_deref(), Some("#20a040"));
+        }
+
+        #[test]
+        fn gray_image_keeps_its_gray() {
+            let png = banded_png(16, &[(rgb(0x80, 0x80, 0x80), 1)]);
+            assert_eq!(dominant_color(&png).as_deref(), Some("#808080"));
+        }
+
+        #[test]
+        fn transparent_pixels_are_ignored() {
+            let transparent = tiny_skia::Color::from_rgba8(0, 0, 0, 0);
+            let png = banded_png(64, &[(transparent, 3), (rgb(0x90, 0x40, 0xc0), 1)]);
+            assert_eq!(dominant_color(&png).as_deref(), Some("#9040c0"));
+
+            let png = banded_png(16, &[(transparent, 1)]);
+            assert_eq!(dominant_color(&png), None);
+        }
+
+        #[test]
+        fn undecodable_images_have_no_color() {
+            assert_eq!(dominant_color(b""), None);
+            assert_eq!(dominant_color(b"<html></html>"), None);
+            assert_eq!(dominant_color(b"\x89PNG\r\n\x1a\ntruncated"), None);
+        }
+    }
+
+    mod fn_accent_css {
+        use super::*;
+
+        #[test]
+        fn colors_the_title_and_icons() {
+            assert_eq!(accent_css("#2f81f7"), ".title, .icon { fill: #2f81f7; }");
+        }
+    }
+}
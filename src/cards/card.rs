@@ -4,6 +4,26 @@ pub type Svg = String;
 use card_theme_macros::build_card_themes;
 build_card_themes!();
 
+use crate::cards::helpers::{format_coord, format_decimal, parse_css_declarations};
+
+/// CardError describes why a [Card] could not be built from the given dimensions and settings.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum CardError {
+    #[error("Card width must be at least {min}, got {width}")]
+    TooNarrow { width: u32, min: u32 },
+    #[error("Card height must be at least {min}, got {height}")]
+    TooShort { height: u32, min: u32 },
+    #[error(
+        "Card offset must not exceed 30% of width or height (max: {max_x}, {max_y}), got x:{x} y:{y}"
+    )]
+    OffsetTooLarge {
+        max_x: u32,
+        max_y: u32,
+        x: u32,
+        y: u32,
+    },
+}
+
 /// CardSettings holds unique settings for the [Card].
 #[derive(Clone, Debug)]
 pub struct CardSettings {
@@ -19,6 +39,127 @@ pub struct CardSettings {
     pub hide_background: bool,
     /// Hide stroke (outline) of background rectangle while preserving layout.
     pub hide_background_stroke: bool,
+    /// Minimum rendered width of the [Card], narrower cards are padded on the right.
+    pub min_width: Option<u32>,
+    /// Maximum rendered width of the [Card], wider cards are scaled down proportionally.
+    pub max_width: Option<u32>,
+    /// Font size (pixels) of the title, also used to lay out the header of the cards.
+    pub title_size: u32,
+    /// Draw layout guides (offsets, row baselines, bar bounds) on top of the card.
+    pub debug: bool,
+    /// Extra CSS rules appended after the theme style. Must be sanitized beforehand,
+    /// it ends up in the `<style>` element as is.
+    pub custom_css: Option<String>,
+    /// Fixed size canvas the [Card] is centered in, `None` renders the card alone.
+    pub canvas: Option<Canvas>,
+}
+
+/// A fixed size canvas (pixels) to center a [Card] in, e.g. for Open Graph images.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Canvas {
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Canvas {
+    /// Open Graph image size, as used by social media link previews.
+    pub const OPEN_GRAPH: Canvas = Canvas {
+        width: 1200,
+        height: 630,
+    };
+    /// Smallest space (pixels) kept between the card and the canvas edges.
+    pub const PADDING: u32 = 48;
+
+    /// Returns the scale fitting a `width`×`height` card into the padded canvas,
+    /// along with the offsets centering the scaled card.
+    fn fit(&self, width: u32, height: u32) -> (f64, f64, f64) {
+        let room_width = self.width.saturating_sub(Self::PADDING * 2).max(1) as f64;
+        let room_height = self.height.saturating_sub(Self::PADDING * 2).max(1) as f64;
+        let scale = (room_width / width as f64).min(room_height / height as f64);
+
+        let offset_x = (self.width as f64 - width as f64 * scale) / 2.0;
+        let offset_y = (self.height as f64 - height as f64 * scale) / 2.0;
+        (scale, offset_x, offset_y)
+    }
+}
+
+/// Key colors of a [CardTheme], e.g. for the swatches of a theme picker.
+/// A color is `None` when the theme does not set it.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize)]
+pub struct ThemeColors {
+    pub background: Option<String>,
+    pub text: Option<String>,
+    pub border: Option<String>,
+    pub title: Option<String>,
+    pub value: Option<String>,
+}
+
+impl CardTheme {
+    /// Returns the theme with the given snake_case name, as used in the `theme` query parameter.
+    pub fn from_name(name: &str) -> Option<CardTheme> {
+        let name = name.trim().to_lowercase();
+        CardTheme::all().iter().find(|t| t.name() == name).cloned()
+    }
+
+    /// Whether the theme is meant to be shown without a background, from its name.
+    pub fn is_transparent(&self) -> bool {
+        self.name().contains("transparent")
+    }
+
+    /// Resolves the key colors from the theme CSS. The last matching declaration wins,
+    /// and `var(--name)` values are looked up in the theme's custom properties.
+    pub fn colors(&self) -> ThemeColors {
+        let declarations = parse_css_declarations(self.load_css());
+        let lookup = |selector: &str, property: &str| {
+            declarations
+                .iter()
+                .rev()
+                .find(|d| d.selector == selector && d.property == property)
+                .map(|d| d.value.as_str())
+        };
+        let resolve = |selector: &str, property: &str| {
+            let value = lookup(selector, property)?;
+            match value.strip_prefix("var(").and_then(|v| v.strip_suffix(')')) {
+                Some(var) => {
+                    let (name, fallback) = var.split_once(',').unwrap_or((var, ""));
+                    declarations
+                        .iter()
+                        .rev()
+                        .find(|d| d.property == name.trim())
+                        .map(|d| d.value.clone())
+                        .or_else(|| Some(fallback.trim().to_string()).filter(|f| !f.is_empty()))
+                }
+                None => Some(value.to_string()),
+            }
+        };
+
+        ThemeColors {
+            background: resolve(".background", "fill"),
+            text: resolve(".label", "fill"),
+            border: resolve(".background", "stroke"),
+            title: resolve(".title", "fill"),
+            value: resolve(".value", "fill"),
+        }
+    }
+}
+
+impl Default for CardSettings {
+    fn default() -> Self {
+        Self {
+            offset_x: 12,
+            offset_y: 12,
+            theme: CardTheme::Light,
+            hide_title: false,
+            hide_background: false,
+            hide_background_stroke: false,
+            min_width: None,
+            max_width: None,
+            title_size: Card::TITLE_FONT_SIZE,
+            debug: false,
+            custom_css: None,
+            canvas: None,
+        }
+    }
 }
 
 /// Card represents a card with a width, height, and title. Its a base wrapper for cards of different types.
@@ -38,6 +179,10 @@ pub struct Card {
 
 impl Card {
     pub const TITLE_FONT_SIZE: u32 = 18;
+    pub const MIN_TITLE_FONT_SIZE: u32 = 10;
+    pub const MAX_TITLE_FONT_SIZE: u32 = 32;
+    pub const MIN_WIDTH: u32 = 100;
+    pub const MIN_HEIGHT: u32 = 60;
 
     /// Creates a new [Card] with the specified parameters.
     pub fn new(
@@ -48,7 +193,7 @@ impl Card {
         body: String,
         outer_class: String,
         settings: CardSettings,
-    ) -> anyhow::Result<Self, anyhow::Error> {
+    ) -> Result<Self, CardError> {
         let card = Card {
             width,
             height,
@@ -59,7 +204,7 @@ impl Card {
             settings,
             outer_class,
         };
-        card.validate().map_err(anyhow::Error::msg)?;
+        card.validate()?;
         Ok(card)
     }
 
@@ -68,11 +213,16 @@ impl Card {
         let theme = self.load_theme_style();
         // Merge the theme style with the base style, indenting it for readability.
         let base_style = self.style.as_str();
-        let style = Self::indent(&format!("{base_style}\n{theme}"), 2);
+        let style = match &self.settings.custom_css {
+            Some(custom_css) => format!("{base_style}\n{theme}\n{custom_css}"),
+            None => format!("{base_style}\n{theme}"),
+        };
+        let style = Self::indent(&style, 2);
 
         let body = Self::indent(&self.body, 4);
+        let (view_width, width, height) = self.clamped_size();
         let rendered_background = if !self.settings.hide_background {
-            self.render_background()
+            self.render_background(view_width)
         } else {
             String::new()
         };
@@ -82,11 +232,11 @@ impl Card {
             String::new()
         };
 
-        format!(
+        let svg = format!(
             r#"<svg
   width="{width}"
   height="{height}"
-  viewBox="0 0 {width} {height}"
+  viewBox="0 0 {view_width} {view_height}"
   fill="none"
   xmlns="http://www.w3.org/2000/svg"
   role="img"
@@ -104,8 +254,10 @@ impl Card {
   </g>
 </svg>
 "#,
-            width = self.width,
-            height = self.height,
+            width = width,
+            height = height,
+            view_width = view_width,
+            view_height = self.height,
             title = self.title,
             description = self.description,
             outer_class = self.outer_class,
@@ -113,40 +265,77 @@ impl Card {
             rendered_background = rendered_background,
             rendered_title = rendered_title,
             style = style
+        );
+
+        match self.settings.canvas {
+            Some(canvas) => self.render_in_canvas(&svg, width, height, canvas),
+            None => svg,
+        }
+    }
+
+    /// Wraps the rendered card `svg` of the given size into the `canvas`, scaled to fit
+    /// its padded area and centered. The canvas takes the theme background unless
+    /// the background is hidden.
+    fn render_in_canvas(&self, svg: &str, width: u32, height: u32, canvas: Canvas) -> Svg {
+        let (scale, offset_x, offset_y) = canvas.fit(width, height);
+        let background = if !self.settings.hide_background {
+            format!(
+                r#"<rect class="background canvas" x="0" y="0" width="{}" height="{}"/>"#,
+                canvas.width, canvas.height
+            )
+        } else {
+            String::new()
+        };
+        let inner = Self::indent(svg, 4);
+
+        format!(
+            r#"<svg
+  width="{canvas_width}"
+  height="{canvas_height}"
+  viewBox="0 0 {canvas_width} {canvas_height}"
+  fill="none"
+  xmlns="http://www.w3.org/2000/svg"
+  role="img"
+  aria-labelledby="title-id"
+  aria-describedby="description-id"
+>
+  {background}
+  <g transform="translate({offset_x}, {offset_y}) scale({scale})">
+{inner}  </g>
+</svg>
+"#,
+            canvas_width = canvas.width,
+            canvas_height = canvas.height,
+            offset_x = format_coord(offset_x),
+            offset_y = format_coord(offset_y),
+            scale = format_decimal(scale, 4),
         )
     }
 
     /// Validates the [Card]'s dimensions and settings.
-    fn validate(&self) -> Result<(), String> {
-        if self.width < 100 {
-            return Err(format!(
-                "Card width must be at least 100, got {}",
-                self.width
-            ));
-        }
-        if self.height < 60 {
-            return Err(format!(
-                "Card height must be at least 100, got {}",
-                self.height
-            ));
+    fn validate(&self) -> Result<(), CardError> {
+        if self.width < Self::MIN_WIDTH {
+            return Err(CardError::TooNarrow {
+                width: self.width,
+                min: Self::MIN_WIDTH,
+            });
         }
-        let max_offset_w = (self.width as f32 * 0.3) as u32;
-        let max_offset_h = (self.height as f32 * 0.3) as u32;
-        if self.settings.offset_x > max_offset_w || self.settings.offset_y > max_offset_h {
-            return Err(format!(
-                "Card offset must not exceed 30% of width or height (max: {}, {}), got x:{} y:{}",
-                max_offset_w, max_offset_h, self.settings.offset_x, self.settings.offset_y
-            ));
+        if self.height < Self::MIN_HEIGHT {
+            return Err(CardError::TooShort {
+                height: self.height,
+                min: Self::MIN_HEIGHT,
+            });
         }
-
-        if self.settings.offset_x >= self.width / 2 || self.settings.offset_y >= self.height / 2 {
-            return Err(format!(
-                "Card offset must be less than half of width and height (max: {}, {}), got x:{} y:{}",
-                self.width as f32 / 2.0,
-                self.height as f32 / 2.0,
-                self.settings.offset_x,
-                self.settings.offset_y
-            ));
+        // 30% of each side also keeps the offsets below half of the card
+        let max_x = (self.width as f32 * 0.3) as u32;
+        let max_y = (self.height as f32 * 0.3) as u32;
+        if self.settings.offset_x > max_x || self.settings.offset_y > max_y {
+            return Err(CardError::OffsetTooLarge {
+                max_x,
+                max_y,
+                x: self.settings.offset_x,
+                y: self.settings.offset_y,
+            });
         }
         Ok(())
     }
@@ -165,15 +354,41 @@ impl Card {
 
     /// Renders the title of the [Card] as an SVG text element.
     fn render_title(&self) -> String {
+        // The stylesheet sets the default size, only a custom one is set inline
+        let style = if self.settings.title_size != Self::TITLE_FONT_SIZE {
+            format!(r#" style="font-size: {}px""#, self.settings.title_size)
+        } else {
+            String::new()
+        };
+
         format!(
-            r#"<g transform="translate({}, {})"><text x="0" y="0" class="title">{}</text></g>"#,
+            r#"<g transform="translate({}, {})"><text x="0" y="0" class="title"{style}>{}</text></g>"#,
             self.settings.offset_x,
-            Self::TITLE_FONT_SIZE + self.settings.offset_y,
+            self.settings.title_size + self.settings.offset_y,
             self.title
         )
     }
 
-    fn render_background(&self) -> String {
+    /// Applies `min_width`/`max_width` and returns the viewBox width along with the
+    /// rendered width and height of the SVG.
+    ///
+    /// `min_width` widens the viewBox, padding the content on the right. `max_width`
+    /// only shrinks the rendered size, keeping the aspect ratio so content isn't distorted.
+    fn clamped_size(&self) -> (u32, u32, u32) {
+        let view_width = self.width.max(self.settings.min_width.unwrap_or(0));
+        let width = self
+            .settings
+            .max_width
+            .map_or(view_width, |max| view_width.min(max));
+        let height = if width < view_width {
+            (self.height as f64 * width as f64 / view_width as f64).round() as u32
+        } else {
+            self.height
+        };
+        (view_width, width, height)
+    }
+
+    fn render_background(&self, width: u32) -> String {
         // If stroke hidden - remove half-pixel inset so fill spans full size.
         let stroke_offset: f32 = if self.settings.hide_background_stroke {
             0.0
@@ -190,14 +405,31 @@ impl Card {
             r#"<rect class="background" x="{pos_x}" y="{pos_y}" rx="5" width="{width}" height="{height}" stroke-opacity="{stroke_opacity}"/>"#,
             pos_x = stroke_offset,
             pos_y = stroke_offset,
-            width = self.width as f32 - stroke_offset * 2.0,
+            width = width as f32 - stroke_offset * 2.0,
             height = self.height as f32 - stroke_offset * 2.0,
             stroke_opacity = stroke_opacity,
         )
     }
 
     fn load_theme_style(&self) -> String {
-        self.settings.theme.load_css().to_string()
+        with_theme_fallbacks(self.settings.theme.load_css())
+    }
+}
+
+/// Style of the unfilled progress bars, for themes that don't define one.
+/// Translucent gray so it reads on both light and dark backgrounds.
+const PROGRESS_BAR_BACKGROUND_FALLBACK: &str = ".progressBarBackground {
+  fill: #8b949e;
+  fill-opacity: 0.25;
+}";
+
+/// Appends a default style for the classes the rendered cards rely on when
+/// the theme `css` doesn't define them.
+fn with_theme_fallbacks(css: &str) -> String {
+    if css.contains(".progressBarBackground") {
+        css.to_string()
+    } else {
+        format!("{css}\n{PROGRESS_BAR_BACKGROUND_FALLBACK}")
     }
 }
 
@@ -224,6 +456,7 @@ mod tests {
                     hide_title: false,
                     hide_background: false,
                     hide_background_stroke: false,
+                    ..Default::default()
                 },
             )
             .expect("Card should be valid");
@@ -250,9 +483,10 @@ mod tests {
                     hide_title: false,
                     hide_background: false,
                     hide_background_stroke: false,
+                    ..Default::default()
                 },
             );
-            assert!(card.is_err());
+            assert!(matches!(card, Err(CardError::TooNarrow { width: 99, .. })));
         }
 
         #[test]
@@ -271,9 +505,10 @@ mod tests {
                     hide_title: false,
                     hide_background: false,
                     hide_background_stroke: false,
+                    ..Default::default()
                 },
             );
-            assert!(card.is_err());
+            assert!(matches!(card, Err(CardError::TooShort { height: 50, .. })));
         }
 
         #[test]
@@ -292,9 +527,10 @@ mod tests {
                     hide_title: false,
                     hide_background: false,
                     hide_background_stroke: false,
+                    ..Default::default()
                 },
             );
-            assert!(card.is_err());
+            assert!(matches!(card, Err(CardError::OffsetTooLarge { x: 50, .. })));
         }
 
         #[test]
@@ -313,9 +549,10 @@ mod tests {
                     hide_title: false,
                     hide_background: false,
                     hide_background_stroke: false,
+                    ..Default::default()
                 },
             );
-            assert!(card.is_err());
+            assert!(matches!(card, Err(CardError::OffsetTooLarge { x: 60, .. })));
         }
     }
 
@@ -348,6 +585,7 @@ mod tests {
                     hide_title: false,
                     hide_background: false,
                     hide_background_stroke: false,
+                    ..Default::default()
                 },
             )
             .unwrap();
@@ -357,6 +595,48 @@ mod tests {
                 r#"<g transform="translate(1, 19)"><text x="0" y="0" class="title">Test Title</text></g>"#
             );
         }
+
+        #[test]
+        fn test_render_title_with_custom_size() {
+            let card = Card::new(
+                100,
+                120,
+                "Test Title".to_string(),
+                "".to_string(),
+                "".to_string(),
+                "".to_string(),
+                CardSettings {
+                    offset_x: 1,
+                    offset_y: 1,
+                    title_size: 24,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+            assert_eq!(
+                card.render_title(),
+                r#"<g transform="translate(1, 25)"><text x="0" y="0" class="title" style="font-size: 24px">Test Title</text></g>"#
+            );
+        }
+    }
+
+    mod fn_with_theme_fallbacks {
+        use super::*;
+
+        #[test]
+        fn keeps_the_theme_progress_bar_background() {
+            let css = CardTheme::Dark.load_css();
+            assert_eq!(with_theme_fallbacks(css), css);
+        }
+
+        #[test]
+        fn adds_a_progress_bar_background_when_missing() {
+            let css = ".title { fill: #000; }";
+            assert_eq!(
+                with_theme_fallbacks(css),
+                format!("{css}\n{PROGRESS_BAR_BACKGROUND_FALLBACK}")
+            );
+        }
     }
 
     mod fn_render {
@@ -378,6 +658,7 @@ mod tests {
                     hide_title: true,
                     hide_background: false,
                     hide_background_stroke: false,
+                    ..Default::default()
                 },
             )
             .unwrap();
@@ -402,6 +683,7 @@ mod tests {
                     hide_title: true,
                     hide_background: false,
                     hide_background_stroke: true,
+                    ..Default::default()
                 },
             )
             .unwrap();
@@ -425,6 +707,7 @@ mod tests {
                     hide_title: true,
                     hide_background: false,
                     hide_background_stroke: false,
+                    ..Default::default()
                 },
             )
             .unwrap();
@@ -451,6 +734,7 @@ mod tests {
                     hide_title: false,
                     hide_background: true,
                     hide_background_stroke: false,
+                    ..Default::default()
                 },
             )
             .unwrap();
@@ -477,6 +761,7 @@ mod tests {
                     hide_title: true,
                     hide_background: true,
                     hide_background_stroke: false,
+                    ..Default::default()
                 },
             )
             .unwrap();
@@ -489,6 +774,83 @@ mod tests {
             assert!(!svg.contains("<rect "));
         }
 
+        #[test]
+        fn test_render_appends_custom_css_after_theme() {
+            let card = Card::new(
+                100,
+                120,
+                "Test Title".to_string(),
+                "Test Desc".to_string(),
+                "Test Body".to_string(),
+                "test-card".to_string(),
+                CardSettings {
+                    custom_css: Some(".title { fill: #ff0000; }".to_string()),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+            let svg = card.render();
+
+            let theme_css = CardTheme::Light.load_css();
+            let last_declaration = theme_css.lines().rfind(|l| l.contains(':')).unwrap();
+            let theme = svg.rfind(last_declaration.trim()).unwrap();
+            let custom = svg.find(".title { fill: #ff0000; }").unwrap();
+            assert!(theme < custom);
+            assert!(custom < svg.find("</style>").unwrap());
+        }
+
+        #[test]
+        fn test_render_in_canvas_centers_scaled_card() {
+            let card = Card::new(
+                400,
+                200,
+                "Test Title".to_string(),
+                "Test Desc".to_string(),
+                "Test Body".to_string(),
+                "test-card".to_string(),
+                CardSettings {
+                    canvas: Some(Canvas::OPEN_GRAPH),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+            let svg = card.render();
+
+            assert!(svg.starts_with("<svg\n  width=\"1200\"\n  height=\"630\""));
+            assert!(svg.contains(
+                r#"<rect class="background canvas" x="0" y="0" width="1200" height="630"/>"#
+            ));
+            // (630 - 2 * 48) / 200 = 2.67 limits the scale, leaving 66 on each side
+            assert!(svg.contains(r#"<g transform="translate(66, 48) scale(2.67)">"#));
+            assert!(svg.contains("    <svg\n      width=\"400\""));
+        }
+
+        #[test]
+        fn test_render_in_canvas_without_background() {
+            let card = Card::new(
+                400,
+                200,
+                "Test Title".to_string(),
+                "Test Desc".to_string(),
+                "Test Body".to_string(),
+                "test-card".to_string(),
+                CardSettings {
+                    hide_background: true,
+                    canvas: Some(Canvas {
+                        width: 600,
+                        height: 600,
+                    }),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+            let svg = card.render();
+
+            assert!(!svg.contains("<rect class=\"background canvas\""));
+            // (600 - 2 * 48) / 400 = 1.26 limits the scale
+            assert!(svg.contains(r#"<g transform="translate(48, 174) scale(1.26)">"#));
+        }
+
         use quick_xml::Reader;
         use quick_xml::events::Event;
 
@@ -508,6 +870,7 @@ mod tests {
                     hide_title: false,
                     hide_background: false,
                     hide_background_stroke: false,
+                    ..Default::default()
                 },
             )
             .unwrap();
@@ -531,4 +894,52 @@ mod tests {
             assert!(found_svg, "SVG root element not found");
         }
     }
+
+    mod fn_colors {
+        use super::*;
+
+        #[test]
+        fn resolves_the_key_colors_of_a_theme() {
+            assert_eq!(
+                CardTheme::from_name("dark").unwrap().colors(),
+                ThemeColors {
+                    background: Some("#0d1117".to_string()),
+                    text: Some("#f0f6fc".to_string()),
+                    border: Some("#30363d".to_string()),
+                    title: Some("#58a6ff".to_string()),
+                    value: Some("#f0f6fc".to_string()),
+                }
+            );
+        }
+
+        #[test]
+        fn every_theme_sets_hex_key_colors() {
+            for theme in CardTheme::all() {
+                let colors = theme.colors();
+                for color in [
+                    colors.background,
+                    colors.text,
+                    colors.border,
+                    colors.title,
+                    colors.value,
+                ] {
+                    let color = color.unwrap_or_else(|| panic!("{}: missing color", theme.name()));
+                    assert!(
+                        color.starts_with('#') && color[1..].chars().all(|c| c.is_ascii_hexdigit()),
+                        "{}: {color} is not a hex color",
+                        theme.name()
+                    );
+                }
+            }
+        }
+
+        #[test]
+        fn unknown_theme_name() {
+            assert!(CardTheme::from_name("unknown_theme").is_none());
+            assert_eq!(
+                CardTheme::from_name(" Transparent_Blue").unwrap().name(),
+                "transparent_blue"
+            );
+        }
+    }
 }
@@ -1,8 +1,20 @@
-use crate::cards::card::{CardSettings, CardTheme, Svg};
+use crate::cards::card::{CardError, CardSettings, CardTheme, Svg};
+use crate::cards::helpers::{escape_xml_text, truncate_with_ellipsis};
+
+/// How an [ErrorCard] is laid out.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ErrorStyle {
+    /// Titled card with the whole message and a link to the docs.
+    #[default]
+    Full,
+    /// Single-line badge with a truncated message, for inline use.
+    Compact,
+}
 
 pub struct ErrorCard {
     pub card_settings: CardSettings,
     pub error_message: String,
+    pub style: ErrorStyle,
 }
 
 impl ErrorCard {
@@ -12,6 +24,9 @@ impl ErrorCard {
     const MESSAGE_LINE_HEIGHT: u32 = 22;
     const LINK_OFFSET: u32 = 20;
     const CARD_PADDING: u32 = 16;
+    const COMPACT_MAX_MSG_LEN: usize = 30;
+    const COMPACT_WIDTH: u32 = 300;
+    const COMPACT_PADDING: u32 = 12;
     const DOCS_URL: &'static str =
         "https://github.com/samgozman/github-statcrab?tab=readme-ov-file#github-statcrab";
 
@@ -26,20 +41,48 @@ impl ErrorCard {
                 hide_title: false,
                 hide_background: false,
                 hide_background_stroke: false,
+                ..Default::default()
             },
             error_message,
+            style: ErrorStyle::Full,
+        }
+    }
+
+    /// Creates a new [ErrorStyle::Compact] ErrorCard with the given error message.
+    pub fn compact(error_message: String) -> Self {
+        Self {
+            card_settings: CardSettings {
+                offset_x: Self::COMPACT_PADDING,
+                offset_y: Self::COMPACT_PADDING,
+                hide_title: true,
+                ..Self::new(String::new()).card_settings
+            },
+            error_message,
+            style: ErrorStyle::Compact,
         }
     }
 
     /// Renders the ErrorCard as an SVG string.
-    pub fn render(&self) -> Svg {
+    /// Fails with a [CardError] when the settings don't fit the card dimensions.
+    pub fn render(&self) -> Result<Svg, CardError> {
+        match self.style {
+            ErrorStyle::Full => self.render_full(),
+            ErrorStyle::Compact => self.render_compact(),
+        }
+    }
+
+    fn render_full(&self) -> Result<Svg, CardError> {
         use crate::cards::card::Card;
 
         // Break the error message into lines if it's too long
         let message_lines = self.break_message_into_lines(&self.error_message);
 
-        // Title block height (title + offset)
-        let header_size_y = Card::TITLE_FONT_SIZE + Self::TITLE_BODY_OFFSET;
+        // Title block height (title + offset) unless title is hidden
+        let header_size_y = if self.card_settings.hide_title {
+            0
+        } else {
+            self.card_settings.title_size + Self::TITLE_BODY_OFFSET
+        };
 
         // Calculate positions
         let icon_x = self.card_settings.offset_x;
@@ -56,7 +99,9 @@ impl ErrorCard {
         for line in &message_lines {
             body_parts.push(format!(
                 r#"<text x="{}" y="{}" class="error-message">{}</text>"#,
-                message_x, message_y, line
+                message_x,
+                message_y,
+                escape_xml_text(line)
             ));
             message_y += Self::MESSAGE_LINE_HEIGHT;
         }
@@ -83,12 +128,45 @@ impl ErrorCard {
             body,
             "errorCard".to_string(),
             self.card_settings.clone(),
-        );
+        )?;
 
-        match card {
-            Ok(card) => self.add_error_styles(&card.render()),
-            Err(e) => format!("Failed to render ErrorCard: {e}"),
-        }
+        Ok(self.add_error_styles(&card.render()))
+    }
+
+    /// Renders the icon and the message truncated to a single line, without title
+    /// nor docs link.
+    fn render_compact(&self) -> Result<Svg, CardError> {
+        use crate::cards::card::Card;
+
+        let message = truncate_with_ellipsis(&self.error_message, Self::COMPACT_MAX_MSG_LEN)
+            .unwrap_or_else(|| self.error_message.clone());
+
+        let icon_x = self.card_settings.offset_x;
+        let height = (self.card_settings.offset_y * 2 + 32).max(Card::MIN_HEIGHT);
+        // The icon is centered vertically, the 15px text baseline sits 5px below its middle
+        let icon_y = (height - 32) / 2;
+        let body = [
+            self.render_error_icon(icon_x, icon_y),
+            format!(
+                r#"<text x="{}" y="{}" class="error-message">{}</text>"#,
+                icon_x + 40,
+                icon_y + 16 + 5,
+                escape_xml_text(&message)
+            ),
+        ]
+        .join("\n");
+
+        let card = Card::new(
+            Self::COMPACT_WIDTH,
+            height,
+            String::from("Error"),
+            escape_xml_text(&self.error_message),
+            body,
+            "errorCard".to_string(),
+            self.card_settings.clone(),
+        )?;
+
+        Ok(self.add_error_styles(&card.render()))
     }
 
     /// Breaks a long error message into multiple lines.
@@ -146,7 +224,9 @@ impl ErrorCard {
         )
     }
 
-    /// Adds error-specific styles to the SVG.
+    /// Adds error-specific styles to the SVG, at the end of the style block so they
+    /// come after the theme. Their colors use the variables a theme may define on
+    /// `.errorCard`, falling back to the light ones.
     fn add_error_styles(&self, svg: &str) -> String {
         let error_styles = include_str!("../../assets/css/error-card.css");
 
@@ -163,6 +243,7 @@ impl ErrorCard {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::cards::card::Card;
 
     #[test]
     fn test_error_card_creation() {
@@ -194,7 +275,7 @@ mod tests {
     #[test]
     fn test_render_produces_valid_svg() {
         let card = ErrorCard::new("Test error".to_string());
-        let svg = card.render();
+        let svg = card.render().unwrap();
 
         // Basic SVG structure checks
         assert!(svg.contains("<svg"));
@@ -205,6 +286,42 @@ mod tests {
         assert!(svg.contains(ErrorCard::DOCS_URL));
     }
 
+    #[test]
+    fn test_render_escapes_the_message() {
+        let message = "invalid hide value: <script>&\"x\"</script>".to_string();
+        for card in [ErrorCard::new(message.clone()), ErrorCard::compact(message)] {
+            let svg = card.render().unwrap();
+            crate::cards::helpers::assert_valid_xml(&svg);
+            assert!(svg.contains("&lt;script&gt;&amp;&quot;x&quot;&lt;/script&gt;"));
+            assert!(!svg.contains("<script>"));
+        }
+    }
+
+    #[test]
+    fn test_render_with_hidden_title() {
+        let card = ErrorCard::new("Test error".to_string());
+        let mut hidden = ErrorCard::new("Test error".to_string());
+        hidden.card_settings.hide_title = true;
+
+        let svg = card.render().unwrap();
+        let hidden_svg = hidden.render().unwrap();
+
+        // Visual title is omitted (the accessible <title> element stays)
+        assert!(svg.contains("class=\"title\""));
+        assert!(!hidden_svg.contains("class=\"title\""));
+
+        // Icon and message move up by the reclaimed header height
+        let header = Card::TITLE_FONT_SIZE + ErrorCard::TITLE_BODY_OFFSET;
+        let padding = ErrorCard::CARD_PADDING;
+        assert!(svg.contains(&format!("cy=\"{}\"", header + padding + 16)));
+        assert!(hidden_svg.contains(&format!("cy=\"{}\"", padding + 16)));
+        assert!(hidden_svg.contains(&format!(
+            "x=\"{}\" y=\"{}\" class=\"error-message\"",
+            padding + 40,
+            padding + 24
+        )));
+    }
+
     #[test]
     fn test_render_error_icon() {
         let card = ErrorCard::new("Test".to_string());
@@ -244,4 +361,68 @@ mod tests {
         assert!(styled_svg.contains("#0284c7")); // Updated link color
         assert!(styled_svg.contains("drop-shadow")); // Icon styling
     }
+
+    #[test]
+    fn test_error_styles_use_theme_variables() {
+        let svg = ErrorCard::new("Test".to_string()).render().unwrap();
+
+        assert!(svg.contains("fill: var(--error-message-color, #991b1b);"));
+        assert!(svg.contains("fill: var(--error-link-color, #0284c7);"));
+        // The light theme keeps the fallback colors
+        assert!(!svg.contains("--error-message-color:"));
+    }
+
+    #[test]
+    fn test_render_with_dark_theme() {
+        let mut card = ErrorCard::new("Test error".to_string());
+        card.card_settings.theme = CardTheme::Dark;
+        let svg = card.render().unwrap();
+
+        // The dark theme swaps the colors dark on dark for lighter ones
+        assert!(svg.contains("--error-message-color: #f85149;"));
+        assert!(svg.contains("--error-link-color: #58a6ff;"));
+        assert!(svg.contains("--error-link-background: #0d2d52;"));
+
+        // The error styles follow the theme block
+        let theme = svg.find(".errorCard {").unwrap();
+        let error_styles = svg.find(".error-message {").unwrap();
+        assert!(theme < error_styles);
+        assert!(error_styles < svg.find("</style>").unwrap());
+    }
+
+    #[test]
+    fn test_render_compact_is_narrow_and_short() {
+        let message = "Test error".to_string();
+        let full = ErrorCard::new(message.clone()).render().unwrap();
+        let compact = ErrorCard::compact(message).render().unwrap();
+
+        let size = |svg: &str, attr: &str| -> u32 {
+            let start = svg.find(&format!("{attr}=\"")).unwrap() + attr.len() + 2;
+            let end = start + svg[start..].find('"').unwrap();
+            svg[start..end].parse().unwrap()
+        };
+        assert_eq!(size(&compact, "height"), Card::MIN_HEIGHT);
+        assert!(size(&compact, "height") < size(&full, "height"));
+        assert!(size(&compact, "width") < size(&full, "width"));
+
+        // Neither the visual title nor the docs link
+        assert!(!compact.contains("class=\"title\""));
+        assert!(!compact.contains(ErrorCard::DOCS_URL));
+        assert!(compact.contains(">Test error</text>"));
+    }
+
+    #[test]
+    fn test_render_compact_truncates_long_message_to_one_line() {
+        let long_message = "This is a very long error message that should be broken into multiple lines for better readability in the error card";
+        let svg = ErrorCard::compact(long_message.to_string())
+            .render()
+            .unwrap();
+
+        assert_eq!(svg.matches("class=\"error-message\"").count(), 1);
+        let expected =
+            truncate_with_ellipsis(long_message, ErrorCard::COMPACT_MAX_MSG_LEN).unwrap();
+        assert!(svg.contains(&format!(">{expected}</text>")));
+        // The whole message stays available in the accessible description
+        assert!(svg.contains(&format!(">{long_message}</desc>")));
+    }
 }
@@ -0,0 +1,199 @@
+//! Example cards rendered with dummy data, used to showcase the themes
+//! (e.g. in the generated themes README).
+
+use crate::cards::card::{CardError, CardSettings, CardTheme, Svg};
+use crate::cards::error_card::ErrorCard;
+use crate::cards::langs_card::{LangsCard, LanguageStat, LayoutType};
+use crate::cards::stats_card::StatsCard;
+
+/// Message of the short [ErrorCard] example.
+pub const SHORT_ERROR_MESSAGE: &str = "Invalid username provided";
+/// Message of the long [ErrorCard] example, wrapping to multiple lines.
+pub const LONG_ERROR_MESSAGE: &str = "The GitHub API returned an error when trying to fetch user statistics. This might be due to rate limiting or an invalid username. Please check your configuration and try again.";
+
+/// Builds the [CardSettings] of the examples for the given theme.
+/// `transparent` hides the background and its stroke.
+pub fn example_settings(theme: CardTheme, transparent: bool) -> CardSettings {
+    CardSettings {
+        offset_x: 12,
+        offset_y: 12,
+        theme,
+        hide_title: false,
+        hide_background: transparent,
+        hide_background_stroke: transparent,
+        ..Default::default()
+    }
+}
+
+/// Renders a [StatsCard] example with dummy data for the given theme.
+pub fn stats_example(theme: CardTheme) -> Result<Svg, CardError> {
+    stats_example_with(example_settings(theme, false))
+}
+
+/// Renders a [StatsCard] example with dummy data using the given settings.
+pub fn stats_example_with(settings: CardSettings) -> Result<Svg, CardError> {
+    StatsCard {
+        card_settings: settings,
+        username: "octocat".to_string(),
+        stars_count: Some(1234),
+        commits_ytd_count: Some(567),
+        issues_count: Some(89),
+        pull_requests_count: Some(123),
+        merge_requests_count: Some(45),
+        reviews_count: Some(67),
+        started_discussions_count: Some(12),
+        answered_discussions_count: Some(34),
+        repositories_count: None,
+        members_count: None,
+        sort: None,
+        icon_size: StatsCard::DEFAULT_ICON_SIZE,
+        commits_year: None,
+        commits_all_years: false,
+        label_gap: StatsCard::DEFAULT_LABEL_GAP,
+        value_gap: StatsCard::DEFAULT_VALUE_GAP,
+        counts_contributions: false,
+        max_rows: None,
+        custom_title: None,
+    }
+    .render()
+}
+
+/// Renders a [LangsCard] example with dummy data for the given theme and layout.
+pub fn langs_example(theme: CardTheme, layout: LayoutType) -> Result<Svg, CardError> {
+    langs_example_with(example_settings(theme, false), layout)
+}
+
+/// Renders a [LangsCard] example with dummy data using the given settings and layout.
+pub fn langs_example_with(settings: CardSettings, layout: LayoutType) -> Result<Svg, CardError> {
+    LangsCard {
+        card_settings: settings,
+        layout,
+        stats: dummy_language_stats(),
+        size_weight: Some(1.0),
+        count_weight: Some(0.0),
+        max_languages: Some(5),
+        ..Default::default()
+    }
+    .render()
+}
+
+/// Renders an [ErrorCard] example with the given message, error cards always use the light theme.
+pub fn error_example(message: &str) -> Result<Svg, CardError> {
+    ErrorCard::new(message.to_string()).render()
+}
+
+fn dummy_language_stats() -> Vec<LanguageStat> {
+    [
+        ("Rust", 45000, 15),
+        ("TypeScript", 35000, 12),
+        ("JavaScript", 25000, 8),
+        ("Python", 15000, 6),
+        ("Go", 10000, 4),
+    ]
+    .into_iter()
+    .map(|(name, size_bytes, repo_count)| LanguageStat {
+        name: name.to_string(),
+        size_bytes,
+        repo_count,
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod fn_stats_example {
+        use super::*;
+
+        #[test]
+        fn renders_dummy_data() {
+            let svg = stats_example(CardTheme::Dark).unwrap();
+            assert!(svg.starts_with("<svg"));
+            assert!(svg.trim_end().ends_with("</svg>"));
+            assert!(svg.contains("@octocat: GitHub Stats"));
+            assert!(svg.contains(">1.2k</text>"));
+            assert!(svg.contains(CardTheme::Dark.load_css().lines().last().unwrap()));
+        }
+
+        #[test]
+        fn transparent_hides_background() {
+            let svg = stats_example_with(example_settings(CardTheme::Dark, true)).unwrap();
+            assert!(!svg.contains("class=\"background\""));
+        }
+    }
+
+    mod fn_langs_example {
+        use super::*;
+
+        #[test]
+        fn renders_dummy_data_in_every_layout() {
+            for layout in [
+                LayoutType::Vertical,
+                LayoutType::Horizontal,
+                LayoutType::Donut,
+            ] {
+                let svg = langs_example(CardTheme::Dracula, layout).unwrap();
+                assert!(svg.starts_with("<svg"));
+                assert!(svg.contains("Most used languages"));
+                for name in ["Rust", "TypeScript", "JavaScript", "Python", "Go"] {
+                    assert!(svg.contains(name), "{name} missing in {layout:?} layout");
+                }
+            }
+        }
+    }
+
+    // Snapshots of the rendered examples, review the changes with `make snapshots-review`
+    mod snapshots {
+        use super::*;
+
+        /// Light and dark defaults, a colorful theme and the transparent one.
+        const THEMES: [CardTheme; 4] = [
+            CardTheme::Light,
+            CardTheme::Dark,
+            CardTheme::Dracula,
+            CardTheme::TransparentBlue,
+        ];
+
+        #[test]
+        fn stats_card() {
+            for theme in THEMES {
+                let name = format!("stats_{}", theme.name());
+                insta::assert_snapshot!(name, stats_example(theme).unwrap());
+            }
+        }
+
+        #[test]
+        fn langs_card() {
+            for theme in THEMES {
+                for layout in [
+                    LayoutType::Vertical,
+                    LayoutType::Horizontal,
+                    LayoutType::Donut,
+                ] {
+                    let name = format!("langs_{}_{}", layout.name(), theme.name());
+                    insta::assert_snapshot!(name, langs_example(theme.clone(), layout).unwrap());
+                }
+            }
+        }
+
+        #[test]
+        fn error_card() {
+            for (name, message) in [("short", SHORT_ERROR_MESSAGE), ("long", LONG_ERROR_MESSAGE)] {
+                let svg = error_example(message).unwrap();
+                insta::assert_snapshot!(format!("error_{name}"), svg);
+            }
+        }
+    }
+
+    mod fn_error_example {
+        use super::*;
+
+        #[test]
+        fn renders_message() {
+            let svg = error_example(SHORT_ERROR_MESSAGE).unwrap();
+            assert!(svg.starts_with("<svg"));
+            assert!(svg.contains(SHORT_ERROR_MESSAGE));
+        }
+    }
+}
@@ -1,13 +1,354 @@
+use std::{collections::HashMap, sync::OnceLock};
+
+/// Color used for languages without a known color.
+const FALLBACK_LANGUAGE_COLOR: &str = "#000000";
+
+// Language colors parsed once from the bundled linguist JSON
+static LANGUAGE_COLORS: OnceLock<HashMap<String, String>> = OnceLock::new();
+
+/// Parses the language colors JSON. On failure logs an error and returns an empty map,
+/// so lookups fall back to the default color instead of panicking in a request handler.
+fn parse_language_colors(json: &str) -> HashMap<String, String> {
+    serde_json::from_str(json).unwrap_or_else(|e| {
+        tracing::error!("Failed to parse language colors JSON: {e}");
+        HashMap::new()
+    })
+}
+
+fn language_color_from(colors: &HashMap<String, String>, language: &str) -> String {
+    colors
+        .get(language)
+        .cloned()
+        .unwrap_or_else(|| FALLBACK_LANGUAGE_COLOR.to_string())
+}
+
 /// A helper functions to retrieve the color associated with a programming language.
 pub fn gel_language_color(language: &str) -> String {
-    let json_str = include_str!("../../assets/configs/language-colors.json");
-    let colors: serde_json::Value =
-        serde_json::from_str(json_str).expect("Failed to parse language colors JSON");
+    let colors = LANGUAGE_COLORS.get_or_init(|| {
+        parse_language_colors(include_str!("../../assets/configs/language-colors.json"))
+    });
+    language_color_from(colors, language)
+}
+
+/// Truncates `text` to at most `max_chars` characters, ending it with an ellipsis.
+/// Returns `None` when the text already fits.
+pub fn truncate_with_ellipsis(text: &str, max_chars: usize) -> Option<String> {
+    if text.chars().count() <= max_chars {
+        return None;
+    }
+
+    let kept: String = text.chars().take(max_chars.saturating_sub(1)).collect();
+    Some(format!("{}…", kept.trim_end()))
+}
+
+/// Escapes the characters with a meaning in XML (`&`, `<`, `>` and the quotes), for
+/// user-controlled text written into the SVG elements and attributes.
+pub fn escape_xml_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Formats a byte count into a short human-readable string (e.g. `1.2 MB`).
+pub fn format_bytes(bytes: usize) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{value:.1} {}", UNITS[unit])
+}
+
+/// Decimal places kept for coordinates and lengths written to the SVG.
+pub const COORD_PRECISION: usize = 2;
+
+/// Formats `value` with at most `precision` decimals, dropping trailing zeros
+/// (e.g. `10` rather than `10.00`, `10.5` rather than `10.50`).
+pub fn format_decimal(value: f64, precision: usize) -> String {
+    let formatted = format!("{value:.precision$}");
+    let trimmed = if formatted.contains('.') {
+        formatted.trim_end_matches('0').trim_end_matches('.')
+    } else {
+        &formatted
+    };
+    match trimmed {
+        "-0" => "0".to_string(),
+        trimmed => trimmed.to_string(),
+    }
+}
+
+/// Formats an SVG coordinate or length with [COORD_PRECISION] decimals at most,
+/// keeping the output small and stable between renders.
+pub fn format_coord(value: f64) -> String {
+    format_decimal(value, COORD_PRECISION)
+}
 
-    if let Some(color) = colors.get(language) {
-        color.as_str().unwrap_or("#000000").to_string()
+/// Splits `total` into widths proportional to `shares`, each one at least `min`.
+/// The shares under the minimum are raised to it and the others shrunk to
+/// compensate, so the widths still sum up to `total`. When `min` doesn't fit
+/// for every share, `total` is split evenly instead.
+pub fn distribute_widths(shares: &[f64], total: f64, min: f64) -> Vec<f64> {
+    if shares.is_empty() {
+        return Vec::new();
+    }
+    if min * shares.len() as f64 >= total {
+        return vec![total / shares.len() as f64; shares.len()];
+    }
+
+    // Pin the shares falling under the minimum until the rest scale above it
+    let mut pinned = vec![false; shares.len()];
+    loop {
+        let free_total = total - min * pinned.iter().filter(|&&p| p).count() as f64;
+        let free_shares: f64 = shares
+            .iter()
+            .zip(&pinned)
+            .filter(|(_, p)| !**p)
+            .map(|(share, _)| share.max(0.0))
+            .sum();
+        let scale = if free_shares > 0.0 {
+            free_total / free_shares
+        } else {
+            0.0
+        };
+
+        let mut changed = false;
+        for (share, pin) in shares.iter().zip(pinned.iter_mut()) {
+            if !*pin && share.max(0.0) * scale < min {
+                *pin = true;
+                changed = true;
+            }
+        }
+        if !changed {
+            return shares
+                .iter()
+                .zip(&pinned)
+                .map(|(share, &pin)| if pin { min } else { share.max(0.0) * scale })
+                .collect();
+        }
+    }
+}
+
+/// Minimum contrast ratio between a bar color and the card background, the WCAG
+/// threshold for graphical objects.
+pub const MIN_CONTRAST_RATIO: f64 = 3.0;
+
+/// Parses a `#rgb`, `#rrggbb` or `#rrggbbaa` color into its RGBA channels.
+pub fn parse_hex_color(color: &str) -> Option<[u8; 4]> {
+    let hex = color.trim().strip_prefix('#')?;
+    if !hex.is_ascii() {
+        return None;
+    }
+    let channel = |i: usize, len: usize| {
+        u8::from_str_radix(&hex[i * len..(i + 1) * len], 16)
+            .ok()
+            .map(|v| if len == 1 { v * 17 } else { v })
+    };
+    match hex.len() {
+        3 => Some([channel(0, 1)?, channel(1, 1)?, channel(2, 1)?, 255]),
+        6 => Some([channel(0, 2)?, channel(1, 2)?, channel(2, 2)?, 255]),
+        8 => Some([
+            channel(0, 2)?,
+            channel(1, 2)?,
+            channel(2, 2)?,
+            channel(3, 2)?,
+        ]),
+        _ => None,
+    }
+}
+
+/// WCAG relative luminance of an RGB color, from `0.0` (black) to `1.0` (white).
+fn relative_luminance([r, g, b]: [f64; 3]) -> f64 {
+    let linear = |c: f64| {
+        let c = c / 255.0;
+        if c <= 0.03928 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    0.2126 * linear(r) + 0.7152 * linear(g) + 0.0722 * linear(b)
+}
+
+fn contrast_ratio_of(a: [f64; 3], b: [f64; 3]) -> f64 {
+    let (a, b) = (relative_luminance(a), relative_luminance(b));
+    (a.max(b) + 0.05) / (a.min(b) + 0.05)
+}
+
+/// WCAG contrast ratio between two colors, from `1.0` (same luminance) to `21.0`.
+/// `None` when one of them isn't a hex color.
+pub fn contrast_ratio(color: &str, background: &str) -> Option<f64> {
+    let rgb = |c: [u8; 4]| [c[0] as f64, c[1] as f64, c[2] as f64];
+    Some(contrast_ratio_of(
+        rgb(parse_hex_color(color)?),
+        rgb(parse_hex_color(background)?),
+    ))
+}
+
+/// Darkens (on light backgrounds) or lightens (on dark ones) `color` until it has at
+/// least `min_ratio` contrast against `background`. Colors that already do, that
+/// can't be parsed or that are drawn on a see-through background are left unchanged.
+pub fn ensure_contrast(color: &str, background: &str, min_ratio: f64) -> String {
+    let (Some(fg), Some(bg)) = (parse_hex_color(color), parse_hex_color(background)) else {
+        return color.to_string();
+    };
+    let [r, g, b, _] = fg.map(f64::from);
+    let [br, bgg, bb, alpha] = bg.map(f64::from);
+    let (fg, bg) = ([r, g, b], [br, bgg, bb]);
+    if alpha < 255.0 || contrast_ratio_of(fg, bg) >= min_ratio {
+        return color.to_string();
+    }
+
+    let target = if relative_luminance(bg) > 0.5 {
+        0.0
     } else {
-        "#000000".to_string()
+        255.0
+    };
+    let mut adjusted = fg;
+    for step in 1..=20 {
+        let t = step as f64 / 20.0;
+        adjusted = fg.map(|c| c + (target - c) * t);
+        if contrast_ratio_of(adjusted, bg) >= min_ratio {
+            break;
+        }
+    }
+    let [r, g, b] = adjusted.map(|c| c.round() as u8);
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
+
+/// A `property: value` declaration of a CSS rule, along with the rule's selector.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CssDeclaration {
+    pub selector: String,
+    pub property: String,
+    pub value: String,
+}
+
+/// Extracts the declarations of flat CSS rules, including `--var: value;` custom properties,
+/// in source order. Comma-separated selectors yield one declaration per selector.
+/// Comments are skipped, nested blocks like `@media` are not supported.
+pub fn parse_css_declarations(css: &str) -> Vec<CssDeclaration> {
+    let mut uncommented = String::with_capacity(css.len());
+    let mut rest = css;
+    while let Some(start) = rest.find("/*") {
+        uncommented.push_str(&rest[..start]);
+        rest = rest[start + 2..]
+            .find("*/")
+            .map_or("", |end| &rest[start + 2 + end + 2..]);
+    }
+    uncommented.push_str(rest);
+
+    let mut declarations = Vec::new();
+    let mut rest = uncommented.as_str();
+    while let Some(open) = rest.find('{') {
+        let Some(close) = rest[open..].find('}').map(|close| open + close) else {
+            break;
+        };
+        let selectors = rest[..open].split(',').map(str::trim);
+        let body = &rest[open + 1..close];
+        for selector in selectors.filter(|s| !s.is_empty()) {
+            for declaration in body.split(';') {
+                let Some((property, value)) = declaration.split_once(':') else {
+                    continue;
+                };
+                let (property, value) = (property.trim(), value.trim());
+                if !property.is_empty() && !value.is_empty() {
+                    declarations.push(CssDeclaration {
+                        selector: selector.to_string(),
+                        property: property.to_string(),
+                        value: value.to_string(),
+                    });
+                }
+            }
+        }
+        rest = &rest[close + 1..];
+    }
+    declarations
+}
+
+/// Layout guides drawn on top of a card in debug mode: the content box inside
+/// the offsets, row baselines and the bounds of elements like bars.
+#[derive(Default)]
+pub struct DebugGuides {
+    baselines: Vec<u32>,
+    bounds: Vec<(u32, u32, u32, u32)>,
+}
+
+impl DebugGuides {
+    /// Adds a row baseline guide at the given Y position.
+    pub fn baseline(&mut self, y: u32) {
+        self.baselines.push(y);
+    }
+
+    /// Adds a bounding box guide.
+    pub fn bounds(&mut self, x: u32, y: u32, width: u32, height: u32) {
+        self.bounds.push((x, y, width, height));
+    }
+
+    /// Renders the guides for a card of the given size and offsets.
+    pub fn render(&self, width: u32, height: u32, offset_x: u32, offset_y: u32) -> String {
+        let mut guides = vec![format!(
+            r#"<rect x="{offset_x}" y="{offset_y}" width="{}" height="{}" stroke-dasharray="4 2"/>"#,
+            width.saturating_sub(offset_x * 2),
+            height.saturating_sub(offset_y * 2)
+        )];
+        guides.extend(self.baselines.iter().map(|y| {
+            format!(
+                r#"<line x1="{offset_x}" y1="{y}" x2="{}" y2="{y}"/>"#,
+                width.saturating_sub(offset_x)
+            )
+        }));
+        guides.extend(
+            self.bounds
+                .iter()
+                .map(|(x, y, w, h)| format!(r#"<rect x="{x}" y="{y}" width="{w}" height="{h}"/>"#)),
+        );
+
+        format!(
+            "<g class=\"debug\" fill=\"none\" stroke=\"#ff00ff\" stroke-opacity=\"0.6\" stroke-width=\"1\">\n  {}\n</g>",
+            guides.join("\n  ")
+        )
+    }
+}
+
+/// Parses the whole `svg` with `quick_xml`, panicking at the first malformed part.
+#[cfg(test)]
+pub(crate) fn assert_valid_xml(svg: &str) {
+    use quick_xml::Reader;
+    use quick_xml::events::Event;
+
+    let mut reader = Reader::from_str(svg);
+    let mut buf = Vec::new();
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Eof) => break,
+            Ok(Event::GeneralRef(reference)) => {
+                let name = reference.decode().unwrap();
+                assert!(
+                    ["amp", "lt", "gt", "quot", "apos"].contains(&name.as_ref())
+                        || reference.is_char_ref(),
+                    "Invalid SVG/XML: unknown entity &{name};"
+                );
+            }
+            Ok(_) => (),
+            Err(e) => panic!("Invalid SVG/XML: {e}"),
+        }
+        buf.clear();
     }
 }
 
@@ -24,4 +365,170 @@ mod tests {
         let color = gel_language_color("NonExistentLanguage");
         assert_eq!(color, "#000000");
     }
+    #[test]
+    fn test_gel_language_color_with_invalid_colors_json() {
+        let colors = parse_language_colors("{\"Rust\": \"#dea584\"");
+        assert!(colors.is_empty());
+        assert_eq!(language_color_from(&colors, "Rust"), "#000000");
+    }
+    #[test]
+    fn test_truncate_with_ellipsis_fits() {
+        assert_eq!(truncate_with_ellipsis("Rust", 4), None);
+    }
+    #[test]
+    fn test_truncate_with_ellipsis_too_long() {
+        assert_eq!(
+            truncate_with_ellipsis("Jupyter Notebook", 9),
+            Some("Jupyter…".to_string())
+        );
+        assert_eq!(
+            truncate_with_ellipsis("Protocol Buffer", 10),
+            Some("Protocol…".to_string())
+        );
+    }
+    #[test]
+    fn test_escape_xml_text() {
+        assert_eq!(
+            escape_xml_text(r#"<b>Tom & "Jerry's"</b>"#),
+            "&lt;b&gt;Tom &amp; &quot;Jerry&apos;s&quot;&lt;/b&gt;"
+        );
+        assert_eq!(escape_xml_text("F# & C++"), "F# &amp; C++");
+        assert_eq!(
+            escape_xml_text("My Open Source Work"),
+            "My Open Source Work"
+        );
+    }
+    #[test]
+    #[should_panic(expected = "Invalid SVG/XML")]
+    fn test_assert_valid_xml_rejects_raw_markup() {
+        assert_valid_xml("<svg><text>Tom & Jerry</text></svg>");
+    }
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(0), "0 B");
+        assert_eq!(format_bytes(1023), "1023 B");
+        assert_eq!(format_bytes(1024), "1.0 KB");
+        assert_eq!(format_bytes(1_258_291), "1.2 MB");
+        assert_eq!(format_bytes(5 * 1024 * 1024 * 1024), "5.0 GB");
+    }
+    #[test]
+    fn test_format_coord_trims_trailing_zeros() {
+        assert_eq!(format_coord(10.0), "10");
+        assert_eq!(format_coord(10.5), "10.5");
+        assert_eq!(format_coord(10.25), "10.25");
+        assert_eq!(format_coord(130.232558), "130.23");
+        assert_eq!(format_coord(99.999), "100");
+        assert_eq!(format_coord(-0.001), "0");
+        assert_eq!(format_coord(0.0), "0");
+    }
+    #[test]
+    fn test_format_decimal_precision() {
+        assert_eq!(format_decimal(2.675, 1), "2.7");
+        assert_eq!(format_decimal(1200.0, 0), "1200");
+        assert_eq!(format_decimal(0.125, 4), "0.125");
+    }
+    #[test]
+    fn test_debug_guides_render() {
+        let mut guides = DebugGuides::default();
+        guides.baseline(40);
+        guides.bounds(12, 48, 220, 8);
+
+        let svg = guides.render(300, 100, 12, 10);
+        assert!(svg.starts_with("<g class=\"debug\""));
+        assert!(
+            svg.contains(r#"<rect x="12" y="10" width="276" height="80" stroke-dasharray="4 2"/>"#)
+        );
+        assert!(svg.contains(r#"<line x1="12" y1="40" x2="288" y2="40"/>"#));
+        assert!(svg.contains(r#"<rect x="12" y="48" width="220" height="8"/>"#));
+    }
+    #[test]
+    fn test_parse_css_declarations() {
+        let css = "/* theme */\n.label, .value {\n  fill: #f0f6fc;\n}\n.errorCard { --error-color: #f85149; /* red */ }";
+        let declarations = parse_css_declarations(css);
+        let found: Vec<_> = declarations
+            .iter()
+            .map(|d| (d.selector.as_str(), d.property.as_str(), d.value.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (".label", "fill", "#f0f6fc"),
+                (".value", "fill", "#f0f6fc"),
+                (".errorCard", "--error-color", "#f85149"),
+            ]
+        );
+    }
+    #[test]
+    fn test_parse_css_declarations_malformed() {
+        assert!(parse_css_declarations(".title { fill: #fff").is_empty());
+        assert!(parse_css_declarations(".title { fill }").is_empty());
+        assert!(parse_css_declarations("/* unterminated .title { fill: #fff; }").is_empty());
+    }
+    #[test]
+    fn test_parse_hex_color() {
+        assert_eq!(parse_hex_color("#f1e05a"), Some([0xf1, 0xe0, 0x5a, 255]));
+        assert_eq!(parse_hex_color("#fff"), Some([255, 255, 255, 255]));
+        assert_eq!(parse_hex_color("#ffffff00"), Some([255, 255, 255, 0]));
+        assert_eq!(parse_hex_color("red"), None);
+        assert_eq!(parse_hex_color("#ggg"), None);
+        assert_eq!(parse_hex_color("#é12"), None);
+    }
+    #[test]
+    fn test_contrast_ratio() {
+        assert_eq!(contrast_ratio("#000000", "#ffffff"), Some(21.0));
+        assert_eq!(contrast_ratio("#123456", "#123456"), Some(1.0));
+        assert_eq!(contrast_ratio("red", "#ffffff"), None);
+    }
+    #[test]
+    fn test_ensure_contrast_adjusts_low_contrast_color() {
+        // JavaScript's yellow is barely visible on white
+        let adjusted = ensure_contrast("#f1e05a", "#ffffff", MIN_CONTRAST_RATIO);
+        assert_ne!(adjusted, "#f1e05a");
+        assert!(contrast_ratio(&adjusted, "#ffffff").unwrap() >= MIN_CONTRAST_RATIO);
+
+        // and a dark blue on a dark background is lightened
+        let adjusted = ensure_contrast("#083fa1", "#0d1117", MIN_CONTRAST_RATIO);
+        assert!(contrast_ratio(&adjusted, "#0d1117").unwrap() >= MIN_CONTRAST_RATIO);
+    }
+    #[test]
+    fn test_ensure_contrast_keeps_high_contrast_color() {
+        assert_eq!(
+            ensure_contrast("#083fa1", "#ffffff", MIN_CONTRAST_RATIO),
+            "#083fa1"
+        );
+        assert_eq!(
+            ensure_contrast("#f1e05a", "#ffffff00", MIN_CONTRAST_RATIO),
+            "#f1e05a"
+        );
+        assert_eq!(ensure_contrast("red", "#ffffff", MIN_CONTRAST_RATIO), "red");
+    }
+
+    #[test]
+    fn test_distribute_widths_keeps_proportions_above_the_minimum() {
+        assert_eq!(
+            distribute_widths(&[50.0, 30.0, 20.0], 200.0, 1.0),
+            [100.0, 60.0, 40.0]
+        );
+        assert!(distribute_widths(&[], 200.0, 1.0).is_empty());
+    }
+
+    #[test]
+    fn test_distribute_widths_raises_tiny_shares() {
+        let widths = distribute_widths(&[90.0, 9.9, 0.1], 100.0, 5.0);
+        assert_eq!(widths[2], 5.0);
+        assert!(widths.iter().all(|&w| w >= 5.0));
+        assert!((widths.iter().sum::<f64>() - 100.0).abs() < 1e-9);
+        // The larger shares keep their ratio
+        assert!((widths[0] / widths[1] - 90.0 / 9.9).abs() < 1e-9);
+
+        // Raising a share can push another one under the minimum
+        let widths = distribute_widths(&[80.0, 5.5, 0.0, 0.0, 0.0], 100.0, 6.0);
+        assert_eq!(&widths[1..], [6.0; 4]);
+        assert!((widths[0] - 76.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_distribute_widths_splits_evenly_when_the_minimum_does_not_fit() {
+        assert_eq!(distribute_widths(&[99.0, 1.0], 10.0, 6.0), [5.0, 5.0]);
+    }
 }
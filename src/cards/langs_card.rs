@@ -1,6 +1,9 @@
 use crate::cards::{
-    card::{CardSettings, Svg},
-    helpers::gel_language_color,
+    card::{CardError, CardSettings, CardTheme, Svg},
+    helpers::{
+        DebugGuides, MIN_CONTRAST_RATIO, distribute_widths, ensure_contrast, escape_xml_text,
+        format_bytes, format_coord, gel_language_color, truncate_with_ellipsis,
+    },
 };
 use std::{cmp::Ordering, collections::HashMap};
 
@@ -54,14 +57,93 @@ impl LanguageStat {
     }
 }
 
+/// Formula used to rank languages, which also defines their displayed percentages.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Ranking {
+    /// `rank = (size_bytes ^ size_weight) * (repo_count ^ count_weight)`
+    PowerLaw { size_weight: f64, count_weight: f64 },
+    /// `rank = size_weight * size_share + count_weight * count_share`, where the shares
+    /// are taken against the totals of all languages and the weights sum up to 1.
+    Blend {
+        size_weight: f64,
+        count_weight: f64,
+        total_size: f64,
+        total_count: f64,
+    },
+}
+
+impl Ranking {
+    /// Creates a [Ranking::Blend] for the given stats, normalizing the weights
+    /// into proportions (equal ones if both are zero).
+    pub fn blend(stats: &[LanguageStat], size_weight: f64, count_weight: f64) -> Self {
+        let weights_sum = size_weight + count_weight;
+        let (size_weight, count_weight) = if weights_sum > 0.0 {
+            (size_weight / weights_sum, count_weight / weights_sum)
+        } else {
+            (0.5, 0.5)
+        };
+
+        Ranking::Blend {
+            size_weight,
+            count_weight,
+            total_size: stats.iter().map(|s| s.size_bytes as f64).sum(),
+            total_count: stats.iter().map(|s| s.repo_count as f64).sum(),
+        }
+    }
+
+    /// Calculates the rank of a single language.
+    pub fn rank(&self, stat: &LanguageStat) -> f64 {
+        match *self {
+            Ranking::PowerLaw {
+                size_weight,
+                count_weight,
+            } => stat.rank(size_weight, count_weight),
+            Ranking::Blend {
+                size_weight,
+                count_weight,
+                total_size,
+                total_count,
+            } => {
+                let share = |value: f64, total: f64| if total > 0.0 { value / total } else { 0.0 };
+                size_weight * share(stat.size_bytes as f64, total_size)
+                    + count_weight * share(stat.repo_count as f64, total_count)
+            }
+        }
+    }
+}
+
 /// Extension trait for [LanguageStat] slice to provide ranking and top N functionality.
 pub trait LanguageStatsExt {
+    /// Returns a new [Vec]<[LanguageStat]> sorted by descending rank of the given [Ranking].
+    fn ranked_by(&self, ranking: &Ranking) -> Vec<LanguageStat>;
+
+    /// Calculates the total rank of the [Vec]<[LanguageStat]> for the given [Ranking].
+    /// Better to be used on a full [Vec]<[LanguageStat]> array rather than on a slice of `top_n`.
+    fn total_rank_by(&self, ranking: &Ranking) -> f64;
+
+    /// Returns top N [LanguageStat] by rank of the given [Ranking] (descending).
+    fn top_n_by(&self, ranking: &Ranking, n: usize) -> Vec<LanguageStat> {
+        let mut ranked = self.ranked_by(ranking);
+        ranked.truncate(n);
+        ranked
+    }
+
     /// Returns a new [Vec]<[LanguageStat]> sorted by descending rank.
-    fn ranked(&self, size_weight: f64, count_weight: f64) -> Vec<LanguageStat>;
+    fn ranked(&self, size_weight: f64, count_weight: f64) -> Vec<LanguageStat> {
+        self.ranked_by(&Ranking::PowerLaw {
+            size_weight,
+            count_weight,
+        })
+    }
 
     /// Calculates the total rank of the [Vec]<[LanguageStat]>.
     /// Better to be used on a full [Vec]<[LanguageStat]> array rather than on a slice of `top_n`.
-    fn total_rank(&self, size_weight: f64, count_weight: f64) -> f64;
+    fn total_rank(&self, size_weight: f64, count_weight: f64) -> f64 {
+        self.total_rank_by(&Ranking::PowerLaw {
+            size_weight,
+            count_weight,
+        })
+    }
 
     /// Returns top N [LanguageStat] by rank (descending).
     fn top_n(&self, size_weight: f64, count_weight: f64, n: usize) -> Vec<LanguageStat> {
@@ -72,12 +154,12 @@ pub trait LanguageStatsExt {
 }
 
 impl LanguageStatsExt for [LanguageStat] {
-    fn ranked(&self, size_weight: f64, count_weight: f64) -> Vec<LanguageStat> {
+    fn ranked_by(&self, ranking: &Ranking) -> Vec<LanguageStat> {
         // Precompute ranks to avoid recomputation during sort comparisons.
         let mut with_rank: Vec<(f64, LanguageStat)> = self
             .iter()
             .cloned()
-            .map(|s| (s.rank(size_weight, count_weight), s))
+            .map(|s| (ranking.rank(&s), s))
             .collect();
 
         with_rank.sort_unstable_by(|a, b| {
@@ -88,22 +170,56 @@ impl LanguageStatsExt for [LanguageStat] {
         with_rank.into_iter().map(|(_, s)| s).collect()
     }
 
-    fn total_rank(&self, size_weight: f64, count_weight: f64) -> f64 {
-        self.iter().map(|s| s.rank(size_weight, count_weight)).sum()
+    fn total_rank_by(&self, ranking: &Ranking) -> f64 {
+        self.iter().map(|s| ranking.rank(s)).sum()
     }
 }
 
 /// Represents the layout type for the [LangsCard] (how the languages are displayed).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum LayoutType {
     Vertical,
     Horizontal,
+    /// A ring of arc segments with the legend on its right.
+    Donut,
+}
+
+impl LayoutType {
+    /// Returns the layout name, as used in the `layout` query parameter.
+    pub fn name(&self) -> &'static str {
+        match self {
+            LayoutType::Vertical => "vertical",
+            LayoutType::Horizontal => "horizontal",
+            LayoutType::Donut => "donut",
+        }
+    }
+
+    /// Returns how many languages the layout shows when the request doesn't say.
+    /// The horizontal layout pairs languages into two columns, so its default is even.
+    pub fn default_max_languages(&self) -> u64 {
+        match self {
+            LayoutType::Vertical => 8,
+            LayoutType::Horizontal | LayoutType::Donut => 6,
+        }
+    }
+}
+
+/// Display order of the languages selected for a [LangsCard].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LangsSort {
+    /// Highest rank first.
+    #[default]
+    Rank,
+    /// Alphabetical by name, the selection itself is still the top ranked languages.
+    Alpha,
 }
 
 /// Represents a card that displays language statistics for a GitHub user.
 /// It calculates the ranking of languages based on their size and repository count.
 /// The ranking is calculated using the formula:
 ///
-/// `rank = (size_bytes ^ size_weight) * (repo_count ^ count_weight)`.
+/// `rank = (size_bytes ^ size_weight) * (repo_count ^ count_weight)`,
+/// or as a blend of size and count shares when `normalize_weights` is set (see [Ranking]).
 pub struct LangsCard {
     pub card_settings: CardSettings,
     pub layout: LayoutType,
@@ -114,9 +230,76 @@ pub struct LangsCard {
     pub count_weight: Option<f64>,
     /// Maximum number of languages to display in the card.
     pub max_languages: Option<u64>,
+    /// Treat the weights as proportions of a [Ranking::Blend] instead of powers.
+    pub normalize_weights: bool,
+    /// Show percentages relative to the displayed languages only (summing up to 100%)
+    /// instead of the share of all languages.
+    pub normalize_percentages: bool,
+    /// Custom colors overriding the linguist defaults, keyed by lowercase language name.
+    pub color_overrides: HashMap<String, String>,
+    /// Shown in place of the languages when there are none to rank. Written into the
+    /// SVG as is, so it must not contain markup.
+    pub empty_note: String,
+    /// Darken or lighten the linguist colors that are hard to tell apart from the theme
+    /// background. Custom colors are kept as is.
+    pub auto_contrast: bool,
+    /// Order of the displayed languages.
+    pub sort: LangsSort,
+    /// Minimum width (pixels) of a language bar or bar segment, taken from the
+    /// larger ones so the total width stays the same.
+    pub min_bar_px: u32,
+    /// Percentage under which the displayed languages are summed up into a single
+    /// "Other" one, shown last. `None` shows every language on its own.
+    pub combine_below: Option<f64>,
+    /// Color of the combined "Other" language.
+    pub other_color: String,
+    /// Percentage under which the displayed languages are dropped. It is applied after
+    /// ranking, so the percentages of the remaining ones keep summing up to less than
+    /// 100%. The top language is kept whatever the threshold, and `0` keeps every one.
+    pub min_percent: f64,
+    /// Render only the legend (color dots, names and percentages) in two columns,
+    /// without any bar or ring whatever the layout.
+    pub legend_only: bool,
+}
+
+impl Default for LangsCard {
+    fn default() -> Self {
+        LangsCard {
+            card_settings: CardSettings {
+                offset_x: 12,
+                offset_y: 12,
+                theme: CardTheme::TransparentBlue,
+                hide_title: false,
+                hide_background: false,
+                hide_background_stroke: false,
+                ..Default::default()
+            },
+            layout: LayoutType::Vertical,
+            stats: Vec::new(),
+            size_weight: None,
+            count_weight: None,
+            max_languages: None,
+            normalize_weights: false,
+            normalize_percentages: false,
+            color_overrides: HashMap::new(),
+            empty_note: Self::DEFAULT_EMPTY_NOTE.to_string(),
+            auto_contrast: false,
+            sort: LangsSort::Rank,
+            min_bar_px: Self::DEFAULT_MIN_BAR_PX,
+            combine_below: None,
+            other_color: Self::DEFAULT_OTHER_COLOR.to_string(),
+            min_percent: 0.0,
+            legend_only: false,
+        }
+    }
 }
 
 impl LangsCard {
+    pub const DEFAULT_EMPTY_NOTE: &'static str = "No source repositories found";
+    pub const DEFAULT_MIN_BAR_PX: u32 = 1;
+    pub const MAX_MIN_BAR_PX: u32 = 40;
+    pub const OTHER_LANGUAGE: &'static str = "Other";
+    pub const DEFAULT_OTHER_COLOR: &'static str = "#ededed";
     const MAX_LANGUAGES: u64 = 20;
     const TITLE_BODY_OFFSET: u32 = 26;
     const ROW_Y_STEP: u32 = 36;
@@ -132,61 +315,86 @@ impl LangsCard {
     const HORIZONTAL_CIRCLE_SIZE: u32 = 8;
     const HORIZONTAL_CIRCLE_TEXT_GAP: u32 = 6;
     const HORIZONTAL_ROW_Y_STEP: u32 = 24;
+    /// Moves the legend-only rows up, so the first dots sit where the horizontal bar would
+    const LEGEND_ONLY_SHIFT: u32 = 6;
+
+    // Donut layout constants
+    const DONUT_SIZE: u32 = 100;
+    const DONUT_THICKNESS: u32 = 20;
+    const DONUT_LEGEND_GAP: u32 = 20;
+    /// How far above the first baseline the ring starts, as the horizontal bar does
+    const DONUT_TOP_SHIFT: u32 = 10;
 
-    pub fn render(&self) -> Svg {
+    /// Approximate average glyph width of the label font in pixels,
+    /// used to estimate how many characters fit into a column.
+    const LABEL_CHAR_WIDTH: f64 = 6.0;
+
+    pub fn render(&self) -> Result<Svg, CardError> {
         use crate::cards::card::Card;
         // Title block height (title + small gap) unless title is hidden
         let header_size_y = if self.card_settings.hide_title {
             0
         } else {
-            Card::TITLE_FONT_SIZE + Self::TITLE_BODY_OFFSET
+            self.card_settings.title_size + Self::TITLE_BODY_OFFSET
         };
 
         // Starting baseline (text y) for the first row.
         // For vertical layout when title is hidden, add some top padding
-        let mut y: u32 =
-            if self.card_settings.hide_title && matches!(self.layout, LayoutType::Vertical) {
-                header_size_y + self.card_settings.offset_y + Self::VERTICAL_TOP_PADDING
-            } else {
-                header_size_y + self.card_settings.offset_y
-            };
-
-        let max_langs = self
-            .max_languages
-            .unwrap_or(Self::MAX_LANGUAGES)
-            .min(Self::MAX_LANGUAGES);
+        let mut y: u32 = if self.card_settings.hide_title
+            && !self.legend_only
+            && matches!(self.layout, LayoutType::Vertical)
+        {
+            header_size_y + self.card_settings.offset_y + Self::VERTICAL_TOP_PADDING
+        } else {
+            header_size_y + self.card_settings.offset_y
+        };
 
-        let top_langs = self.stats.top_n(
-            self.size_weight.unwrap_or(1.0),
-            self.count_weight.unwrap_or(0.0),
-            max_langs as usize,
-        );
+        let percentages = self.computed_percentages();
 
         let mut lines = Vec::new();
-        let total_rank = self.stats.total_rank(
-            self.size_weight.unwrap_or(1.0),
-            self.count_weight.unwrap_or(0.0),
-        );
 
+        let mut guides = DebugGuides::default();
+        if percentages.is_empty() {
+            // Nothing to rank, explain the empty card instead
+            let note_y = y + Self::ROW_Y_STEP / 2;
+            lines.push(format!(
+                "<g class=\"row\">\n<text class=\"label\" x=\"{}\" y=\"{note_y}\">{}</text>\n</g>",
+                self.card_settings.offset_x, self.empty_note
+            ));
+            guides.baseline(note_y);
+        }
         match self.layout {
+            _ if percentages.is_empty() => {}
+            // The labels of the horizontal layout alone, where its bar would start
+            _ if self.legend_only => {
+                let label_y = y.saturating_sub(Self::LEGEND_ONLY_SHIFT);
+                lines.extend(self.render_legend(&percentages, label_y, &mut guides));
+            }
             LayoutType::Vertical => {
-                for stat in top_langs.iter() {
-                    let color = gel_language_color(&stat.name);
+                let values: Vec<f64> = percentages.iter().map(|(_, value)| *value).collect();
+                let bar_widths = self.vertical_bar_widths(&values);
+
+                for ((stat, value), bar_width) in percentages.iter().zip(bar_widths) {
+                    let color = self.language_color(&stat.name);
                     let label = &stat.name;
-                    let rank = stat.rank(
-                        self.size_weight.unwrap_or(1.0),
-                        self.count_weight.unwrap_or(0.0),
-                    );
-                    // Value is the percentage of the total rank.
-                    let value = rank / total_rank * 100.0;
+                    let value = *value;
 
                     lines.push(Self::render_line_vertical(
                         &color,
                         label,
                         value,
+                        bar_width,
+                        &Self::tooltip(stat, value),
                         self.card_settings.offset_x,
                         y,
                     ));
+                    guides.baseline(y);
+                    guides.bounds(
+                        self.card_settings.offset_x,
+                        y + Self::BAR_HEIGHT,
+                        Self::VERTICAL_BAR_WIDTH,
+                        Self::BAR_HEIGHT,
+                    );
 
                     y += Self::ROW_Y_STEP;
                 }
@@ -195,85 +403,76 @@ impl LangsCard {
                 // Create a single horizontal bar with stacked segments
                 let total_width = Self::HORIZONTAL_COLUMN_WIDTH * 2 + Self::HORIZONTAL_COLUMN_GAP;
                 let bar_spacing = 10;
+                // The bar sits above the baseline, but not above the top edge of the card
+                // when the title is hidden and the offset is smaller than the spacing
+                let bar_y = y.saturating_sub(bar_spacing);
 
-                lines.push(Self::render_horizontal_bar(
-                    &top_langs,
-                    self.size_weight.unwrap_or(1.0),
-                    self.count_weight.unwrap_or(0.0),
+                lines.push(self.render_horizontal_bar(
+                    &percentages,
                     self.card_settings.offset_x,
-                    y - bar_spacing,
+                    bar_y,
                     total_width,
                 ));
+                guides.bounds(
+                    self.card_settings.offset_x,
+                    bar_y,
+                    total_width,
+                    Self::BAR_HEIGHT,
+                );
 
-                y += Self::BAR_HEIGHT + bar_spacing;
-
-                // Add language labels below the bar in columnar order (1,3 / 2,4)
-                let mut label_y = y;
-                let num_rows = top_langs.len().div_ceil(2);
-
-                for row_index in 0..num_rows {
-                    let mut row_items = Vec::new();
-
-                    // First column: languages at positions 0, 2, 4, ...
-                    if let Some(stat) = top_langs.get(row_index) {
-                        let color = gel_language_color(&stat.name);
-                        let label = &stat.name;
-                        let rank = stat.rank(
-                            self.size_weight.unwrap_or(1.0),
-                            self.count_weight.unwrap_or(0.0),
-                        );
-                        let value = rank / total_rank * 100.0;
-
-                        row_items.push(Self::render_line_horizontal(
-                            &color,
-                            label,
-                            value,
-                            self.card_settings.offset_x,
-                            label_y,
-                        ));
-                    }
-
-                    // Second column: languages at positions num_rows, num_rows+1, ...
-                    if let Some(stat) = top_langs.get(row_index + num_rows) {
-                        let color = gel_language_color(&stat.name);
-                        let label = &stat.name;
-                        let rank = stat.rank(
-                            self.size_weight.unwrap_or(1.0),
-                            self.count_weight.unwrap_or(0.0),
-                        );
-                        let value = rank / total_rank * 100.0;
-
-                        let x_offset = self.card_settings.offset_x
-                            + Self::HORIZONTAL_COLUMN_WIDTH
-                            + Self::HORIZONTAL_COLUMN_GAP;
-
-                        row_items.push(Self::render_line_horizontal(
-                            &color, label, value, x_offset, label_y,
-                        ));
-                    }
+                y = bar_y + bar_spacing + Self::BAR_HEIGHT + bar_spacing;
 
-                    lines.push(format!("<g class=\"row\">\n{}\n</g>", row_items.join("\n")));
+                // Add language labels below the bar
+                lines.extend(self.render_legend(&percentages, y, &mut guides));
+            }
+            LayoutType::Donut => {
+                let body_top = self.donut_body_top(y);
+                let body_height = Self::donut_body_height(percentages.len());
+                let x = self.card_settings.offset_x;
+
+                // The ring and the legend are both centered in the body
+                let ring_top = body_top + (body_height - Self::DONUT_SIZE) / 2;
+                lines.push(self.render_donut(&percentages, x, ring_top));
+                guides.bounds(x, ring_top, Self::DONUT_SIZE, Self::DONUT_SIZE);
+
+                let legend_x = x + Self::DONUT_SIZE + Self::DONUT_LEGEND_GAP;
+                let legend_height = Self::HORIZONTAL_ROW_Y_STEP * percentages.len() as u32;
+                let mut label_y =
+                    body_top + (body_height - legend_height) / 2 + Self::HORIZONTAL_ROW_Y_STEP / 2;
+                for (stat, value) in &percentages {
+                    let color = self.language_color(&stat.name);
+                    lines.push(format!(
+                        "<g class=\"row\">\n{}\n</g>",
+                        Self::render_line_horizontal(&color, &stat.name, *value, legend_x, label_y)
+                    ));
+                    guides.baseline(label_y);
                     label_y += Self::HORIZONTAL_ROW_Y_STEP;
                 }
             }
         }
 
-        let body = lines.join("\n");
-
         // TODO: Note height calculation is 3px smaller than the actual height. Need to fix it.
         let height = match self.layout {
+            _ if percentages.is_empty() => {
+                header_size_y + Self::ROW_Y_STEP + self.card_settings.offset_y * 2
+            }
+            _ if self.legend_only => {
+                Self::HORIZONTAL_ROW_Y_STEP * percentages.len().div_ceil(2) as u32
+                    + header_size_y
+                    + self.card_settings.offset_y * 2
+            }
             LayoutType::Vertical => {
                 if self.card_settings.hide_title {
-                    Self::ROW_Y_STEP * top_langs.len() as u32 + self.card_settings.offset_y * 2
+                    Self::ROW_Y_STEP * percentages.len() as u32 + self.card_settings.offset_y * 2
                 } else {
-                    Self::ROW_Y_STEP * top_langs.len() as u32
+                    Self::ROW_Y_STEP * percentages.len() as u32
                         + header_size_y
                         + self.card_settings.offset_y * 2
                 }
             }
             LayoutType::Horizontal => {
                 // For horizontal layout, we have a bar + grouped labels (2 per row)
-                let num_rows = top_langs.len().div_ceil(2); // Ceiling division for label rows
+                let num_rows = percentages.len().div_ceil(2); // Ceiling division for label rows
 
                 if self.card_settings.hide_title {
                     Self::BAR_HEIGHT
@@ -286,9 +485,20 @@ impl LangsCard {
                         + self.card_settings.offset_y * 2
                 }
             }
+            LayoutType::Donut => {
+                self.donut_body_top(header_size_y + self.card_settings.offset_y)
+                    + Self::donut_body_height(percentages.len())
+                    + self.card_settings.offset_y
+            }
         };
 
         let width: u32 = match self.layout {
+            // Two columns with gap, as the labels of the horizontal layout
+            _ if self.legend_only => {
+                Self::HORIZONTAL_COLUMN_WIDTH * 2
+                    + Self::HORIZONTAL_COLUMN_GAP
+                    + self.card_settings.offset_x * 2
+            }
             LayoutType::Vertical => {
                 Self::VERTICAL_BAR_WIDTH
                     + self.card_settings.offset_x * 2
@@ -301,8 +511,26 @@ impl LangsCard {
                     + Self::HORIZONTAL_COLUMN_GAP
                     + self.card_settings.offset_x * 2
             }
+            LayoutType::Donut => {
+                // Ring and a legend column
+                Self::DONUT_SIZE
+                    + Self::DONUT_LEGEND_GAP
+                    + Self::HORIZONTAL_COLUMN_WIDTH
+                    + self.card_settings.offset_x * 2
+            }
         };
 
+        if self.card_settings.debug {
+            lines.push(guides.render(
+                width,
+                height,
+                self.card_settings.offset_x,
+                self.card_settings.offset_y,
+            ));
+        }
+
+        let body = lines.join("\n");
+
         let card = Card::new(
             width,
             height,
@@ -311,19 +539,129 @@ impl LangsCard {
             body,
             "langsCard".to_string(),
             self.card_settings.clone(),
+        )?;
+
+        Ok(card.render())
+    }
+
+    /// Returns the displayed top languages in the [LangsSort] order, each with its
+    /// percentage of the total rank (see `top_languages`), without the ones under
+    /// `min_percent` but the top one. This is what the card shows.
+    pub fn computed_percentages(&self) -> Vec<(LanguageStat, f64)> {
+        let ranking = self.ranking();
+        let (top_langs, total_rank) = self.top_languages(&ranking);
+
+        let mut percentages: Vec<_> = top_langs
+            .into_iter()
+            .map(|stat| {
+                let value = ranking.rank(&stat) / total_rank * 100.0;
+                (stat, value)
+            })
+            .collect();
+        // A threshold above every language still keeps the top one, the card would
+        // claim there are no languages otherwise
+        let top = percentages
+            .iter()
+            .map(|(_, value)| *value)
+            .fold(0.0, f64::max);
+        percentages.retain(|(_, value)| *value >= self.min_percent.min(top));
+        match self.combine_below {
+            Some(threshold) => Self::combine_small_languages(percentages, threshold),
+            None => percentages,
+        }
+    }
+
+    /// Sums up the languages under `threshold` percent into an "Other" one appended
+    /// at the end, its size and repository count being the sums of theirs. A single
+    /// language under the threshold is kept as is.
+    fn combine_small_languages(
+        percentages: Vec<(LanguageStat, f64)>,
+        threshold: f64,
+    ) -> Vec<(LanguageStat, f64)> {
+        let is_small = |value: f64| value < threshold;
+        if percentages
+            .iter()
+            .filter(|(_, value)| is_small(*value))
+            .count()
+            < 2
+        {
+            return percentages;
+        }
+
+        let (mut kept, small): (Vec<_>, Vec<_>) = percentages
+            .into_iter()
+            .partition(|(_, value)| !is_small(*value));
+
+        let other = small.into_iter().fold(
+            (
+                LanguageStat {
+                    name: Self::OTHER_LANGUAGE.to_string(),
+                    size_bytes: 0,
+                    repo_count: 0,
+                },
+                0.0,
+            ),
+            |(mut other, total), (stat, value)| {
+                other.size_bytes = other.size_bytes.saturating_add(stat.size_bytes);
+                other.repo_count = other.repo_count.saturating_add(stat.repo_count);
+                (other, total + value)
+            },
         );
+        kept.push(other);
+        kept
+    }
+
+    /// Returns the displayed top languages along with the total rank their percentages
+    /// are relative to: the rank of all languages, or only of the displayed ones when
+    /// `normalize_percentages` is set so that the percentages sum up to 100%.
+    fn top_languages(&self, ranking: &Ranking) -> (Vec<LanguageStat>, f64) {
+        let max_langs = self
+            .max_languages
+            .unwrap_or(Self::MAX_LANGUAGES)
+            .min(Self::MAX_LANGUAGES);
+
+        let mut top_langs = self.stats.top_n_by(ranking, max_langs as usize);
+        if self.sort == LangsSort::Alpha {
+            top_langs.sort_by_cached_key(|stat| stat.name.to_lowercase());
+        }
+        let total_rank = if self.normalize_percentages {
+            top_langs.total_rank_by(ranking)
+        } else {
+            self.stats.total_rank_by(ranking)
+        };
 
-        match card {
-            Ok(card) => card.render(),
-            // TODO: handle error properly
-            Err(e) => format!("Failed to render LangsCard: {e}"),
+        (top_langs, total_rank)
+    }
+
+    /// Resolves the [Ranking] of the card from its weights, the blend shares
+    /// are taken against all stats rather than the displayed ones.
+    fn ranking(&self) -> Ranking {
+        let size_weight = self.size_weight.unwrap_or(1.0);
+        let count_weight = self.count_weight.unwrap_or(0.0);
+        if self.normalize_weights {
+            Ranking::blend(&self.stats, size_weight, count_weight)
+        } else {
+            Ranking::PowerLaw {
+                size_weight,
+                count_weight,
+            }
         }
     }
 
+    /// Widths of the vertical layout bars filled for the percentage `values`, each one
+    /// at least [LangsCard::min_bar_px] wide with the same total as without minimum.
+    fn vertical_bar_widths(&self, values: &[f64]) -> Vec<f64> {
+        let total = Self::VERTICAL_BAR_WIDTH as f64 * values.iter().sum::<f64>() / 100.0;
+        distribute_widths(values, total, self.min_bar_px as f64)
+    }
+
+    /// Renders a vertical layout row, its bar filled over `fill_width` pixels.
     fn render_line_vertical(
         color: &str,
         label: &str,
         value: f64,
+        fill_width: f64,
+        tooltip: &str,
         pos_x: u32,
         pos_y: u32,
     ) -> String {
@@ -337,15 +675,17 @@ impl LangsCard {
         let bar_width: u32 = Self::VERTICAL_BAR_WIDTH;
 
         let percent_str = format!("{value:.2}%");
-        let percent_bar_width = (bar_width as f64 * value / 100.0).round() as u32;
+        let fill_width = format_coord(fill_width.min(bar_width as f64));
+        let label = Self::render_label(label, Self::label_char_budget(bar_width));
 
         format!(
             r##"<g class="row">
+  <title>{tooltip}</title>
   <text x="{label_x}" y="{label_y}" class="label">{label}</text>
   <text x="{percent_x}" y="{percent_y}" class="value">{percent_str}</text>
   <svg width="{bar_width}" x="{bar_container_x}" y="{bar_container_y}">
       <rect rx="5" ry="5" x="0" y="0" width="{bar_width}" height="{bar_height}" class="progressBarBackground"/>
-      <rect rx="5" ry="5" x="0" y="0" width="{percent_bar_width}" height="{bar_height}" fill="{color}"/>
+      <rect rx="5" ry="5" x="0" y="0" width="{fill_width}" height="{bar_height}" fill="{color}"/>
   </svg>
 </g>"##
         )
@@ -364,6 +704,12 @@ impl LangsCard {
         let label_y = pos_y + 4;
 
         let percent_str = format!("{value:.2}%");
+        // The label shares the column with the percentage (and a space in between)
+        let label_width = Self::HORIZONTAL_COLUMN_WIDTH
+            - Self::HORIZONTAL_CIRCLE_SIZE
+            - Self::HORIZONTAL_CIRCLE_TEXT_GAP;
+        let budget = Self::label_char_budget(label_width).saturating_sub(percent_str.len() + 1);
+        let label = Self::render_label(label, budget);
 
         format!(
             r##"<circle cx="{circle_x}" cy="{circle_y}" r="{}" fill="{color}"/>
@@ -372,10 +718,181 @@ impl LangsCard {
         )
     }
 
+    /// Renders the labels of the languages in two columns from the baseline `pos_y`, in
+    /// columnar order (1,3 / 2,4), one row group per line.
+    fn render_legend(
+        &self,
+        percentages: &[(LanguageStat, f64)],
+        pos_y: u32,
+        guides: &mut DebugGuides,
+    ) -> Vec<String> {
+        let mut rows = Vec::new();
+        let mut label_y = pos_y;
+        let num_rows = percentages.len().div_ceil(2);
+
+        for row_index in 0..num_rows {
+            let mut row_items = Vec::new();
+
+            // First column: languages at positions 0, 1, ... num_rows - 1
+            if let Some((stat, value)) = percentages.get(row_index) {
+                let color = self.language_color(&stat.name);
+                row_items.push(Self::render_line_horizontal(
+                    &color,
+                    &stat.name,
+                    *value,
+                    self.card_settings.offset_x,
+                    label_y,
+                ));
+            }
+
+            // Second column: languages at positions num_rows, num_rows+1, ...
+            if let Some((stat, value)) = percentages.get(row_index + num_rows) {
+                let color = self.language_color(&stat.name);
+                let x_offset = self.card_settings.offset_x
+                    + Self::HORIZONTAL_COLUMN_WIDTH
+                    + Self::HORIZONTAL_COLUMN_GAP;
+                row_items.push(Self::render_line_horizontal(
+                    &color, &stat.name, *value, x_offset, label_y,
+                ));
+            }
+
+            rows.push(format!("<g class=\"row\">\n{}\n</g>", row_items.join("\n")));
+            guides.baseline(label_y);
+            label_y += Self::HORIZONTAL_ROW_Y_STEP;
+        }
+        rows
+    }
+
+    /// Top of the donut layout body, given the baseline `y` of the first row.
+    fn donut_body_top(&self, y: u32) -> u32 {
+        if self.card_settings.hide_title {
+            y
+        } else {
+            y.saturating_sub(Self::DONUT_TOP_SHIFT)
+        }
+    }
+
+    /// Height of the donut layout body: the ring or the legend of `languages` rows if taller.
+    fn donut_body_height(languages: usize) -> u32 {
+        Self::DONUT_SIZE.max(Self::HORIZONTAL_ROW_Y_STEP.saturating_mul(languages as u32))
+    }
+
+    /// Renders the ring of the donut layout at `pos_x`, `pos_y` (its top left corner),
+    /// one arc segment per language starting at the top and going clockwise. Like the
+    /// horizontal bar, the segments are relative to the displayed languages and their
+    /// arcs at least [LangsCard::min_bar_px] long, while tooltips show the percentages.
+    fn render_donut(&self, percentages: &[(LanguageStat, f64)], pos_x: u32, pos_y: u32) -> String {
+        let thickness = Self::DONUT_THICKNESS;
+        // The segments are stroked along the middle of the ring
+        let radius = (Self::DONUT_SIZE - thickness) as f64 / 2.0;
+        let center_x = pos_x as f64 + Self::DONUT_SIZE as f64 / 2.0;
+        let center_y = pos_y as f64 + Self::DONUT_SIZE as f64 / 2.0;
+        let point = |angle: f64| {
+            format!(
+                "{} {}",
+                format_coord(center_x + radius * angle.cos()),
+                format_coord(center_y + radius * angle.sin())
+            )
+        };
+        let radius_str = format_coord(radius);
+
+        // Relative to one another, they don't need to sum up to 100%
+        let shares: Vec<f64> = percentages.iter().map(|(_, value)| *value).collect();
+        let circumference = 2.0 * std::f64::consts::PI * radius;
+        let lengths = distribute_widths(&shares, circumference, self.min_bar_px as f64);
+
+        let mut segments = Vec::new();
+        let mut start = -std::f64::consts::FRAC_PI_2;
+        for ((stat, value), length) in percentages.iter().zip(lengths) {
+            let color = self.language_color(&stat.name);
+            let tooltip = Self::tooltip(stat, *value);
+            let sweep = length / radius;
+            let end = start + sweep;
+
+            // A single arc can't end where it starts, a full ring takes two halves
+            let d = if sweep >= 2.0 * std::f64::consts::PI - 1e-9 {
+                let half = start + std::f64::consts::PI;
+                format!(
+                    "M {} A {radius_str} {radius_str} 0 1 1 {} A {radius_str} {radius_str} 0 1 1 {}",
+                    point(start),
+                    point(half),
+                    point(start)
+                )
+            } else {
+                let large_arc = u8::from(sweep > std::f64::consts::PI);
+                format!(
+                    "M {} A {radius_str} {radius_str} 0 {large_arc} 1 {}",
+                    point(start),
+                    point(end)
+                )
+            };
+            segments.push(format!(
+                r##"<path class="donut-segment" d="{d}" fill="none" stroke="{color}" stroke-width="{thickness}"><title>{tooltip}</title></path>"##
+            ));
+            start = end;
+        }
+
+        format!("<g class=\"donut\">\n  {}\n</g>", segments.join("\n  "))
+    }
+
+    /// Returns the color for the language, preferring a custom override over the linguist default.
+    fn language_color(&self, name: &str) -> String {
+        if let Some(color) = self.color_overrides.get(&name.to_lowercase()) {
+            return color.clone();
+        }
+        if self.combine_below.is_some() && name == Self::OTHER_LANGUAGE {
+            return self.other_color.clone();
+        }
+        let color = gel_language_color(name);
+        if !self.auto_contrast || self.card_settings.hide_background {
+            return color;
+        }
+        match self.card_settings.theme.colors().background {
+            Some(background) => ensure_contrast(&color, &background, MIN_CONTRAST_RATIO),
+            None => color,
+        }
+    }
+
+    /// Tooltip describing the language share, size and repository count,
+    /// e.g. `Rust — 40.2% • 1.2 MB • 15 repos`.
+    fn tooltip(stat: &LanguageStat, percent: f64) -> String {
+        let repos = if stat.repo_count == 1 {
+            "repo"
+        } else {
+            "repos"
+        };
+        format!(
+            "{} — {percent:.1}% • {} • {} {repos}",
+            escape_xml_text(&stat.name),
+            format_bytes(stat.size_bytes),
+            stat.repo_count
+        )
+    }
+
+    /// Number of label characters that fit into `width` pixels.
+    fn label_char_budget(width: u32) -> usize {
+        (width as f64 / Self::LABEL_CHAR_WIDTH).floor() as usize
+    }
+
+    /// Renders the label text content, truncated with an ellipsis if it exceeds `max_chars`.
+    /// The full name is preserved in a `<title>` tooltip for truncated labels.
+    fn render_label(label: &str, max_chars: usize) -> String {
+        match truncate_with_ellipsis(label, max_chars) {
+            Some(truncated) => format!(
+                "<title>{}</title>{}",
+                escape_xml_text(label),
+                escape_xml_text(&truncated)
+            ),
+            None => escape_xml_text(label),
+        }
+    }
+
+    /// Renders the stacked bar of the horizontal layout. Segment widths are always relative
+    /// to the displayed languages and at least [LangsCard::min_bar_px] wide, while
+    /// tooltips show the percentages.
     fn render_horizontal_bar(
-        stats: &[LanguageStat],
-        size_weight: f64,
-        count_weight: f64,
+        &self,
+        percentages: &[(LanguageStat, f64)],
         pos_x: u32,
         pos_y: u32,
         total_width: u32,
@@ -384,35 +901,21 @@ impl LangsCard {
         let mut segments = Vec::new();
         let mut current_x = 0f64;
 
-        // Calculate total rank from reduced stats slice to calculate percentages width properly
-        let relative_total_rank = stats.total_rank(size_weight, count_weight);
-
-        // Calculate all percentages first
-        let percentages: Vec<f64> = stats
-            .iter()
-            .map(|stat| {
-                let rank = stat.rank(size_weight, count_weight);
-                rank / relative_total_rank * 100.0
-            })
-            .collect();
+        // The widths fill the bar with the displayed languages only, relative to one another
+        let shares: Vec<f64> = percentages.iter().map(|(_, value)| *value).collect();
+        let widths = distribute_widths(&shares, total_width as f64, self.min_bar_px as f64);
 
-        // Create segments with proper rounding to avoid gaps/overlaps
-        for (i, stat) in stats.iter().enumerate() {
-            let color = gel_language_color(&stat.name);
-
-            // Calculate the expected end position for this segment
-            let expected_end_x =
-                total_width as f64 * percentages[0..=i].iter().sum::<f64>() / 100.0;
-            let segment_width = (expected_end_x - current_x).round() as u32;
-
-            // Ensure minimum width of 1px for very small segments
-            let segment_width = segment_width.max(1);
+        // Each segment ends where the next one starts to avoid gaps/overlaps
+        for ((stat, value), segment_width) in percentages.iter().zip(widths) {
+            let color = self.language_color(&stat.name);
+            let tooltip = Self::tooltip(stat, *value);
 
             segments.push(format!(
-                r##"<rect mask="url(#bar-mask)" x="{}" y="0" width="{segment_width}" height="{bar_height}" fill="{color}"/>"##,
-                current_x.round() as u32
+                r##"<rect mask="url(#bar-mask)" x="{}" y="0" width="{}" height="{bar_height}" fill="{color}"><title>{tooltip}</title></rect>"##,
+                format_coord(current_x),
+                format_coord(segment_width)
             ));
-            current_x += segment_width as f64;
+            current_x += segment_width;
         }
 
         format!(
@@ -550,6 +1053,243 @@ mod tests {
         }
     }
 
+    mod fn_top_languages {
+        use super::*;
+
+        fn card(normalize_percentages: bool) -> LangsCard {
+            let stats = [("Rust", 5000), ("Go", 3000), ("C", 1500), ("Zig", 500)]
+                .into_iter()
+                .map(|(name, size_bytes)| LanguageStat {
+                    name: name.to_string(),
+                    size_bytes,
+                    repo_count: 1,
+                })
+                .collect();
+
+            LangsCard {
+                stats,
+                max_languages: Some(2),
+                normalize_percentages,
+                ..Default::default()
+            }
+        }
+
+        fn displayed_percentages(card: &LangsCard) -> Vec<f64> {
+            card.computed_percentages()
+                .into_iter()
+                .map(|(_, value)| value)
+                .collect()
+        }
+
+        #[test]
+        fn computed_percentages_follow_the_ranking() {
+            let names: Vec<String> = card(false)
+                .computed_percentages()
+                .into_iter()
+                .map(|(stat, _)| stat.name)
+                .collect();
+            assert_eq!(names, ["Rust", "Go"]);
+        }
+
+        #[test]
+        fn share_of_all_languages_by_default() {
+            let percentages = displayed_percentages(&card(false));
+            assert_eq!(percentages, [50.0, 30.0]);
+        }
+
+        #[test]
+        fn normalized_percentages_sum_up_to_100() {
+            let percentages = displayed_percentages(&card(true));
+            assert_eq!(percentages.len(), 2);
+            assert!((percentages.iter().sum::<f64>() - 100.0).abs() < 1e-9);
+            assert!((percentages[0] - 62.5).abs() < 1e-9);
+        }
+
+        #[test]
+        fn debug_guides_follow_the_layout() {
+            let plain = card(false).render().unwrap();
+            assert!(!plain.contains("class=\"debug\""));
+
+            for layout in [
+                LayoutType::Vertical,
+                LayoutType::Horizontal,
+                LayoutType::Donut,
+            ] {
+                let mut debug = LangsCard {
+                    layout,
+                    ..card(false)
+                };
+                debug.card_settings.debug = true;
+                let svg = debug.render().unwrap();
+                assert!(svg.contains("<g class=\"debug\""), "{layout:?}");
+                // Bar (or ring) bounds are outlined
+                let (width, height) = match layout {
+                    LayoutType::Vertical => (LangsCard::VERTICAL_BAR_WIDTH, LangsCard::BAR_HEIGHT),
+                    LayoutType::Horizontal => (
+                        LangsCard::HORIZONTAL_COLUMN_WIDTH * 2 + LangsCard::HORIZONTAL_COLUMN_GAP,
+                        LangsCard::BAR_HEIGHT,
+                    ),
+                    LayoutType::Donut => (LangsCard::DONUT_SIZE, LangsCard::DONUT_SIZE),
+                };
+                assert!(
+                    svg.contains(&format!("width=\"{width}\" height=\"{height}\"/>")),
+                    "{layout:?}"
+                );
+            }
+        }
+
+        #[test]
+        fn alpha_sort_orders_the_top_ranked_languages_by_name() {
+            let alpha_card = |layout| LangsCard {
+                layout,
+                max_languages: Some(3),
+                sort: LangsSort::Alpha,
+                ..card(false)
+            };
+            let displayed: Vec<(String, f64)> = alpha_card(LayoutType::Vertical)
+                .computed_percentages()
+                .into_iter()
+                .map(|(stat, value)| (stat.name, value))
+                .collect();
+            // Zig is still left out by rank, the percentages are unchanged
+            assert_eq!(
+                displayed,
+                [
+                    ("C".to_string(), 15.0),
+                    ("Go".to_string(), 30.0),
+                    ("Rust".to_string(), 50.0)
+                ]
+            );
+
+            for layout in [
+                LayoutType::Vertical,
+                LayoutType::Horizontal,
+                LayoutType::Donut,
+            ] {
+                let svg = alpha_card(layout).render().unwrap();
+                let position = |name: &str| svg.find(&format!("<title>{name} — ")).unwrap();
+                assert!(position("C") < position("Go"), "{layout:?}");
+                assert!(position("Go") < position("Rust"), "{layout:?}");
+                assert!(!svg.contains("Zig"), "{layout:?}");
+            }
+        }
+
+        #[test]
+        fn normalized_percentages_are_rendered() {
+            let svg = card(true).render().unwrap();
+            assert!(svg.contains(">62.50%</text>"));
+            assert!(svg.contains(">37.50%</text>"));
+            assert!(svg.contains("Rust — 62.5%"));
+        }
+    }
+
+    mod fn_ranking {
+        use super::*;
+
+        fn stats() -> [LanguageStat; 3] {
+            [
+                LanguageStat {
+                    name: "Rust".to_string(),
+                    size_bytes: 500,
+                    repo_count: 2,
+                },
+                LanguageStat {
+                    name: "Go".to_string(),
+                    size_bytes: 100,
+                    repo_count: 8,
+                },
+                LanguageStat {
+                    name: "C".to_string(),
+                    size_bytes: 500,
+                    repo_count: 1,
+                },
+            ]
+        }
+
+        #[test]
+        fn blend_differs_from_power_law() {
+            let stats = stats();
+            let power_law = Ranking::PowerLaw {
+                size_weight: 0.5,
+                count_weight: 0.5,
+            };
+            let blend = Ranking::blend(&stats, 0.5, 0.5);
+
+            // Power-law favours the big Rust codebase
+            let names: Vec<_> = stats
+                .ranked_by(&power_law)
+                .into_iter()
+                .map(|s| s.name)
+                .collect();
+            assert_eq!(names, ["Rust", "Go", "C"]);
+
+            // The blend gives Go's repo share (8/11) half of the say
+            let names: Vec<_> = stats
+                .ranked_by(&blend)
+                .into_iter()
+                .map(|s| s.name)
+                .collect();
+            assert_eq!(names, ["Go", "Rust", "C"]);
+
+            let go = blend.rank(&stats[1]);
+            let expected = 0.5 * 100.0 / 1100.0 + 0.5 * 8.0 / 11.0;
+            assert!(
+                (go - expected).abs() < 1e-9,
+                "rank was {go}, expected {expected}"
+            );
+        }
+
+        #[test]
+        fn blend_ranks_sum_up_to_one() {
+            let stats = stats();
+            let total = stats.total_rank_by(&Ranking::blend(&stats, 0.7, 0.3));
+            assert!((total - 1.0).abs() < 1e-9, "total was {total}");
+        }
+
+        #[test]
+        fn blend_normalizes_weights() {
+            let stats = stats();
+            assert_eq!(
+                Ranking::blend(&stats, 2.0, 2.0),
+                Ranking::blend(&stats, 0.5, 0.5)
+            );
+            assert_eq!(
+                Ranking::blend(&stats, 0.0, 0.0),
+                Ranking::blend(&stats, 1.0, 1.0)
+            );
+        }
+
+        #[test]
+        fn blend_without_stats() {
+            let ranking = Ranking::blend(&[], 1.0, 0.0);
+            assert_eq!(ranking.rank(&stats()[0]), 0.0);
+        }
+
+        #[test]
+        fn card_uses_blend_when_normalized() {
+            let card = LangsCard {
+                stats: stats().to_vec(),
+                size_weight: Some(0.5),
+                count_weight: Some(0.5),
+                normalize_weights: true,
+                ..Default::default()
+            };
+            assert_eq!(card.ranking(), Ranking::blend(&card.stats, 0.5, 0.5));
+
+            let card = LangsCard {
+                normalize_weights: false,
+                ..card
+            };
+            assert_eq!(
+                card.ranking(),
+                Ranking::PowerLaw {
+                    size_weight: 0.5,
+                    count_weight: 0.5
+                }
+            );
+        }
+    }
+
     mod fn_top_n {
         use super::*;
 
@@ -643,9 +1383,17 @@ mod tests {
             let pos_x = 10;
             let pos_y = 20;
 
-            let rendered = LangsCard::render_line_vertical(color, label, value, pos_x, pos_y);
+            let rendered = LangsCard::render_line_vertical(
+                color,
+                label,
+                value,
+                220.0 * value / 100.0,
+                "Tooltip",
+                pos_x,
+                pos_y,
+            );
             // Basic structure
-            assert!(rendered.contains("<g class=\"row\">"));
+            assert!(rendered.contains("<g class=\"row\">\n  <title>Tooltip</title>"));
             // Label and its coordinates
             assert!(rendered.contains("x=\"12\" y=\"20\" class=\"label\">Rust</text>"));
             // Percentage text and its coordinates, formatted to 2 decimals
@@ -656,8 +1404,54 @@ mod tests {
             assert!(
                 rendered.contains("width=\"220\" height=\"8\" class=\"progressBarBackground\"")
             );
-            // Foreground bar width rounding: round(220 * 30.55 / 100) = 67
-            assert!(rendered.contains("width=\"67\" height=\"8\" fill=\"#00ADD8\""));
+            // Foreground bar width keeps two decimals at most: 220 * 30.55 / 100 = 67.21
+            assert!(rendered.contains("width=\"67.21\" height=\"8\" fill=\"#00ADD8\""));
+            // Short labels are not truncated and have no label tooltip
+            assert_eq!(rendered.matches("<title>").count(), 1);
+        }
+
+        #[test]
+        fn test_render_line_vertical_truncates_long_label() {
+            let label = "An Extraordinarily Long Programming Language Name";
+            let rendered =
+                LangsCard::render_line_vertical("#00ADD8", label, 10.0, 22.0, "", 10, 20);
+            assert!(rendered.contains(&format!("<title>{label}</title>")));
+            assert!(rendered.contains("</title>An Extraordinarily Long Programming…</text>"));
+        }
+
+        #[test]
+        fn test_language_names_are_escaped_in_every_layout() {
+            let stats = vec![
+                LanguageStat {
+                    name: "F#".to_string(),
+                    size_bytes: 3000,
+                    repo_count: 2,
+                },
+                LanguageStat {
+                    name: "<Tom & \"Jerry\">".to_string(),
+                    size_bytes: 1000,
+                    repo_count: 1,
+                },
+            ];
+            for layout in [
+                LayoutType::Vertical,
+                LayoutType::Horizontal,
+                LayoutType::Donut,
+            ] {
+                let svg = LangsCard {
+                    stats: stats.clone(),
+                    layout,
+                    ..Default::default()
+                }
+                .render()
+                .unwrap();
+                crate::cards::helpers::assert_valid_xml(&svg);
+                assert!(svg.contains("F#"), "{layout:?}");
+                assert!(
+                    svg.contains("&lt;Tom &amp; &quot;Jerry&quot;&gt;"),
+                    "{layout:?}"
+                );
+            }
         }
     }
 
@@ -665,6 +1459,73 @@ mod tests {
         use super::*;
         use crate::cards::card::{CardSettings, CardTheme};
 
+        #[test]
+        fn every_theme_styles_the_progress_bar_background() {
+            for theme in CardTheme::all() {
+                let svg = LangsCard {
+                    card_settings: CardSettings {
+                        theme: theme.clone(),
+                        ..Default::default()
+                    },
+                    stats: vec![LanguageStat {
+                        name: "Rust".to_string(),
+                        size_bytes: 100,
+                        repo_count: 1,
+                    }],
+                    ..Default::default()
+                }
+                .render()
+                .unwrap();
+
+                assert!(svg.contains("class=\"progressBarBackground\""));
+                let style = &svg[svg.find("<style>").unwrap()..svg.find("</style>").unwrap()];
+                assert!(
+                    style.contains(".progressBarBackground {"),
+                    "{} leaves the progress bar background unstyled",
+                    theme.name()
+                );
+            }
+        }
+
+        #[test]
+        fn only_forks_render_the_empty_note() {
+            // Forks are filtered out by the query, so their languages never arrive
+            let stats = LanguageStat::from_edges(Vec::new());
+
+            for layout in [
+                LayoutType::Vertical,
+                LayoutType::Horizontal,
+                LayoutType::Donut,
+            ] {
+                let svg = LangsCard {
+                    stats: stats.clone(),
+                    layout,
+                    ..Default::default()
+                }
+                .render()
+                .unwrap();
+
+                assert!(
+                    svg.contains(">No source repositories found</text>"),
+                    "{layout:?}"
+                );
+                assert_eq!(svg.matches("<g class=\"row\">").count(), 1);
+                assert!(!svg.contains("NaN"));
+                assert!(!svg.contains("bar-mask"));
+            }
+        }
+
+        #[test]
+        fn custom_empty_note() {
+            let svg = LangsCard {
+                empty_note: "Only forks here".to_string(),
+                ..Default::default()
+            }
+            .render()
+            .unwrap();
+            assert!(svg.contains(">Only forks here</text>"));
+        }
+
         #[test]
         fn test_render() {
             let card = LangsCard {
@@ -675,6 +1536,7 @@ mod tests {
                     theme: CardTheme::TransparentBlue,
                     hide_background: false,
                     hide_background_stroke: false,
+                    ..Default::default()
                 },
                 layout: LayoutType::Vertical,
                 stats: vec![
@@ -697,9 +1559,10 @@ mod tests {
                 size_weight: Some(1.0),
                 count_weight: Some(0.0),
                 max_languages: Some(2),
+                ..Default::default()
             };
 
-            let svg = card.render();
+            let svg = card.render().unwrap();
             // Basic SVG structure and title
             assert!(svg.contains("<svg"));
             assert!(svg.contains("Most used languages"));
@@ -711,12 +1574,87 @@ mod tests {
             assert!(svg.contains(">46.51%</text>"));
             assert!(svg.contains(">30.23%</text>"));
             // Progress bar widths for 46.51% and 30.23% on 220px width
-            assert!(svg.contains("width=\"102\" height=\"8\" fill=\"#00ADD8\""));
-            assert!(svg.contains("width=\"67\" height=\"8\" fill=\"#f1e05a\""));
+            assert!(svg.contains("width=\"102.33\" height=\"8\" fill=\"#00ADD8\""));
+            assert!(svg.contains("width=\"66.51\" height=\"8\" fill=\"#f1e05a\""));
             // Rust should not appear since max_languages is 2
             assert!(!svg.contains(">Rust</text>"));
         }
 
+        #[test]
+        fn test_render_with_color_overrides() {
+            let stats = vec![
+                LanguageStat {
+                    name: "Rust".to_string(),
+                    size_bytes: 1000,
+                    repo_count: 10,
+                },
+                LanguageStat {
+                    name: "Go".to_string(),
+                    size_bytes: 2000,
+                    repo_count: 5,
+                },
+            ];
+            let color_overrides = HashMap::from([("rust".to_string(), "#ff0000".to_string())]);
+
+            for layout in [
+                LayoutType::Vertical,
+                LayoutType::Horizontal,
+                LayoutType::Donut,
+            ] {
+                let svg = LangsCard {
+                    layout,
+                    stats: stats.clone(),
+                    color_overrides: color_overrides.clone(),
+                    ..Default::default()
+                }
+                .render()
+                .unwrap();
+
+                // Overridden language uses the custom color
+                assert!(svg.contains("fill=\"#ff0000\""));
+                assert!(!svg.contains("#dea584"));
+                // Other languages keep the linguist default
+                assert!(svg.contains("fill=\"#00ADD8\""));
+            }
+        }
+
+        #[test]
+        fn test_render_with_auto_contrast() {
+            let stats = vec![
+                LanguageStat {
+                    name: "JavaScript".to_string(),
+                    size_bytes: 2000,
+                    repo_count: 5,
+                },
+                LanguageStat {
+                    name: "Assembly".to_string(),
+                    size_bytes: 1000,
+                    repo_count: 10,
+                },
+            ];
+            let card = |auto_contrast| {
+                LangsCard {
+                    card_settings: CardSettings {
+                        theme: CardTheme::Light,
+                        ..Default::default()
+                    },
+                    stats: stats.clone(),
+                    auto_contrast,
+                    ..Default::default()
+                }
+                .render()
+                .unwrap()
+            };
+
+            // JavaScript's yellow is too light for the white background and gets darkened
+            let svg = card(true);
+            assert!(!svg.contains("fill=\"#f1e05a\""));
+            // Assembly's dark green already stands out
+            assert!(svg.contains("fill=\"#6E4C13\""));
+
+            assert!(card(false).contains("fill=\"#f1e05a\""));
+        }
+
         #[test]
         fn test_render_max_languages_does_not_affect_calculations() {
             let stats = vec![
@@ -751,12 +1689,14 @@ mod tests {
                     theme: CardTheme::TransparentBlue,
                     hide_background: false,
                     hide_background_stroke: false,
+                    ..Default::default()
                 },
                 layout: LayoutType::Vertical,
                 stats: stats.clone(),
                 size_weight: Some(1.0),
                 count_weight: Some(0.0),
                 max_languages: Some(2),
+                ..Default::default()
             };
 
             // Create card that shows all 4 languages
@@ -768,16 +1708,18 @@ mod tests {
                     theme: CardTheme::TransparentBlue,
                     hide_background: false,
                     hide_background_stroke: false,
+                    ..Default::default()
                 },
                 layout: LayoutType::Vertical,
                 stats: stats.clone(),
                 size_weight: Some(1.0),
                 count_weight: Some(0.0),
                 max_languages: Some(4),
+                ..Default::default()
             };
 
-            let svg_max_2 = card_max_2.render();
-            let svg_max_4 = card_max_4.render();
+            let svg_max_2 = card_max_2.render().unwrap();
+            let svg_max_4 = card_max_4.render().unwrap();
 
             // Both cards should show the same percentages for the top 2 languages
             // because percentages are calculated from the total rank of all stats,
@@ -808,6 +1750,65 @@ mod tests {
         }
     }
 
+    mod fn_render_width_clamp {
+        use super::*;
+
+        #[test]
+        fn test_max_width_scales_down_langs_card() {
+            let card = LangsCard {
+                card_settings: CardSettings {
+                    max_width: Some(150),
+                    ..Default::default()
+                },
+                stats: vec![LanguageStat {
+                    name: "Rust".to_string(),
+                    size_bytes: 100,
+                    repo_count: 1,
+                }],
+                ..Default::default()
+            };
+
+            let svg = card.render().unwrap();
+            assert!(svg.contains("width=\"150\""));
+            // The viewBox keeps the natural vertical layout width (220 + 12 * 2 + 10 + 46)
+            assert!(svg.contains("viewBox=\"0 0 300 "));
+        }
+    }
+
+    mod fn_tooltip {
+        use super::*;
+
+        #[test]
+        fn test_each_language_has_a_detailed_tooltip() {
+            for layout in [
+                LayoutType::Vertical,
+                LayoutType::Horizontal,
+                LayoutType::Donut,
+            ] {
+                let card = LangsCard {
+                    layout,
+                    stats: vec![
+                        LanguageStat {
+                            name: "Rust".to_string(),
+                            size_bytes: 1_258_291,
+                            repo_count: 15,
+                        },
+                        LanguageStat {
+                            name: "Go".to_string(),
+                            size_bytes: 1_887_437,
+                            repo_count: 1,
+                        },
+                    ],
+                    ..Default::default()
+                };
+
+                let svg = card.render().unwrap();
+                assert!(svg.contains("<title>Rust — 40.0% • 1.2 MB • 15 repos</title>"));
+                assert!(svg.contains("<title>Go — 60.0% • 1.8 MB • 1 repo</title>"));
+            }
+        }
+    }
+
     mod fn_render_line_horizontal {
         use super::*;
 
@@ -825,12 +1826,66 @@ mod tests {
             // Label and percentage in the same text element
             assert!(rendered.contains("x=\"24\" y=\"24\" class=\"label\">Rust 30.55%</text>"));
         }
+
+        #[test]
+        fn test_render_line_horizontal_truncates_long_label() {
+            let rendered =
+                LangsCard::render_line_horizontal("#DA5B0B", "Jupyter Notebook", 12.5, 10, 20);
+            // Visible text is truncated, the full name is kept in the tooltip
+            assert!(rendered.contains("<title>Jupyter Notebook</title>Jupyter Not…"));
+            assert!(!rendered.contains(">Jupyter Notebook 12.50%</text>"));
+            assert!(rendered.contains("12.50%</text>"));
+        }
     }
 
     mod fn_render_horizontal_layout {
         use super::*;
         use crate::cards::card::{CardSettings, CardTheme};
 
+        #[test]
+        fn test_render_horizontal_layout_with_tiny_offsets() {
+            let stats: Vec<_> = ["Rust", "Go", "Python", "C", "Zig", "Nix"]
+                .iter()
+                .enumerate()
+                .map(|(i, name)| LanguageStat {
+                    name: name.to_string(),
+                    size_bytes: 1000 * (i + 1),
+                    repo_count: 1,
+                })
+                .collect();
+            for hide_title in [true, false] {
+                for offset_y in [0, 1, 5, 9, 10, 12] {
+                    let svg = LangsCard {
+                        card_settings: CardSettings {
+                            offset_y,
+                            hide_title,
+                            ..Default::default()
+                        },
+                        layout: LayoutType::Horizontal,
+                        stats: stats.clone(),
+                        ..Default::default()
+                    }
+                    .render()
+                    .unwrap();
+
+                    let y_attr = |marker: &str, attr: &str| -> u32 {
+                        let tag = &svg[svg.find(marker).unwrap()..];
+                        let start = tag.find(&format!(" {attr}=\"")).unwrap() + attr.len() + 3;
+                        tag[start..start + tag[start..].find('"').unwrap()]
+                            .parse()
+                            .unwrap()
+                    };
+                    let bar_y = y_attr("<svg width=\"276\"", "y");
+                    let label_cy = y_attr("<circle", "cy");
+                    if hide_title {
+                        assert_eq!(bar_y, offset_y.saturating_sub(10), "{offset_y}");
+                    }
+                    // The labels always stay below the bar
+                    assert_eq!(label_cy, bar_y + 28, "{offset_y}");
+                }
+            }
+        }
+
         #[test]
         fn test_render_horizontal_layout() {
             let card = LangsCard {
@@ -841,6 +1896,7 @@ mod tests {
                     theme: CardTheme::TransparentBlue,
                     hide_background: false,
                     hide_background_stroke: false,
+                    ..Default::default()
                 },
                 layout: LayoutType::Horizontal,
                 stats: vec![
@@ -868,9 +1924,10 @@ mod tests {
                 size_weight: Some(1.0),
                 count_weight: Some(0.0),
                 max_languages: Some(4),
+                ..Default::default()
             };
 
-            let svg = card.render();
+            let svg = card.render().unwrap();
             // Basic SVG structure and title
             assert!(svg.contains("<svg"));
             assert!(svg.contains("Most used languages"));
@@ -889,9 +1946,57 @@ mod tests {
             assert!(svg.matches("<rect").count() >= 4); // At least 4 segments for 4 languages
         }
 
+        #[test]
+        fn test_render_bars_meet_the_minimum_width() {
+            let stats: Vec<LanguageStat> =
+                [("Go", 100_000), ("Rust", 50_000), ("C", 100), ("Zig", 10)]
+                    .into_iter()
+                    .map(|(name, size_bytes)| LanguageStat {
+                        name: name.to_string(),
+                        size_bytes,
+                        repo_count: 1,
+                    })
+                    .collect();
+            let fill_widths = |svg: &str, prefix: &str| -> Vec<f64> {
+                svg.match_indices(prefix)
+                    .filter_map(|(i, _)| {
+                        let rest = &svg[i + prefix.len()..];
+                        let (width, rest) = rest.split_once('"')?;
+                        rest.starts_with(" height=\"8\" fill=\"#")
+                            .then(|| width.parse().unwrap())
+                    })
+                    .collect()
+            };
+
+            for (layout, total) in [
+                (LayoutType::Vertical, None),
+                (LayoutType::Horizontal, Some(276.0)),
+            ] {
+                let card = |min_bar_px| LangsCard {
+                    layout,
+                    stats: stats.clone(),
+                    min_bar_px,
+                    ..Default::default()
+                };
+                let plain = fill_widths(&card(0).render().unwrap(), "width=\"");
+                let widths = fill_widths(&card(6).render().unwrap(), "width=\"");
+
+                assert_eq!(widths.len(), 4, "{layout:?}");
+                assert!(plain.iter().any(|&w| w < 6.0), "{layout:?}");
+                assert!(widths.iter().all(|&w| w >= 6.0), "{layout:?}: {widths:?}");
+                // The larger bars give up what the tiny ones gain
+                let sum = |widths: &[f64]| widths.iter().sum::<f64>();
+                assert!((sum(&widths) - sum(&plain)).abs() < 0.05, "{layout:?}");
+                if let Some(total) = total {
+                    assert!((sum(&widths) - total).abs() < 0.05, "{layout:?}");
+                }
+                assert!(widths[0] < plain[0], "{layout:?}");
+            }
+        }
+
         #[test]
         fn test_render_horizontal_bar() {
-            let stats = vec![
+            let stats = [
                 LanguageStat {
                     name: "Go".to_string(),
                     size_bytes: 2000,
@@ -909,8 +2014,19 @@ mod tests {
                 },
             ];
 
-            let rendered = LangsCard::render_horizontal_bar(
-                &stats, 1.0, 0.0, 10,  // pos_x
+            let card = LangsCard {
+                size_weight: Some(1.0),
+                count_weight: Some(0.0),
+                ..Default::default()
+            };
+            let total_rank = stats.total_rank(1.0, 0.0);
+            let percentages: Vec<(LanguageStat, f64)> = stats
+                .iter()
+                .map(|stat| (stat.clone(), stat.rank(1.0, 0.0) / total_rank * 100.0))
+                .collect();
+            let rendered = card.render_horizontal_bar(
+                &percentages,
+                10,  // pos_x
                 20,  // pos_y
                 280, // total_width
             );
@@ -931,6 +2047,11 @@ mod tests {
             // Segments should be positioned correctly (first starts at x=0)
             assert!(rendered.contains("x=\"0\""));
 
+            // Segment coordinates keep two decimals at most, each one starting where the previous ends
+            assert!(rendered.contains("x=\"0\" y=\"0\" width=\"130.23\""));
+            assert!(rendered.contains("x=\"130.23\" y=\"0\" width=\"84.65\""));
+            assert!(rendered.contains("x=\"214.88\" y=\"0\" width=\"65.12\""));
+
             // Should have mask
             assert!(rendered.contains("<mask id=\"bar-mask\">"));
         }
@@ -945,6 +2066,7 @@ mod tests {
                     theme: CardTheme::TransparentBlue,
                     hide_background: false,
                     hide_background_stroke: false,
+                    ..Default::default()
                 },
                 layout: LayoutType::Horizontal,
                 stats: vec![
@@ -972,9 +2094,10 @@ mod tests {
                 size_weight: Some(1.0),
                 count_weight: Some(0.0),
                 max_languages: Some(4),
+                ..Default::default()
             };
 
-            let svg = card.render();
+            let svg = card.render().unwrap();
 
             // Extract row groups to verify ordering
             let rows: Vec<&str> = svg.matches(r#"<g class="row">"#).collect();
@@ -1049,4 +2172,337 @@ mod tests {
             );
         }
     }
+
+    mod fn_render_donut_layout {
+        use super::*;
+        use crate::cards::card::CardSettings;
+
+        fn stats(count: usize) -> Vec<LanguageStat> {
+            ["Rust", "Go", "Python", "C", "Zig", "Nix", "Lua", "Ruby"]
+                .iter()
+                .take(count)
+                .enumerate()
+                .map(|(i, name)| LanguageStat {
+                    name: name.to_string(),
+                    size_bytes: 1000 * (i + 1),
+                    repo_count: 1,
+                })
+                .collect()
+        }
+
+        fn donut_card(count: usize, hide_title: bool) -> LangsCard {
+            LangsCard {
+                card_settings: CardSettings {
+                    hide_title,
+                    ..Default::default()
+                },
+                layout: LayoutType::Donut,
+                stats: stats(count),
+                max_languages: Some(count as u64),
+                ..Default::default()
+            }
+        }
+
+        fn attr(tag: &str, name: &str) -> u32 {
+            let start = tag.find(&format!(" {name}=\"")).unwrap() + name.len() + 3;
+            tag[start..start + tag[start..].find('"').unwrap()]
+                .parse()
+                .unwrap()
+        }
+
+        #[test]
+        fn renders_a_segment_per_language() {
+            for count in 1..=8 {
+                let svg = donut_card(count, false).render().unwrap();
+                assert_eq!(svg.matches("<path class=\"donut-segment\"").count(), count);
+                assert_eq!(svg.matches("<g class=\"row\">").count(), count);
+                assert!(!svg.contains("NaN"), "{count}");
+            }
+        }
+
+        #[test]
+        fn single_language_fills_the_ring() {
+            let svg = donut_card(1, false).render().unwrap();
+            let d = &svg[svg.find(" d=\"M ").unwrap()..];
+            let d = &d[4..d[4..].find('"').unwrap() + 4];
+            // Two half arcs back to the starting point
+            assert_eq!(d.matches(" A ").count(), 2);
+            let start = &d[2..d.find(" A ").unwrap()];
+            assert!(d.ends_with(start), "{d}");
+        }
+
+        #[test]
+        fn segments_connect_around_the_ring() {
+            let svg = donut_card(3, false).render().unwrap();
+            let paths: Vec<&str> = svg
+                .split("<path class=\"donut-segment\" d=\"")
+                .skip(1)
+                .map(|tail| &tail[..tail.find('"').unwrap()])
+                .collect();
+            let start = |d: &str| d[2..d.find(" A ").unwrap()].to_string();
+            let end = |d: &str| {
+                let coords: Vec<&str> = d.split(' ').collect();
+                coords[coords.len() - 2..].join(" ")
+            };
+            for pair in paths.windows(2) {
+                assert_eq!(end(pair[0]), start(pair[1]));
+            }
+            // The last segment closes the ring at the top
+            assert_eq!(end(paths[2]), start(paths[0]));
+        }
+
+        #[test]
+        fn height_fits_the_ring_and_the_legend() {
+            for hide_title in [true, false] {
+                let short = donut_card(2, hide_title).render().unwrap();
+                let long = donut_card(8, hide_title).render().unwrap();
+                let header = if hide_title {
+                    0
+                } else {
+                    CardSettings::default().title_size + LangsCard::TITLE_BODY_OFFSET
+                        - LangsCard::DONUT_TOP_SHIFT
+                };
+                let offsets = LangsCard::default().card_settings.offset_y * 2;
+                // The ring is taller than two legend rows, eight rows are taller than the ring
+                assert_eq!(
+                    attr(&short, "height"),
+                    header + LangsCard::DONUT_SIZE + offsets,
+                    "{hide_title}"
+                );
+                assert_eq!(
+                    attr(&long, "height"),
+                    header + LangsCard::HORIZONTAL_ROW_Y_STEP * 8 + offsets,
+                    "{hide_title}"
+                );
+                let width = LangsCard::DONUT_SIZE
+                    + LangsCard::DONUT_LEGEND_GAP
+                    + LangsCard::HORIZONTAL_COLUMN_WIDTH
+                    + LangsCard::default().card_settings.offset_x * 2;
+                assert_eq!(attr(&short, "width"), width);
+            }
+        }
+    }
+
+    mod fn_min_percent {
+        use super::*;
+
+        /// A card with languages of 79.5%, 20% and 0.5% of the total size.
+        fn card(min_percent: f64) -> LangsCard {
+            let stats = [("Rust", 795), ("Go", 200), ("Nix", 5)]
+                .into_iter()
+                .map(|(name, size_bytes)| LanguageStat {
+                    name: name.to_string(),
+                    size_bytes,
+                    repo_count: 1,
+                })
+                .collect();
+            LangsCard {
+                stats,
+                min_percent,
+                size_weight: Some(1.0),
+                count_weight: Some(0.0),
+                ..Default::default()
+            }
+        }
+
+        fn displayed(card: &LangsCard) -> Vec<(String, f64)> {
+            card.computed_percentages()
+                .into_iter()
+                .map(|(stat, value)| (stat.name, value))
+                .collect()
+        }
+
+        #[test]
+        fn keeps_every_language_by_default() {
+            assert_eq!(displayed(&card(0.0)).len(), 3);
+        }
+
+        #[test]
+        fn drops_the_languages_under_the_threshold_after_ranking() {
+            let card = card(1.0);
+            let displayed = displayed(&card);
+            assert_eq!(
+                displayed,
+                [("Rust".to_string(), 79.5), ("Go".to_string(), 20.0)]
+            );
+            // The percentages are still of the whole set
+            let total: f64 = displayed.iter().map(|(_, value)| value).sum();
+            assert!(total < 100.0);
+
+            let svg = card.render().unwrap();
+            assert!(!svg.contains("Nix"));
+        }
+
+        #[test]
+        fn keeps_the_top_language_above_every_threshold() {
+            let card = card(100.0);
+            assert_eq!(displayed(&card), [("Rust".to_string(), 79.5)]);
+
+            let svg = card.render().unwrap();
+            assert!(svg.contains("Rust"));
+            assert!(!svg.contains(LangsCard::DEFAULT_EMPTY_NOTE));
+        }
+    }
+
+    mod fn_combine_small_languages {
+        use super::*;
+
+        /// A card with languages of 50%, 30%, 15%, 3% and 2% of the total size.
+        fn card(combine_below: Option<f64>) -> LangsCard {
+            let stats = [("Rust", 50), ("Go", 30), ("C", 15), ("Zig", 3), ("Nix", 2)]
+                .into_iter()
+                .map(|(name, size_bytes)| LanguageStat {
+                    name: name.to_string(),
+                    size_bytes,
+                    repo_count: 1,
+                })
+                .collect();
+            LangsCard {
+                stats,
+                combine_below,
+                ..Default::default()
+            }
+        }
+
+        fn displayed(card: &LangsCard) -> Vec<(String, f64)> {
+            card.computed_percentages()
+                .into_iter()
+                .map(|(stat, value)| (stat.name, value))
+                .collect()
+        }
+
+        #[test]
+        fn keeps_every_language_by_default() {
+            assert_eq!(displayed(&card(None)).len(), 5);
+        }
+
+        #[test]
+        fn keeps_a_single_language_under_the_threshold() {
+            // Only Nix is under 3%, the threshold itself is kept
+            let names: Vec<String> = displayed(&card(Some(3.0)))
+                .into_iter()
+                .map(|(name, _)| name)
+                .collect();
+            assert_eq!(names, ["Rust", "Go", "C", "Zig", "Nix"]);
+        }
+
+        #[test]
+        fn combines_the_languages_under_the_threshold() {
+            let card = card(Some(20.0));
+            assert_eq!(
+                displayed(&card),
+                [
+                    ("Rust".to_string(), 50.0),
+                    ("Go".to_string(), 30.0),
+                    ("Other".to_string(), 20.0)
+                ]
+            );
+
+            let (other, _) = card.computed_percentages().pop().unwrap();
+            assert_eq!(other.size_bytes, 20);
+            assert_eq!(other.repo_count, 3);
+        }
+
+        #[test]
+        fn appends_other_after_alphabetical_languages() {
+            let card = LangsCard {
+                sort: LangsSort::Alpha,
+                ..card(Some(4.0))
+            };
+            let names: Vec<String> = displayed(&card).into_iter().map(|(name, _)| name).collect();
+            assert_eq!(names, ["C", "Go", "Rust", "Other"]);
+        }
+
+        #[test]
+        fn renders_other_with_its_color() {
+            for layout in [
+                LayoutType::Vertical,
+                LayoutType::Horizontal,
+                LayoutType::Donut,
+            ] {
+                let card = LangsCard {
+                    layout,
+                    other_color: "#abcdef".to_string(),
+                    ..card(Some(20.0))
+                };
+                let svg = card.render().unwrap();
+                assert!(svg.contains("Other"), "{layout:?}");
+                assert!(svg.contains("#abcdef"), "{layout:?}");
+                assert!(!svg.contains("Zig"), "{layout:?}");
+            }
+
+            let svg = card(Some(20.0)).render().unwrap();
+            assert!(svg.contains(LangsCard::DEFAULT_OTHER_COLOR));
+        }
+    }
+
+    mod fn_render_legend_only {
+        use super::*;
+
+        fn card(layout: LayoutType) -> LangsCard {
+            let stats = [("Rust", 500), ("Go", 300), ("Python", 150)]
+                .into_iter()
+                .map(|(name, size_bytes)| LanguageStat {
+                    name: name.to_string(),
+                    size_bytes,
+                    repo_count: 1,
+                })
+                .collect();
+            LangsCard {
+                card_settings: CardSettings {
+                    hide_background: true,
+                    ..LangsCard::default().card_settings
+                },
+                layout,
+                stats,
+                size_weight: Some(1.0),
+                count_weight: Some(0.0),
+                legend_only: true,
+                ..Default::default()
+            }
+        }
+
+        fn attr(svg: &str, name: &str) -> u32 {
+            let start = svg.find(&format!(" {name}=\"")).unwrap() + name.len() + 3;
+            svg[start..start + svg[start..].find('"').unwrap()]
+                .parse()
+                .unwrap()
+        }
+
+        #[test]
+        fn renders_the_legend_without_bars() {
+            for layout in [
+                LayoutType::Vertical,
+                LayoutType::Horizontal,
+                LayoutType::Donut,
+            ] {
+                let svg = card(layout).render().unwrap();
+                assert!(!svg.contains("<rect"), "{layout:?}");
+                assert!(!svg.contains("donut-segment"), "{layout:?}");
+                assert_eq!(svg.matches("<circle ").count(), 3, "{layout:?}");
+                for entry in ["Rust 52.63%", "Go 31.58%", "Python 15.79%"] {
+                    assert!(svg.contains(entry), "{layout:?}: {entry}");
+                }
+            }
+        }
+
+        #[test]
+        fn card_is_sized_to_the_legend() {
+            let legend = card(LayoutType::Horizontal).render().unwrap();
+            let horizontal = LangsCard {
+                legend_only: false,
+                ..card(LayoutType::Horizontal)
+            }
+            .render()
+            .unwrap();
+            assert_eq!(attr(&legend, "width"), attr(&horizontal, "width"));
+            assert_eq!(
+                attr(&legend, "height"),
+                attr(&horizontal, "height") - LangsCard::BAR_HEIGHT
+            );
+            // Two rows of the legend, whatever the layout
+            let vertical = card(LayoutType::Vertical).render().unwrap();
+            assert_eq!(attr(&vertical, "height"), attr(&legend, "height"));
+        }
+    }
 }
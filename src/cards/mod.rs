@@ -1,5 +1,9 @@
+pub mod accent;
 pub mod card;
 pub mod error_card;
+pub mod examples;
 pub mod helpers;
 pub mod langs_card;
+pub mod raster;
 pub mod stats_card;
+pub mod streak_card;
@@ -0,0 +1,283 @@
+//! Rasterization of the rendered cards to PNG or WebP, for the platforms that don't
+//! display SVG images (e.g. some email clients).
+
+use std::sync::{Arc, OnceLock};
+
+use resvg::{tiny_skia, usvg};
+
+/// Smallest `scale` a card can be rasterized with.
+pub const MIN_SCALE: f32 = 0.5;
+/// Largest `scale` a card can be rasterized with.
+pub const MAX_SCALE: f32 = 3.0;
+
+/// CSS resvg ignores, the properties using it would be drawn with their defaults.
+const UNSUPPORTED_CSS: [(&str, &str); 3] = [
+    ("var(", "CSS variables"),
+    ("@media", "media queries"),
+    ("@keyframes", "animations"),
+];
+
+/// Embedded sans-serif face drawing the text when the system has none of
+/// [SANS_SERIF_FAMILIES], e.g. in the release image that comes without any font.
+const FALLBACK_FONT: &[u8] = include_bytes!("../../assets/fonts/DejaVuSans.ttf");
+/// Family of the [FALLBACK_FONT].
+const FALLBACK_FAMILY: &str = "DejaVu Sans";
+
+/// Fonts drawing the sans-serif text of the cards, by preference.
+const SANS_SERIF_FAMILIES: [&str; 5] = [
+    "Helvetica Neue",
+    "Helvetica",
+    "Arial",
+    "Liberation Sans",
+    "DejaVu Sans",
+];
+
+/// Reasons a card can't be rasterized, it is served as SVG instead.
+#[derive(thiserror::Error, Debug)]
+pub enum RasterError {
+    #[error("unsupported SVG feature: {0}")]
+    Unsupported(&'static str),
+    #[error("invalid SVG: {0}")]
+    InvalidSvg(#[from] usvg::Error),
+    #[error("invalid size {width}x{height}")]
+    InvalidSize { width: u32, height: u32 },
+    #[error("image encoding failed: {0}")]
+    Encoding(String),
+}
+
+/// Rasterizes a card to PNG using the system fonts (or the embedded fallback), `scale`
+/// times its SVG size (clamped to [MIN_SCALE]..=[MAX_SCALE]).
+pub fn svg_to_png(svg: &str, scale: f32) -> Result<Vec<u8>, RasterError> {
+    render_png(svg, scale, Arc::clone(system_fonts()))
+}
+
+/// Rasterizes a card to lossless WebP using the system fonts (or the embedded fallback),
+/// `scale` times its SVG size (clamped to [MIN_SCALE]..=[MAX_SCALE]).
+pub fn svg_to_webp(svg: &str, scale: f32) -> Result<Vec<u8>, RasterError> {
+    render_webp(svg, scale, Arc::clone(system_fonts()))
+}
+
+/// The system fonts and the [FALLBACK_FONT], loaded once.
+fn system_fonts() -> &'static Arc<usvg::fontdb::Database> {
+    static FONTS: OnceLock<Arc<usvg::fontdb::Database>> = OnceLock::new();
+    FONTS.get_or_init(|| {
+        let mut fontdb = usvg::fontdb::Database::new();
+        fontdb.load_system_fonts();
+        Arc::new(with_fallback_font(fontdb))
+    })
+}
+
+/// Adds the [FALLBACK_FONT] to `fontdb` and picks the sans-serif family of the cards.
+fn with_fallback_font(mut fontdb: usvg::fontdb::Database) -> usvg::fontdb::Database {
+    fontdb.load_font_data(FALLBACK_FONT.to_vec());
+    // The cards fall back to sans-serif, which fontdb resolves to Arial, often not
+    // installed on servers: the text would be left out
+    let family = SANS_SERIF_FAMILIES
+        .into_iter()
+        .find(|family| has_family(&fontdb, family))
+        .unwrap_or(FALLBACK_FAMILY);
+    fontdb.set_sans_serif_family(family);
+    fontdb
+}
+
+/// Tells whether a face of `family` is in `fontdb`.
+fn has_family(fontdb: &usvg::fontdb::Database, family: &str) -> bool {
+    fontdb
+        .faces()
+        .any(|face| face.families.iter().any(|(name, _)| name == family))
+}
+
+/// Rasterizes the SVG to PNG, drawing the text with the fonts of `fontdb`.
+fn render_png(
+    svg: &str,
+    scale: f32,
+    fontdb: Arc<usvg::fontdb::Database>,
+) -> Result<Vec<u8>, RasterError> {
+    render_pixmap(svg, scale, fontdb)?
+        .encode_png()
+        .map_err(|e| RasterError::Encoding(e.to_string()))
+}
+
+/// Rasterizes the SVG to lossless WebP, drawing the text with the fonts of `fontdb`.
+fn render_webp(
+    svg: &str,
+    scale: f32,
+    fontdb: Arc<usvg::fontdb::Database>,
+) -> Result<Vec<u8>, RasterError> {
+    let pixmap = render_pixmap(svg, scale, fontdb)?;
+    // The pixmap is premultiplied, WebP stores straight alpha
+    let rgba: Vec<u8> = pixmap
+        .pixels()
+        .iter()
+        .flat_map(|pixel| {
+            let color = pixel.demultiply();
+            [color.red(), color.green(), color.blue(), color.alpha()]
+        })
+        .collect();
+
+    let mut webp = Vec::new();
+    image_webp::WebPEncoder::new(&mut webp)
+        .encode(
+            &rgba,
+            pixmap.width(),
+            pixmap.height(),
+            image_webp::ColorType::Rgba8,
+        )
+        .map_err(|e| RasterError::Encoding(e.to_string()))?;
+    Ok(webp)
+}
+
+/// Draws the SVG on a pixmap `scale` times its size, with the fonts of `fontdb`.
+fn render_pixmap(
+    svg: &str,
+    scale: f32,
+    fontdb: Arc<usvg::fontdb::Database>,
+) -> Result<tiny_skia::Pixmap, RasterError> {
+    if let Some((_, feature)) = UNSUPPORTED_CSS.iter().find(|(css, _)| svg.contains(css)) {
+        return Err(RasterError::Unsupported(feature));
+    }
+
+    let options = usvg::Options {
+        fontdb,
+        // Cards only reference their own content, never files
+        image_href_resolver: usvg::ImageHrefResolver {
+            resolve_string: Box::new(|_, _| None),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let tree = usvg::Tree::from_str(svg, &options)?;
+
+    let scale = scale.clamp(MIN_SCALE, MAX_SCALE);
+    let size = tree.size();
+    let width = (size.width() * scale).ceil() as u32;
+    let height = (size.height() * scale).ceil() as u32;
+    let mut pixmap =
+        tiny_skia::Pixmap::new(width, height).ok_or(RasterError::InvalidSize { width, height })?;
+
+    resvg::render(
+        &tree,
+        tiny_skia::Transform::from_scale(scale, scale),
+        &mut pixmap.as_mut(),
+    );
+    Ok(pixmap)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cards::examples::{langs_example, stats_example};
+    use crate::cards::{card::CardTheme, langs_card::LayoutType};
+
+    /// Reads the width and height from the header of a PNG.
+    fn png_size(png: &[u8]) -> (u32, u32) {
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+        let width = u32::from_be_bytes(png[16..20].try_into().unwrap());
+        let height = u32::from_be_bytes(png[20..24].try_into().unwrap());
+        (width, height)
+    }
+
+    fn svg_size(svg: &str) -> (u32, u32) {
+        let attr = |name: &str| -> u32 {
+            let start = svg.find(&format!(" {name}=\"")).unwrap() + name.len() + 3;
+            let end = svg[start..].find('"').unwrap() + start;
+            svg[start..end].parse().unwrap()
+        };
+        (attr("width"), attr("height"))
+    }
+
+    fn no_fonts() -> Arc<usvg::fontdb::Database> {
+        Arc::new(usvg::fontdb::Database::new())
+    }
+
+    mod fn_render_png {
+        use super::*;
+
+        #[test]
+        fn keeps_the_card_size() {
+            for svg in [
+                stats_example(CardTheme::Dark).unwrap(),
+                langs_example(CardTheme::Light, LayoutType::Horizontal).unwrap(),
+            ] {
+                let png = render_png(&svg, 1.0, no_fonts()).unwrap();
+                assert_eq!(png_size(&png), svg_size(&svg));
+            }
+        }
+
+        #[test]
+        fn scales_the_card() {
+            let svg = stats_example(CardTheme::Dark).unwrap();
+            let (width, height) = svg_size(&svg);
+            let png = render_png(&svg, 2.0, no_fonts()).unwrap();
+            assert_eq!(png_size(&png), (width * 2, height * 2));
+        }
+
+        #[test]
+        fn clamps_the_scale() {
+            let svg = stats_example(CardTheme::Dark).unwrap();
+            let (width, height) = svg_size(&svg);
+            let png = render_png(&svg, 10.0, no_fonts()).unwrap();
+            assert_eq!(png_size(&png), (width * 3, height * 3));
+            let png = render_png(&svg, 0.0, no_fonts()).unwrap();
+            assert_eq!(png_size(&png), (width.div_ceil(2), height.div_ceil(2)));
+        }
+
+        #[test]
+        fn rejects_css_variables() {
+            let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" width="10" height="10"><style>rect { fill: var(--c, red); }</style><rect width="10" height="10"/></svg>"#;
+            assert!(matches!(
+                render_png(svg, 1.0, no_fonts()),
+                Err(RasterError::Unsupported(_))
+            ));
+        }
+
+        #[test]
+        fn rejects_invalid_svg() {
+            assert!(matches!(
+                render_png("<svg", 1.0, no_fonts()),
+                Err(RasterError::InvalidSvg(_))
+            ));
+        }
+
+        #[test]
+        fn draws_the_text_without_system_fonts() {
+            let fontdb = with_fallback_font(usvg::fontdb::Database::new());
+            assert!(has_family(&fontdb, FALLBACK_FAMILY));
+
+            let svg = stats_example(CardTheme::Dark).unwrap();
+            let with_text = render_png(&svg, 1.0, Arc::new(fontdb)).unwrap();
+            // Without any font the text is left out
+            let without_text = render_png(&svg, 1.0, no_fonts()).unwrap();
+            assert_eq!(png_size(&with_text), png_size(&without_text));
+            assert_ne!(with_text, without_text);
+        }
+    }
+
+    mod fn_render_webp {
+        use super::*;
+
+        #[test]
+        fn encodes_a_webp_of_the_scaled_card() {
+            let svg = stats_example(CardTheme::Dark).unwrap();
+            let (width, height) = svg_size(&svg);
+            let webp = render_webp(&svg, 2.0, no_fonts()).unwrap();
+            assert_eq!(&webp[..4], b"RIFF");
+            assert_eq!(&webp[8..12], b"WEBP");
+
+            let mut decoder =
+                image_webp::WebPDecoder::new(std::io::Cursor::new(webp.as_slice())).unwrap();
+            assert_eq!(decoder.dimensions(), (width * 2, height * 2));
+            let mut pixels = vec![0; decoder.output_buffer_size().unwrap()];
+            decoder.read_image(&mut pixels).unwrap();
+        }
+
+        #[test]
+        fn rejects_css_variables() {
+            let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" width="10" height="10"><style>rect { fill: var(--c, red); }</style><rect width="10" height="10"/></svg>"#;
+            assert!(matches!(
+                render_webp(svg, 1.0, no_fonts()),
+                Err(RasterError::Unsupported(_))
+            ));
+        }
+    }
+}
@@ -1,4 +1,7 @@
-use crate::cards::card::{CardSettings, CardTheme, Svg};
+use std::borrow::Cow;
+
+use crate::cards::card::{CardError, CardSettings, CardTheme, Svg};
+use crate::cards::helpers::{DebugGuides, escape_xml_text};
 
 pub struct StatsCard {
     pub card_settings: CardSettings,
@@ -11,6 +14,35 @@ pub struct StatsCard {
     pub reviews_count: Option<u32>,
     pub started_discussions_count: Option<u32>,
     pub answered_discussions_count: Option<u32>,
+    /// Shown for organizations, like the members count.
+    pub repositories_count: Option<u32>,
+    pub members_count: Option<u32>,
+    /// Optional ordering of the rows by value, definition order when `None`.
+    pub sort: Option<StatsSort>,
+    /// Width and height (pixels) of the row icons.
+    pub icon_size: u32,
+    /// Year the commits are counted in, shown in their label. `Commits YTD` when `None`.
+    pub commits_year: Option<i32>,
+    /// The commits are counted over every year, labelled `Commits` whatever the `commits_year`.
+    pub commits_all_years: bool,
+    /// Space (pixels) between the row icons and the labels.
+    pub label_gap: u32,
+    /// Space (pixels) left after the widest label before the values.
+    pub value_gap: u32,
+    /// The commits row also sums up other contribution types, labelled `Contributions`.
+    pub counts_contributions: bool,
+    /// Rows of a column before the following ones wrap into the next column to its
+    /// right. `None` keeps every row in a single column.
+    pub max_rows: Option<u32>,
+    /// Title shown instead of `@username: GitHub Stats`, escaped when rendered.
+    pub custom_title: Option<String>,
+}
+
+/// Ordering of the [StatsCard] rows by their numeric value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatsSort {
+    ValueDesc,
+    ValueAsc,
 }
 
 impl Default for StatsCard {
@@ -23,6 +55,7 @@ impl Default for StatsCard {
                 hide_title: false,
                 hide_background: false,
                 hide_background_stroke: false,
+                ..Default::default()
             },
             username: String::new(),
             stars_count: None,
@@ -33,22 +66,49 @@ impl Default for StatsCard {
             reviews_count: None,
             started_discussions_count: None,
             answered_discussions_count: None,
+            repositories_count: None,
+            members_count: None,
+            sort: None,
+            icon_size: Self::DEFAULT_ICON_SIZE,
+            commits_year: None,
+            commits_all_years: false,
+            label_gap: Self::DEFAULT_LABEL_GAP,
+            value_gap: Self::DEFAULT_VALUE_GAP,
+            counts_contributions: false,
+            max_rows: None,
+            custom_title: None,
         }
     }
 }
 
 impl StatsCard {
+    pub const DEFAULT_ICON_SIZE: u32 = 16;
+    pub const MIN_ICON_SIZE: u32 = 10;
+    pub const MAX_ICON_SIZE: u32 = 24;
+    pub const DEFAULT_LABEL_GAP: u32 = 8;
+    pub const MIN_LABEL_GAP: u32 = 2;
+    pub const MAX_LABEL_GAP: u32 = 32;
+    pub const DEFAULT_VALUE_GAP: u32 = 32;
+    pub const MIN_VALUE_GAP: u32 = 8;
+    pub const MAX_VALUE_GAP: u32 = 96;
+    pub const DEFAULT_MAX_ROWS: u32 = 4;
+    pub const MAX_MAX_ROWS: u32 = 10;
+
     // Constants for rendering the card (in pixels).
     const MAX_USERNAME_LEN: usize = 13;
     const VALUE_SIZE: u32 = 31;
-    const LABEL_SIZE: u32 = 222;
-    const ICON_SIZE: u32 = 15;
-    const ICON_OFFSET: u32 = 8;
+    /// Width reserved for the widest label, the values start [Self::value_gap] after it.
+    const LABEL_WIDTH: u32 = 190;
+    /// Height of the middle of the label glyphs above their baseline, icons are centered on it.
+    const LABEL_MIDLINE: u32 = 5;
     const TITLE_BODY_OFFSET: u32 = 1;
     const ROW_Y_STEP: u32 = 27;
+    /// Space between two columns of rows, see [StatsCard::max_rows].
+    const COLUMN_GAP: u32 = 24;
 
     /// Renders the [StatsCard] as an [Svg] string.
-    pub fn render(&self) -> Svg {
+    /// Fails with a [CardError] when the settings don't fit the card dimensions.
+    pub fn render(&self) -> Result<Svg, CardError> {
         use crate::cards::card::Card;
 
         // Prepare stat lines (label, value, Option)
@@ -57,125 +117,89 @@ impl StatsCard {
         let header_size_y = if self.card_settings.hide_title {
             0
         } else {
-            Card::TITLE_FONT_SIZE + Self::TITLE_BODY_OFFSET
+            self.card_settings.title_size + Self::TITLE_BODY_OFFSET
         };
 
         // Starting baseline (text y) for the first stat row.
         // If title is visible: keep previous spacing (title height + row step + top offset).
         // If title is hidden: start so that the icon's top sits exactly at offset_y, giving
-        // symmetric padding top/bottom. Baseline = offset_y + ICON_SIZE.
+        // symmetric padding top/bottom. Baseline = offset_y + icon_top_offset.
+        // Saturating, so huge offsets fail the card validation instead of overflowing.
         let mut y: u32 = if self.card_settings.hide_title {
-            self.card_settings.offset_y + Self::ICON_SIZE
+            self.card_settings
+                .offset_y
+                .saturating_add(self.icon_top_offset())
         } else {
-            header_size_y + Self::ROW_Y_STEP + self.card_settings.offset_y
+            (header_size_y + Self::ROW_Y_STEP).saturating_add(self.card_settings.offset_y)
         };
 
-        if let Some(val) = self.stars_count {
-            lines.push(self.render_line(
-                StatIcon::Stars,
-                "Stars",
-                val,
-                self.card_settings.offset_x,
-                y,
-            ));
-            y += Self::ROW_Y_STEP;
-        }
-        if let Some(val) = self.commits_ytd_count {
-            lines.push(self.render_line(
-                StatIcon::CommitsYTD,
-                "Commits YTD",
-                val,
-                self.card_settings.offset_x,
-                y,
-            ));
-            y += Self::ROW_Y_STEP;
-        }
-        if let Some(val) = self.issues_count {
-            lines.push(self.render_line(
-                StatIcon::Issues,
-                "Issues",
-                val,
-                self.card_settings.offset_x,
-                y,
-            ));
-            y += Self::ROW_Y_STEP;
-        }
-        if let Some(val) = self.pull_requests_count {
-            lines.push(self.render_line(
-                StatIcon::PullRequests,
-                "Pull Requests",
-                val,
-                self.card_settings.offset_x,
-                y,
-            ));
-            y += Self::ROW_Y_STEP;
-        }
-        if let Some(val) = self.merge_requests_count {
-            lines.push(self.render_line(
-                StatIcon::MergeRequests,
-                "Merge Requests",
-                val,
-                self.card_settings.offset_x,
-                y,
-            ));
-            y += Self::ROW_Y_STEP;
-        }
-        if let Some(val) = self.reviews_count {
-            lines.push(self.render_line(
-                StatIcon::Reviews,
-                "Reviews",
-                val,
-                self.card_settings.offset_x,
-                y,
-            ));
-            y += Self::ROW_Y_STEP;
-        }
-        if let Some(val) = self.started_discussions_count {
-            lines.push(self.render_line(
-                StatIcon::StartedDiscussions,
-                "Started Discussions",
-                val,
-                self.card_settings.offset_x,
-                y,
-            ));
-            y += Self::ROW_Y_STEP;
-        }
-        if let Some(val) = self.answered_discussions_count {
-            lines.push(self.render_line(
-                StatIcon::AnsweredDiscussions,
-                "Answered Discussions",
-                val,
-                self.card_settings.offset_x,
-                y,
-            ));
+        let rows = self.rows();
+        let rows_per_column = self
+            .max_rows
+            .map_or(rows.len(), |max_rows| max_rows as usize)
+            .max(1);
+        let column_width = self.column_width();
+
+        let mut guides = DebugGuides::default();
+        let first_y = y;
+        for (index, (icon, label, value)) in rows.iter().enumerate() {
+            // Rows fill a column from the top before wrapping into the next one
+            let column = (index / rows_per_column) as u32;
+            if index % rows_per_column == 0 {
+                y = first_y;
+            }
+            let x = self
+                .card_settings
+                .offset_x
+                .saturating_add(column.saturating_mul(column_width + Self::COLUMN_GAP));
+            lines.push(self.render_line(*icon, label, *value, x, y));
+            if column == 0 {
+                guides.baseline(y);
+            }
+            y = y.saturating_add(Self::ROW_Y_STEP);
         }
 
-        // Calculate card height: top margin + (lines * step) + bottom margin
-        let line_count = lines.len().max(1) as u32;
+        // Calculate card height: top margin + (lines * step) + bottom margin,
+        // the lines of the tallest column
+        let line_count = rows.len().clamp(1, rows_per_column) as u32;
+        let column_count = rows.len().div_ceil(rows_per_column).max(1) as u32;
         let height = if self.card_settings.hide_title {
-            // Height so last baseline + offset_y is the bottom edge.
-            // last_baseline = first_baseline + (lines-1)*ROW_Y_STEP
-            // first_baseline = offset_y + ICON_SIZE
-            // height = last_baseline + offset_y
-            self.card_settings.offset_y * 2 + Self::ICON_SIZE + (line_count - 1) * Self::ROW_Y_STEP
+            // Height so the bottom of the last icon + offset_y is the bottom edge.
+            // first_icon_top = offset_y
+            // last_icon_bottom = first_icon_top + (lines-1)*ROW_Y_STEP + icon_size
+            // height = last_icon_bottom + offset_y
+            (self.icon_size + (line_count - 1) * Self::ROW_Y_STEP)
+                .saturating_add(self.card_settings.offset_y.saturating_mul(2))
         } else {
-            header_size_y + line_count * Self::ROW_Y_STEP + self.card_settings.offset_y * 2
+            // The last baseline sits offset_y above the bottom edge, unless its icon
+            // reaches further down (large icons with tiny offsets).
+            let icon_overhang = self.icon_size.saturating_sub(self.icon_top_offset());
+            (header_size_y + line_count * Self::ROW_Y_STEP)
+                .saturating_add(self.card_settings.offset_y)
+                .saturating_add(self.card_settings.offset_y.max(icon_overhang))
         };
-        let width: u32 = Self::LABEL_SIZE
-            + Self::ICON_SIZE
-            + Self::ICON_OFFSET
-            + Self::VALUE_SIZE
-            + self.card_settings.offset_x * 2;
+        let width: u32 = (column_count * column_width + (column_count - 1) * Self::COLUMN_GAP)
+            .saturating_add(self.card_settings.offset_x.saturating_mul(2));
+
+        if self.card_settings.debug {
+            lines.push(guides.render(
+                width,
+                height,
+                self.card_settings.offset_x,
+                self.card_settings.offset_y,
+            ));
+        }
 
         let body = lines.join("\n");
 
-        // Build title respecting username length limit.
-        let display_title =
-            if self.username.is_empty() || self.username.len() > Self::MAX_USERNAME_LEN {
-                "GitHub Stats".to_string()
-            } else {
-                format!("@{}: GitHub Stats", self.username)
-            };
+        // Build title respecting username length limit, unless a custom one is set.
+        let display_title = if let Some(title) = &self.custom_title {
+            escape_xml_text(title)
+        } else if self.username.is_empty() || self.username.len() > Self::MAX_USERNAME_LEN {
+            "GitHub Stats".to_string()
+        } else {
+            format!("@{}: GitHub Stats", escape_xml_text(&self.username))
+        };
 
         let card = Card::new(
             width,
@@ -185,12 +209,70 @@ impl StatsCard {
             body,
             "statsCard".to_string(),
             self.card_settings.clone(),
-        );
-        match card {
-            Ok(card) => card.render(),
-            // TODO: handle error properly
-            Err(e) => format!("Failed to render StatsCard: {e}"),
+        )?;
+        Ok(card.render())
+    }
+
+    /// Width (pixels) of a column of rows: icon, label and value.
+    fn column_width(&self) -> u32 {
+        self.icon_size + self.label_gap + Self::LABEL_WIDTH + self.value_gap + Self::VALUE_SIZE
+    }
+
+    /// Collects the visible rows (icon, label, value) in display order.
+    ///
+    /// Hidden (`None`) stats are skipped; the requested [StatsSort] is applied
+    /// as a stable sort, so rows with equal values keep their definition order.
+    fn rows(&self) -> Vec<(StatIcon, Cow<'static, str>, u32)> {
+        let commits_label = match (self.counts_contributions, self.commits_year) {
+            (false, _) if self.commits_all_years => Cow::Borrowed("Commits"),
+            (false, Some(year)) => Cow::Owned(format!("Commits {year}")),
+            (false, None) => Cow::Borrowed("Commits YTD"),
+            (true, Some(year)) => Cow::Owned(format!("Contributions {year}")),
+            (true, None) => Cow::Borrowed("Contributions YTD"),
+        };
+        let mut rows: Vec<(StatIcon, Cow<'static, str>, u32)> = [
+            (StatIcon::Stars, "Stars".into(), self.stars_count),
+            (StatIcon::CommitsYTD, commits_label, self.commits_ytd_count),
+            (StatIcon::Issues, "Issues".into(), self.issues_count),
+            (
+                StatIcon::PullRequests,
+                "Pull Requests".into(),
+                self.pull_requests_count,
+            ),
+            (
+                StatIcon::MergeRequests,
+                "Merge Requests".into(),
+                self.merge_requests_count,
+            ),
+            (StatIcon::Reviews, "Reviews".into(), self.reviews_count),
+            (
+                StatIcon::StartedDiscussions,
+                "Started Discussions".into(),
+                self.started_discussions_count,
+            ),
+            (
+                StatIcon::AnsweredDiscussions,
+                "Answered Discussions".into(),
+                self.answered_discussions_count,
+            ),
+            (
+                StatIcon::Repositories,
+                "Repositories".into(),
+                self.repositories_count,
+            ),
+            (StatIcon::Members, "Members".into(), self.members_count),
+        ]
+        .into_iter()
+        .filter_map(|(icon, label, value)| value.map(|v| (icon, label, v)))
+        .collect();
+
+        match self.sort {
+            Some(StatsSort::ValueDesc) => rows.sort_by_key(|row| std::cmp::Reverse(row.2)),
+            Some(StatsSort::ValueAsc) => rows.sort_by_key(|row| row.2),
+            None => {}
         }
+
+        rows
     }
 
     /// Format a numeric value into a shortened human form.
@@ -225,18 +307,27 @@ impl StatsCard {
             StatIcon::Reviews => include_str!("../../assets/icons/eye.svg"),
             StatIcon::StartedDiscussions => include_str!("../../assets/icons/messages.svg"),
             StatIcon::AnsweredDiscussions => include_str!("../../assets/icons/message-check.svg"),
+            StatIcon::Repositories => include_str!("../../assets/icons/book.svg"),
+            StatIcon::Members => include_str!("../../assets/icons/users.svg"),
         };
 
         // Insert x and y attributes into the SVG root element
         // Assumes the SVG starts with <svg ...>
         if let Some(idx) = svg.find('>') {
             let (start, rest) = svg.split_at(idx);
-            format!("{start} x=\"{x}\" y=\"{y}\" width=\"16\" height=\"16\"{rest}")
+            let size = self.icon_size;
+            format!("{start} x=\"{x}\" y=\"{y}\" width=\"{size}\" height=\"{size}\"{rest}")
         } else {
             svg.to_string()
         }
     }
 
+    /// Distance (pixels) from the row baseline up to the top of its icon,
+    /// which centers the icon on the middle of the label glyphs.
+    fn icon_top_offset(&self) -> u32 {
+        Self::LABEL_MIDLINE + self.icon_size / 2
+    }
+
     /// Renders the line for the [StatsCard].
     fn render_line(
         &self,
@@ -246,8 +337,8 @@ impl StatsCard {
         pos_x: u32,
         pos_y: u32,
     ) -> String {
-        let pos_x_label = pos_x + Self::ICON_SIZE + Self::ICON_OFFSET;
-        let pos_x_value = pos_x_label + Self::LABEL_SIZE;
+        let pos_x_label = pos_x.saturating_add(self.icon_size + self.label_gap);
+        let pos_x_value = pos_x_label.saturating_add(Self::LABEL_WIDTH + self.value_gap);
 
         format!(
             r#"<g class="row">
@@ -255,16 +346,17 @@ impl StatsCard {
   <text class="label" x="{pos_x_label}" y="{pos_y}">{label}:</text>
   <text class="value" x="{pos_x_value}" y="{pos_y}">{value}</text>
 </g>"#,
-            icon = self.load_icon(icon, pos_x, pos_y.saturating_sub(Self::ICON_SIZE)),
+            icon = self.load_icon(icon, pos_x, pos_y.saturating_sub(self.icon_top_offset())),
             pos_x_label = pos_x_label,
             pos_y = pos_y,
-            label = label,
+            label = escape_xml_text(label),
             pos_x_value = pos_x_value,
             value = self.format_value(value)
         )
     }
 }
 
+#[derive(Clone, Copy)]
 enum StatIcon {
     Stars,
     CommitsYTD,
@@ -274,12 +366,36 @@ enum StatIcon {
     Reviews,
     StartedDiscussions,
     AnsweredDiscussions,
+    Repositories,
+    Members,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    mod fn_render_org_rows {
+        use super::*;
+
+        #[test]
+        fn renders_repositories_and_members() {
+            let svg = StatsCard {
+                username: "rust-lang".to_string(),
+                stars_count: Some(108_000),
+                repositories_count: Some(230),
+                members_count: Some(42),
+                ..Default::default()
+            }
+            .render()
+            .unwrap();
+
+            assert_eq!(svg.matches("<g class=\"row\">").count(), 3);
+            assert!(svg.contains(">Repositories:</text>"));
+            assert!(svg.contains(">Members:</text>"));
+            assert!(svg.contains(">108k</text>"));
+        }
+    }
+
     mod fn_render_line {
         use super::*;
 
@@ -297,6 +413,58 @@ mod tests {
             assert!(line.contains("y=\"20"));
         }
 
+        #[test]
+        fn icon_matches_configured_size() {
+            for icon_size in [StatsCard::MIN_ICON_SIZE, 16, StatsCard::MAX_ICON_SIZE] {
+                let card = StatsCard {
+                    icon_size,
+                    ..Default::default()
+                };
+                let line = card.render_line(StatIcon::Stars, "Stars", 42, 10, 40);
+                assert!(
+                    line.contains(&format!("width=\"{icon_size}\" height=\"{icon_size}\"")),
+                    "{icon_size}"
+                );
+                // The label follows the icon
+                let label_x = 10 + icon_size + StatsCard::DEFAULT_LABEL_GAP;
+                assert!(line.contains(&format!("class=\"label\" x=\"{label_x}\"")));
+            }
+        }
+
+        #[test]
+        fn gaps_move_the_label_and_value() {
+            let line = StatsCard::default().render_line(StatIcon::Stars, "Stars", 42, 10, 40);
+            assert!(line.contains("class=\"label\" x=\"34\""));
+            assert!(line.contains("class=\"value\" x=\"256\""));
+
+            let card = StatsCard {
+                label_gap: 4,
+                value_gap: 64,
+                ..Default::default()
+            };
+            let line = card.render_line(StatIcon::Stars, "Stars", 42, 10, 40);
+            assert!(line.contains("class=\"label\" x=\"30\""));
+            assert!(line.contains("class=\"value\" x=\"284\""));
+        }
+
+        #[test]
+        fn icon_is_centered_on_the_label() {
+            for icon_size in [12, 16, 20] {
+                let card = StatsCard {
+                    icon_size,
+                    ..Default::default()
+                };
+                let line = card.render_line(StatIcon::Stars, "Stars", 42, 10, 40);
+                let icon_y = 40 - StatsCard::LABEL_MIDLINE - icon_size / 2;
+                assert!(
+                    line.contains(&format!("x=\"10\" y=\"{icon_y}\"")),
+                    "{icon_size}"
+                );
+                // The icon center sits on the label midline
+                assert_eq!(icon_y + icon_size / 2, 40 - StatsCard::LABEL_MIDLINE);
+            }
+        }
+
         #[test]
         fn formatted_thousands_decimal() {
             let card = StatsCard::default();
@@ -314,6 +482,71 @@ mod tests {
 
     mod fn_render {
         use super::*;
+        use crate::cards::card::Card;
+
+        #[test]
+        fn oversized_offset_returns_card_error() {
+            let card = StatsCard {
+                card_settings: CardSettings {
+                    offset_x: 1000,
+                    ..Default::default()
+                },
+                stars_count: Some(10),
+                commits_ytd_count: Some(20),
+                ..Default::default()
+            };
+            assert!(matches!(
+                card.render(),
+                Err(CardError::OffsetTooLarge { x: 1000, .. })
+            ));
+        }
+
+        #[test]
+        fn commits_label_shows_the_year() {
+            let card = StatsCard {
+                stars_count: Some(10),
+                commits_ytd_count: Some(20),
+                commits_year: Some(2024),
+                ..Default::default()
+            };
+            let svg = card.render().unwrap();
+            assert!(svg.contains(">Commits 2024:</text>"));
+            assert!(!svg.contains("Commits YTD"));
+        }
+
+        #[test]
+        fn commits_label_of_all_years() {
+            let card = StatsCard {
+                stars_count: Some(10),
+                commits_ytd_count: Some(2000),
+                commits_year: Some(2024),
+                commits_all_years: true,
+                ..Default::default()
+            };
+            let svg = card.render().unwrap();
+            assert!(svg.contains(">Commits:</text>"));
+            assert!(!svg.contains("Commits 2024"));
+            assert!(!svg.contains("Commits YTD"));
+        }
+
+        #[test]
+        fn contributions_label_replaces_the_commits_one() {
+            for (year, label) in [
+                (None, "Contributions YTD"),
+                (Some(2024), "Contributions 2024"),
+            ] {
+                let card = StatsCard {
+                    stars_count: Some(10),
+                    commits_ytd_count: Some(20),
+                    commits_year: year,
+                    counts_contributions: true,
+                    ..Default::default()
+                };
+                let svg = card.render().unwrap();
+                assert!(svg.contains(&format!(">{label}:</text>")));
+                assert!(!svg.contains("Commits"));
+            }
+        }
 
         #[test]
         fn with_some_fields() {
@@ -323,7 +556,7 @@ mod tests {
                 commits_ytd_count: Some(20),
                 ..Default::default()
             };
-            let svg = card.render();
+            let svg = card.render().unwrap();
             assert!(svg.contains("@octocat: GitHub Stats"));
             assert!(svg.contains(">Stars:</text>"));
             assert!(svg.contains(">10</text>"));
@@ -341,13 +574,29 @@ mod tests {
                 commits_ytd_count: Some(20),
                 ..Default::default()
             };
-            let svg = card.render();
+            let svg = card.render().unwrap();
             // Should use default title instead of username
             assert!(svg.contains("GitHub Stats"));
             // Should not contain username in title
             assert!(!svg.contains("@averylongusername"));
         }
 
+        #[test]
+        fn custom_title_replaces_the_default_one_escaped() {
+            let card = StatsCard {
+                username: "octocat".to_string(),
+                stars_count: Some(10),
+                commits_ytd_count: Some(20),
+                custom_title: Some("<My> Open & Source".to_string()),
+                ..Default::default()
+            };
+            let svg = card.render().unwrap();
+            assert!(svg.contains(r#"<title id="title-id">&lt;My&gt; Open &amp; Source</title>"#));
+            assert!(svg.contains(r#"class="title">&lt;My&gt; Open &amp; Source</text>"#));
+            assert!(!svg.contains("<My>"));
+            assert!(!svg.contains("GitHub Stats"));
+        }
+
         #[test]
         fn svg_is_valid_xml() {
             let card = StatsCard {
@@ -356,7 +605,7 @@ mod tests {
                 commits_ytd_count: Some(2),
                 ..Default::default()
             };
-            let svg = card.render();
+            let svg = card.render().unwrap();
             // Use quick_xml to check well-formedness
             use quick_xml::Reader;
             use quick_xml::events::Event;
@@ -376,6 +625,280 @@ mod tests {
             }
             assert!(found_svg, "SVG root element not found");
         }
+
+        #[test]
+        fn crafted_username_is_escaped() {
+            let card = StatsCard {
+                username: "a<b>&\"c".to_string(),
+                stars_count: Some(1),
+                commits_ytd_count: Some(2),
+                ..Default::default()
+            };
+            let svg = card.render().unwrap();
+            crate::cards::helpers::assert_valid_xml(&svg);
+            assert!(svg.contains("@a&lt;b&gt;&amp;&quot;c: GitHub Stats"));
+        }
+
+        /// Returns the value of the first `name="..."` attribute (the root `<svg>` one).
+        fn svg_attr<'a>(svg: &'a str, name: &str) -> &'a str {
+            let start = svg.find(&format!("{name}=\"")).unwrap() + name.len() + 2;
+            let end = svg[start..].find('"').unwrap() + start;
+            &svg[start..end]
+        }
+
+        #[test]
+        fn tiny_offsets_keep_the_rows_inside_the_card() {
+            for hide_title in [true, false] {
+                for offset in [0, 1, 5] {
+                    let svg = StatsCard {
+                        username: "octocat".to_string(),
+                        stars_count: Some(10),
+                        commits_ytd_count: Some(20),
+                        issues_count: Some(30),
+                        pull_requests_count: Some(40),
+                        card_settings: CardSettings {
+                            offset_x: offset,
+                            offset_y: offset,
+                            hide_title,
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    }
+                    .render()
+                    .unwrap();
+
+                    let height: u32 = svg_attr(&svg, "height").parse().unwrap();
+                    let icon = &svg[svg.find("<g class=\"row\">").unwrap()..];
+                    let icon_y: u32 = svg_attr(&icon[icon.find(" y=").unwrap()..], "y")
+                        .parse()
+                        .unwrap();
+                    assert!(icon_y >= offset, "{offset}");
+                    assert!(icon_y < height, "{offset}");
+                }
+            }
+        }
+
+        /// A [StatsCard] showing every stat row, with the given offsets on both axes.
+        fn all_rows_card(offset: u32, hide_title: bool, icon_size: u32) -> StatsCard {
+            StatsCard {
+                username: "octocat".to_string(),
+                stars_count: Some(1),
+                commits_ytd_count: Some(2),
+                issues_count: Some(3),
+                pull_requests_count: Some(4),
+                merge_requests_count: Some(5),
+                reviews_count: Some(6),
+                started_discussions_count: Some(7),
+                answered_discussions_count: Some(8),
+                icon_size,
+                card_settings: CardSettings {
+                    offset_x: offset,
+                    offset_y: offset,
+                    hide_title,
+                    ..Default::default()
+                },
+                ..Default::default()
+            }
+        }
+
+        #[test]
+        fn all_rows_with_the_largest_query_offsets_render() {
+            // 100 is the largest offset the card routes accept
+            for hide_title in [true, false] {
+                for icon_size in [StatsCard::MIN_ICON_SIZE, StatsCard::MAX_ICON_SIZE] {
+                    for offset in [0, 100] {
+                        let case =
+                            format!("offset {offset}, hide_title {hide_title}, icon {icon_size}");
+                        let svg = all_rows_card(offset, hide_title, icon_size)
+                            .render()
+                            .unwrap_or_else(|e| panic!("{case}: {e}"));
+
+                        let width: u32 = svg_attr(&svg, "width").parse().unwrap();
+                        let height: u32 = svg_attr(&svg, "height").parse().unwrap();
+                        assert_eq!(svg_attr(&svg, "viewBox"), format!("0 0 {width} {height}"));
+                        assert_eq!(svg.matches("<g class=\"row\">").count(), 8);
+
+                        // The last icon stays inside the card, ending exactly offset_y
+                        // above the bottom edge without a title
+                        let last_row = &svg[svg.rfind("<g class=\"row\">").unwrap()..];
+                        let icon_y: u32 = svg_attr(&last_row[last_row.find(" y=").unwrap()..], "y")
+                            .parse()
+                            .unwrap();
+                        let icon_bottom = icon_y + icon_size;
+                        assert!(icon_bottom <= height, "{case}");
+                        if hide_title {
+                            assert_eq!(icon_bottom + offset, height, "{case}");
+                        }
+                    }
+                }
+            }
+        }
+
+        #[test]
+        fn offsets_too_large_for_all_rows_fail_validation() {
+            for hide_title in [true, false] {
+                for offset in [1_000, u32::MAX / 2, u32::MAX] {
+                    let result =
+                        all_rows_card(offset, hide_title, StatsCard::MAX_ICON_SIZE).render();
+                    assert!(
+                        matches!(result, Err(CardError::OffsetTooLarge { .. })),
+                        "{offset} {hide_title}"
+                    );
+                }
+            }
+        }
+
+        #[test]
+        fn title_size_moves_the_rows_down() {
+            let card = |title_size| StatsCard {
+                stars_count: Some(10),
+                card_settings: CardSettings {
+                    title_size,
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+
+            let default_svg = card(Card::TITLE_FONT_SIZE).render().unwrap();
+            let large_svg = card(28).render().unwrap();
+            assert!(!default_svg.contains("font-size"));
+            assert!(large_svg.contains("class=\"title\" style=\"font-size: 28px\""));
+
+            // Header grows by 10px, so does the card and the first row baseline
+            let default_height: u32 = svg_attr(&default_svg, "height").parse().unwrap();
+            let large_height: u32 = svg_attr(&large_svg, "height").parse().unwrap();
+            assert_eq!(large_height, default_height + 10);
+
+            let row_y = |svg: &str| -> u32 {
+                let label = &svg[svg.find("<text class=\"label\"").unwrap()..];
+                svg_attr(&label[label.find(" y=").unwrap()..], "y")
+                    .parse()
+                    .unwrap()
+            };
+            assert_eq!(row_y(&large_svg), row_y(&default_svg) + 10);
+        }
+
+        #[test]
+        fn width_follows_the_gaps() {
+            let width = |label_gap, value_gap| -> u32 {
+                let svg = StatsCard {
+                    username: "octocat".to_string(),
+                    stars_count: Some(10),
+                    commits_ytd_count: Some(20),
+                    label_gap,
+                    value_gap,
+                    ..Default::default()
+                }
+                .render()
+                .unwrap();
+                svg_attr(&svg, "width").parse().unwrap()
+            };
+
+            let default_width = width(StatsCard::DEFAULT_LABEL_GAP, StatsCard::DEFAULT_VALUE_GAP);
+            assert_eq!(default_width, 301);
+            assert_eq!(width(4, 64), default_width - 4 + 32);
+        }
+
+        #[test]
+        fn width_respects_min_and_max_width() {
+            for username in ["ab", "a-very-long-github-username-here"] {
+                let card = |min_width, max_width| StatsCard {
+                    username: username.to_string(),
+                    stars_count: Some(10),
+                    commits_ytd_count: Some(20),
+                    card_settings: CardSettings {
+                        min_width,
+                        max_width,
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                };
+
+                let natural = card(None, None).render().unwrap();
+                let natural_width: u32 = svg_attr(&natural, "width").parse().unwrap();
+                let natural_height: u32 = svg_attr(&natural, "height").parse().unwrap();
+
+                // Padded: wider SVG and viewBox, same height
+                let padded = card(Some(natural_width + 100), None).render().unwrap();
+                let padded_width = natural_width + 100;
+                assert_eq!(svg_attr(&padded, "width"), padded_width.to_string());
+                assert_eq!(
+                    svg_attr(&padded, "viewBox"),
+                    format!("0 0 {padded_width} {natural_height}")
+                );
+
+                // Scaled down: narrower SVG, the viewBox keeps the content size
+                let max_width = natural_width / 2;
+                let scaled = card(None, Some(max_width)).render().unwrap();
+                assert_eq!(svg_attr(&scaled, "width"), max_width.to_string());
+                assert_eq!(
+                    svg_attr(&scaled, "viewBox"),
+                    format!("0 0 {natural_width} {natural_height}")
+                );
+                let scaled_height: u32 = svg_attr(&scaled, "height").parse().unwrap();
+                assert!(scaled_height < natural_height);
+
+                // Bounds that already fit leave the card untouched
+                let fitting = card(Some(100), Some(natural_width + 1)).render().unwrap();
+                assert_eq!(svg_attr(&fitting, "width"), natural_width.to_string());
+            }
+        }
+    }
+
+    mod fn_rows {
+        use super::*;
+
+        fn card(sort: Option<StatsSort>) -> StatsCard {
+            StatsCard {
+                stars_count: Some(5),
+                commits_ytd_count: Some(300),
+                issues_count: None,
+                pull_requests_count: Some(42),
+                reviews_count: Some(42),
+                sort,
+                ..Default::default()
+            }
+        }
+
+        fn labels(card: &StatsCard) -> Vec<String> {
+            card.rows()
+                .into_iter()
+                .map(|(_, label, _)| label.into_owned())
+                .collect()
+        }
+
+        #[test]
+        fn definition_order_by_default() {
+            assert_eq!(
+                labels(&card(None)),
+                vec!["Stars", "Commits YTD", "Pull Requests", "Reviews"]
+            );
+        }
+
+        #[test]
+        fn value_desc() {
+            assert_eq!(
+                labels(&card(Some(StatsSort::ValueDesc))),
+                vec!["Commits YTD", "Pull Requests", "Reviews", "Stars"]
+            );
+        }
+
+        #[test]
+        fn value_asc() {
+            assert_eq!(
+                labels(&card(Some(StatsSort::ValueAsc))),
+                vec!["Stars", "Pull Requests", "Reviews", "Commits YTD"]
+            );
+        }
+
+        #[test]
+        fn rendered_rows_follow_sort_order() {
+            let svg = card(Some(StatsSort::ValueDesc)).render().unwrap();
+            let commits = svg.find(">Commits YTD:</text>").unwrap();
+            let prs = svg.find(">Pull Requests:</text>").unwrap();
+            let stars = svg.find(">Stars:</text>").unwrap();
+            assert!(commits < prs && prs < stars);
+        }
     }
 
     mod fn_format_value {
@@ -417,4 +940,90 @@ mod tests {
             assert_eq!(card.format_value(15_234), "15k");
         }
     }
+
+    mod fn_render_columns {
+        use super::*;
+
+        fn card(max_rows: Option<u32>) -> StatsCard {
+            StatsCard {
+                username: "octocat".to_string(),
+                stars_count: Some(1),
+                commits_ytd_count: Some(2),
+                issues_count: Some(3),
+                pull_requests_count: Some(4),
+                merge_requests_count: Some(5),
+                reviews_count: Some(6),
+                started_discussions_count: Some(7),
+                answered_discussions_count: Some(8),
+                max_rows,
+                ..Default::default()
+            }
+        }
+
+        fn root_attr(svg: &str, name: &str) -> u32 {
+            let start = svg.find(&format!(" {name}=\"")).unwrap() + name.len() + 3;
+            svg[start..start + svg[start..].find('"').unwrap()]
+                .parse()
+                .unwrap()
+        }
+
+        /// The `x` and `y` of the label of every row, in order.
+        fn label_positions(svg: &str) -> Vec<(u32, u32)> {
+            svg.match_indices("<text class=\"label\"")
+                .map(|(start, _)| {
+                    let tag = &svg[start..];
+                    (root_attr(tag, "x"), root_attr(tag, "y"))
+                })
+                .collect()
+        }
+
+        #[test]
+        fn wraps_into_two_columns_of_four_rows() {
+            let svg = card(Some(4)).render().unwrap();
+            let positions = label_positions(&svg);
+            assert_eq!(positions.len(), 8);
+
+            let (first, second) = positions.split_at(4);
+            // Each column has four rows, the second one on the same baselines to the right
+            assert!(first.iter().all(|(x, _)| *x == first[0].0));
+            assert!(second.iter().all(|(x, _)| *x == second[0].0));
+            assert!(second[0].0 > first[0].0);
+            let baselines =
+                |column: &[(u32, u32)]| column.iter().map(|(_, y)| *y).collect::<Vec<_>>();
+            assert_eq!(baselines(first), baselines(second));
+            assert!(svg.contains(">Stars:</text>"));
+            assert!(svg.contains(">Answered Discussions:</text>"));
+        }
+
+        #[test]
+        fn width_doubles_and_height_follows_the_tallest_column() {
+            let single = card(None).render().unwrap();
+            let columns = card(Some(4)).render().unwrap();
+            let offsets = 2 * StatsCard::default().card_settings.offset_x;
+            let column_width = root_attr(&single, "width") - offsets;
+            assert_eq!(
+                root_attr(&columns, "width"),
+                2 * column_width + StatsCard::COLUMN_GAP + offsets
+            );
+            assert_eq!(
+                root_attr(&single, "height") - root_attr(&columns, "height"),
+                4 * StatsCard::ROW_Y_STEP
+            );
+
+            // A third column holds the rows left over
+            let three = card(Some(3)).render().unwrap();
+            assert_eq!(
+                root_attr(&three, "width"),
+                3 * column_width + 2 * StatsCard::COLUMN_GAP + offsets
+            );
+        }
+
+        #[test]
+        fn max_rows_above_the_row_count_keeps_one_column() {
+            assert_eq!(
+                card(Some(10)).render().unwrap(),
+                card(None).render().unwrap()
+            );
+        }
+    }
 }
@@ -0,0 +1,417 @@
+use crate::cards::card::{Card, CardError, CardSettings, Svg};
+use crate::cards::helpers::{DebugGuides, escape_xml_text};
+
+/// Represents a single day of the GitHub contribution calendar.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContributionDay {
+    /// Calendar date as returned by GitHub (`YYYY-MM-DD`).
+    pub date: String,
+    /// The number of contributions made on this day.
+    pub contribution_count: u32,
+}
+
+/// Represents a run of consecutive days with at least one contribution.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StreakRange {
+    /// First day of the streak (`YYYY-MM-DD`).
+    pub start: String,
+    /// Last day of the streak (`YYYY-MM-DD`).
+    pub end: String,
+    /// Number of days in the streak.
+    pub length: u32,
+}
+
+/// Streak statistics computed from a contribution calendar.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StreakStats {
+    /// Sum of all contributions in the calendar.
+    pub total_contributions: u32,
+    /// The streak that is still ongoing, if any.
+    pub current_streak: Option<StreakRange>,
+    /// The longest streak in the calendar, if any.
+    pub longest_streak: Option<StreakRange>,
+    /// First day of the calendar (`YYYY-MM-DD`), the start of the total.
+    pub first_day: Option<String>,
+}
+
+impl StreakStats {
+    /// Computes the streak statistics from chronologically ordered contribution days.
+    ///
+    /// Days are bucketed using GitHub's returned day boundaries, which follow the user's
+    /// configured timezone, so no timezone conversion is applied here. The days are
+    /// expected to be contiguous, the way `contributionCalendar` returns them, with the
+    /// last day being "today".
+    ///
+    /// Today without contributions does not break the current streak: the day is
+    /// not over yet, so a streak that ends yesterday is still reported as current.
+    pub fn from_days(days: &[ContributionDay]) -> Self {
+        let total_contributions = days.iter().map(|d| d.contribution_count).sum();
+
+        let mut longest_streak: Option<StreakRange> = None;
+        let mut run_start: Option<usize> = None;
+
+        for (i, day) in days.iter().enumerate() {
+            if day.contribution_count > 0 {
+                let start = *run_start.get_or_insert(i);
+                let length = (i - start + 1) as u32;
+                if longest_streak.as_ref().is_none_or(|l| length > l.length) {
+                    longest_streak = Some(Self::range(days, start, i));
+                }
+            } else {
+                run_start = None;
+            }
+        }
+
+        Self {
+            total_contributions,
+            current_streak: Self::current_streak(days),
+            longest_streak,
+            first_day: days.first().map(|d| d.date.clone()),
+        }
+    }
+
+    /// Finds the streak ending today, or yesterday when today has no contributions yet.
+    fn current_streak(days: &[ContributionDay]) -> Option<StreakRange> {
+        let mut end = days.len().checked_sub(1)?;
+        if days[end].contribution_count == 0 {
+            end = end.checked_sub(1)?;
+        }
+        if days[end].contribution_count == 0 {
+            return None;
+        }
+
+        let start = days[..end]
+            .iter()
+            .rposition(|d| d.contribution_count == 0)
+            .map(|i| i + 1)
+            .unwrap_or(0);
+
+        Some(Self::range(days, start, end))
+    }
+
+    fn range(days: &[ContributionDay], start: usize, end: usize) -> StreakRange {
+        StreakRange {
+            start: days[start].date.clone(),
+            end: days[end].date.clone(),
+            length: (end - start + 1) as u32,
+        }
+    }
+}
+
+/// Card with the current streak, the longest streak and the total contributions of
+/// the user, side by side.
+pub struct StreakCard {
+    pub card_settings: CardSettings,
+    pub username: String,
+    pub stats: StreakStats,
+}
+
+impl StreakCard {
+    // Constants for rendering the card (in pixels).
+    const MAX_USERNAME_LEN: usize = 13;
+    const COLUMN_WIDTH: u32 = 160;
+    /// Space between the title baseline and the top of the values.
+    const TITLE_BODY_OFFSET: u32 = 14;
+    const VALUE_SIZE: u32 = 24;
+    const LABEL_Y_STEP: u32 = 22;
+    const DATES_Y_STEP: u32 = 18;
+    /// Room left under the dates baseline for the descenders.
+    const DATES_DESCENT: u32 = 4;
+
+    /// Renders the [StreakCard] as an [Svg] string.
+    /// Fails with a [CardError] when the settings don't fit the card dimensions.
+    pub fn render(&self) -> Result<Svg, CardError> {
+        let settings = &self.card_settings;
+        let header_size_y = if settings.hide_title {
+            0
+        } else {
+            settings.title_size + Self::TITLE_BODY_OFFSET
+        };
+        let value_y = (header_size_y + Self::VALUE_SIZE).saturating_add(settings.offset_y);
+        let label_y = value_y.saturating_add(Self::LABEL_Y_STEP);
+        let dates_y = label_y.saturating_add(Self::DATES_Y_STEP);
+
+        let mut lines: Vec<String> = self
+            .columns()
+            .iter()
+            .enumerate()
+            .map(|(i, (value, label, dates))| {
+                let x = settings
+                    .offset_x
+                    .saturating_add(Self::COLUMN_WIDTH * i as u32 + Self::COLUMN_WIDTH / 2);
+                format!(
+                    r#"    <g class="column">
+      <text class="value" x="{x}" y="{value_y}" text-anchor="middle">{value}</text>
+      <text class="label" x="{x}" y="{label_y}" text-anchor="middle">{label}</text>
+      <text class="label dates" x="{x}" y="{dates_y}" text-anchor="middle">{dates}</text>
+    </g>"#
+                )
+            })
+            .collect();
+
+        let width = (3 * Self::COLUMN_WIDTH).saturating_add(settings.offset_x.saturating_mul(2));
+        let height = (dates_y + Self::DATES_DESCENT).saturating_add(settings.offset_y);
+
+        if settings.debug {
+            let mut guides = DebugGuides::default();
+            for y in [value_y, label_y, dates_y] {
+                guides.baseline(y);
+            }
+            lines.push(guides.render(width, height, settings.offset_x, settings.offset_y));
+        }
+
+        let display_title =
+            if self.username.is_empty() || self.username.len() > Self::MAX_USERNAME_LEN {
+                "Contribution Streak".to_string()
+            } else {
+                format!("@{}: Contribution Streak", escape_xml_text(&self.username))
+            };
+
+        let card = Card::new(
+            width,
+            height,
+            display_title,
+            String::from("GitHub contribution streak"),
+            lines.join("\n"),
+            "streakCard".to_string(),
+            settings.clone(),
+        )?;
+        Ok(card.render())
+    }
+
+    /// Value, label and dates of the current streak, longest streak and total columns.
+    fn columns(&self) -> [(u32, &'static str, String); 3] {
+        let stats = &self.stats;
+        let streak = |range: &Option<StreakRange>| match range {
+            Some(range) => (range.length, format_date_range(&range.start, &range.end)),
+            None => (0, "No streak yet".to_string()),
+        };
+        let (current, current_dates) = streak(&stats.current_streak);
+        let (longest, longest_dates) = streak(&stats.longest_streak);
+        let total_dates = match &stats.first_day {
+            Some(first_day) if stats.total_contributions > 0 => {
+                format!("{} - Present", format_date(first_day))
+            }
+            _ => "No contributions yet".to_string(),
+        };
+
+        [
+            (current, "Current Streak", current_dates),
+            (longest, "Longest Streak", longest_dates),
+            (
+                stats.total_contributions,
+                "Total Contributions",
+                total_dates,
+            ),
+        ]
+    }
+}
+
+/// Formats a `YYYY-MM-DD` date as `Jan 2, 2024`, leaving other strings as they are.
+fn format_date(date: &str) -> String {
+    match parse_date(date) {
+        Some((year, month, day)) => format!("{month} {day}, {year}"),
+        None => date.to_string(),
+    }
+}
+
+/// Formats a streak range as `Jan 2 - Mar 3, 2024`, with the year of both ends
+/// when they differ and a single date for single-day streaks.
+fn format_date_range(start: &str, end: &str) -> String {
+    if start == end {
+        return format_date(start);
+    }
+    match (parse_date(start), parse_date(end)) {
+        (Some((start_year, month, day)), Some((end_year, ..))) if start_year == end_year => {
+            format!("{month} {day} - {}", format_date(end))
+        }
+        _ => format!("{} - {}", format_date(start), format_date(end)),
+    }
+}
+
+/// Splits a `YYYY-MM-DD` date into its year, month abbreviation and day.
+fn parse_date(date: &str) -> Option<(u32, &'static str, u32)> {
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+    let mut parts = date.splitn(3, '-').map(str::parse::<u32>);
+    let (year, month, day) = (
+        parts.next()?.ok()?,
+        parts.next()?.ok()?,
+        parts.next()?.ok()?,
+    );
+    let month = MONTHS.get(month.checked_sub(1)? as usize)?;
+    (1..=31).contains(&day).then_some((year, month, day))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn days(counts: &[u32]) -> Vec<ContributionDay> {
+        counts
+            .iter()
+            .enumerate()
+            .map(|(i, &count)| ContributionDay {
+                date: format!("2024-01-{:02}", i + 1),
+                contribution_count: count,
+            })
+            .collect()
+    }
+
+    mod fn_from_days {
+        use super::*;
+
+        #[test]
+        fn today_zero_yesterday_one_preserves_current_streak() {
+            let stats = StreakStats::from_days(&days(&[0, 2, 1, 0]));
+            assert_eq!(
+                stats.current_streak,
+                Some(StreakRange {
+                    start: "2024-01-02".to_string(),
+                    end: "2024-01-03".to_string(),
+                    length: 2,
+                })
+            );
+        }
+
+        #[test]
+        fn today_with_contributions_extends_current_streak() {
+            let stats = StreakStats::from_days(&days(&[1, 0, 1, 3]));
+            let current = stats.current_streak.unwrap();
+            assert_eq!(current.start, "2024-01-03");
+            assert_eq!(current.end, "2024-01-04");
+            assert_eq!(current.length, 2);
+        }
+
+        #[test]
+        fn two_days_without_contributions_break_current_streak() {
+            let stats = StreakStats::from_days(&days(&[4, 1, 0, 0]));
+            assert_eq!(stats.current_streak, None);
+            assert_eq!(stats.longest_streak.unwrap().length, 2);
+        }
+
+        #[test]
+        fn longest_streak_and_total() {
+            let stats = StreakStats::from_days(&days(&[1, 1, 1, 0, 2, 2, 0, 5]));
+            assert_eq!(stats.total_contributions, 12);
+            let longest = stats.longest_streak.unwrap();
+            assert_eq!(longest.start, "2024-01-01");
+            assert_eq!(longest.end, "2024-01-03");
+            assert_eq!(longest.length, 3);
+            assert_eq!(stats.current_streak.unwrap().length, 1);
+        }
+
+        #[test]
+        fn no_contributions() {
+            let stats = StreakStats::from_days(&days(&[0, 0, 0]));
+            assert_eq!(stats.total_contributions, 0);
+            assert_eq!(stats.current_streak, None);
+            assert_eq!(stats.longest_streak, None);
+
+            let stats = StreakStats::from_days(&[]);
+            assert_eq!(stats.current_streak, None);
+        }
+    }
+
+    fn card(counts: &[u32]) -> StreakCard {
+        StreakCard {
+            card_settings: CardSettings::default(),
+            username: "octocat".to_string(),
+            stats: StreakStats::from_days(&days(counts)),
+        }
+    }
+
+    mod fn_render {
+        use super::*;
+
+        #[test]
+        fn renders_the_three_columns() {
+            let svg = card(&[1, 1, 1, 0, 2, 2, 0, 5]).render().unwrap();
+            assert!(svg.contains("@octocat: Contribution Streak"));
+            assert!(svg.contains(r#"class="streakCard""#));
+            for expected in [
+                ">1</text>",
+                ">Current Streak</text>",
+                ">Jan 8, 2024</text>",
+                ">3</text>",
+                ">Longest Streak</text>",
+                ">Jan 1 - Jan 3, 2024</text>",
+                ">12</text>",
+                ">Total Contributions</text>",
+                ">Jan 1, 2024 - Present</text>",
+            ] {
+                assert!(svg.contains(expected), "{expected}");
+            }
+        }
+
+        #[test]
+        fn account_without_contributions() {
+            let svg = card(&[0, 0, 0]).render().unwrap();
+            assert_eq!(svg.matches(">0</text>").count(), 3);
+            assert_eq!(svg.matches(">No streak yet</text>").count(), 2);
+            assert!(svg.contains(">No contributions yet</text>"));
+
+            let empty = StreakCard {
+                stats: StreakStats::from_days(&[]),
+                ..card(&[])
+            };
+            assert!(
+                empty
+                    .render()
+                    .unwrap()
+                    .contains(">No contributions yet</text>")
+            );
+        }
+
+        #[test]
+        fn hidden_title_shortens_the_card() {
+            let mut card = card(&[1]);
+            let with_title = card.render().unwrap();
+            card.card_settings.hide_title = true;
+            let without_title = card.render().unwrap();
+            assert!(with_title.contains(r#"height="123""#));
+            assert!(without_title.contains(r#"height="92""#));
+            assert!(!without_title.contains("Contribution Streak</text>"));
+        }
+
+        #[test]
+        fn long_usernames_are_left_out_of_the_title() {
+            let card = StreakCard {
+                username: "a-very-long-username".to_string(),
+                ..card(&[1])
+            };
+            let svg = card.render().unwrap();
+            assert!(svg.contains(">Contribution Streak</text>"));
+            assert!(!svg.contains("a-very-long-username"));
+        }
+    }
+
+    mod fn_format_date_range {
+        use super::*;
+
+        #[test]
+        fn same_year_is_written_once() {
+            assert_eq!(
+                format_date_range("2024-01-02", "2024-03-13"),
+                "Jan 2 - Mar 13, 2024"
+            );
+            assert_eq!(
+                format_date_range("2023-12-30", "2024-01-02"),
+                "Dec 30, 2023 - Jan 2, 2024"
+            );
+            assert_eq!(format_date_range("2024-05-01", "2024-05-01"), "May 1, 2024");
+        }
+
+        #[test]
+        fn unexpected_dates_are_kept() {
+            assert_eq!(format_date("2024-13-01"), "2024-13-01");
+            assert_eq!(format_date("yesterday"), "yesterday");
+            assert_eq!(
+                format_date_range("2024-00-01", "2024-01-02"),
+                "2024-00-01 - Jan 2, 2024"
+            );
+        }
+    }
+}
@@ -1,10 +1,16 @@
-use reqwest::Client;
 use serde_json::json;
+use std::collections::HashMap;
 use std::env;
-use std::sync::OnceLock;
-use std::sync::{Arc, RwLock};
-
-use crate::github::cache::get_github_cache;
+use std::fmt;
+use std::hash::{BuildHasher, RandomState};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+use std::time::Duration;
+
+use crate::cards::streak_card::ContributionDay;
+use crate::github::cache::{Cached, get_github_cache};
+use crate::github::clock::{Clock, SystemClock};
+use crate::github::transport::{GRAPHQL_ENDPOINT, GraphQLTransport, HttpTransport};
 use crate::github::types::*;
 
 #[derive(Debug, Clone, Default)]
@@ -24,6 +30,47 @@ fn get_rate_limit_state() -> Arc<RwLock<GitHubRateLimit>> {
         .clone()
 }
 
+/// Rate limit states of the tokens supplied with the requests, keyed by a hash of the token
+static TOKEN_RATE_LIMIT_STATES: OnceLock<TokenRateLimitStates> = OnceLock::new();
+
+/// Most token rate limit states kept, all of them are forgotten beyond it
+const MAX_TOKEN_RATE_LIMIT_STATES: usize = 1024;
+
+type TokenRateLimitStates = Mutex<(RandomState, HashMap<u64, Arc<RwLock<GitHubRateLimit>>>)>;
+
+/// Returns the rate limit state of a token supplied with a request, shared by
+/// the requests using the same token, separate from the server token's one.
+fn token_rate_limit_state(token: &GitHubToken) -> Arc<RwLock<GitHubRateLimit>> {
+    let states =
+        TOKEN_RATE_LIMIT_STATES.get_or_init(|| Mutex::new((RandomState::new(), HashMap::new())));
+    let mut guard = states
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    let (hasher, states) = &mut *guard;
+
+    let key = hasher.hash_one(&token.0);
+    if !states.contains_key(&key) && states.len() >= MAX_TOKEN_RATE_LIMIT_STATES {
+        // A forgotten state starts over without data, GitHub still enforces the limit
+        states.clear();
+    }
+    states.entry(key).or_default().clone()
+}
+
+/// Clears the global rate limit state for a test, returning a guard that keeps other
+/// tests using it from running until the guard is dropped.
+#[cfg(test)]
+pub(crate) fn reset_rate_limit_state_for_test() -> std::sync::MutexGuard<'static, ()> {
+    static TEST_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+    let guard = TEST_LOCK
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    let state = get_rate_limit_state();
+    *state
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()) = GitHubRateLimit::default();
+    guard
+}
+
 /// Get the current GitHub rate limit information
 pub fn get_github_rate_limit() -> GitHubRateLimit {
     let state = get_rate_limit_state();
@@ -34,9 +81,11 @@ pub fn get_github_rate_limit() -> GitHubRateLimit {
     guard.clone()
 }
 
-/// Update the GitHub rate limit information from response headers
-fn update_rate_limit_from_headers(headers: &reqwest::header::HeaderMap) {
-    let state = get_rate_limit_state();
+/// Update the GitHub rate limit information `state` from response headers
+fn update_rate_limit_from_headers(
+    state: &RwLock<GitHubRateLimit>,
+    headers: &reqwest::header::HeaderMap,
+) {
     let mut guard = state.write().unwrap_or_else(|poisoned| {
         // If the lock is poisoned, we still want to update the data
         poisoned.into_inner()
@@ -64,14 +113,26 @@ fn update_rate_limit_from_headers(headers: &reqwest::header::HeaderMap) {
         .and_then(|s| s.parse().ok());
 }
 
-/// Check if we should make a GitHub API request based on current rate limits
-fn check_rate_limit_before_request() -> Result<(), GitHubApiError> {
-    let rate_limit = get_github_rate_limit();
-    check_rate_limit_with_data(&rate_limit)
+/// Check if we should make a GitHub API request based on the current rate limit `state`
+/// and the time told by the `clock`
+fn check_rate_limit_before_request(
+    state: &RwLock<GitHubRateLimit>,
+    clock: &dyn Clock,
+) -> Result<(), GitHubApiError> {
+    let rate_limit = state
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .clone();
+    check_rate_limit_with_data(&rate_limit, rate_limit_threshold(), clock)
 }
 
-/// Check if we should make a GitHub API request based on provided rate limit data
-fn check_rate_limit_with_data(rate_limit: &GitHubRateLimit) -> Result<(), GitHubApiError> {
+/// Check if we should make a GitHub API request based on provided rate limit data,
+/// blocking below `threshold` remaining requests until its reset time by the `clock`
+fn check_rate_limit_with_data(
+    rate_limit: &GitHubRateLimit,
+    threshold: u64,
+    clock: &dyn Clock,
+) -> Result<(), GitHubApiError> {
     // If we don't have rate limit info yet, allow the request
     if rate_limit.remaining.is_none() || rate_limit.reset.is_none() {
         return Ok(());
@@ -81,14 +142,9 @@ fn check_rate_limit_with_data(rate_limit: &GitHubRateLimit) -> Result<(), GitHub
     let reset_time = rate_limit.reset.unwrap();
 
     // Check if remaining requests are below threshold
-    if remaining < 100 {
+    if remaining < threshold {
         // Check if we're still within the rate limit window
-        let current_time = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .map(|d| d.as_secs())
-            .unwrap_or(0);
-
-        if current_time < reset_time {
+        if clock.now() < reset_time {
             return Err(GitHubApiError::RateLimitProtection(remaining, reset_time));
         }
 
@@ -99,10 +155,341 @@ fn check_rate_limit_with_data(rate_limit: &GitHubRateLimit) -> Result<(), GitHub
     Ok(())
 }
 
+/// Remaining GitHub requests below which new requests wait for the rate limit reset,
+/// unless `RATE_LIMIT_MIN_REMAINING` sets another threshold
+pub const RATE_LIMIT_THRESHOLD: u64 = 100;
+
+/// Threshold of the rate limit protection, read once from `RATE_LIMIT_MIN_REMAINING`
+pub fn rate_limit_threshold() -> u64 {
+    static THRESHOLD: OnceLock<u64> = OnceLock::new();
+    *THRESHOLD.get_or_init(|| {
+        parse_rate_limit_threshold(env::var("RATE_LIMIT_MIN_REMAINING").ok().as_deref())
+    })
+}
+
+/// Parses the threshold of the rate limit protection, [RATE_LIMIT_THRESHOLD] when unset or invalid
+fn parse_rate_limit_threshold(value: Option<&str>) -> u64 {
+    let Some(value) = value.map(str::trim).filter(|value| !value.is_empty()) else {
+        return RATE_LIMIT_THRESHOLD;
+    };
+    value.parse().unwrap_or_else(|_| {
+        tracing::warn!(
+            "Invalid RATE_LIMIT_MIN_REMAINING {value:?}, using {RATE_LIMIT_THRESHOLD} instead"
+        );
+        RATE_LIMIT_THRESHOLD
+    })
+}
+
+/// Returns the calendar year in UTC at the time told by the `clock`
+pub fn current_utc_year_with(clock: &dyn Clock) -> i32 {
+    utc_year(clock.now())
+}
+
+/// Returns the calendar year (UTC) of a unix timestamp in seconds
+fn utc_year(unix_secs: u64) -> i32 {
+    // Civil from days, see https://howardhinnant.github.io/date_algorithms.html
+    let days = (unix_secs / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+    let year = year_of_era + era * 400 + if month >= 10 { 1 } else { 0 };
+    year as i32
+}
+
+/// Tells whether a GitHub token is configured, without exposing it
+pub fn is_token_configured() -> bool {
+    env::var("GITHUB_TOKEN").is_ok_and(|token| !token.trim().is_empty())
+}
+
+/// Repository fields needed for the stats
+const STATS_REPOSITORY_FIELDS: &str = "name stargazers { totalCount }";
+
+/// Repository fields needed for both the stats and the languages, see [GitHubApi::fetch_user_full]
+const PROFILE_REPOSITORY_FIELDS: &str = "name isFork stargazers { totalCount } \
+    languages(first: 10, orderBy: {field: SIZE, direction: DESC}) { edges { size node { color name } } }";
+
+/// Maximum number of attempts for a single GraphQL request, retries included
+const MAX_ATTEMPTS: u32 = 3;
+
+/// Delay before the first retry, doubled for every following one
+const RETRY_BACKOFF: Duration = Duration::from_millis(250);
+
+/// Maximum number of GitHub requests a single [GitHubApi] client may send, retries included.
+/// The routes create one client per rendered card, so this bounds the requests per render.
+const REQUEST_BUDGET: u32 = 50;
+
+/// Tracks how many GitHub requests a client can still send
+#[derive(Debug)]
+struct RequestBudget {
+    remaining: AtomicU32,
+}
+
+impl RequestBudget {
+    fn new(limit: u32) -> Self {
+        Self {
+            remaining: AtomicU32::new(limit),
+        }
+    }
+
+    /// Consumes one request from the budget, returns `false` when it is exhausted
+    fn try_acquire(&self) -> bool {
+        self.remaining
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |r| r.checked_sub(1))
+            .is_ok()
+    }
+}
+
+/// Runs `attempt` until it succeeds, fails with a non-retryable error or runs out of
+/// attempts. Every attempt, retries included, is counted against `budget`: once it is
+/// exhausted the last error is returned, or [GitHubApiError::RequestBudgetExceeded]
+/// if no attempt could be made at all.
+async fn with_retry_budget<T, F, Fut>(
+    budget: &RequestBudget,
+    max_attempts: u32,
+    backoff: Duration,
+    mut attempt: F,
+) -> Result<T, GitHubApiError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, GitHubApiError>>,
+{
+    let mut last_error = None;
+
+    for attempt_number in 0..max_attempts {
+        if !budget.try_acquire() {
+            tracing::warn!("GitHub request budget exhausted, giving up");
+            break;
+        }
+
+        if let Some(e) = &last_error {
+            let delay = backoff * 2u32.pow(attempt_number - 1);
+            tracing::warn!("Retrying GitHub request in {delay:?} after error: {e}");
+            tokio::time::sleep(delay).await;
+        }
+
+        match attempt().await {
+            Err(e @ GitHubApiError::ServerError(_)) => last_error = Some(e),
+            result => return result,
+        }
+    }
+
+    Err(last_error.unwrap_or(GitHubApiError::RequestBudgetExceeded))
+}
+
+/// Most bytes read from an error response body, an HTML error page can be quite large.
+const MAX_ERROR_BODY_BYTES: usize = 16 * 1024;
+
+/// Host the avatars are downloaded from, the URLs the API returns are only followed
+/// when they point there.
+const AVATAR_URL_PREFIX: &str = "https://avatars.githubusercontent.com/";
+
+/// Size (in pixels) of the avatars downloaded to derive an accent color from.
+const AVATAR_SIZE: u32 = 64;
+
+/// Largest avatar image read, a 64 pixels avatar is a few kilobytes.
+const MAX_AVATAR_BYTES: usize = 512 * 1024;
+
+/// Most characters of a response body kept in [GitHubApiError::UnexpectedResponse].
+const RESPONSE_SNIPPET_CHARS: usize = 200;
+
+/// Reads at most `limit` bytes of the response body, ignoring read errors since the
+/// body only ends up in an error message or is checked afterwards.
+async fn read_bounded_body(mut response: reqwest::Response, limit: usize) -> Vec<u8> {
+    let mut body = Vec::new();
+    while body.len() < limit {
+        match response.chunk().await {
+            Ok(Some(chunk)) => body.extend_from_slice(&chunk),
+            _ => break,
+        }
+    }
+    body.truncate(limit);
+    body
+}
+
+/// Turns a response body of any encoding into a short single line snippet.
+fn response_snippet(body: &[u8]) -> String {
+    let text = String::from_utf8_lossy(body);
+    let line = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    match line.char_indices().nth(RESPONSE_SNIPPET_CHARS) {
+        Some((end, _)) => format!("{}…", &line[..end]),
+        None => line,
+    }
+}
+
+/// Reads the GraphQL response of a request that got past the rate limit checks.
+async fn read_response<T>(response: reqwest::Response) -> Result<GraphQLResponse<T>, GitHubApiError>
+where
+    T: serde::de::DeserializeOwned,
+{
+    let status = response.status();
+
+    // GitHub server errors are usually transient and worth a retry
+    if status.is_server_error() {
+        let snippet = response_snippet(&read_bounded_body(response, MAX_ERROR_BODY_BYTES).await);
+        tracing::warn!("GitHub API returned HTTP {status}: {snippet}");
+        return Err(GitHubApiError::ServerError(status.as_u16()));
+    }
+
+    // Other HTTP errors come with anything from JSON to an HTML page, keep a snippet of it
+    if !status.is_success() {
+        let retry_after = response
+            .headers()
+            .get(reqwest::header::RETRY_AFTER)
+            .map(|v| v.to_str().unwrap_or_default().to_string());
+        let body = read_bounded_body(response, MAX_ERROR_BODY_BYTES).await;
+        if status == reqwest::StatusCode::FORBIDDEN
+            && let Some(wait) = secondary_rate_limit_wait(retry_after.as_deref(), &body)
+        {
+            tracing::warn!("GitHub API secondary rate limit hit, retry in {wait}s");
+            return Err(GitHubApiError::SecondaryRateLimit(wait));
+        }
+        let error = GitHubApiError::UnexpectedResponse {
+            status: status.as_u16(),
+            snippet: response_snippet(&body),
+        };
+        sentry::capture_error(&error);
+        tracing::error!("{error}");
+        return Err(error);
+    }
+
+    let body = response.bytes().await.map_err(|e| {
+        sentry::capture_error(&e);
+        tracing::error!("Failed to read GitHub API response: {e}");
+        GitHubApiError::NetworkError(e)
+    })?;
+
+    parse_response_body(status.as_u16(), &body)
+}
+
+/// Wait (in seconds) after a secondary rate limit without a usable `Retry-After`,
+/// GitHub asks to wait at least a minute.
+const SECONDARY_RATE_LIMIT_WAIT: u64 = 60;
+
+/// Parts of a `403` body telling a secondary rate limit: its message and the anchors
+/// of the documentation of the secondary (formerly abuse) rate limits.
+const SECONDARY_RATE_LIMIT_MARKERS: [&str; 3] = [
+    "secondary rate limit",
+    "#secondary-rate-limits",
+    "#abuse-rate-limits",
+];
+
+/// Seconds to wait when a `403` is a secondary rate limit, i.e. it comes with a
+/// `Retry-After` header or points to the documentation of these limits.
+fn secondary_rate_limit_wait(retry_after: Option<&str>, body: &[u8]) -> Option<u64> {
+    let body = String::from_utf8_lossy(body).to_lowercase();
+    let documented = SECONDARY_RATE_LIMIT_MARKERS
+        .iter()
+        .any(|marker| body.contains(marker));
+    if retry_after.is_none() && !documented {
+        return None;
+    }
+    Some(
+        retry_after
+            .and_then(|value| value.trim().parse().ok())
+            .unwrap_or(SECONDARY_RATE_LIMIT_WAIT),
+    )
+}
+
+/// Parses a GraphQL response body, reporting a body that isn't the expected JSON
+/// with a snippet of it rather than with the JSON parse error alone.
+fn parse_response_body<T>(status: u16, body: &[u8]) -> Result<GraphQLResponse<T>, GitHubApiError>
+where
+    T: serde::de::DeserializeOwned,
+{
+    serde_json::from_slice(body).map_err(|e| {
+        let error = GitHubApiError::UnexpectedResponse {
+            status,
+            snippet: response_snippet(body),
+        };
+        sentry::capture_error(&error);
+        tracing::error!("Failed to parse GitHub API response: {e}: {error}");
+        error
+    })
+}
+
+/// Collects the repositories of `first_page` and the following ones fetched by `next_page`
+/// (given the cursor to continue after, `None` once the user is gone), stopping after
+/// `max_repos` repositories. Also tells whether repositories were left out by the cap.
+async fn collect_repositories<F, Fut>(
+    first_page: RepositoriesConnection,
+    max_repos: Option<usize>,
+    mut next_page: F,
+) -> Result<(Vec<RepositoryNode>, bool), GitHubApiError>
+where
+    F: FnMut(Option<String>) -> Fut,
+    Fut: Future<Output = Result<Option<RepositoriesConnection>, GitHubApiError>>,
+{
+    let max_repos = max_repos.unwrap_or(usize::MAX);
+    let mut page = first_page;
+    let mut repositories = Vec::new();
+
+    loop {
+        repositories.extend(page.nodes);
+        if repositories.len() >= max_repos {
+            let truncated = repositories.len() > max_repos || page.page_info.has_next_page;
+            repositories.truncate(max_repos);
+            return Ok((repositories, truncated));
+        }
+        if !page.page_info.has_next_page {
+            return Ok((repositories, false));
+        }
+        match next_page(page.page_info.end_cursor).await? {
+            Some(next) => page = next,
+            None => return Ok((repositories, false)),
+        }
+    }
+}
+
+/// Turns the response of the organization lookup into the error for a user not found.
+/// A failed lookup is not worth more than the user itself not being found.
+async fn not_found_error<Fut>(organization_lookup: Fut) -> GitHubApiError
+where
+    Fut: Future<Output = Result<GraphQLResponse<OrganizationQueryResponse>, GitHubApiError>>,
+{
+    match organization_lookup.await {
+        Ok(GraphQLResponse {
+            data:
+                Some(OrganizationQueryResponse {
+                    organization: Some(organization),
+                }),
+            ..
+        }) => GitHubApiError::IsOrganization(organization.login),
+        _ => GitHubApiError::UserNotFound,
+    }
+}
+
+/// A GitHub token, redacted from the `Debug` output so it never ends up in the logs
+#[derive(Clone, PartialEq, Eq)]
+pub struct GitHubToken(String);
+
+impl GitHubToken {
+    pub fn new(token: String) -> Self {
+        Self(token)
+    }
+}
+
+impl fmt::Debug for GitHubToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("GitHubToken(<redacted>)")
+    }
+}
+
 #[derive(Debug)]
 pub struct GitHubApi {
-    client: Client,
-    token: Option<String>,
+    /// Sends the requests, the live API unless replaced with [GitHubApi::with_transport]
+    transport: Arc<dyn GraphQLTransport>,
+    token: Option<GitHubToken>,
+    /// The token was supplied with the request rather than configured for the server
+    request_token: bool,
+    /// Rate limit state of the token, see [GitHubApi::with_token]
+    rate_limit: Arc<RwLock<GitHubRateLimit>>,
+    budget: RequestBudget,
+    /// Tells the time of the rate limit resets and the current year, the system clock
+    /// unless replaced with [GitHubApi::with_clock]
+    clock: Arc<dyn Clock>,
 }
 
 impl Default for GitHubApi {
@@ -112,12 +499,73 @@ impl Default for GitHubApi {
 }
 
 impl GitHubApi {
-    /// Create a new GitHub API client
+    /// Create a new GitHub API client using the server's `GITHUB_TOKEN`
     pub fn new() -> Self {
-        let client = Client::new();
-        let token = env::var("GITHUB_TOKEN").ok();
+        Self {
+            transport: Arc::new(HttpTransport::default()),
+            token: env::var("GITHUB_TOKEN").ok().map(GitHubToken),
+            request_token: false,
+            rate_limit: get_rate_limit_state(),
+            budget: RequestBudget::new(REQUEST_BUDGET),
+            clock: Arc::new(SystemClock),
+        }
+    }
 
-        Self { client, token }
+    /// Create a new GitHub API client using a token supplied with the request.
+    ///
+    /// Its rate limit is tracked apart from the server token's one, and what it fetches
+    /// is kept out of the shared cache as the token may see private repositories.
+    pub fn with_token(token: GitHubToken) -> Self {
+        Self {
+            transport: Arc::new(HttpTransport::default()),
+            rate_limit: token_rate_limit_state(&token),
+            token: Some(token),
+            request_token: true,
+            budget: RequestBudget::new(REQUEST_BUDGET),
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    /// Sends the requests of the client with `transport` instead of to the live API,
+    /// e.g. a [MockTransport](crate::github::transport::MockTransport) in tests.
+    pub fn with_transport(self, transport: Arc<dyn GraphQLTransport>) -> Self {
+        Self { transport, ..self }
+    }
+
+    /// Tells the time with `clock` instead of the system clock, e.g. a
+    /// [FixedClock](crate::github::FixedClock) in tests.
+    pub fn with_clock(self, clock: Arc<dyn Clock>) -> Self {
+        Self { clock, ..self }
+    }
+
+    /// The clock the client tells the time with
+    pub fn clock(&self) -> &dyn Clock {
+        self.clock.as_ref()
+    }
+
+    /// Tells whether the client uses a token supplied with the request
+    pub fn uses_request_token(&self) -> bool {
+        self.request_token
+    }
+
+    /// Creates a client sharing the transport and token of this one
+    /// with a request budget of its own.
+    fn detached(&self) -> Self {
+        Self {
+            transport: Arc::clone(&self.transport),
+            token: self.token.clone(),
+            request_token: self.request_token,
+            rate_limit: self.rate_limit.clone(),
+            budget: RequestBudget::new(REQUEST_BUDGET),
+            clock: Arc::clone(&self.clock),
+        }
+    }
+
+    /// Results fetched by this client can be shared with the other clients through the
+    /// cache. A request token may see private repositories and contributions, so what it
+    /// fetches is only for its owner and never cached.
+    fn shares_cache(&self) -> bool {
+        !self.request_token
     }
 
     /// Validate username format
@@ -155,106 +603,149 @@ impl GitHubApi {
         Ok(())
     }
 
-    /// Get the GraphQL query for fetching user stats
-    fn get_stats_query() -> String {
-        r#"
-        query GetUserStats($login: String!, $after: String) {
-            user(login: $login) {
+    /// Get the GraphQL query for fetching the user stats, requesting `repository_fields`
+    /// for each of its repositories.
+    ///
+    /// Contributions are counted within the calendar `year` (UTC). With `include_private`,
+    /// the query also asks for the authenticated `viewer` to check it owns the token.
+    fn get_user_query(options: StatsOptions, year: i32, repository_fields: &str) -> String {
+        let viewer = if options.include_private {
+            "viewer { login }"
+        } else {
+            ""
+        };
+        let affiliations = options.affiliations.graphql_list();
+
+        format!(
+            r#"
+        query GetUserStats($login: String!, $after: String) {{
+            {viewer}
+            user(login: $login) {{
                 name
                 login
-                contributionsCollection {
+                createdAt
+                contributionsCollection(from: "{year}-01-01T00:00:00Z", to: "{year}-12-31T23:59:59Z") {{
                     totalCommitContributions
                     totalPullRequestReviewContributions
-                }
-                pullRequests(first: 1) {
+                }}
+                pullRequests(first: 1) {{
                     totalCount
-                }
-                mergedPullRequests: pullRequests(states: MERGED) {
+                }}
+                mergedPullRequests: pullRequests(states: MERGED) {{
                     totalCount
-                }
-                openIssues: issues(states: OPEN) {
+                }}
+                openIssues: issues(states: OPEN) {{
                     totalCount
-                }
-                closedIssues: issues(states: CLOSED) {
+                }}
+                closedIssues: issues(states: CLOSED) {{
                     totalCount
-                }
-                repositoryDiscussions {
+                }}
+                repositoryDiscussions {{
                     totalCount
-                }
-                repositoryDiscussionComments(onlyAnswers: true) {
+                }}
+                repositoryDiscussionComments(onlyAnswers: true) {{
                     totalCount
-                }
-                repositories(first: 100, ownerAffiliations: OWNER, orderBy: {direction: DESC, field: STARGAZERS}, after: $after) {
+                }}
+                repositories(first: 100, ownerAffiliations: {affiliations}, orderBy: {{direction: DESC, field: STARGAZERS}}, after: $after) {{
                     totalCount
-                    nodes {
-                        name
-                        stargazers {
-                            totalCount
-                        }
-                    }
-                    pageInfo {
+                    nodes {{
+                        {repository_fields}
+                    }}
+                    pageInfo {{
                         hasNextPage
                         endCursor
-                    }
-                }
-            }
+                    }}
+                }}
+            }}
+        }}
+        "#
+        )
+    }
+
+    /// Private contributions can only be counted for the owner of the token
+    fn ensure_token_owner(
+        viewer: Option<&ViewerData>,
+        username: &str,
+    ) -> Result<(), GitHubApiError> {
+        match viewer {
+            Some(viewer) if viewer.login.eq_ignore_ascii_case(username) => Ok(()),
+            _ => Err(GitHubApiError::PrivateContributionsForbidden(
+                username.to_string(),
+            )),
         }
-        "#.to_string()
     }
 
-    /// Get the GraphQL query for fetching additional repositories (pagination)
-    fn get_repos_query() -> String {
-        r#"
-        query GetUserRepos($login: String!, $after: String) {
-            user(login: $login) {
-                repositories(first: 100, ownerAffiliations: OWNER, orderBy: {direction: DESC, field: STARGAZERS}, after: $after) {
+    /// Get the GraphQL query for fetching additional repositories (pagination) with the
+    /// given `affiliations`, requesting `repository_fields` for each of them
+    fn get_repos_query(affiliations: RepositoryAffiliations, repository_fields: &str) -> String {
+        let affiliations = affiliations.graphql_list();
+        format!(
+            r#"
+        query GetUserRepos($login: String!, $after: String) {{
+            user(login: $login) {{
+                repositories(first: 100, ownerAffiliations: {affiliations}, orderBy: {{direction: DESC, field: STARGAZERS}}, after: $after) {{
                     totalCount
-                    nodes {
-                        name
-                        stargazers {
-                            totalCount
-                        }
-                    }
-                    pageInfo {
+                    nodes {{
+                        {repository_fields}
+                    }}
+                    pageInfo {{
                         hasNextPage
                         endCursor
-                    }
-                }
-            }
-        }
-        "#.to_string()
+                    }}
+                }}
+            }}
+        }}
+        "#
+        )
     }
 
-    /// Get the GraphQL query for fetching user languages
-    fn get_languages_query() -> String {
-        r#"
-        query GetUserLanguages($login: String!, $after: String) {
-            user(login: $login) {
-                repositories(ownerAffiliations: OWNER, isFork: false, first: 100, after: $after) {
-                    nodes {
+    /// Get the GraphQL query for fetching the languages of the user repositories with
+    /// the given `affiliations`
+    fn get_languages_query(affiliations: RepositoryAffiliations) -> String {
+        let affiliations = affiliations.graphql_list();
+        format!(
+            r#"
+        query GetUserLanguages($login: String!, $after: String) {{
+            user(login: $login) {{
+                repositories(ownerAffiliations: {affiliations}, isFork: false, first: 100, after: $after) {{
+                    nodes {{
                         name
-                        languages(first: 10, orderBy: {field: SIZE, direction: DESC}) {
-                            edges {
+                        languages(first: 10, orderBy: {{field: SIZE, direction: DESC}}) {{
+                            edges {{
                                 size
-                                node {
+                                node {{
                                     color
                                     name
-                                }
-                            }
-                        }
-                    }
-                    pageInfo {
+                                }}
+                            }}
+                        }}
+                    }}
+                    pageInfo {{
                         hasNextPage
                         endCursor
-                    }
-                }
+                    }}
+                }}
+            }}
+        }}
+        "#
+        )
+    }
+
+    /// Error for a `username` the user query found nothing for: [GitHubApiError::IsOrganization]
+    /// when it is the login of an organization, [GitHubApiError::UserNotFound] otherwise.
+    async fn not_found_error(&self, username: &str) -> GitHubApiError {
+        let query = r#"
+        query GetOrganization($login: String!) {
+            organization(login: $login) {
+                login
             }
         }
-        "#
-        .to_string()
+        "#;
+        let variables = json!({ "login": username });
+        not_found_error(self.execute_query(query, variables)).await
     }
 
-    /// Execute a GraphQL query
+    /// Execute a GraphQL query, retrying GitHub server errors within the request budget
     #[tracing::instrument(name = "github_api_request", skip(self, query, variables))]
     async fn execute_query<T>(
         &self,
@@ -266,9 +757,6 @@ impl GitHubApi {
     {
         let token = self.token.as_ref().ok_or(GitHubApiError::MissingToken)?;
 
-        // Check rate limit before making the request
-        check_rate_limit_before_request()?;
-
         let payload = json!({
             "query": query,
             "variables": variables
@@ -281,37 +769,39 @@ impl GitHubApi {
                 "github_request",
                 sentry::protocol::Context::Other({
                     let mut map = std::collections::BTreeMap::new();
-                    map.insert(
-                        "endpoint".to_string(),
-                        "https://api.github.com/graphql".into(),
-                    );
+                    map.insert("endpoint".to_string(), GRAPHQL_ENDPOINT.into());
                     map.insert("variables".to_string(), variables.to_string().into());
                     map
                 }),
             );
         });
 
-        let response = self
-            .client
-            .post("https://api.github.com/graphql")
-            .header("Authorization", format!("Bearer {token}"))
-            .header("User-Agent", "github-statcrab")
-            .json(&payload)
-            .send()
-            .await
-            .map_err(|e| {
-                // Report network errors to Sentry
-                sentry::capture_error(&e);
-                tracing::error!("GitHub API network error: {e}");
-                GitHubApiError::NetworkError(e)
-            })?;
+        with_retry_budget(&self.budget, MAX_ATTEMPTS, RETRY_BACKOFF, || {
+            self.send_query(&token.0, &payload)
+        })
+        .await
+    }
+
+    /// Send a single GraphQL request to GitHub
+    async fn send_query<T>(
+        &self,
+        token: &str,
+        payload: &serde_json::Value,
+    ) -> Result<GraphQLResponse<T>, GitHubApiError>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        // Check rate limit before making the request
+        check_rate_limit_before_request(&self.rate_limit, self.clock())?;
+
+        let response = self.transport.post(token, payload).await?;
 
         if response.status() == reqwest::StatusCode::UNAUTHORIZED {
             return Err(GitHubApiError::MissingToken);
         }
 
         // Update rate limit information from response headers
-        update_rate_limit_from_headers(response.headers());
+        update_rate_limit_from_headers(&self.rate_limit, response.headers());
 
         if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
             // Rate limit info for debugging
@@ -328,45 +818,148 @@ impl GitHubApi {
             return Err(GitHubApiError::RateLimitExceeded);
         }
 
-        // Check for other HTTP errors
-        if !response.status().is_success() {
-            let status = response.status();
-            let error_msg = format!("GitHub API returned HTTP {status}");
-            sentry::capture_message(&error_msg, sentry::Level::Error);
-            tracing::error!("{error_msg}");
-        }
-
-        let response_body: GraphQLResponse<T> = response.json().await.map_err(|e| {
-            sentry::capture_error(&e);
-            tracing::error!("Failed to parse GitHub API response: {e}");
-            GitHubApiError::NetworkError(e)
-        })?;
-
-        Ok(response_body)
+        read_response(response).await
     }
 
     /// Fetch user statistics from GitHub
-    #[tracing::instrument(name = "fetch_user_stats", fields(username = %username))]
     pub async fn fetch_user_stats(&self, username: &str) -> Result<GitHubStats, GitHubApiError> {
+        self.fetch_user_stats_with_options(username, StatsOptions::default())
+            .await
+    }
+
+    /// Fetch user statistics from GitHub with the given options
+    pub async fn fetch_user_stats_with_options(
+        &self,
+        username: &str,
+        options: StatsOptions,
+    ) -> Result<GitHubStats, GitHubApiError> {
+        self.fetch_cached_user_stats(username, options)
+            .await
+            .map(|cached| cached.value)
+    }
+
+    /// Fetch user statistics through the cache, telling whether a stale entry was served
+    #[tracing::instrument(name = "fetch_user_stats", fields(username = %username, include_private = options.include_private))]
+    pub async fn fetch_cached_user_stats(
+        &self,
+        username: &str,
+        options: StatsOptions,
+    ) -> Result<Cached<GitHubStats>, GitHubApiError> {
         Self::validate_username(username)?;
 
+        if !self.shares_cache() {
+            return self
+                .fetch_user_stats_uncached(username, options)
+                .await
+                .map(Cached::fresh);
+        }
+
         let cache = get_github_cache();
         let username_owned = username.to_string();
-        let api_ref = self;
+        // The fetch may outlive this request as a background refresh
+        let api = self.detached();
 
         cache
-            .get_or_insert_user_stats(username_owned.clone(), || async move {
-                api_ref.fetch_user_stats_uncached(&username_owned).await
+            .get_or_insert_user_stats(username_owned.clone(), options, move || async move {
+                api.fetch_user_stats_uncached(&username_owned, options)
+                    .await
             })
             .await
     }
 
+    /// Fetch user statistics from GitHub and store them in the cache, replacing
+    /// the cached ones whatever their age
+    pub async fn refresh_cached_user_stats(
+        &self,
+        username: &str,
+        options: StatsOptions,
+    ) -> Result<(), GitHubApiError> {
+        Self::validate_username(username)?;
+
+        let stats = self.fetch_user_stats_uncached(username, options).await?;
+        if !self.shares_cache() {
+            return Ok(());
+        }
+        get_github_cache()
+            .insert_user_stats(username.to_string(), options, stats)
+            .await;
+        Ok(())
+    }
+
     /// Fetch user statistics from GitHub without caching
-    #[tracing::instrument(name = "fetch_user_stats_uncached", fields(username = %username))]
+    #[tracing::instrument(name = "fetch_user_stats_uncached", fields(username = %username, include_private = options.include_private))]
     async fn fetch_user_stats_uncached(
         &self,
         username: &str,
+        options: StatsOptions,
     ) -> Result<GitHubStats, GitHubApiError> {
+        let year = options.commits_year(self.clock());
+        let (user, repositories) = self
+            .fetch_user_repositories(username, options, year, STATS_REPOSITORY_FIELDS)
+            .await?;
+
+        Ok(Self::build_stats(user, &repositories, year))
+    }
+
+    /// Fetch user statistics and languages from GitHub
+    pub async fn fetch_user_full(&self, username: &str) -> Result<UserProfile, GitHubApiError> {
+        self.fetch_user_full_with_options(username, StatsOptions::default())
+            .await
+    }
+
+    /// Fetch user statistics and languages from GitHub with the given options.
+    /// Both come from a single pass over the repositories, costing about half
+    /// the requests of [GitHubApi::fetch_user_stats_with_options] and
+    /// [GitHubApi::fetch_user_languages] together.
+    #[tracing::instrument(name = "fetch_user_full", fields(username = %username, include_private = options.include_private))]
+    pub async fn fetch_user_full_with_options(
+        &self,
+        username: &str,
+        options: StatsOptions,
+    ) -> Result<UserProfile, GitHubApiError> {
+        Self::validate_username(username)?;
+
+        if !self.shares_cache() {
+            return self.fetch_user_full_uncached(username, options).await;
+        }
+
+        let cache = get_github_cache();
+        let username_owned = username.to_string();
+        // The fetch may outlive this request as a background refresh
+        let api = self.detached();
+
+        cache
+            .get_or_insert_user_profile(username_owned.clone(), options, move || async move {
+                api.fetch_user_full_uncached(&username_owned, options).await
+            })
+            .await
+            .map(|cached| cached.value)
+    }
+
+    /// Fetch user statistics and languages from GitHub without caching
+    #[tracing::instrument(name = "fetch_user_full_uncached", fields(username = %username, include_private = options.include_private))]
+    async fn fetch_user_full_uncached(
+        &self,
+        username: &str,
+        options: StatsOptions,
+    ) -> Result<UserProfile, GitHubApiError> {
+        let year = options.commits_year(self.clock());
+        let (user, repositories) = self
+            .fetch_user_repositories(username, options, year, PROFILE_REPOSITORY_FIELDS)
+            .await?;
+
+        Ok(Self::build_profile(user, &repositories, year))
+    }
+
+    /// Fetch the user data with the contributions of `year` and all of its repositories
+    /// with the given `repository_fields`
+    async fn fetch_user_repositories(
+        &self,
+        username: &str,
+        options: StatsOptions,
+        year: i32,
+        repository_fields: &str,
+    ) -> Result<(UserData, Vec<RepositoryNode>), GitHubApiError> {
         Self::validate_username(username)?;
 
         // Initial query to get basic stats and first page of repositories
@@ -375,7 +968,7 @@ impl GitHubApi {
             "after": null,
         });
 
-        let query = Self::get_stats_query();
+        let query = Self::get_user_query(options, year, repository_fields);
         let response: GraphQLResponse<UserQueryResponse> =
             self.execute_query(&query, variables).await?;
 
@@ -384,7 +977,7 @@ impl GitHubApi {
             && let Some(error) = errors.first()
         {
             if error.error_type.as_deref() == Some("NOT_FOUND") {
-                return Err(GitHubApiError::UserNotFound);
+                return Err(self.not_found_error(username).await);
             }
             return Err(GitHubApiError::GraphQLError(error.message.clone()));
         }
@@ -392,89 +985,218 @@ impl GitHubApi {
         let user_response = response.data.ok_or(GitHubApiError::GraphQLError(
             "No data in response".to_string(),
         ))?;
-        let user = user_response.user.ok_or(GitHubApiError::UserNotFound)?;
+        let Some(user) = user_response.user else {
+            return Err(self.not_found_error(username).await);
+        };
 
-        // Collect all repositories (handle pagination)
-        let mut all_repositories = user.repositories.nodes.clone();
-        let mut has_next_page = user.repositories.page_info.has_next_page;
-        let mut end_cursor = user.repositories.page_info.end_cursor.clone();
+        // The commits count of the token owner already includes its private commits
+        if options.include_private {
+            Self::ensure_token_owner(user_response.viewer.as_ref(), username)?;
+        }
 
-        // Fetch additional pages of repositories if needed
-        while has_next_page {
+        // Collect the repositories (handle pagination), up to the requested number
+        let max_repos = options.max_repos.map(|max| max as usize);
+        let repos_query = Self::get_repos_query(options.affiliations, repository_fields);
+        let first_page = user.repositories.clone();
+        let (all_repositories, truncated) = collect_repositories(first_page, max_repos, |after| {
             let variables = json!({
                 "login": username,
-                "after": end_cursor
+                "after": after
             });
-
-            let repos_query = Self::get_repos_query();
-            let repos_response: GraphQLResponse<UserQueryResponse> =
-                self.execute_query(&repos_query, variables).await?;
-
-            if let Some(data) = repos_response.data {
-                if let Some(user_data) = data.user {
-                    all_repositories.extend(user_data.repositories.nodes);
-                    has_next_page = user_data.repositories.page_info.has_next_page;
-                    end_cursor = user_data.repositories.page_info.end_cursor;
-                } else {
-                    break;
-                }
-            } else {
-                break;
+            let repos_query = &repos_query;
+            async move {
+                let repos_response: GraphQLResponse<UserRepositoriesQueryResponse> =
+                    self.execute_query(repos_query, variables).await?;
+                Ok(repos_response
+                    .data
+                    .and_then(|data| data.user)
+                    .map(|user| user.repositories))
             }
+        })
+        .await?;
+        if truncated {
+            tracing::info!(
+                "Counting the stars of the top {} repositories only",
+                all_repositories.len()
+            );
         }
 
+        Ok((user, all_repositories))
+    }
+
+    /// Builds the final stats from the user data and all of its repositories, with the
+    /// contributions counted in `year`. A missing contributions collection, or a `year`
+    /// before the account was created, counts as zero commits and reviews.
+    fn build_stats(user: UserData, repositories: &[RepositoryNode], year: i32) -> GitHubStats {
         // Calculate total stars
-        let total_stars = all_repositories
+        let total_stars = repositories
             .iter()
             .map(|repo| repo.stargazers.total_count)
             .sum();
 
-        // Build the final stats
-        let stats = GitHubStats {
+        let created_year = user
+            .created_at
+            .as_deref()
+            .and_then(|created_at| created_at.get(..4)?.parse::<i32>().ok());
+        let contributions = match created_year {
+            Some(created_year) if year < created_year => ContributionsCollection::default(),
+            _ => user.contributions_collection.unwrap_or_default(),
+        };
+
+        GitHubStats {
             name: user.name,
             login: user.login,
             total_stars,
-            total_commits_ytd: user.contributions_collection.total_commit_contributions,
+            total_commits_ytd: contributions.total_commit_contributions,
             total_prs: user.pull_requests.total_count,
             total_merged_prs: user.merged_pull_requests.map_or(0, |mrs| mrs.total_count),
-            total_reviews: user
-                .contributions_collection
-                .total_pull_request_review_contributions,
+            total_reviews: contributions.total_pull_request_review_contributions,
             total_issues: user.open_issues.total_count + user.closed_issues.total_count,
             total_discussions_started: user.repository_discussions.map_or(0, |rd| rd.total_count),
             total_discussions_answered: user
                 .repository_discussion_comments
                 .map_or(0, |rdc| rdc.total_count),
-        };
+            commits_year: Some(year),
+        }
+    }
+
+    /// Builds the stats and languages from the user data and all of its repositories.
+    /// Stars count every repository while the languages skip forks, like
+    /// [GitHubApi::fetch_user_stats] and [GitHubApi::fetch_user_languages] do.
+    fn build_profile(user: UserData, repositories: &[RepositoryNode], year: i32) -> UserProfile {
+        let languages = Self::build_languages(
+            repositories
+                .iter()
+                .filter(|repo| !repo.is_fork)
+                .filter_map(|repo| Some((repo.name.as_str(), repo.languages.as_ref()?))),
+            &[],
+        );
 
-        Ok(stats)
+        UserProfile {
+            stats: Self::build_stats(user, repositories, year),
+            languages,
+        }
     }
 
-    /// Fetch user languages from GitHub
-    #[tracing::instrument(name = "fetch_user_languages", fields(username = %username, excluded_repos = exclude_repos.len()))]
-    pub async fn fetch_user_languages(
-        &self,
-        username: &str,
+    /// Builds the language stats from the languages of each named repository,
+    /// skipping the excluded ones.
+    fn build_languages<'a>(
+        repositories: impl IntoIterator<Item = (&'a str, &'a LanguagesConnection)>,
         exclude_repos: &[String],
-    ) -> Result<Vec<crate::cards::langs_card::LanguageStat>, GitHubApiError> {
-        Self::validate_username(username)?;
+    ) -> Vec<crate::cards::langs_card::LanguageStat> {
+        // Create a set for quick lookup of excluded repositories
+        let exclude_set: std::collections::HashSet<&str> =
+            exclude_repos.iter().map(String::as_str).collect();
 
-        let cache = get_github_cache();
-        let username_owned = username.to_string();
-        let exclude_repos_owned = exclude_repos.to_vec();
-        let api_ref = self;
+        // Create LangEdge structs using the existing pattern
+        let mut edges = Vec::new();
 
-        cache
-            .get_or_insert_user_languages(username_owned.clone(), &exclude_repos_owned, || {
-                let exclude_repos_cloned = exclude_repos_owned.clone();
-                async move {
-                    api_ref
-                        .fetch_user_languages_uncached(&username_owned, &exclude_repos_cloned)
-                        .await
-                }
-            })
-            .await
-    }
+        for (name, languages) in repositories {
+            // Skip excluded repositories
+            if exclude_set.contains(name) {
+                continue;
+            }
+
+            // Process language edges for this repository
+            for edge in &languages.edges {
+                edges.push(crate::cards::langs_card::LangEdge {
+                    name: edge.node.name.clone(),
+                    size_bytes: edge.size,
+                });
+            }
+        }
+
+        // Use the existing from_edges method to convert to LanguageStat
+        crate::cards::langs_card::LanguageStat::from_edges(edges)
+    }
+
+    /// Fetch user languages from GitHub
+    pub async fn fetch_user_languages(
+        &self,
+        username: &str,
+        exclude_repos: &[String],
+    ) -> Result<Vec<crate::cards::langs_card::LanguageStat>, GitHubApiError> {
+        self.fetch_user_languages_with_affiliations(
+            username,
+            exclude_repos,
+            RepositoryAffiliations::default(),
+        )
+        .await
+    }
+
+    /// Fetch the languages of the user repositories with the given `affiliations`
+    pub async fn fetch_user_languages_with_affiliations(
+        &self,
+        username: &str,
+        exclude_repos: &[String],
+        affiliations: RepositoryAffiliations,
+    ) -> Result<Vec<crate::cards::langs_card::LanguageStat>, GitHubApiError> {
+        self.fetch_cached_user_languages(username, exclude_repos, affiliations)
+            .await
+            .map(|cached| cached.value)
+    }
+
+    /// Fetch user languages through the cache, telling whether a stale entry was served
+    #[tracing::instrument(name = "fetch_user_languages", fields(username = %username, excluded_repos = exclude_repos.len()))]
+    pub async fn fetch_cached_user_languages(
+        &self,
+        username: &str,
+        exclude_repos: &[String],
+        affiliations: RepositoryAffiliations,
+    ) -> Result<Cached<Vec<crate::cards::langs_card::LanguageStat>>, GitHubApiError> {
+        Self::validate_username(username)?;
+
+        if !self.shares_cache() {
+            return self
+                .fetch_user_languages_uncached(username, exclude_repos, affiliations)
+                .await
+                .map(Cached::fresh);
+        }
+
+        let cache = get_github_cache();
+        let username_owned = username.to_string();
+        let exclude_repos_owned = exclude_repos.to_vec();
+        // The fetch may outlive this request as a background refresh
+        let api = self.detached();
+
+        cache
+            .get_or_insert_user_languages(
+                username_owned.clone(),
+                exclude_repos,
+                affiliations,
+                move || async move {
+                    api.fetch_user_languages_uncached(
+                        &username_owned,
+                        &exclude_repos_owned,
+                        affiliations,
+                    )
+                    .await
+                },
+            )
+            .await
+    }
+
+    /// Fetch user languages from GitHub and store them in the cache, replacing
+    /// the cached ones whatever their age. Only the owned repositories are counted.
+    pub async fn refresh_cached_user_languages(
+        &self,
+        username: &str,
+        exclude_repos: &[String],
+    ) -> Result<(), GitHubApiError> {
+        Self::validate_username(username)?;
+
+        let affiliations = RepositoryAffiliations::default();
+        let languages = self
+            .fetch_user_languages_uncached(username, exclude_repos, affiliations)
+            .await?;
+        if !self.shares_cache() {
+            return Ok(());
+        }
+        get_github_cache()
+            .insert_user_languages(username.to_string(), exclude_repos, affiliations, languages)
+            .await;
+        Ok(())
+    }
 
     /// Fetch user languages from GitHub without caching
     #[tracing::instrument(name = "fetch_user_languages_uncached", fields(username = %username, excluded_repos = exclude_repos.len()))]
@@ -482,6 +1204,7 @@ impl GitHubApi {
         &self,
         username: &str,
         exclude_repos: &[String],
+        affiliations: RepositoryAffiliations,
     ) -> Result<Vec<crate::cards::langs_card::LanguageStat>, GitHubApiError> {
         let mut all_repos = Vec::new();
         let mut after_cursor: Option<String> = None;
@@ -494,7 +1217,7 @@ impl GitHubApi {
                 "after": after_cursor
             });
 
-            let query = Self::get_languages_query();
+            let query = Self::get_languages_query(affiliations);
             let response: GraphQLResponse<LanguagesQueryResponse> =
                 self.execute_query(&query, variables).await?;
 
@@ -503,7 +1226,7 @@ impl GitHubApi {
                 && let Some(error) = errors.first()
             {
                 if error.error_type.as_deref() == Some("NOT_FOUND") {
-                    return Err(GitHubApiError::UserNotFound);
+                    return Err(self.not_found_error(username).await);
                 }
                 return Err(GitHubApiError::GraphQLError(error.message.clone()));
             }
@@ -511,49 +1234,563 @@ impl GitHubApi {
             let user_response = response.data.ok_or(GitHubApiError::GraphQLError(
                 "No data in response".to_string(),
             ))?;
-            let user = user_response.user.ok_or(GitHubApiError::UserNotFound)?;
+            let Some(user) = user_response.user else {
+                return Err(self.not_found_error(username).await);
+            };
 
             all_repos.extend(user.repositories.nodes);
             has_next_page = user.repositories.page_info.has_next_page;
             after_cursor = user.repositories.page_info.end_cursor;
         }
 
-        // Create a set for quick lookup of excluded repositories
-        let exclude_set: std::collections::HashSet<&String> = exclude_repos.iter().collect();
+        Ok(Self::build_languages(
+            all_repos
+                .iter()
+                .map(|repo| (repo.name.as_str(), &repo.languages)),
+            exclude_repos,
+        ))
+    }
 
-        // Create LangEdge structs using the existing pattern
-        let mut edges = Vec::new();
+    /// Fetch the accent color derived from the dominant color of the user's avatar
+    /// through the cache, `None` when the avatar has no usable color
+    #[tracing::instrument(name = "fetch_avatar_color", fields(username = %username))]
+    pub async fn fetch_cached_avatar_color(
+        &self,
+        username: &str,
+    ) -> Result<Cached<Option<String>>, GitHubApiError> {
+        Self::validate_username(username)?;
 
-        for repo in all_repos {
-            // Skip excluded repositories
-            if exclude_set.contains(&repo.name) {
-                continue;
+        if !self.shares_cache() {
+            return self
+                .fetch_avatar_color_uncached(username)
+                .await
+                .map(Cached::fresh);
+        }
+
+        let cache = get_github_cache();
+        let username_owned = username.to_string();
+        // The fetch may outlive this request as a background refresh
+        let api = self.detached();
+
+        cache
+            .get_or_insert_avatar_color(username_owned.clone(), move || async move {
+                api.fetch_avatar_color_uncached(&username_owned).await
+            })
+            .await
+    }
+
+    /// Fetch the avatar of the user from GitHub and derive its dominant color, without caching
+    #[tracing::instrument(name = "fetch_avatar_color_uncached", fields(username = %username))]
+    async fn fetch_avatar_color_uncached(
+        &self,
+        username: &str,
+    ) -> Result<Option<String>, GitHubApiError> {
+        let query = format!(
+            r#"
+        query GetUserAvatar($login: String!) {{
+            user(login: $login) {{
+                avatarUrl(size: {AVATAR_SIZE})
+            }}
+        }}
+        "#
+        );
+        let variables = json!({ "login": username });
+        let response: GraphQLResponse<UserAvatarQueryResponse> =
+            self.execute_query(&query, variables).await?;
+        if let Some(errors) = response.errors
+            && let Some(error) = errors.first()
+        {
+            if error.error_type.as_deref() == Some("NOT_FOUND") {
+                return Err(self.not_found_error(username).await);
             }
+            return Err(GitHubApiError::GraphQLError(error.message.clone()));
+        }
+        let Some(user) = response.data.and_then(|data| data.user) else {
+            return Err(self.not_found_error(username).await);
+        };
 
-            // Process language edges for this repository
-            for edge in repo.languages.edges {
-                edges.push(crate::cards::langs_card::LangEdge {
-                    name: edge.node.name,
-                    size_bytes: edge.size,
-                });
+        if !user.avatar_url.starts_with(AVATAR_URL_PREFIX) {
+            tracing::warn!("Ignoring the avatar of {username} at {}", user.avatar_url);
+            return Ok(None);
+        }
+        let response = self.transport.get(&user.avatar_url).await?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(GitHubApiError::UnexpectedResponse {
+                status: status.as_u16(),
+                snippet: format!("avatar of {username}"),
+            });
+        }
+        let image = read_bounded_body(response, MAX_AVATAR_BYTES + 1).await;
+        if image.len() > MAX_AVATAR_BYTES {
+            tracing::warn!(
+                "Ignoring the avatar of {username}, larger than {MAX_AVATAR_BYTES} bytes"
+            );
+            return Ok(None);
+        }
+        Ok(crate::cards::accent::dominant_color(&image))
+    }
+
+    /// Fetch the days of the user's contribution calendar (the last year) from GitHub
+    pub async fn fetch_contribution_calendar(
+        &self,
+        username: &str,
+    ) -> Result<Vec<ContributionDay>, GitHubApiError> {
+        self.fetch_cached_contribution_calendar(username)
+            .await
+            .map(|cached| cached.value)
+    }
+
+    /// Fetch the contribution calendar days through the cache, telling whether a stale
+    /// entry was served
+    #[tracing::instrument(name = "fetch_contribution_calendar", fields(username = %username))]
+    pub async fn fetch_cached_contribution_calendar(
+        &self,
+        username: &str,
+    ) -> Result<Cached<Vec<ContributionDay>>, GitHubApiError> {
+        Self::validate_username(username)?;
+
+        if !self.shares_cache() {
+            return self
+                .fetch_contribution_calendar_uncached(username)
+                .await
+                .map(Cached::fresh);
+        }
+
+        let cache = get_github_cache();
+        let username_owned = username.to_string();
+        // The fetch may outlive this request as a background refresh
+        let api = self.detached();
+
+        cache
+            .get_or_insert_contribution_calendar(username_owned.clone(), move || async move {
+                api.fetch_contribution_calendar_uncached(&username_owned)
+                    .await
+            })
+            .await
+    }
+
+    /// Fetch the contribution calendar days from GitHub, oldest first, without caching
+    #[tracing::instrument(name = "fetch_contribution_calendar_uncached", fields(username = %username))]
+    async fn fetch_contribution_calendar_uncached(
+        &self,
+        username: &str,
+    ) -> Result<Vec<ContributionDay>, GitHubApiError> {
+        let query = r#"
+        query GetContributionCalendar($login: String!) {
+            user(login: $login) {
+                contributionsCollection {
+                    contributionCalendar {
+                        weeks {
+                            contributionDays {
+                                date
+                                contributionCount
+                            }
+                        }
+                    }
+                }
             }
         }
+        "#;
+        let variables = json!({ "login": username });
+        let response: GraphQLResponse<ContributionCalendarQueryResponse> =
+            self.execute_query(query, variables).await?;
+        if let Some(errors) = response.errors
+            && let Some(error) = errors.first()
+        {
+            if error.error_type.as_deref() == Some("NOT_FOUND") {
+                return Err(self.not_found_error(username).await);
+            }
+            return Err(GitHubApiError::GraphQLError(error.message.clone()));
+        }
+        let Some(user) = response.data.and_then(|data| data.user) else {
+            return Err(self.not_found_error(username).await);
+        };
 
-        // Use the existing from_edges method to convert to LanguageStat
-        let stats = crate::cards::langs_card::LanguageStat::from_edges(edges);
+        Ok(user
+            .contributions_collection
+            .contribution_calendar
+            .weeks
+            .into_iter()
+            .flat_map(|week| week.contribution_days)
+            .collect())
+    }
+
+    /// Fetch the commits of a user summed over every calendar year since the account
+    /// was created, the private ones included with `include_private`
+    pub async fn fetch_lifetime_commits(
+        &self,
+        username: &str,
+        include_private: bool,
+    ) -> Result<u32, GitHubApiError> {
+        self.fetch_cached_lifetime_commits(username, include_private)
+            .await
+            .map(|cached| cached.value)
+    }
+
+    /// Fetch the lifetime commits through the cache, telling whether a stale entry was served
+    #[tracing::instrument(name = "fetch_lifetime_commits", fields(username = %username, include_private = include_private))]
+    pub async fn fetch_cached_lifetime_commits(
+        &self,
+        username: &str,
+        include_private: bool,
+    ) -> Result<Cached<u32>, GitHubApiError> {
+        Self::validate_username(username)?;
+
+        if !self.shares_cache() {
+            return self
+                .fetch_lifetime_commits_uncached(username, include_private)
+                .await
+                .map(Cached::fresh);
+        }
+
+        let cache = get_github_cache();
+        let username_owned = username.to_string();
+        // The fetch may outlive this request as a background refresh
+        let api = self.detached();
+
+        cache
+            .get_or_insert_lifetime_commits(
+                username_owned.clone(),
+                include_private,
+                move || async move {
+                    api.fetch_lifetime_commits_uncached(&username_owned, include_private)
+                        .await
+                },
+            )
+            .await
+    }
+
+    /// Fetch the lifetime commits from GitHub without caching: one query per calendar
+    /// year (UTC) from the year the account was created to the current one
+    #[tracing::instrument(name = "fetch_lifetime_commits_uncached", fields(username = %username, include_private = include_private))]
+    async fn fetch_lifetime_commits_uncached(
+        &self,
+        username: &str,
+        include_private: bool,
+    ) -> Result<u32, GitHubApiError> {
+        let viewer = if include_private {
+            "viewer { login }"
+        } else {
+            ""
+        };
+        let query = format!(
+            r#"
+        query GetUserCreatedAt($login: String!) {{
+            {viewer}
+            user(login: $login) {{
+                createdAt
+            }}
+        }}
+        "#
+        );
+        let response: GraphQLResponse<UserCreatedAtQueryResponse> = self
+            .execute_query(&query, json!({ "login": username }))
+            .await?;
+        if let Some(errors) = response.errors
+            && let Some(error) = errors.first()
+        {
+            if error.error_type.as_deref() == Some("NOT_FOUND") {
+                return Err(self.not_found_error(username).await);
+            }
+            return Err(GitHubApiError::GraphQLError(error.message.clone()));
+        }
+        let data = response.data.ok_or(GitHubApiError::GraphQLError(
+            "No data in response".to_string(),
+        ))?;
+        let Some(user) = data.user else {
+            return Err(self.not_found_error(username).await);
+        };
+        if include_private {
+            Self::ensure_token_owner(data.viewer.as_ref(), username)?;
+        }
+
+        let current_year = current_utc_year_with(self.clock());
+        let created_year = user
+            .created_at
+            .get(..4)
+            .and_then(|year| year.parse::<i32>().ok())
+            .unwrap_or(current_year);
+
+        let query = Self::get_year_commits_query();
+        let mut total: u32 = 0;
+        for year in created_year..=current_year {
+            let variables = json!({
+                "login": username,
+                "from": format!("{year}-01-01T00:00:00Z"),
+                "to": format!("{year}-12-31T23:59:59Z"),
+            });
+            let response: GraphQLResponse<YearCommitsQueryResponse> =
+                self.execute_query(query, variables).await?;
+            if let Some(errors) = response.errors
+                && let Some(error) = errors.first()
+            {
+                return Err(GitHubApiError::GraphQLError(error.message.clone()));
+            }
+            let commits = response.data.and_then(|data| data.user).map_or(0, |user| {
+                user.contributions_collection.total_commit_contributions
+            });
+            total = total.saturating_add(commits);
+        }
+        Ok(total)
+    }
+
+    /// Get the GraphQL query for the commits of a user between `$from` and `$to`, at most
+    /// a year apart
+    fn get_year_commits_query() -> &'static str {
+        r#"
+        query GetYearCommits($login: String!, $from: DateTime!, $to: DateTime!) {
+            user(login: $login) {
+                contributionsCollection(from: $from, to: $to) {
+                    totalCommitContributions
+                }
+            }
+        }
+        "#
+    }
+
+    /// Fetch organization statistics through the cache, telling whether a stale entry was served
+    #[tracing::instrument(name = "fetch_org_stats", fields(org = %org))]
+    pub async fn fetch_cached_org_stats(
+        &self,
+        org: &str,
+    ) -> Result<Cached<OrgStats>, GitHubApiError> {
+        Self::validate_username(org)?;
+
+        if !self.shares_cache() {
+            return self.fetch_org_stats_uncached(org).await.map(Cached::fresh);
+        }
+
+        let cache = get_github_cache();
+        let org_owned = org.to_string();
+        // The fetch may outlive this request as a background refresh
+        let api = self.detached();
+
+        cache
+            .get_or_insert_org_stats(org_owned.clone(), move || async move {
+                api.fetch_org_stats_uncached(&org_owned).await
+            })
+            .await
+    }
+
+    /// Fetch organization statistics from GitHub without caching
+    #[tracing::instrument(name = "fetch_org_stats_uncached", fields(org = %org))]
+    async fn fetch_org_stats_uncached(&self, org: &str) -> Result<OrgStats, GitHubApiError> {
+        let variables = json!({
+            "login": org,
+            "after": null,
+        });
+        let response: GraphQLResponse<OrgQueryResponse> = self
+            .execute_query(&Self::get_org_query(), variables)
+            .await?;
+        let org_data = Self::org_from_response(response)?;
+
+        let repos_query = Self::get_org_repos_query();
+        let first_page = org_data.repositories.clone();
+        let (repositories, _) = collect_repositories(first_page, None, |after| {
+            let variables = json!({
+                "login": org,
+                "after": after
+            });
+            let repos_query = &repos_query;
+            async move {
+                let repos_response: GraphQLResponse<OrgRepositoriesQueryResponse> =
+                    self.execute_query(repos_query, variables).await?;
+                Ok(repos_response
+                    .data
+                    .and_then(|data| data.organization)
+                    .map(|org| org.repositories))
+            }
+        })
+        .await?;
+
+        Ok(Self::build_org_stats(org_data, &repositories))
+    }
+
+    /// Get the GraphQL query for fetching an organization with its first page of repositories
+    fn get_org_query() -> String {
+        format!(
+            r#"
+        query GetOrganizationStats($login: String!, $after: String) {{
+            organization(login: $login) {{
+                name
+                login
+                membersWithRole {{
+                    totalCount
+                }}
+                repositories(first: 100, isFork: false, after: $after) {{
+                    totalCount
+                    nodes {{
+                        {PROFILE_REPOSITORY_FIELDS}
+                    }}
+                    pageInfo {{
+                        hasNextPage
+                        endCursor
+                    }}
+                }}
+            }}
+        }}
+        "#
+        )
+    }
+
+    /// Get the GraphQL query for fetching additional organization repositories (pagination)
+    fn get_org_repos_query() -> String {
+        format!(
+            r#"
+        query GetOrganizationRepos($login: String!, $after: String) {{
+            organization(login: $login) {{
+                repositories(first: 100, isFork: false, after: $after) {{
+                    nodes {{
+                        {PROFILE_REPOSITORY_FIELDS}
+                    }}
+                    pageInfo {{
+                        hasNextPage
+                        endCursor
+                    }}
+                }}
+            }}
+        }}
+        "#
+        )
+    }
+
+    /// Extracts the organization from the first response of the organization query,
+    /// [GitHubApiError::OrgNotFound] when there is none with the login.
+    fn org_from_response(
+        response: GraphQLResponse<OrgQueryResponse>,
+    ) -> Result<OrgData, GitHubApiError> {
+        if let Some(errors) = response.errors
+            && let Some(error) = errors.first()
+        {
+            if error.error_type.as_deref() == Some("NOT_FOUND") {
+                return Err(GitHubApiError::OrgNotFound);
+            }
+            return Err(GitHubApiError::GraphQLError(error.message.clone()));
+        }
+
+        response
+            .data
+            .ok_or(GitHubApiError::GraphQLError(
+                "No data in response".to_string(),
+            ))?
+            .organization
+            .ok_or(GitHubApiError::OrgNotFound)
+    }
+
+    /// Builds the organization statistics from its data and all of its repositories.
+    fn build_org_stats(org: OrgData, repositories: &[RepositoryNode]) -> OrgStats {
+        let total_stars = repositories
+            .iter()
+            .map(|repo| repo.stargazers.total_count)
+            .fold(0, u32::saturating_add);
+        let languages = Self::build_languages(
+            repositories
+                .iter()
+                .filter_map(|repo| Some((repo.name.as_str(), repo.languages.as_ref()?))),
+            &[],
+        );
 
-        Ok(stats)
+        OrgStats {
+            name: org.name,
+            login: org.login,
+            total_repos: org.repositories.total_count,
+            total_stars,
+            total_members: org.members_with_role.total_count,
+            languages,
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::github::clock::FixedClock;
+
+    /// Time told by the [FixedClock] of the rate limit tests (2023-11-14)
+    const NOW: u64 = 1_700_000_000;
+
+    fn rate_limit_headers(remaining: u64, reset: u64) -> reqwest::header::HeaderMap {
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert("x-ratelimit-limit", 5000.into());
+        headers.insert("x-ratelimit-remaining", remaining.into());
+        headers.insert("x-ratelimit-used", (5000 - remaining).into());
+        headers.insert("x-ratelimit-reset", reset.into());
+        headers
+    }
+
+    #[test]
+    fn test_rate_limit_state_protects_after_low_remaining_headers() {
+        let _guard = reset_rate_limit_state_for_test();
+        assert!(check_rate_limit_before_request(&get_rate_limit_state(), &SystemClock).is_ok());
+
+        update_rate_limit_from_headers(&get_rate_limit_state(), &rate_limit_headers(10, u64::MAX));
+        assert!(matches!(
+            check_rate_limit_before_request(&get_rate_limit_state(), &SystemClock),
+            Err(GitHubApiError::RateLimitProtection(10, u64::MAX))
+        ));
+    }
+
+    #[test]
+    fn test_rate_limit_state_is_reset_between_tests() {
+        // Whatever the other test left behind, this one starts without rate limit data
+        let _guard = reset_rate_limit_state_for_test();
+        assert!(get_github_rate_limit().remaining.is_none());
+        assert!(check_rate_limit_before_request(&get_rate_limit_state(), &SystemClock).is_ok());
+
+        update_rate_limit_from_headers(
+            &get_rate_limit_state(),
+            &rate_limit_headers(4000, u64::MAX),
+        );
+        assert_eq!(get_github_rate_limit().remaining, Some(4000));
+        assert!(check_rate_limit_before_request(&get_rate_limit_state(), &SystemClock).is_ok());
+    }
+
+    #[test]
+    fn test_request_token_has_its_own_rate_limit_state() {
+        let _guard = reset_rate_limit_state_for_test();
+        let token = || GitHubToken::new("ghp_test_own_bucket".to_string());
+        let api = GitHubApi::with_token(token());
+        assert!(api.uses_request_token());
+        assert!(!GitHubApi::new().uses_request_token());
+
+        update_rate_limit_from_headers(&api.rate_limit, &rate_limit_headers(10, u64::MAX));
+        assert!(check_rate_limit_before_request(&api.rate_limit, &SystemClock).is_err());
+        // Shared by the clients of the same token only
+        assert!(
+            check_rate_limit_before_request(
+                &GitHubApi::with_token(token()).rate_limit,
+                &SystemClock
+            )
+            .is_err()
+        );
+        assert!(check_rate_limit_before_request(&api.detached().rate_limit, &SystemClock).is_err());
+        assert!(get_github_rate_limit().remaining.is_none());
+        let other = GitHubApi::with_token(GitHubToken::new("ghp_test_other".to_string()));
+        assert!(check_rate_limit_before_request(&other.rate_limit, &SystemClock).is_ok());
+    }
+
+    #[test]
+    fn test_token_is_redacted_from_debug_output() {
+        let token = GitHubToken::new("ghp_secret".to_string());
+        assert_eq!(format!("{token:?}"), "GitHubToken(<redacted>)");
+        let api = GitHubApi::with_token(token);
+        assert!(!format!("{api:?}").contains("ghp_secret"));
+    }
+
+    #[test]
+    fn test_request_tokens_skip_the_cache() {
+        let api = GitHubApi::with_token(GitHubToken::new("ghp_secret".to_string()));
+        assert!(!api.shares_cache());
+        assert!(!api.detached().shares_cache());
+        assert!(GitHubApi::new().shares_cache());
+    }
 
     #[test]
     fn test_rate_limit_check_with_no_data() {
         let rate_limit = GitHubRateLimit::default();
-        let result = check_rate_limit_with_data(&rate_limit);
+        let result =
+            check_rate_limit_with_data(&rate_limit, RATE_LIMIT_THRESHOLD, &FixedClock(NOW));
         assert!(
             result.is_ok(),
             "Should allow request when no rate limit data available"
@@ -567,15 +1804,12 @@ mod tests {
             remaining: Some(500),
             used: Some(4500),
             reset: Some(
-                std::time::SystemTime::now()
-                    .duration_since(std::time::UNIX_EPOCH)
-                    .unwrap()
-                    .as_secs()
-                    + 3600, // 1 hour from now
+                NOW + 3600, // 1 hour from now
             ),
         };
 
-        let result = check_rate_limit_with_data(&rate_limit);
+        let result =
+            check_rate_limit_with_data(&rate_limit, RATE_LIMIT_THRESHOLD, &FixedClock(NOW));
         assert!(
             result.is_ok(),
             "Should allow request when sufficient requests remaining"
@@ -584,11 +1818,7 @@ mod tests {
 
     #[test]
     fn test_rate_limit_protection_triggered() {
-        let reset_time = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs()
-            + 3600; // 1 hour from now
+        let reset_time = NOW + 3600; // 1 hour from now
 
         let rate_limit = GitHubRateLimit {
             limit: Some(5000),
@@ -597,7 +1827,8 @@ mod tests {
             reset: Some(reset_time),
         };
 
-        let result = check_rate_limit_with_data(&rate_limit);
+        let result =
+            check_rate_limit_with_data(&rate_limit, RATE_LIMIT_THRESHOLD, &FixedClock(NOW));
         assert!(
             result.is_err(),
             "Should block request when remaining requests below threshold"
@@ -613,11 +1844,7 @@ mod tests {
 
     #[test]
     fn test_rate_limit_allows_after_reset_time() {
-        let past_reset_time = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs()
-            - 3600; // 1 hour ago
+        let past_reset_time = NOW - 3600; // 1 hour ago
 
         let rate_limit = GitHubRateLimit {
             limit: Some(5000),
@@ -626,7 +1853,8 @@ mod tests {
             reset: Some(past_reset_time),
         };
 
-        let result = check_rate_limit_with_data(&rate_limit);
+        let result =
+            check_rate_limit_with_data(&rate_limit, RATE_LIMIT_THRESHOLD, &FixedClock(NOW));
         assert!(
             result.is_ok(),
             "Should allow request when reset time has passed even with low remaining count"
@@ -643,7 +1871,8 @@ mod tests {
             reset: None, // No reset time
         };
 
-        let result = check_rate_limit_with_data(&rate_limit);
+        let result =
+            check_rate_limit_with_data(&rate_limit, RATE_LIMIT_THRESHOLD, &FixedClock(NOW));
         assert!(
             result.is_ok(),
             "Should allow request when reset time is not available"
@@ -654,16 +1883,11 @@ mod tests {
             limit: Some(5000),
             remaining: None, // No remaining count
             used: Some(4950),
-            reset: Some(
-                std::time::SystemTime::now()
-                    .duration_since(std::time::UNIX_EPOCH)
-                    .unwrap()
-                    .as_secs()
-                    + 3600,
-            ),
+            reset: Some(NOW + 3600),
         };
 
-        let result = check_rate_limit_with_data(&rate_limit);
+        let result =
+            check_rate_limit_with_data(&rate_limit, RATE_LIMIT_THRESHOLD, &FixedClock(NOW));
         assert!(
             result.is_ok(),
             "Should allow request when remaining count is not available"
@@ -672,11 +1896,7 @@ mod tests {
 
     #[test]
     fn test_rate_limit_boundary_conditions() {
-        let future_reset_time = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs()
-            + 3600; // 1 hour from now
+        let future_reset_time = NOW + 3600; // 1 hour from now
 
         // Test exactly at threshold (100)
         let rate_limit = GitHubRateLimit {
@@ -686,7 +1906,8 @@ mod tests {
             reset: Some(future_reset_time),
         };
 
-        let result = check_rate_limit_with_data(&rate_limit);
+        let result =
+            check_rate_limit_with_data(&rate_limit, RATE_LIMIT_THRESHOLD, &FixedClock(NOW));
         assert!(
             result.is_ok(),
             "Should allow request when remaining is exactly at threshold"
@@ -700,10 +1921,719 @@ mod tests {
             reset: Some(future_reset_time),
         };
 
-        let result = check_rate_limit_with_data(&rate_limit);
+        let result =
+            check_rate_limit_with_data(&rate_limit, RATE_LIMIT_THRESHOLD, &FixedClock(NOW));
         assert!(
             result.is_err(),
             "Should block request when remaining is below threshold"
         );
     }
+
+    #[test]
+    fn test_rate_limit_custom_threshold_boundary_conditions() {
+        let future_reset_time = NOW + 3600; // 1 hour from now
+
+        // Test exactly at a custom threshold (10)
+        let rate_limit = GitHubRateLimit {
+            limit: Some(5000),
+            remaining: Some(10), // Exactly at threshold
+            used: Some(4990),
+            reset: Some(future_reset_time),
+        };
+
+        let result = check_rate_limit_with_data(&rate_limit, 10, &FixedClock(NOW));
+        assert!(
+            result.is_ok(),
+            "Should allow request when remaining is exactly at the custom threshold"
+        );
+
+        // Test just below the custom threshold (9)
+        let rate_limit = GitHubRateLimit {
+            limit: Some(5000),
+            remaining: Some(9), // Below threshold
+            used: Some(4991),
+            reset: Some(future_reset_time),
+        };
+
+        let result = check_rate_limit_with_data(&rate_limit, 10, &FixedClock(NOW));
+        assert!(
+            matches!(result, Err(GitHubApiError::RateLimitProtection(9, _))),
+            "Should block request when remaining is below the custom threshold"
+        );
+
+        // 50 remaining is blocked by the default threshold only
+        let rate_limit = GitHubRateLimit {
+            limit: Some(5000),
+            remaining: Some(50),
+            used: Some(4950),
+            reset: Some(future_reset_time),
+        };
+
+        assert!(check_rate_limit_with_data(&rate_limit, 10, &FixedClock(NOW)).is_ok());
+        assert!(
+            check_rate_limit_with_data(&rate_limit, RATE_LIMIT_THRESHOLD, &FixedClock(NOW))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_parse_rate_limit_threshold() {
+        assert_eq!(parse_rate_limit_threshold(None), RATE_LIMIT_THRESHOLD);
+        assert_eq!(parse_rate_limit_threshold(Some("10")), 10);
+        assert_eq!(parse_rate_limit_threshold(Some(" 0 ")), 0);
+        assert_eq!(parse_rate_limit_threshold(Some("")), RATE_LIMIT_THRESHOLD);
+        assert_eq!(parse_rate_limit_threshold(Some("-5")), RATE_LIMIT_THRESHOLD);
+        assert_eq!(
+            parse_rate_limit_threshold(Some("few")),
+            RATE_LIMIT_THRESHOLD
+        );
+    }
+
+    #[test]
+    fn test_rate_limit_protection_lifts_at_the_reset_time() {
+        let rate_limit = GitHubRateLimit {
+            limit: Some(5000),
+            remaining: Some(10),
+            used: Some(4990),
+            reset: Some(NOW),
+        };
+
+        assert!(matches!(
+            check_rate_limit_with_data(&rate_limit, RATE_LIMIT_THRESHOLD, &FixedClock(NOW - 1)),
+            Err(GitHubApiError::RateLimitProtection(10, NOW))
+        ));
+        assert!(
+            check_rate_limit_with_data(&rate_limit, RATE_LIMIT_THRESHOLD, &FixedClock(NOW)).is_ok()
+        );
+        assert!(
+            check_rate_limit_with_data(&rate_limit, RATE_LIMIT_THRESHOLD, &FixedClock(NOW + 1))
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_stats_query_include_private() {
+        let query =
+            GitHubApi::get_user_query(StatsOptions::default(), 2024, STATS_REPOSITORY_FIELDS);
+        assert!(!query.contains("viewer"));
+
+        let query = GitHubApi::get_user_query(
+            StatsOptions {
+                include_private: true,
+                ..Default::default()
+            },
+            2024,
+            STATS_REPOSITORY_FIELDS,
+        );
+        assert!(query.contains("viewer { login }"));
+        assert!(!query.contains("restrictedContributionsCount"));
+    }
+
+    #[test]
+    fn test_queries_ask_for_the_affiliations() {
+        let affiliations = RepositoryAffiliations {
+            collaborator: true,
+            ..RepositoryAffiliations::OWNER
+        };
+        let options = StatsOptions {
+            affiliations,
+            ..Default::default()
+        };
+        let owned = "ownerAffiliations: [OWNER],";
+        let both = "ownerAffiliations: [OWNER, COLLABORATOR],";
+
+        let query =
+            GitHubApi::get_user_query(StatsOptions::default(), 2024, STATS_REPOSITORY_FIELDS);
+        assert!(query.contains(owned));
+        assert!(GitHubApi::get_user_query(options, 2024, STATS_REPOSITORY_FIELDS).contains(both));
+        assert!(GitHubApi::get_repos_query(affiliations, STATS_REPOSITORY_FIELDS).contains(both));
+        assert!(GitHubApi::get_languages_query(RepositoryAffiliations::OWNER).contains(owned));
+        assert!(GitHubApi::get_languages_query(affiliations).contains(both));
+    }
+
+    #[test]
+    fn test_year_commits_query() {
+        let query = GitHubApi::get_year_commits_query();
+        assert!(query.contains("contributionsCollection(from: $from, to: $to)"));
+        assert!(query.contains("totalCommitContributions"));
+        assert!(!query.contains("restrictedContributionsCount"));
+    }
+
+    #[test]
+    fn test_ensure_token_owner() {
+        let viewer = ViewerData {
+            login: "Octocat".to_string(),
+        };
+        assert!(GitHubApi::ensure_token_owner(Some(&viewer), "octocat").is_ok());
+
+        match GitHubApi::ensure_token_owner(Some(&viewer), "someone-else") {
+            Err(GitHubApiError::PrivateContributionsForbidden(username)) => {
+                assert_eq!(username, "someone-else");
+            }
+            other => panic!("Expected PrivateContributionsForbidden, got {other:?}"),
+        }
+
+        assert!(matches!(
+            GitHubApi::ensure_token_owner(None, "octocat"),
+            Err(GitHubApiError::PrivateContributionsForbidden(_))
+        ));
+    }
+
+    fn repository_page(stars: &[u32], next: Option<&str>) -> RepositoriesConnection {
+        RepositoriesConnection {
+            total_count: stars.len() as u32,
+            nodes: stars
+                .iter()
+                .map(|&total_count| RepositoryNode {
+                    name: String::new(),
+                    is_fork: false,
+                    stargazers: CountableConnection { total_count },
+                    languages: None,
+                })
+                .collect(),
+            page_info: PageInfo {
+                has_next_page: next.is_some(),
+                end_cursor: next.map(str::to_string),
+            },
+        }
+    }
+
+    /// Collects the repositories from three mocked pages, returning their stars,
+    /// whether they were truncated and the cursors of the pages that were fetched.
+    async fn collect_mocked_pages(max_repos: Option<usize>) -> (Vec<u32>, bool, Vec<String>) {
+        let mut fetched = Vec::new();
+        let (repositories, truncated) =
+            collect_repositories(repository_page(&[50, 40], Some("a")), max_repos, |after| {
+                let after = after.unwrap();
+                fetched.push(after.clone());
+                async move {
+                    Ok(Some(match after.as_str() {
+                        "a" => repository_page(&[30, 20], Some("b")),
+                        _ => repository_page(&[10], None),
+                    }))
+                }
+            })
+            .await
+            .unwrap();
+        let stars = repositories
+            .iter()
+            .map(|repo| repo.stargazers.total_count)
+            .collect();
+        (stars, truncated, fetched)
+    }
+
+    #[tokio::test]
+    async fn test_collect_repositories_fetches_every_page() {
+        assert_eq!(
+            collect_mocked_pages(None).await,
+            (
+                vec![50, 40, 30, 20, 10],
+                false,
+                vec!["a".into(), "b".into()]
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn test_collect_repositories_stops_at_max_repos() {
+        let (stars, truncated, fetched) = collect_mocked_pages(Some(3)).await;
+        assert_eq!(stars.iter().sum::<u32>(), 120);
+        assert!(truncated);
+        assert_eq!(fetched, ["a"]);
+
+        // Ending exactly on a page boundary doesn't fetch the next page either
+        let (stars, truncated, fetched) = collect_mocked_pages(Some(2)).await;
+        assert_eq!(stars, [50, 40]);
+        assert!(truncated);
+        assert!(fetched.is_empty());
+
+        let (stars, truncated, _) = collect_mocked_pages(Some(5)).await;
+        assert_eq!(stars.len(), 5);
+        assert!(!truncated);
+    }
+
+    fn mocked_response(status: u16, body: &'static str) -> reqwest::Response {
+        axum::http::Response::builder()
+            .status(status)
+            .header("content-type", "text/html")
+            .body(body)
+            .unwrap()
+            .into()
+    }
+
+    const HTML_ERROR_PAGE: &str = "<html>\n  <body>\n    <h1>Bad Gateway</h1>\n  </body>\n</html>";
+
+    #[tokio::test]
+    async fn test_read_response_with_html_error_page() {
+        let result =
+            read_response::<UserQueryResponse>(mocked_response(403, HTML_ERROR_PAGE)).await;
+        match result {
+            Err(GitHubApiError::UnexpectedResponse { status, snippet }) => {
+                assert_eq!(status, 403);
+                assert_eq!(
+                    snippet,
+                    "<html> <body> <h1>Bad Gateway</h1> </body> </html>"
+                );
+            }
+            other => panic!("expected UnexpectedResponse, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_read_response_with_html_server_error_is_retryable() {
+        let result =
+            read_response::<UserQueryResponse>(mocked_response(502, HTML_ERROR_PAGE)).await;
+        assert!(matches!(result, Err(GitHubApiError::ServerError(502))));
+    }
+
+    #[tokio::test]
+    async fn test_read_response_with_non_json_success_body() {
+        let result =
+            read_response::<UserQueryResponse>(mocked_response(200, HTML_ERROR_PAGE)).await;
+        assert!(matches!(
+            result,
+            Err(GitHubApiError::UnexpectedResponse { status: 200, .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_read_response_with_secondary_rate_limit() {
+        let response: reqwest::Response = axum::http::Response::builder()
+            .status(403)
+            .header("retry-after", "30")
+            .body("{}")
+            .unwrap()
+            .into();
+        let result = read_response::<UserQueryResponse>(response).await;
+        assert!(matches!(
+            result,
+            Err(GitHubApiError::SecondaryRateLimit(30))
+        ));
+    }
+
+    #[test]
+    fn test_secondary_rate_limit_wait() {
+        let documented = br#"{"message": "You have exceeded a secondary rate limit.", "documentation_url": "https://docs.github.com/graphql/overview/rate-limits-and-node-limits-for-the-graphql-api#secondary-rate-limits"}"#;
+        assert_eq!(secondary_rate_limit_wait(Some("30"), b""), Some(30));
+        assert_eq!(secondary_rate_limit_wait(None, documented), Some(60));
+        assert_eq!(
+            secondary_rate_limit_wait(None, b"see the docs#abuse-rate-limits"),
+            Some(60)
+        );
+        // An HTTP date isn't read, the default wait applies
+        assert_eq!(
+            secondary_rate_limit_wait(Some("Wed, 21 Oct 2015 07:28:00 GMT"), b""),
+            Some(60)
+        );
+        assert_eq!(
+            secondary_rate_limit_wait(None, HTML_ERROR_PAGE.as_bytes()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_response_snippet_is_bounded_and_lossy() {
+        let body = [b"\xff\xfe broken ".as_slice(), &[b'a'; 1000]].concat();
+        let snippet = response_snippet(&body);
+        assert!(snippet.starts_with("\u{FFFD}\u{FFFD} broken"));
+        assert!(snippet.ends_with('…'));
+        assert_eq!(snippet.chars().count(), RESPONSE_SNIPPET_CHARS + 1);
+    }
+
+    #[tokio::test]
+    async fn test_retry_stops_when_budget_is_exhausted() {
+        let budget = RequestBudget::new(2);
+        let mut calls = 0;
+
+        let result: Result<(), _> =
+            with_retry_budget(&budget, MAX_ATTEMPTS, Duration::ZERO, || {
+                calls += 1;
+                async { Err(GitHubApiError::ServerError(503)) }
+            })
+            .await;
+
+        // Both budgeted attempts were spent on 503s, the third one was never sent
+        assert_eq!(calls, 2);
+        assert!(matches!(result, Err(GitHubApiError::ServerError(503))));
+
+        // Further requests fail right away without calling GitHub
+        let result: Result<(), _> =
+            with_retry_budget(&budget, MAX_ATTEMPTS, Duration::ZERO, || {
+                calls += 1;
+                async { Ok(()) }
+            })
+            .await;
+        assert_eq!(calls, 2);
+        assert!(matches!(result, Err(GitHubApiError::RequestBudgetExceeded)));
+    }
+
+    #[tokio::test]
+    async fn test_retry_recovers_from_server_error() {
+        let budget = RequestBudget::new(REQUEST_BUDGET);
+        let mut calls = 0;
+
+        let result = with_retry_budget(&budget, MAX_ATTEMPTS, Duration::ZERO, || {
+            calls += 1;
+            let attempt = calls;
+            async move {
+                if attempt == 1 {
+                    Err(GitHubApiError::ServerError(502))
+                } else {
+                    Ok(attempt)
+                }
+            }
+        })
+        .await;
+
+        assert_eq!(result.unwrap(), 2);
+        assert_eq!(budget.remaining.load(Ordering::SeqCst), REQUEST_BUDGET - 2);
+    }
+
+    #[tokio::test]
+    async fn test_retry_gives_up_after_max_attempts() {
+        let budget = RequestBudget::new(REQUEST_BUDGET);
+        let mut calls = 0;
+
+        let result: Result<(), _> =
+            with_retry_budget(&budget, MAX_ATTEMPTS, Duration::ZERO, || {
+                calls += 1;
+                async { Err(GitHubApiError::ServerError(500)) }
+            })
+            .await;
+
+        assert_eq!(calls, MAX_ATTEMPTS);
+        assert!(matches!(result, Err(GitHubApiError::ServerError(500))));
+    }
+
+    #[tokio::test]
+    async fn test_retry_skips_non_retryable_errors() {
+        let budget = RequestBudget::new(REQUEST_BUDGET);
+        let mut calls = 0;
+
+        let result: Result<(), _> =
+            with_retry_budget(&budget, MAX_ATTEMPTS, Duration::ZERO, || {
+                calls += 1;
+                async { Err(GitHubApiError::UserNotFound) }
+            })
+            .await;
+
+        assert_eq!(calls, 1);
+        assert!(matches!(result, Err(GitHubApiError::UserNotFound)));
+    }
+
+    fn org_repository(name: &str, stars: u32, rust_bytes: usize) -> serde_json::Value {
+        json!({
+            "name": name,
+            "isFork": false,
+            "stargazers": { "totalCount": stars },
+            "languages": { "edges": [{ "size": rust_bytes, "node": { "color": "#dea584", "name": "Rust" } }] }
+        })
+    }
+
+    fn org_response(payload: serde_json::Value) -> GraphQLResponse<OrgQueryResponse> {
+        serde_json::from_value(payload).unwrap()
+    }
+
+    #[test]
+    fn test_build_org_stats_sums_up_the_repositories() {
+        let response = org_response(json!({
+            "data": {
+                "organization": {
+                    "name": "The Rust Programming Language",
+                    "login": "rust-lang",
+                    "membersWithRole": { "totalCount": 42 },
+                    "repositories": {
+                        "totalCount": 3,
+                        "nodes": [org_repository("rust", 90_000, 3000), org_repository("cargo", 12_000, 1000)],
+                        "pageInfo": { "hasNextPage": true, "endCursor": "c1" }
+                    }
+                }
+            }
+        }));
+        let org = GitHubApi::org_from_response(response).unwrap();
+
+        let next_page: RepositoriesConnection = serde_json::from_value(json!({
+            "nodes": [org_repository("rustup", 6_000, 500)],
+            "pageInfo": { "hasNextPage": false, "endCursor": null }
+        }))
+        .unwrap();
+        let mut repositories = org.repositories.nodes.clone();
+        repositories.extend(next_page.nodes);
+
+        let stats = GitHubApi::build_org_stats(org, &repositories);
+        assert_eq!(stats.login, "rust-lang");
+        assert_eq!(stats.total_repos, 3);
+        assert_eq!(stats.total_stars, 108_000);
+        assert_eq!(stats.total_members, 42);
+        assert_eq!(stats.languages.len(), 1);
+        assert_eq!(stats.languages[0].size_bytes, 4500);
+        assert_eq!(stats.languages[0].repo_count, 3);
+    }
+
+    #[test]
+    fn test_build_org_stats_saturates_the_stars() {
+        let org = GitHubApi::org_from_response(org_response(json!({
+            "data": {
+                "organization": {
+                    "name": null,
+                    "login": "big",
+                    "membersWithRole": { "totalCount": 1 },
+                    "repositories": {
+                        "nodes": [org_repository("a", u32::MAX, 1), org_repository("b", 1, 1)],
+                        "pageInfo": { "hasNextPage": false, "endCursor": null }
+                    }
+                }
+            }
+        })))
+        .unwrap();
+        let repositories = org.repositories.nodes.clone();
+        assert_eq!(
+            GitHubApi::build_org_stats(org, &repositories).total_stars,
+            u32::MAX
+        );
+    }
+
+    #[test]
+    fn test_org_from_response_not_found() {
+        let null_org = org_response(json!({ "data": { "organization": null } }));
+        assert!(matches!(
+            GitHubApi::org_from_response(null_org),
+            Err(GitHubApiError::OrgNotFound)
+        ));
+
+        let not_found = org_response(json!({
+            "data": { "organization": null },
+            "errors": [{ "message": "Could not resolve to an Organization with the login of 'ghost'.", "type": "NOT_FOUND" }]
+        }));
+        assert!(matches!(
+            GitHubApi::org_from_response(not_found),
+            Err(GitHubApiError::OrgNotFound)
+        ));
+    }
+
+    /// The organization lookup answering with `payload`
+    async fn organization_lookup(
+        payload: serde_json::Value,
+    ) -> Result<GraphQLResponse<OrganizationQueryResponse>, GitHubApiError> {
+        Ok(serde_json::from_value(payload).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_not_found_error_for_an_organization() {
+        // The user query returns null for an organization login, the lookup finds it
+        let user: GraphQLResponse<UserQueryResponse> =
+            serde_json::from_value(json!({ "data": { "user": null } })).unwrap();
+        assert!(user.data.unwrap().user.is_none());
+
+        let error = not_found_error(organization_lookup(
+            json!({ "data": { "organization": { "login": "rust-lang" } } }),
+        ))
+        .await;
+        assert!(matches!(error, GitHubApiError::IsOrganization(login) if login == "rust-lang"));
+    }
+
+    #[tokio::test]
+    async fn test_not_found_error_without_an_organization() {
+        let error = not_found_error(organization_lookup(json!({
+            "data": { "organization": null },
+            "errors": [{ "message": "Could not resolve to an Organization", "type": "NOT_FOUND" }]
+        })))
+        .await;
+        assert!(matches!(error, GitHubApiError::UserNotFound));
+
+        let error = not_found_error(async { Err(GitHubApiError::ServerError(502)) }).await;
+        assert!(matches!(error, GitHubApiError::UserNotFound));
+    }
+
+    fn user_payload(contributions: serde_json::Value) -> String {
+        json!({
+            "data": {
+                "user": {
+                    "name": "Octo Cat",
+                    "login": "octocat",
+                    "contributionsCollection": contributions,
+                    "pullRequests": { "totalCount": 4 },
+                    "mergedPullRequests": { "totalCount": 3 },
+                    "openIssues": { "totalCount": 1 },
+                    "closedIssues": { "totalCount": 2 },
+                    "repositories": {
+                        "nodes": [{ "stargazers": { "totalCount": 5 } }],
+                        "pageInfo": { "hasNextPage": false, "endCursor": null }
+                    }
+                }
+            }
+        })
+        .to_string()
+    }
+
+    fn stats_from(payload: &str) -> GitHubStats {
+        stats_for_year(payload, 2024)
+    }
+
+    fn stats_for_year(payload: &str, year: i32) -> GitHubStats {
+        let response: GraphQLResponse<UserQueryResponse> = serde_json::from_str(payload).unwrap();
+        let user = response.data.unwrap().user.unwrap();
+        let repositories = user.repositories.nodes.clone();
+        GitHubApi::build_stats(user, &repositories, year)
+    }
+
+    #[test]
+    fn test_build_stats_with_null_contributions() {
+        let stats = stats_from(&user_payload(serde_json::Value::Null));
+        assert_eq!(stats.total_commits_ytd, 0);
+        assert_eq!(stats.total_reviews, 0);
+        // The rest of the stats are unaffected
+        assert_eq!(stats.total_stars, 5);
+        assert_eq!(stats.total_prs, 4);
+        assert_eq!(stats.total_issues, 3);
+    }
+
+    #[test]
+    fn test_build_stats_with_missing_contributions() {
+        let mut payload: serde_json::Value =
+            serde_json::from_str(&user_payload(serde_json::Value::Null)).unwrap();
+        payload["data"]["user"]
+            .as_object_mut()
+            .unwrap()
+            .remove("contributionsCollection");
+
+        let stats = stats_from(&payload.to_string());
+        assert_eq!(stats.total_commits_ytd, 0);
+        assert_eq!(stats.total_reviews, 0);
+    }
+
+    #[test]
+    fn test_build_stats_with_contributions() {
+        let stats = stats_from(&user_payload(json!({
+            "totalCommitContributions": 10,
+            "totalPullRequestReviewContributions": 2
+        })));
+        assert_eq!(stats.total_commits_ytd, 10);
+        assert_eq!(stats.total_reviews, 2);
+    }
+
+    #[test]
+    fn test_profile_query_requests_stars_and_languages() {
+        let query =
+            GitHubApi::get_user_query(StatsOptions::default(), 2024, PROFILE_REPOSITORY_FIELDS);
+        assert_eq!(query.matches("repositories(").count(), 1);
+        assert!(query.contains("stargazers { totalCount }"));
+        assert!(query.contains("isFork"));
+        assert!(query.contains("languages(first: 10"));
+    }
+
+    #[test]
+    fn test_build_profile_from_single_query() {
+        let mut payload: serde_json::Value = serde_json::from_str(&user_payload(json!({
+            "totalCommitContributions": 10,
+            "totalPullRequestReviewContributions": 2
+        })))
+        .unwrap();
+        payload["data"]["user"]["repositories"]["nodes"] = json!([
+            {
+                "name": "crab",
+                "isFork": false,
+                "stargazers": { "totalCount": 7 },
+                "languages": { "edges": [
+                    { "size": 300, "node": { "color": "#dea584", "name": "Rust" } },
+                    { "size": 100, "node": { "color": "#89e051", "name": "Shell" } }
+                ] }
+            },
+            {
+                "name": "tool",
+                "isFork": false,
+                "stargazers": { "totalCount": 2 },
+                "languages": { "edges": [
+                    { "size": 200, "node": { "color": "#dea584", "name": "Rust" } }
+                ] }
+            },
+            {
+                "name": "forked",
+                "isFork": true,
+                "stargazers": { "totalCount": 40 },
+                "languages": { "edges": [
+                    { "size": 9000, "node": { "color": "#00ADD8", "name": "Go" } }
+                ] }
+            }
+        ]);
+
+        let response: GraphQLResponse<UserQueryResponse> = serde_json::from_value(payload).unwrap();
+        let user = response.data.unwrap().user.unwrap();
+        let repositories = user.repositories.nodes.clone();
+        let profile = GitHubApi::build_profile(user, &repositories, 2024);
+
+        // Stars count forks as well, like the stats card
+        assert_eq!(profile.stats.total_stars, 49);
+        assert_eq!(profile.stats.total_commits_ytd, 10);
+        assert_eq!(profile.stats.total_prs, 4);
+
+        // Languages skip forks, like the languages card
+        let languages: Vec<(&str, usize, u64)> = profile
+            .languages
+            .iter()
+            .map(|l| (l.name.as_str(), l.size_bytes, l.repo_count))
+            .collect();
+        assert_eq!(languages.len(), 2);
+        assert!(languages.contains(&("Rust", 500, 2)));
+        assert!(languages.contains(&("Shell", 100, 1)));
+    }
+
+    #[test]
+    fn test_utc_year() {
+        assert_eq!(utc_year(0), 1970);
+        // 2023-12-31T23:59:59Z and 2024-01-01T00:00:00Z
+        assert_eq!(utc_year(1_704_067_199), 2023);
+        assert_eq!(utc_year(1_704_067_200), 2024);
+        // 2024-02-29T12:00:00Z
+        assert_eq!(utc_year(1_709_208_000), 2024);
+        assert!(current_utc_year_with(&SystemClock) >= 2024);
+        assert_eq!(current_utc_year_with(&FixedClock(NOW)), 2023);
+    }
+
+    #[test]
+    fn test_stats_query_counts_contributions_in_year() {
+        let query =
+            GitHubApi::get_user_query(StatsOptions::default(), 2021, STATS_REPOSITORY_FIELDS);
+        assert!(query.contains(
+            r#"contributionsCollection(from: "2021-01-01T00:00:00Z", to: "2021-12-31T23:59:59Z")"#
+        ));
+        assert!(query.contains("createdAt"));
+    }
+
+    #[test]
+    fn test_build_stats_for_past_year() {
+        let payload = |commits: u32| {
+            let mut payload: serde_json::Value = serde_json::from_str(&user_payload(json!({
+                "totalCommitContributions": commits,
+                "totalPullRequestReviewContributions": 1
+            })))
+            .unwrap();
+            payload["data"]["user"]["createdAt"] = json!("2019-05-01T10:00:00Z");
+            payload.to_string()
+        };
+
+        let current = stats_for_year(&payload(120), 2024);
+        let past = stats_for_year(&payload(35), 2020);
+        assert_eq!(current.total_commits_ytd, 120);
+        assert_eq!(current.commits_year, Some(2024));
+        assert_eq!(past.total_commits_ytd, 35);
+        assert_eq!(past.commits_year, Some(2020));
+    }
+
+    #[test]
+    fn test_build_stats_before_account_creation() {
+        let mut payload: serde_json::Value = serde_json::from_str(&user_payload(json!({
+            "totalCommitContributions": 12,
+            "totalPullRequestReviewContributions": 3
+        })))
+        .unwrap();
+        payload["data"]["user"]["createdAt"] = json!("2019-05-01T10:00:00Z");
+
+        let stats = stats_for_year(&payload.to_string(), 2018);
+        assert_eq!(stats.total_commits_ytd, 0);
+        assert_eq!(stats.total_reviews, 0);
+        assert_eq!(stats.commits_year, Some(2018));
+        // The rest of the stats are not tied to the year
+        assert_eq!(stats.total_stars, 5);
+    }
 }
@@ -1,8 +1,17 @@
 use moka::future::Cache;
-use std::{env, sync::OnceLock, time::Duration};
+use std::{
+    collections::HashSet,
+    env,
+    future::Future,
+    sync::{Arc, Mutex, OnceLock},
+    time::{Duration, Instant},
+};
 
 use crate::cards::langs_card::LanguageStat;
-use crate::github::types::GitHubStats;
+use crate::cards::streak_card::ContributionDay;
+use crate::github::types::{
+    GitHubApiError, GitHubStats, OrgStats, RepositoryAffiliations, StatsOptions, UserProfile,
+};
 
 /// Cache configuration settings
 #[derive(Debug, Clone)]
@@ -13,6 +22,10 @@ pub struct CacheConfig {
     pub user_stats_ttl: Duration,
     /// TTL for user languages cache
     pub user_languages_ttl: Duration,
+    /// How long past their TTL entries are served while being refreshed in the background
+    pub swr_window: Duration,
+    /// How long expired entries are retained to be served when a fresh fetch fails
+    pub fail_open_window: Duration,
 }
 
 impl Default for CacheConfig {
@@ -21,6 +34,8 @@ impl Default for CacheConfig {
             max_capacity_mb: 32,
             user_stats_ttl: Duration::from_secs(900), // 15 minutes
             user_languages_ttl: Duration::from_secs(3600), // 1 hour
+            swr_window: Duration::ZERO,
+            fail_open_window: Duration::from_secs(3600), // 1 hour
         }
     }
 }
@@ -45,26 +60,73 @@ impl CacheConfig {
             .map(Duration::from_secs)
             .unwrap_or(Duration::from_secs(3600));
 
+        let swr_window = env::var("CACHE_SWR_SECONDS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .map(Duration::from_secs)
+            .unwrap_or(Duration::ZERO);
+
+        let fail_open_window = env::var("CACHE_FAIL_OPEN_SECONDS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .map(Duration::from_secs)
+            .unwrap_or(Duration::from_secs(3600));
+
         Self {
             max_capacity_mb,
             user_stats_ttl,
             user_languages_ttl,
+            swr_window,
+            fail_open_window,
         }
     }
 }
 
+/// Cache key for user stats. Holds every [StatsOptions] as they change the fetched
+/// data itself, so an option added there can never be served another option's stats.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct StatsCacheKey {
+    pub username: String,
+    pub options: StatsOptions,
+}
+
 /// Cache key for GitHub API responses
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
 pub enum CacheKey {
+    UserStats(StatsCacheKey),
+    UserProfile(StatsCacheKey),
     UserLanguages {
         username: String,
         excluded_repos_hash: u64,
+        affiliations: RepositoryAffiliations,
+    },
+    OrgStats(String),
+    AvatarColor(String),
+    ContributionCalendar(String),
+    LifetimeCommits {
+        username: String,
+        include_private: bool,
     },
 }
 
 impl CacheKey {
-    /// Create a cache key for user languages with excluded repositories
-    pub fn user_languages(username: String, excluded_repos: &[String]) -> Self {
+    /// Create a cache key for user stats fetched with the given options
+    pub fn user_stats(username: String, options: StatsOptions) -> Self {
+        Self::UserStats(StatsCacheKey { username, options })
+    }
+
+    /// Create a cache key for the user profile (stats and languages) fetched with the given options
+    pub fn user_profile(username: String, options: StatsOptions) -> Self {
+        Self::UserProfile(StatsCacheKey { username, options })
+    }
+
+    /// Create a cache key for user languages with excluded repositories, counted over
+    /// the repositories with the given `affiliations`
+    pub fn user_languages(
+        username: String,
+        excluded_repos: &[String],
+        affiliations: RepositoryAffiliations,
+    ) -> Self {
         use std::collections::hash_map::DefaultHasher;
         use std::hash::{Hash, Hasher};
 
@@ -75,23 +137,67 @@ impl CacheKey {
         Self::UserLanguages {
             username,
             excluded_repos_hash,
+            affiliations,
+        }
+    }
+}
+
+/// A cached value along with the moment it was fetched from GitHub
+#[derive(Debug, Clone)]
+struct CacheEntry<T> {
+    value: T,
+    fetched_at: Instant,
+}
+
+/// A value returned by the cache
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cached<T> {
+    pub value: T,
+    /// The value outlived its TTL, either served while being refreshed
+    /// in the background or because a fresh fetch failed
+    pub stale: bool,
+    /// Time since the value was fetched from GitHub, zero for a fresh fetch
+    pub age: Duration,
+}
+
+impl<T> Cached<T> {
+    /// A value just fetched from GitHub
+    pub fn fresh(value: T) -> Self {
+        Self {
+            value,
+            stale: false,
+            age: Duration::ZERO,
         }
     }
 }
 
 /// GitHub API response cache manager
 pub struct GitHubCache {
-    stats_cache: Cache<String, GitHubStats>,
-    languages_cache: Cache<CacheKey, Vec<LanguageStat>>,
+    stats_cache: Cache<CacheKey, CacheEntry<GitHubStats>>,
+    languages_cache: Cache<CacheKey, CacheEntry<Vec<LanguageStat>>>,
+    profiles_cache: Cache<CacheKey, CacheEntry<UserProfile>>,
+    orgs_cache: Cache<CacheKey, CacheEntry<OrgStats>>,
+    avatar_colors_cache: Cache<CacheKey, CacheEntry<Option<String>>>,
+    calendars_cache: Cache<CacheKey, CacheEntry<Vec<ContributionDay>>>,
+    lifetime_commits_cache: Cache<CacheKey, CacheEntry<u32>>,
+    user_stats_ttl: Duration,
+    user_languages_ttl: Duration,
+    swr_window: Duration,
+    /// Keys with a background refresh in flight, so a burst of requests refreshes once
+    refreshing: Arc<Mutex<HashSet<CacheKey>>>,
 }
 
 impl GitHubCache {
     /// Create a new cache instance with the given configuration
     pub fn new(config: CacheConfig) -> Self {
+        // Entries are kept past their TTL for the SWR and fail-open windows,
+        // freshness is checked against `fetched_at` on every read
+        let retention = config.swr_window + config.fail_open_window;
         let stats_cache = Cache::builder()
-            .weigher(|_key: &String, value: &GitHubStats| {
+            .weigher(|_key: &CacheKey, entry: &CacheEntry<GitHubStats>| {
                 // Rough estimation based on struct size and string contents
-                let base_size = std::mem::size_of::<GitHubStats>();
+                let value = &entry.value;
+                let base_size = std::mem::size_of::<CacheEntry<GitHubStats>>();
                 let name_size = value.name.as_ref().map(|n| n.len()).unwrap_or(0);
                 let login_size = value.login.len();
                 (base_size + name_size + login_size)
@@ -99,96 +205,448 @@ impl GitHubCache {
                     .unwrap_or(u32::MAX)
             })
             .max_capacity(config.max_capacity_mb * 1024 * 1024)
-            .time_to_live(config.user_stats_ttl)
+            .time_to_live(config.user_stats_ttl + retention)
             .build();
 
         let languages_cache = Cache::builder()
-            .weigher(|_key: &CacheKey, value: &Vec<LanguageStat>| {
+            .weigher(|_key: &CacheKey, entry: &CacheEntry<Vec<LanguageStat>>| {
                 // Rough estimation for Vec<LanguageStat>
-                let base_size = std::mem::size_of::<Vec<LanguageStat>>();
-                let contents_size = value
+                let base_size = std::mem::size_of::<CacheEntry<Vec<LanguageStat>>>();
+                let contents_size = entry
+                    .value
                     .iter()
                     .map(|lang| std::mem::size_of::<LanguageStat>() + lang.name.len())
                     .sum::<usize>();
                 (base_size + contents_size).try_into().unwrap_or(u32::MAX)
             })
             .max_capacity(config.max_capacity_mb * 1024 * 1024)
-            .time_to_live(config.user_languages_ttl)
+            .time_to_live(config.user_languages_ttl + retention)
+            .build();
+
+        // Profiles hold stats, so they expire with the stats TTL
+        let profiles_cache = Cache::builder()
+            .weigher(|_key: &CacheKey, entry: &CacheEntry<UserProfile>| {
+                let value = &entry.value;
+                let base_size = std::mem::size_of::<CacheEntry<UserProfile>>();
+                let stats_size = value.stats.name.as_ref().map(|n| n.len()).unwrap_or(0)
+                    + value.stats.login.len();
+                let languages_size = value
+                    .languages
+                    .iter()
+                    .map(|lang| std::mem::size_of::<LanguageStat>() + lang.name.len())
+                    .sum::<usize>();
+                (base_size + stats_size + languages_size)
+                    .try_into()
+                    .unwrap_or(u32::MAX)
+            })
+            .max_capacity(config.max_capacity_mb * 1024 * 1024)
+            .time_to_live(config.user_stats_ttl + retention)
+            .build();
+
+        // Organizations hold stats too, so they expire with the stats TTL
+        let orgs_cache = Cache::builder()
+            .weigher(|_key: &CacheKey, entry: &CacheEntry<OrgStats>| {
+                let value = &entry.value;
+                let base_size = std::mem::size_of::<CacheEntry<OrgStats>>();
+                let stats_size =
+                    value.name.as_ref().map(|n| n.len()).unwrap_or(0) + value.login.len();
+                let languages_size = value
+                    .languages
+                    .iter()
+                    .map(|lang| std::mem::size_of::<LanguageStat>() + lang.name.len())
+                    .sum::<usize>();
+                (base_size + stats_size + languages_size)
+                    .try_into()
+                    .unwrap_or(u32::MAX)
+            })
+            .max_capacity(config.max_capacity_mb * 1024 * 1024)
+            .time_to_live(config.user_stats_ttl + retention)
+            .build();
+
+        // Avatars change about as rarely as the languages, so they share their TTL
+        let avatar_colors_cache = Cache::builder()
+            .weigher(|_key: &CacheKey, entry: &CacheEntry<Option<String>>| {
+                let base_size = std::mem::size_of::<CacheEntry<Option<String>>>();
+                let color_size = entry.value.as_ref().map(|c| c.len()).unwrap_or(0);
+                (base_size + color_size).try_into().unwrap_or(u32::MAX)
+            })
+            .max_capacity(config.max_capacity_mb * 1024 * 1024)
+            .time_to_live(config.user_languages_ttl + retention)
+            .build();
+
+        // Today's contributions keep changing, so the calendars expire with the stats
+        let calendars_cache = Cache::builder()
+            .weigher(
+                |_key: &CacheKey, entry: &CacheEntry<Vec<ContributionDay>>| {
+                    let base_size = std::mem::size_of::<CacheEntry<Vec<ContributionDay>>>();
+                    let days_size = entry
+                        .value
+                        .iter()
+                        .map(|day| std::mem::size_of::<ContributionDay>() + day.date.len())
+                        .sum::<usize>();
+                    (base_size + days_size).try_into().unwrap_or(u32::MAX)
+                },
+            )
+            .max_capacity(config.max_capacity_mb * 1024 * 1024)
+            .time_to_live(config.user_stats_ttl + retention)
+            .build();
+
+        // The commits of the current year keep changing, so the totals expire with the stats
+        let lifetime_commits_cache = Cache::builder()
+            .weigher(|_key: &CacheKey, _entry: &CacheEntry<u32>| {
+                std::mem::size_of::<CacheEntry<u32>>()
+                    .try_into()
+                    .unwrap_or(u32::MAX)
+            })
+            .max_capacity(config.max_capacity_mb * 1024 * 1024)
+            .time_to_live(config.user_stats_ttl + retention)
             .build();
 
         Self {
             stats_cache,
             languages_cache,
+            profiles_cache,
+            orgs_cache,
+            avatar_colors_cache,
+            calendars_cache,
+            lifetime_commits_cache,
+            user_stats_ttl: config.user_stats_ttl,
+            user_languages_ttl: config.user_languages_ttl,
+            swr_window: config.swr_window,
+            refreshing: Arc::default(),
         }
     }
 
-    /// Get or insert user stats with the configured TTL
+    /// Get or insert user stats with the configured TTL.
+    /// Serves an entry within the SWR window while refreshing it in the background,
+    /// and falls back to an expired entry when the fetch fails.
     pub async fn get_or_insert_user_stats<F, Fut>(
         &self,
         username: String,
+        options: StatsOptions,
         fetch_fn: F,
-    ) -> Result<GitHubStats, crate::github::types::GitHubApiError>
+    ) -> Result<Cached<GitHubStats>, GitHubApiError>
     where
-        F: FnOnce() -> Fut,
-        Fut:
-            std::future::Future<Output = Result<GitHubStats, crate::github::types::GitHubApiError>>,
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<GitHubStats, GitHubApiError>> + Send + 'static,
     {
-        if let Some(stats) = self.stats_cache.get(&username).await {
-            tracing::debug!("Cache hit for user stats: {}", username);
-            return Ok(stats);
-        }
+        let key = CacheKey::user_stats(username.clone(), options);
+        tracing::debug!("Looking up user stats: {}", username);
+        self.get_or_fetch(&self.stats_cache, self.user_stats_ttl, key, fetch_fn)
+            .await
+    }
 
-        tracing::debug!("Cache miss for user stats: {}, fetching...", username);
-        let stats = fetch_fn().await?;
+    /// Get or insert user languages with the configured TTL.
+    /// Serves an entry within the SWR window while refreshing it in the background,
+    /// and falls back to an expired entry when the fetch fails.
+    pub async fn get_or_insert_user_languages<F, Fut>(
+        &self,
+        username: String,
+        excluded_repos: &[String],
+        affiliations: RepositoryAffiliations,
+        fetch_fn: F,
+    ) -> Result<Cached<Vec<LanguageStat>>, GitHubApiError>
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<Vec<LanguageStat>, GitHubApiError>> + Send + 'static,
+    {
+        let key = CacheKey::user_languages(username.clone(), excluded_repos, affiliations);
+        tracing::debug!("Looking up user languages: {}", username);
+        self.get_or_fetch(
+            &self.languages_cache,
+            self.user_languages_ttl,
+            key,
+            fetch_fn,
+        )
+        .await
+    }
 
-        // Insert into cache (TTL is handled by the cache configuration)
-        self.stats_cache.insert(username, stats.clone()).await;
+    /// Get or insert a user profile with the configured stats TTL.
+    /// Serves an entry within the SWR window while refreshing it in the background,
+    /// and falls back to an expired entry when the fetch fails.
+    pub async fn get_or_insert_user_profile<F, Fut>(
+        &self,
+        username: String,
+        options: StatsOptions,
+        fetch_fn: F,
+    ) -> Result<Cached<UserProfile>, GitHubApiError>
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<UserProfile, GitHubApiError>> + Send + 'static,
+    {
+        let key = CacheKey::user_profile(username.clone(), options);
+        tracing::debug!("Looking up user profile: {}", username);
+        self.get_or_fetch(&self.profiles_cache, self.user_stats_ttl, key, fetch_fn)
+            .await
+    }
 
-        Ok(stats)
+    /// Get or insert organization stats with the configured stats TTL.
+    /// Serves an entry within the SWR window while refreshing it in the background,
+    /// and falls back to an expired entry when the fetch fails.
+    pub async fn get_or_insert_org_stats<F, Fut>(
+        &self,
+        org: String,
+        fetch_fn: F,
+    ) -> Result<Cached<OrgStats>, GitHubApiError>
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<OrgStats, GitHubApiError>> + Send + 'static,
+    {
+        tracing::debug!("Looking up organization stats: {}", org);
+        let key = CacheKey::OrgStats(org);
+        self.get_or_fetch(&self.orgs_cache, self.user_stats_ttl, key, fetch_fn)
+            .await
     }
 
-    /// Get or insert user languages with the configured TTL
-    pub async fn get_or_insert_user_languages<F, Fut>(
+    /// Get or insert the accent color derived from a user's avatar (`None` when the
+    /// avatar has no dominant color) with the configured languages TTL.
+    pub async fn get_or_insert_avatar_color<F, Fut>(
+        &self,
+        username: String,
+        fetch_fn: F,
+    ) -> Result<Cached<Option<String>>, GitHubApiError>
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<Option<String>, GitHubApiError>> + Send + 'static,
+    {
+        tracing::debug!("Looking up avatar color: {}", username);
+        let key = CacheKey::AvatarColor(username);
+        self.get_or_fetch(
+            &self.avatar_colors_cache,
+            self.user_languages_ttl,
+            key,
+            fetch_fn,
+        )
+        .await
+    }
+
+    /// Get or insert the contribution calendar days of a user with the configured stats TTL.
+    pub async fn get_or_insert_contribution_calendar<F, Fut>(
+        &self,
+        username: String,
+        fetch_fn: F,
+    ) -> Result<Cached<Vec<ContributionDay>>, GitHubApiError>
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<Vec<ContributionDay>, GitHubApiError>> + Send + 'static,
+    {
+        tracing::debug!("Looking up contribution calendar: {}", username);
+        let key = CacheKey::ContributionCalendar(username);
+        self.get_or_fetch(&self.calendars_cache, self.user_stats_ttl, key, fetch_fn)
+            .await
+    }
+
+    /// Get or insert the commits of a user summed over every year with the configured
+    /// stats TTL, kept apart from the commits of a single year in the stats.
+    pub async fn get_or_insert_lifetime_commits<F, Fut>(
+        &self,
+        username: String,
+        include_private: bool,
+        fetch_fn: F,
+    ) -> Result<Cached<u32>, GitHubApiError>
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<u32, GitHubApiError>> + Send + 'static,
+    {
+        tracing::debug!("Looking up lifetime commits: {}", username);
+        let key = CacheKey::LifetimeCommits {
+            username,
+            include_private,
+        };
+        self.get_or_fetch(
+            &self.lifetime_commits_cache,
+            self.user_stats_ttl,
+            key,
+            fetch_fn,
+        )
+        .await
+    }
+
+    /// Stores freshly fetched user stats, replacing the entry whatever its age.
+    pub async fn insert_user_stats(
+        &self,
+        username: String,
+        options: StatsOptions,
+        stats: GitHubStats,
+    ) {
+        let key = CacheKey::user_stats(username, options);
+        Self::insert(&self.stats_cache, key, stats).await;
+    }
+
+    /// Stores freshly fetched user languages, replacing the entry whatever its age.
+    pub async fn insert_user_languages(
         &self,
         username: String,
         excluded_repos: &[String],
+        affiliations: RepositoryAffiliations,
+        languages: Vec<LanguageStat>,
+    ) {
+        let key = CacheKey::user_languages(username, excluded_repos, affiliations);
+        Self::insert(&self.languages_cache, key, languages).await;
+    }
+
+    async fn insert<T>(cache: &Cache<CacheKey, CacheEntry<T>>, key: CacheKey, value: T)
+    where
+        T: Clone + Send + Sync + 'static,
+    {
+        let entry = CacheEntry {
+            value,
+            fetched_at: Instant::now(),
+        };
+        cache.insert(key, entry).await;
+    }
+
+    /// Returns the entry under `key` while it is younger than `ttl`. Younger than
+    /// `ttl` plus the SWR window, it is returned as stale and refreshed in the
+    /// background. Otherwise a fresh one is fetched and stored; when that fetch fails,
+    /// a retained expired entry is served as stale instead of the error.
+    async fn get_or_fetch<T, F, Fut>(
+        &self,
+        cache: &Cache<CacheKey, CacheEntry<T>>,
+        ttl: Duration,
+        key: CacheKey,
         fetch_fn: F,
-    ) -> Result<Vec<LanguageStat>, crate::github::types::GitHubApiError>
+    ) -> Result<Cached<T>, GitHubApiError>
     where
-        F: FnOnce() -> Fut,
-        Fut: std::future::Future<
-                Output = Result<Vec<LanguageStat>, crate::github::types::GitHubApiError>,
-            >,
+        T: Clone + Send + Sync + 'static,
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<T, GitHubApiError>> + Send + 'static,
     {
-        let key = CacheKey::user_languages(username.clone(), excluded_repos);
+        let cached = cache.get(&key).await;
+        if let Some(entry) = &cached {
+            let age = entry.fetched_at.elapsed();
+            if age < ttl {
+                tracing::debug!("Cache hit for {:?}", key);
+                return Ok(Cached {
+                    value: entry.value.clone(),
+                    stale: false,
+                    age,
+                });
+            }
+            if age < ttl + self.swr_window {
+                tracing::debug!("Serving stale {:?} while revalidating", key);
+                self.refresh_in_background(cache.clone(), key, fetch_fn);
+                return Ok(Cached {
+                    value: entry.value.clone(),
+                    stale: true,
+                    age,
+                });
+            }
+        }
 
-        if let Some(languages) = self.languages_cache.get(&key).await {
-            tracing::debug!("Cache hit for user languages: {}", username);
-            return Ok(languages);
+        tracing::debug!("Cache miss for {:?}, fetching...", key);
+        match fetch_fn().await {
+            Ok(value) => {
+                let entry = CacheEntry {
+                    value: value.clone(),
+                    fetched_at: Instant::now(),
+                };
+                cache.insert(key, entry).await;
+                Ok(Cached::fresh(value))
+            }
+            Err(e) => match cached {
+                Some(entry) => {
+                    tracing::warn!("Serving stale {:?} after fetch failure: {}", key, e);
+                    Ok(Cached {
+                        age: entry.fetched_at.elapsed(),
+                        value: entry.value,
+                        stale: true,
+                    })
+                }
+                None => Err(e),
+            },
         }
+    }
 
-        tracing::debug!("Cache miss for user languages: {}, fetching...", username);
-        let languages = fetch_fn().await?;
+    /// Spawns a task replacing the entry under `key` with a freshly fetched one,
+    /// unless a refresh of that key is already in flight. A failed refresh keeps
+    /// the current entry.
+    fn refresh_in_background<T, F, Fut>(
+        &self,
+        cache: Cache<CacheKey, CacheEntry<T>>,
+        key: CacheKey,
+        fetch_fn: F,
+    ) where
+        T: Clone + Send + Sync + 'static,
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<T, GitHubApiError>> + Send + 'static,
+    {
+        let refreshing = Arc::clone(&self.refreshing);
+        if !lock_refreshing(&refreshing).insert(key.clone()) {
+            return;
+        }
 
-        // Insert into cache (TTL is handled by the cache configuration)
-        self.languages_cache.insert(key, languages.clone()).await;
+        tokio::spawn(async move {
+            match fetch_fn().await {
+                Ok(value) => {
+                    let entry = CacheEntry {
+                        value,
+                        fetched_at: Instant::now(),
+                    };
+                    cache.insert(key.clone(), entry).await;
+                }
+                Err(e) => tracing::warn!("Background refresh of {:?} failed: {}", key, e),
+            }
+            lock_refreshing(&refreshing).remove(&key);
+        });
+    }
 
-        Ok(languages)
+    /// Drops every cached entry, so tests sharing the global cache start from scratch.
+    #[cfg(test)]
+    pub(crate) fn invalidate_all(&self) {
+        self.stats_cache.invalidate_all();
+        self.languages_cache.invalidate_all();
+        self.profiles_cache.invalidate_all();
+        self.orgs_cache.invalidate_all();
+        self.avatar_colors_cache.invalidate_all();
+        self.calendars_cache.invalidate_all();
+        self.lifetime_commits_cache.invalidate_all();
     }
 
     /// Get current cache statistics for monitoring
     pub fn stats(&self) -> CacheStats {
         CacheStats {
-            entry_count: self.stats_cache.entry_count() + self.languages_cache.entry_count(),
-            weighted_size: self.stats_cache.weighted_size() + self.languages_cache.weighted_size(),
+            entry_count: self.stats_cache.entry_count()
+                + self.languages_cache.entry_count()
+                + self.profiles_cache.entry_count()
+                + self.orgs_cache.entry_count()
+                + self.avatar_colors_cache.entry_count()
+                + self.calendars_cache.entry_count()
+                + self.lifetime_commits_cache.entry_count(),
+            weighted_size: self.stats_cache.weighted_size()
+                + self.languages_cache.weighted_size()
+                + self.profiles_cache.weighted_size()
+                + self.orgs_cache.weighted_size()
+                + self.avatar_colors_cache.weighted_size()
+                + self.calendars_cache.weighted_size()
+                + self.lifetime_commits_cache.weighted_size(),
             stats_cache_entries: self.stats_cache.entry_count(),
             stats_cache_size: self.stats_cache.weighted_size(),
             languages_cache_entries: self.languages_cache.entry_count(),
             languages_cache_size: self.languages_cache.weighted_size(),
+            profiles_cache_entries: self.profiles_cache.entry_count(),
+            profiles_cache_size: self.profiles_cache.weighted_size(),
+            orgs_cache_entries: self.orgs_cache.entry_count(),
+            orgs_cache_size: self.orgs_cache.weighted_size(),
+            avatar_colors_cache_entries: self.avatar_colors_cache.entry_count(),
+            avatar_colors_cache_size: self.avatar_colors_cache.weighted_size(),
+            calendars_cache_entries: self.calendars_cache.entry_count(),
+            calendars_cache_size: self.calendars_cache.weighted_size(),
+            lifetime_commits_cache_entries: self.lifetime_commits_cache.entry_count(),
+            lifetime_commits_cache_size: self.lifetime_commits_cache.weighted_size(),
         }
     }
 }
 
+/// Locks the set of keys being refreshed, recovering it if a refresh task panicked
+fn lock_refreshing(
+    refreshing: &Mutex<HashSet<CacheKey>>,
+) -> std::sync::MutexGuard<'_, HashSet<CacheKey>> {
+    refreshing
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
 /// Cache statistics for monitoring
 #[derive(Debug, Clone)]
 pub struct CacheStats {
@@ -204,6 +662,26 @@ pub struct CacheStats {
     pub languages_cache_entries: u64,
     /// Weighted size of languages cache in bytes
     pub languages_cache_size: u64,
+    /// Number of entries in profiles cache
+    pub profiles_cache_entries: u64,
+    /// Weighted size of profiles cache in bytes
+    pub profiles_cache_size: u64,
+    /// Number of entries in organizations cache
+    pub orgs_cache_entries: u64,
+    /// Weighted size of organizations cache in bytes
+    pub orgs_cache_size: u64,
+    /// Number of entries in avatar colors cache
+    pub avatar_colors_cache_entries: u64,
+    /// Weighted size of avatar colors cache in bytes
+    pub avatar_colors_cache_size: u64,
+    /// Number of entries in contribution calendars cache
+    pub calendars_cache_entries: u64,
+    /// Weighted size of contribution calendars cache in bytes
+    pub calendars_cache_size: u64,
+    /// Number of entries in lifetime commits cache
+    pub lifetime_commits_cache_entries: u64,
+    /// Weighted size of lifetime commits cache in bytes
+    pub lifetime_commits_cache_size: u64,
 }
 
 // Global cache instance
@@ -233,21 +711,405 @@ mod tests {
         assert_eq!(config.max_capacity_mb, 32);
         assert_eq!(config.user_stats_ttl, Duration::from_secs(900));
         assert_eq!(config.user_languages_ttl, Duration::from_secs(3600));
+        assert_eq!(config.swr_window, Duration::ZERO);
+        assert_eq!(config.fail_open_window, Duration::from_secs(3600));
+    }
+
+    #[test]
+    fn test_cache_key_user_stats() {
+        let public = CacheKey::user_stats("user1".to_string(), StatsOptions::default());
+        let private = CacheKey::user_stats(
+            "user1".to_string(),
+            StatsOptions {
+                include_private: true,
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(
+            public,
+            CacheKey::user_stats("user1".to_string(), StatsOptions::default())
+        );
+        assert_ne!(public, private);
+    }
+
+    #[test]
+    fn test_cache_key_user_stats_per_year() {
+        let year = |year| StatsOptions {
+            year: Some(year),
+            ..Default::default()
+        };
+
+        assert_ne!(
+            CacheKey::user_stats("user1".to_string(), year(2020)),
+            CacheKey::user_stats("user1".to_string(), year(2021))
+        );
+    }
+
+    #[test]
+    fn test_cache_key_user_stats_holds_all_options() {
+        let options = StatsOptions {
+            include_private: true,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            CacheKey::user_stats("user1".to_string(), options),
+            CacheKey::UserStats(StatsCacheKey {
+                username: "user1".to_string(),
+                options,
+            })
+        );
+        assert_ne!(
+            CacheKey::user_stats("user1".to_string(), options),
+            CacheKey::user_stats("user2".to_string(), options)
+        );
+    }
+
+    #[test]
+    fn test_cache_key_user_profile() {
+        let options = StatsOptions::default();
+        assert_eq!(
+            CacheKey::user_profile("user1".to_string(), options),
+            CacheKey::user_profile("user1".to_string(), options)
+        );
+        assert_ne!(
+            CacheKey::user_profile("user1".to_string(), options),
+            CacheKey::user_stats("user1".to_string(), options)
+        );
     }
 
     #[test]
     fn test_cache_key_user_languages() {
+        let owner = RepositoryAffiliations::OWNER;
         let key1 = CacheKey::user_languages(
             "user1".to_string(),
             &["repo1".to_string(), "repo2".to_string()],
+            owner,
         );
         let key2 = CacheKey::user_languages(
             "user1".to_string(),
             &["repo1".to_string(), "repo2".to_string()],
+            owner,
         );
-        let key3 = CacheKey::user_languages("user1".to_string(), &["repo1".to_string()]);
+        let key3 = CacheKey::user_languages("user1".to_string(), &["repo1".to_string()], owner);
 
         assert_eq!(key1, key2);
         assert_ne!(key1, key3);
     }
+
+    #[test]
+    fn test_cache_key_different_affiliations() {
+        let member = RepositoryAffiliations {
+            organization_member: true,
+            ..RepositoryAffiliations::OWNER
+        };
+
+        assert_ne!(
+            CacheKey::user_languages("user1".to_string(), &[], RepositoryAffiliations::OWNER),
+            CacheKey::user_languages("user1".to_string(), &[], member)
+        );
+        assert_ne!(
+            CacheKey::user_stats("user1".to_string(), StatsOptions::default()),
+            CacheKey::user_stats(
+                "user1".to_string(),
+                StatsOptions {
+                    affiliations: member,
+                    ..Default::default()
+                }
+            )
+        );
+    }
+
+    mod fn_get_or_insert_user_stats {
+        use super::*;
+
+        fn stats(login: &str) -> GitHubStats {
+            GitHubStats {
+                login: login.to_string(),
+                ..Default::default()
+            }
+        }
+
+        fn cache_with_ttl(ttl: Duration) -> GitHubCache {
+            GitHubCache::new(CacheConfig {
+                user_stats_ttl: ttl,
+                fail_open_window: Duration::from_secs(60),
+                ..Default::default()
+            })
+        }
+
+        #[tokio::test]
+        async fn fresh_entry_skips_the_fetch() {
+            let cache = cache_with_ttl(Duration::from_secs(60));
+            let options = StatsOptions::default();
+
+            cache
+                .get_or_insert_user_stats("user1".into(), options, || async { Ok(stats("v1")) })
+                .await
+                .unwrap();
+            let cached = cache
+                .get_or_insert_user_stats("user1".into(), options, || async {
+                    panic!("fresh entries must not be refetched")
+                })
+                .await
+                .unwrap();
+
+            assert_eq!((cached.value, cached.stale), (stats("v1"), false));
+            assert!(cached.age < Duration::from_secs(60));
+        }
+
+        #[tokio::test]
+        async fn inserted_entry_replaces_the_cached_one() {
+            let cache = cache_with_ttl(Duration::from_secs(60));
+            let options = StatsOptions::default();
+
+            cache
+                .get_or_insert_user_stats("user1".into(), options, || async { Ok(stats("v1")) })
+                .await
+                .unwrap();
+            cache
+                .insert_user_stats("user1".into(), options, stats("v2"))
+                .await;
+            let cached = cache
+                .get_or_insert_user_stats("user1".into(), options, || async {
+                    panic!("inserted entries are fresh")
+                })
+                .await
+                .unwrap();
+
+            assert_eq!((cached.value, cached.stale), (stats("v2"), false));
+        }
+
+        #[tokio::test]
+        async fn options_are_cached_separately() {
+            let cache = cache_with_ttl(Duration::from_secs(60));
+            let private = StatsOptions {
+                include_private: true,
+                ..Default::default()
+            };
+
+            cache
+                .get_or_insert_user_stats("user1".into(), StatsOptions::default(), || async {
+                    Ok(stats("public"))
+                })
+                .await
+                .unwrap();
+            let cached = cache
+                .get_or_insert_user_stats("user1".into(), private, || async {
+                    Ok(stats("private"))
+                })
+                .await
+                .unwrap();
+
+            assert_eq!(cached.value, stats("private"));
+        }
+
+        #[tokio::test]
+        async fn invalidate_all_drops_every_entry() {
+            let cache = cache_with_ttl(Duration::from_secs(60));
+            let options = StatsOptions::default();
+
+            cache
+                .get_or_insert_user_stats("user1".into(), options, || async { Ok(stats("v1")) })
+                .await
+                .unwrap();
+            cache.invalidate_all();
+            let cached = cache
+                .get_or_insert_user_stats("user1".into(), options, || async { Ok(stats("v2")) })
+                .await
+                .unwrap();
+            assert_eq!(cached.value.login, "v2");
+        }
+
+        #[tokio::test]
+        async fn expired_entry_is_refetched() {
+            let cache = cache_with_ttl(Duration::ZERO);
+            let options = StatsOptions::default();
+
+            cache
+                .get_or_insert_user_stats("user1".into(), options, || async { Ok(stats("v1")) })
+                .await
+                .unwrap();
+            let cached = cache
+                .get_or_insert_user_stats("user1".into(), options, || async { Ok(stats("v2")) })
+                .await
+                .unwrap();
+
+            assert_eq!(cached.value, stats("v2"));
+            assert!(!cached.stale);
+        }
+
+        #[tokio::test]
+        async fn serves_stale_entry_when_fetch_fails() {
+            let cache = cache_with_ttl(Duration::ZERO);
+            let options = StatsOptions::default();
+
+            cache
+                .get_or_insert_user_stats("user1".into(), options, || async { Ok(stats("v1")) })
+                .await
+                .unwrap();
+            let cached = cache
+                .get_or_insert_user_stats("user1".into(), options, || async {
+                    Err(GitHubApiError::ServerError(503))
+                })
+                .await
+                .unwrap();
+
+            assert_eq!((cached.value, cached.stale), (stats("v1"), true));
+        }
+
+        #[tokio::test]
+        async fn fetch_error_without_entry_is_returned() {
+            let cache = cache_with_ttl(Duration::ZERO);
+
+            let result = cache
+                .get_or_insert_user_stats("user1".into(), StatsOptions::default(), || async {
+                    Err(GitHubApiError::ServerError(503))
+                })
+                .await;
+
+            assert!(matches!(result, Err(GitHubApiError::ServerError(503))));
+        }
+    }
+
+    mod fn_get_or_insert_org_stats {
+        use super::*;
+
+        fn org(login: &str) -> OrgStats {
+            OrgStats {
+                login: login.to_string(),
+                ..Default::default()
+            }
+        }
+
+        #[tokio::test]
+        async fn caches_per_organization() {
+            let cache = GitHubCache::new(CacheConfig::default());
+
+            cache
+                .get_or_insert_org_stats("rust-lang".into(), || async { Ok(org("rust-lang")) })
+                .await
+                .unwrap();
+            let cached = cache
+                .get_or_insert_org_stats("rust-lang".into(), || async {
+                    panic!("fresh entries must not be refetched")
+                })
+                .await
+                .unwrap();
+            assert_eq!(cached.value.login, "rust-lang");
+
+            let other = cache
+                .get_or_insert_org_stats("tokio-rs".into(), || async { Ok(org("tokio-rs")) })
+                .await
+                .unwrap();
+            assert_eq!(other.value.login, "tokio-rs");
+        }
+    }
+
+    mod fn_get_or_fetch_with_swr {
+        use super::*;
+        use std::sync::atomic::{AtomicU32, Ordering};
+
+        fn stats(login: &str) -> GitHubStats {
+            GitHubStats {
+                login: login.to_string(),
+                ..Default::default()
+            }
+        }
+
+        fn swr_cache() -> GitHubCache {
+            GitHubCache::new(CacheConfig {
+                user_stats_ttl: Duration::ZERO,
+                swr_window: Duration::from_secs(60),
+                fail_open_window: Duration::ZERO,
+                ..Default::default()
+            })
+        }
+
+        async fn cached_login(cache: &GitHubCache) -> Option<String> {
+            let key = CacheKey::user_stats("user1".into(), StatsOptions::default());
+            cache
+                .stats_cache
+                .get(&key)
+                .await
+                .map(|entry| entry.value.login)
+        }
+
+        #[tokio::test]
+        async fn serves_stale_entry_and_refreshes_in_background() {
+            let cache = swr_cache();
+            let options = StatsOptions::default();
+
+            cache
+                .get_or_insert_user_stats("user1".into(), options, || async { Ok(stats("v1")) })
+                .await
+                .unwrap();
+
+            // The refresh is held back until the stale value has been returned
+            let (release, released) = tokio::sync::oneshot::channel::<()>();
+            let cached = cache
+                .get_or_insert_user_stats("user1".into(), options, || async move {
+                    released.await.ok();
+                    Ok(stats("v2"))
+                })
+                .await
+                .unwrap();
+
+            assert_eq!((cached.value, cached.stale), (stats("v1"), true));
+
+            release.send(()).unwrap();
+            tokio::time::timeout(Duration::from_secs(5), async {
+                while cached_login(&cache).await.as_deref() != Some("v2") {
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                }
+            })
+            .await
+            .expect("background refresh should replace the entry");
+        }
+
+        #[tokio::test]
+        async fn refreshes_a_key_once_at_a_time() {
+            let cache = swr_cache();
+            let options = StatsOptions::default();
+            let fetches = Arc::new(AtomicU32::new(0));
+
+            cache
+                .get_or_insert_user_stats("user1".into(), options, || async { Ok(stats("v1")) })
+                .await
+                .unwrap();
+
+            let (release, released) = tokio::sync::oneshot::channel::<()>();
+            let counter = Arc::clone(&fetches);
+            cache
+                .get_or_insert_user_stats("user1".into(), options, move || async move {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                    released.await.ok();
+                    Ok(stats("v2"))
+                })
+                .await
+                .unwrap();
+            for _ in 0..3 {
+                let counter = Arc::clone(&fetches);
+                let cached = cache
+                    .get_or_insert_user_stats("user1".into(), options, move || async move {
+                        counter.fetch_add(1, Ordering::SeqCst);
+                        Ok(stats("v3"))
+                    })
+                    .await
+                    .unwrap();
+                assert_eq!(cached.value, stats("v1"));
+            }
+
+            release.send(()).unwrap();
+            tokio::time::timeout(Duration::from_secs(5), async {
+                while cached_login(&cache).await.as_deref() != Some("v2") {
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                }
+            })
+            .await
+            .expect("background refresh should replace the entry");
+            assert_eq!(fetches.load(Ordering::SeqCst), 1);
+        }
+    }
 }
@@ -0,0 +1,50 @@
+//! Source of the current time for the time-dependent logic (rate limit resets,
+//! the year the commits are counted in), so it can be fixed in tests.
+
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Tells the current time
+pub trait Clock: fmt::Debug + Send + Sync {
+    /// Current unix timestamp in seconds
+    fn now(&self) -> u64;
+}
+
+/// The system wall clock
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0)
+    }
+}
+
+/// A clock stopped at the given unix timestamp (seconds)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixedClock(pub u64);
+
+impl Clock for FixedClock {
+    fn now(&self) -> u64 {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixed_clock_returns_its_timestamp() {
+        assert_eq!(FixedClock(1_700_000_000).now(), 1_700_000_000);
+    }
+
+    #[test]
+    fn system_clock_is_past_the_fixed_one() {
+        // 2023-11-14, any real clock is later
+        assert!(SystemClock.now() > FixedClock(1_700_000_000).now());
+    }
+}
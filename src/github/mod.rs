@@ -1,7 +1,11 @@
 pub mod api;
 pub mod cache;
+pub mod clock;
+pub mod prewarm;
+pub mod transport;
 pub mod types;
 
-pub use api::{GitHubApi, get_github_rate_limit};
+pub use api::{GitHubApi, GitHubToken, get_github_rate_limit};
 pub use cache::get_github_cache;
+pub use clock::{Clock, FixedClock, SystemClock};
 pub use types::*;
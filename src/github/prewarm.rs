@@ -0,0 +1,211 @@
+//! Periodic refresh of the cached data of frequently viewed profiles, so their
+//! cards are served from the cache instead of waiting for GitHub.
+
+use std::{env, future::Future, time::Duration};
+
+use crate::github::{GitHubApi, GitHubApiError, StatsOptions};
+
+/// Prewarm configuration settings
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrewarmConfig {
+    /// Usernames whose stats and languages are refreshed
+    pub usernames: Vec<String>,
+    /// Time between two refreshes, meant to be shorter than the cache TTLs
+    pub interval: Duration,
+}
+
+impl PrewarmConfig {
+    /// Shorter than the default stats TTL (15 minutes)
+    pub const DEFAULT_INTERVAL: Duration = Duration::from_secs(600);
+
+    /// Load prewarm configuration from environment variables,
+    /// `None` when `PREWARM_USERNAMES` is unset or empty
+    pub fn from_env() -> Option<Self> {
+        Self::from_vars(|key| env::var(key).ok())
+    }
+
+    /// Load prewarm configuration using the given variable lookup
+    pub fn from_vars<F>(var: F) -> Option<Self>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let usernames: Vec<String> = var("PREWARM_USERNAMES")?
+            .split(',')
+            .map(str::trim)
+            .filter(|u| !u.is_empty())
+            .map(str::to_string)
+            .collect();
+        if usernames.is_empty() {
+            return None;
+        }
+
+        let interval = var("PREWARM_INTERVAL_SECONDS")
+            .and_then(|v| v.trim().parse().ok())
+            .filter(|&secs| secs > 0)
+            .map(Duration::from_secs)
+            .unwrap_or(Self::DEFAULT_INTERVAL);
+
+        Some(Self {
+            usernames,
+            interval,
+        })
+    }
+}
+
+/// Spawns the task refreshing the configured usernames every interval, starting right away.
+pub fn spawn(config: PrewarmConfig) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(config.interval);
+        // A slow run (e.g. waiting on retries) delays the next one rather than bursting
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        loop {
+            interval.tick().await;
+            let refreshed = prewarm_once(&config.usernames, refresh_user).await;
+            tracing::info!(
+                "Prewarmed {refreshed} of {} configured usernames",
+                config.usernames.len()
+            );
+        }
+    })
+}
+
+/// Refreshes the cached stats and languages the cards of `username` use by default.
+async fn refresh_user(username: String) -> Result<(), GitHubApiError> {
+    // A client per fetch, as for the cards, so each one gets its own request budget
+    GitHubApi::new()
+        .refresh_cached_user_stats(&username, StatsOptions::default())
+        .await?;
+    GitHubApi::new()
+        .refresh_cached_user_languages(&username, &[])
+        .await
+}
+
+/// Runs `refresh` for each username in turn and returns how many succeeded.
+///
+/// Stops at the first rate limit error, the remaining usernames wait for the next
+/// run rather than eating into the requests left for the cards.
+pub async fn prewarm_once<F, Fut>(usernames: &[String], mut refresh: F) -> usize
+where
+    F: FnMut(String) -> Fut,
+    Fut: Future<Output = Result<(), GitHubApiError>>,
+{
+    let mut refreshed = 0;
+    for username in usernames {
+        match refresh(username.clone()).await {
+            Ok(()) => refreshed += 1,
+            Err(
+                e @ (GitHubApiError::RateLimitProtection(..)
+                | GitHubApiError::RateLimitExceeded
+                | GitHubApiError::SecondaryRateLimit(_)),
+            ) => {
+                tracing::warn!("Stopping the prewarm at {username}: {e}");
+                break;
+            }
+            Err(e) => tracing::warn!("Failed to prewarm {username}: {e}"),
+        }
+    }
+    refreshed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    fn config_from(vars: &[(&str, &str)]) -> Option<PrewarmConfig> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        PrewarmConfig::from_vars(|key| vars.get(key).cloned())
+    }
+
+    fn usernames(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    mod fn_from_vars {
+        use super::*;
+
+        #[test]
+        fn disabled_without_usernames() {
+            assert_eq!(config_from(&[]), None);
+            assert_eq!(config_from(&[("PREWARM_USERNAMES", " , ")]), None);
+        }
+
+        #[test]
+        fn parses_usernames_and_interval() {
+            let config = config_from(&[
+                ("PREWARM_USERNAMES", "octocat, torvalds,"),
+                ("PREWARM_INTERVAL_SECONDS", "300"),
+            ])
+            .unwrap();
+            assert_eq!(config.usernames, usernames(&["octocat", "torvalds"]));
+            assert_eq!(config.interval, Duration::from_secs(300));
+        }
+
+        #[test]
+        fn invalid_interval_uses_the_default() {
+            for interval in ["0", "soon"] {
+                let config = config_from(&[
+                    ("PREWARM_USERNAMES", "octocat"),
+                    ("PREWARM_INTERVAL_SECONDS", interval),
+                ])
+                .unwrap();
+                assert_eq!(config.interval, PrewarmConfig::DEFAULT_INTERVAL);
+            }
+        }
+    }
+
+    mod fn_prewarm_once {
+        use super::*;
+
+        /// Runs [prewarm_once] with a refresh recording the usernames it was called
+        /// with, failing with the error `fail` returns for a username.
+        async fn run(
+            names: &[&str],
+            fail: fn(&str) -> Option<GitHubApiError>,
+        ) -> (usize, Vec<String>) {
+            let calls = Arc::new(Mutex::new(Vec::new()));
+            let refreshed = prewarm_once(&usernames(names), |username| {
+                let calls = Arc::clone(&calls);
+                async move {
+                    calls.lock().unwrap().push(username.clone());
+                    fail(&username).map_or(Ok(()), Err)
+                }
+            })
+            .await;
+            let calls = calls.lock().unwrap().clone();
+            (refreshed, calls)
+        }
+
+        #[tokio::test]
+        async fn refreshes_every_username() {
+            let (refreshed, calls) = run(&["octocat", "torvalds"], |_| None).await;
+            assert_eq!(refreshed, 2);
+            assert_eq!(calls, usernames(&["octocat", "torvalds"]));
+        }
+
+        #[tokio::test]
+        async fn keeps_going_after_a_failure() {
+            let (refreshed, calls) = run(&["ghost", "octocat"], |username| {
+                (username == "ghost").then_some(GitHubApiError::UserNotFound)
+            })
+            .await;
+            assert_eq!(refreshed, 1);
+            assert_eq!(calls, usernames(&["ghost", "octocat"]));
+        }
+
+        #[tokio::test]
+        async fn stops_at_rate_limit_protection() {
+            let (refreshed, calls) = run(&["octocat", "torvalds", "ghost"], |username| {
+                (username == "torvalds").then_some(GitHubApiError::RateLimitProtection(10, 0))
+            })
+            .await;
+            assert_eq!(refreshed, 1);
+            assert_eq!(calls, usernames(&["octocat", "torvalds"]));
+        }
+    }
+}
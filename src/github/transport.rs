@@ -0,0 +1,288 @@
+//! Transport of the GraphQL requests to GitHub, replaceable so the tests can answer
+//! them with canned responses instead of calling the live API.
+
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Mutex;
+
+use reqwest::Client;
+
+use crate::github::GitHubApiError;
+
+/// GitHub GraphQL API endpoint
+pub const GRAPHQL_ENDPOINT: &str = "https://api.github.com/graphql";
+
+/// Future of a [GraphQLTransport::post].
+pub type TransportFuture<'a> =
+    Pin<Box<dyn Future<Output = Result<reqwest::Response, GitHubApiError>> + Send + 'a>>;
+
+/// Sends a GraphQL payload (`{"query": ..., "variables": ...}`) with the given token.
+///
+/// The whole response is handed back: [crate::github::GitHubApi] reads the rate limit
+/// headers and status from it as it does for the live API.
+pub trait GraphQLTransport: fmt::Debug + Send + Sync {
+    fn post<'a>(&'a self, token: &'a str, payload: &'a serde_json::Value) -> TransportFuture<'a>;
+
+    /// Downloads a file the API links to (e.g. an avatar), without the token.
+    fn get<'a>(&'a self, url: &'a str) -> TransportFuture<'a>;
+}
+
+/// Sends the requests to the live GitHub API, the default transport.
+#[derive(Debug, Clone, Default)]
+pub struct HttpTransport {
+    client: Client,
+}
+
+impl GraphQLTransport for HttpTransport {
+    fn post<'a>(&'a self, token: &'a str, payload: &'a serde_json::Value) -> TransportFuture<'a> {
+        Box::pin(async move {
+            self.client
+                .post(GRAPHQL_ENDPOINT)
+                .header("Authorization", format!("Bearer {token}"))
+                .header("User-Agent", "github-statcrab")
+                .json(payload)
+                .send()
+                .await
+                .map_err(|e| {
+                    // Report network errors to Sentry
+                    sentry::capture_error(&e);
+                    tracing::error!("GitHub API network error: {e}");
+                    GitHubApiError::NetworkError(e)
+                })
+        })
+    }
+
+    fn get<'a>(&'a self, url: &'a str) -> TransportFuture<'a> {
+        Box::pin(async move {
+            self.client
+                .get(url)
+                .header("User-Agent", "github-statcrab")
+                .send()
+                .await
+                .map_err(|e| {
+                    tracing::warn!("GitHub download network error: {e}");
+                    GitHubApiError::NetworkError(e)
+                })
+        })
+    }
+}
+
+/// Answers the requests with canned JSON bodies, queued by GraphQL operation name
+/// (e.g. `GetUserStats`) and served in order. A request with no response left gets
+/// a `404`. The payloads are recorded to check what was asked, e.g. the cursors.
+/// Downloads are answered with the files served by URL, `404` for the others.
+#[derive(Debug, Default)]
+pub struct MockTransport {
+    responses: Mutex<HashMap<String, VecDeque<CannedResponse>>>,
+    requests: Mutex<Vec<serde_json::Value>>,
+    files: Mutex<HashMap<String, Vec<u8>>>,
+    downloads: Mutex<Vec<String>>,
+}
+
+/// A response queued in a [MockTransport].
+#[derive(Debug)]
+struct CannedResponse {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: serde_json::Value,
+}
+
+impl MockTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queues a `200` response with the `body` for the next request of `operation`
+    pub fn respond(self, operation: &str, body: serde_json::Value) -> Self {
+        self.respond_with_status(operation, 200, body)
+    }
+
+    /// Queues a response with the `status` and `body` for the next request of `operation`
+    pub fn respond_with_status(
+        self,
+        operation: &str,
+        status: u16,
+        body: serde_json::Value,
+    ) -> Self {
+        self.respond_with_headers(operation, status, &[], body)
+    }
+
+    /// Queues a response with the `status`, `headers` and `body` for the next request
+    /// of `operation`, e.g. the rate limit headers
+    pub fn respond_with_headers(
+        self,
+        operation: &str,
+        status: u16,
+        headers: &[(&str, &str)],
+        body: serde_json::Value,
+    ) -> Self {
+        let headers = headers
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        self.responses
+            .lock()
+            .unwrap()
+            .entry(operation.to_string())
+            .or_default()
+            .push_back(CannedResponse {
+                status,
+                headers,
+                body,
+            });
+        self
+    }
+
+    /// Serves `body` to every download of `url`
+    pub fn serve(self, url: &str, body: Vec<u8>) -> Self {
+        self.files.lock().unwrap().insert(url.to_string(), body);
+        self
+    }
+
+    /// URLs downloaded so far, in order
+    pub fn downloads(&self) -> Vec<String> {
+        self.downloads.lock().unwrap().clone()
+    }
+
+    /// Payloads of the requests received so far, in order
+    pub fn requests(&self) -> Vec<serde_json::Value> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    /// Variables of the requests of `operation` received so far, in order
+    pub fn variables(&self, operation: &str) -> Vec<serde_json::Value> {
+        self.requests()
+            .into_iter()
+            .filter(|payload| operation_name(payload) == Some(operation))
+            .map(|payload| payload["variables"].clone())
+            .collect()
+    }
+}
+
+impl GraphQLTransport for MockTransport {
+    fn post<'a>(&'a self, _token: &'a str, payload: &'a serde_json::Value) -> TransportFuture<'a> {
+        self.requests.lock().unwrap().push(payload.clone());
+        let operation = operation_name(payload).unwrap_or_default();
+        let canned = self
+            .responses
+            .lock()
+            .unwrap()
+            .get_mut(operation)
+            .and_then(VecDeque::pop_front)
+            .unwrap_or_else(|| {
+                let message = format!("no canned response for {operation:?}");
+                CannedResponse {
+                    status: 404,
+                    headers: Vec::new(),
+                    body: serde_json::json!({ "message": message }),
+                }
+            });
+
+        let mut builder = axum::http::Response::builder()
+            .status(canned.status)
+            .header("content-type", "application/json");
+        for (name, value) in &canned.headers {
+            builder = builder.header(name, value);
+        }
+        let response = builder
+            .body(canned.body.to_string())
+            .expect("a canned response is a valid HTTP response");
+        Box::pin(async move { Ok(response.into()) })
+    }
+
+    fn get<'a>(&'a self, url: &'a str) -> TransportFuture<'a> {
+        self.downloads.lock().unwrap().push(url.to_string());
+        let (status, body) = match self.files.lock().unwrap().get(url) {
+            Some(body) => (200, body.clone()),
+            None => (404, Vec::new()),
+        };
+
+        let response = axum::http::Response::builder()
+            .status(status)
+            .body(body)
+            .expect("a served file is a valid HTTP response");
+        Box::pin(async move { Ok(response.into()) })
+    }
+}
+
+/// Name of the operation of a GraphQL payload: `GetUserStats` for `query GetUserStats(...)`.
+fn operation_name(payload: &serde_json::Value) -> Option<&str> {
+    let query = payload["query"].as_str()?.trim_start();
+    let rest = query.strip_prefix("query")?.trim_start();
+    let end = rest
+        .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .unwrap_or(rest.len());
+    (end > 0).then(|| &rest[..end])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn payload(query: &str) -> serde_json::Value {
+        json!({ "query": query, "variables": { "login": "octocat" } })
+    }
+
+    mod fn_operation_name {
+        use super::*;
+
+        #[test]
+        fn reads_the_query_name() {
+            let query = "\n  query GetUserStats($login: String!) { user(login: $login) { login } }";
+            assert_eq!(operation_name(&payload(query)), Some("GetUserStats"));
+            assert_eq!(
+                operation_name(&payload("query Q{viewer{login}}")),
+                Some("Q")
+            );
+        }
+
+        #[test]
+        fn anonymous_queries_have_no_name() {
+            assert_eq!(operation_name(&payload("{ viewer { login } }")), None);
+            assert_eq!(operation_name(&payload("query { viewer { login } }")), None);
+        }
+    }
+
+    mod mock_transport {
+        use super::*;
+
+        #[tokio::test]
+        async fn serves_the_responses_in_order() {
+            let transport = MockTransport::new()
+                .respond("First", json!({ "data": 1 }))
+                .respond("First", json!({ "data": 2 }));
+            let first = payload("query First { a }");
+
+            for expected in ["{\"data\":1}", "{\"data\":2}"] {
+                let response = transport.post("token", &first).await.unwrap();
+                assert_eq!(response.status(), 200);
+                assert_eq!(response.text().await.unwrap(), expected);
+            }
+            let response = transport.post("token", &first).await.unwrap();
+            assert_eq!(response.status(), 404);
+
+            assert_eq!(transport.requests().len(), 3);
+            assert_eq!(transport.variables("First")[0]["login"], "octocat");
+            assert!(transport.variables("Second").is_empty());
+        }
+
+        #[tokio::test]
+        async fn serves_the_files_by_url() {
+            let transport = MockTransport::new().serve("https://example.com/a.png", vec![1, 2]);
+
+            let response = transport.get("https://example.com/a.png").await.unwrap();
+            assert_eq!(response.status(), 200);
+            assert_eq!(response.bytes().await.unwrap().as_ref(), [1, 2]);
+            let response = transport.get("https://example.com/b.png").await.unwrap();
+            assert_eq!(response.status(), 404);
+
+            assert_eq!(
+                transport.downloads(),
+                ["https://example.com/a.png", "https://example.com/b.png"]
+            );
+        }
+    }
+}
@@ -1,7 +1,8 @@
+use crate::cards::streak_card::ContributionDay;
 use serde::{Deserialize, Serialize};
 
 /// GitHub user statistics
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct GitHubStats {
     pub name: Option<String>,
     pub login: String,
@@ -13,6 +14,9 @@ pub struct GitHubStats {
     pub total_issues: u32,
     pub total_discussions_started: u32,
     pub total_discussions_answered: u32,
+    /// Calendar year (UTC) the commits and reviews were counted in
+    #[serde(default)]
+    pub commits_year: Option<i32>,
 }
 
 impl GitHubStats {
@@ -35,8 +39,158 @@ impl GitHubStats {
             reviews_count: Some(self.total_reviews),
             started_discussions_count: Some(self.total_discussions_started),
             answered_discussions_count: Some(self.total_discussions_answered),
+            repositories_count: None,
+            members_count: None,
+            sort: None,
+            icon_size: StatsCard::DEFAULT_ICON_SIZE,
+            commits_year: self.commits_year,
+            commits_all_years: false,
+            label_gap: StatsCard::DEFAULT_LABEL_GAP,
+            value_gap: StatsCard::DEFAULT_VALUE_GAP,
+            counts_contributions: false,
+            max_rows: None,
+            custom_title: None,
         }
     }
+
+    /// Sums up the counts of the given contribution types, each counted once
+    pub fn contributions(&self, types: &[ContributionType]) -> u32 {
+        ContributionType::ALL
+            .into_iter()
+            .filter(|t| types.contains(t))
+            .map(|t| match t {
+                ContributionType::Commits => self.total_commits_ytd,
+                ContributionType::PullRequests => self.total_prs,
+                ContributionType::Issues => self.total_issues,
+                ContributionType::Reviews => self.total_reviews,
+            })
+            .fold(0, u32::saturating_add)
+    }
+}
+
+/// Organization statistics, summed up over its repositories
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OrgStats {
+    pub name: Option<String>,
+    pub login: String,
+    /// Repositories owned by the organization, forks excluded
+    pub total_repos: u32,
+    pub total_stars: u32,
+    /// Members visible to the token
+    pub total_members: u32,
+    pub languages: Vec<crate::cards::langs_card::LanguageStat>,
+}
+
+impl OrgStats {
+    /// Create a StatsCard from the organization statistics
+    pub fn to_stats_card(
+        &self,
+        card_settings: crate::cards::card::CardSettings,
+    ) -> crate::cards::stats_card::StatsCard {
+        crate::cards::stats_card::StatsCard {
+            card_settings,
+            username: self.login.clone(),
+            stars_count: Some(self.total_stars),
+            repositories_count: Some(self.total_repos),
+            members_count: Some(self.total_members),
+            ..Default::default()
+        }
+    }
+}
+
+/// Contribution types that can be summed up in the commits row of the stats card
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContributionType {
+    Commits,
+    PullRequests,
+    Issues,
+    Reviews,
+}
+
+impl ContributionType {
+    pub const ALL: [ContributionType; 4] = [
+        ContributionType::Commits,
+        ContributionType::PullRequests,
+        ContributionType::Issues,
+        ContributionType::Reviews,
+    ];
+
+    /// Returns the type name, as used in the `contribution_types` query parameter
+    pub fn name(&self) -> &'static str {
+        match self {
+            ContributionType::Commits => "commits",
+            ContributionType::PullRequests => "pull_requests",
+            ContributionType::Issues => "issues",
+            ContributionType::Reviews => "reviews",
+        }
+    }
+}
+
+/// User statistics and languages fetched in a single pass over the repositories
+#[derive(Debug, Clone, Default)]
+pub struct UserProfile {
+    pub stats: GitHubStats,
+    pub languages: Vec<crate::cards::langs_card::LanguageStat>,
+}
+
+/// Options that change which statistics are fetched for a user
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct StatsOptions {
+    /// Count private commits, which GitHub includes in the commits of the token owner.
+    /// Only allowed for the token owner's own username.
+    pub include_private: bool,
+    /// Calendar year (UTC) the commits and reviews are counted in, the current one when `None`.
+    pub year: Option<i32>,
+    /// Most repositories (the most starred first) counted in the stars total, all of them when `None`.
+    pub max_repos: Option<u32>,
+    /// Relations to the user of the repositories counted, the owned ones by default.
+    pub affiliations: RepositoryAffiliations,
+}
+
+impl StatsOptions {
+    /// The year the commits and reviews are counted in, the current one is told by the `clock`
+    pub fn commits_year(&self, clock: &dyn crate::github::Clock) -> i32 {
+        self.year
+            .unwrap_or_else(|| crate::github::api::current_utc_year_with(clock))
+    }
+}
+
+/// Relations of a user to the repositories fetched for it, the GraphQL
+/// `ownerAffiliations` list. At least one is set, only [RepositoryAffiliations::OWNER]
+/// by default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RepositoryAffiliations {
+    pub owner: bool,
+    pub collaborator: bool,
+    pub organization_member: bool,
+}
+
+impl RepositoryAffiliations {
+    /// The repositories owned by the user
+    pub const OWNER: RepositoryAffiliations = RepositoryAffiliations {
+        owner: true,
+        collaborator: false,
+        organization_member: false,
+    };
+
+    /// The `RepositoryAffiliation` enum values, as a GraphQL list: e.g. `[OWNER, COLLABORATOR]`
+    pub fn graphql_list(&self) -> String {
+        let values: Vec<&str> = [
+            (self.owner, "OWNER"),
+            (self.collaborator, "COLLABORATOR"),
+            (self.organization_member, "ORGANIZATION_MEMBER"),
+        ]
+        .into_iter()
+        .filter_map(|(set, value)| set.then_some(value))
+        .collect();
+        format!("[{}]", values.join(", "))
+    }
+}
+
+impl Default for RepositoryAffiliations {
+    fn default() -> Self {
+        Self::OWNER
+    }
 }
 
 /// GitHub API error types
@@ -44,6 +198,10 @@ impl GitHubStats {
 pub enum GitHubApiError {
     #[error("User not found")]
     UserNotFound,
+    #[error("{0} is an organization, not a user")]
+    IsOrganization(String),
+    #[error("Organization not found")]
+    OrgNotFound,
     #[error("Invalid username: {0}")]
     InvalidUsername(String),
     #[error("Rate limit exceeded")]
@@ -52,12 +210,22 @@ pub enum GitHubApiError {
         "Rate limit protection: remaining requests ({0}) below threshold, waiting until reset at {1}"
     )]
     RateLimitProtection(u64, u64),
+    #[error("Secondary rate limit exceeded, retry after {0} seconds")]
+    SecondaryRateLimit(u64),
     #[error("Network error: {0}")]
     NetworkError(#[from] reqwest::Error),
     #[error("GraphQL error: {0}")]
     GraphQLError(String),
     #[error("Missing GitHub token")]
     MissingToken,
+    #[error("GitHub API server error: HTTP {0}")]
+    ServerError(u16),
+    #[error("Unexpected GitHub API response: HTTP {status}: {snippet}")]
+    UnexpectedResponse { status: u16, snippet: String },
+    #[error("GitHub request budget exceeded")]
+    RequestBudgetExceeded,
+    #[error("Private contributions are only available for the token owner, not {0}")]
+    PrivateContributionsForbidden(String),
 }
 
 /// GraphQL response wrapper
@@ -78,6 +246,136 @@ pub struct GraphQLError {
 #[derive(Debug, Deserialize)]
 pub struct UserQueryResponse {
     pub user: Option<UserData>,
+    /// The authenticated user, only requested when private contributions are included
+    #[serde(default)]
+    pub viewer: Option<ViewerData>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ViewerData {
+    pub login: String,
+}
+
+/// Response of the following pages of the user repositories
+#[derive(Debug, Deserialize)]
+pub struct UserRepositoriesQueryResponse {
+    pub user: Option<UserRepositoriesData>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UserRepositoriesData {
+    pub repositories: RepositoriesConnection,
+}
+
+/// Response of the organization lookup made when a user is not found
+#[derive(Debug, Deserialize)]
+pub struct OrganizationQueryResponse {
+    pub organization: Option<OrganizationData>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OrganizationData {
+    pub login: String,
+}
+
+/// Response of the avatar query of the accent color
+#[derive(Debug, Deserialize)]
+pub struct UserAvatarQueryResponse {
+    pub user: Option<UserAvatarData>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UserAvatarData {
+    #[serde(rename = "avatarUrl")]
+    pub avatar_url: String,
+}
+
+/// Response of the account creation date query of the lifetime commits
+#[derive(Debug, Deserialize)]
+pub struct UserCreatedAtQueryResponse {
+    pub user: Option<UserCreatedAtData>,
+    /// The authenticated user, only requested when private contributions are included
+    #[serde(default)]
+    pub viewer: Option<ViewerData>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UserCreatedAtData {
+    #[serde(rename = "createdAt")]
+    pub created_at: String,
+}
+
+/// Response of the commits of a user within a calendar year
+#[derive(Debug, Deserialize)]
+pub struct YearCommitsQueryResponse {
+    pub user: Option<YearCommitsUser>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct YearCommitsUser {
+    pub contributions_collection: YearCommits,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct YearCommits {
+    pub total_commit_contributions: u32,
+}
+
+/// Response of the contribution calendar query of the streak card
+#[derive(Debug, Deserialize)]
+pub struct ContributionCalendarQueryResponse {
+    pub user: Option<ContributionCalendarUser>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContributionCalendarUser {
+    pub contributions_collection: ContributionCalendarCollection,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContributionCalendarCollection {
+    pub contribution_calendar: ContributionCalendar,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ContributionCalendar {
+    pub weeks: Vec<ContributionWeek>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContributionWeek {
+    pub contribution_days: Vec<ContributionDay>,
+}
+
+/// Response of the organization statistics query
+#[derive(Debug, Deserialize)]
+pub struct OrgQueryResponse {
+    pub organization: Option<OrgData>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OrgData {
+    pub name: Option<String>,
+    pub login: String,
+    #[serde(rename = "membersWithRole")]
+    pub members_with_role: CountableConnection,
+    pub repositories: RepositoriesConnection,
+}
+
+/// Response of the following pages of the organization repositories
+#[derive(Debug, Deserialize)]
+pub struct OrgRepositoriesQueryResponse {
+    pub organization: Option<OrgRepositoriesData>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OrgRepositoriesData {
+    pub repositories: RepositoriesConnection,
 }
 
 #[derive(Debug, Deserialize)]
@@ -89,8 +387,12 @@ pub struct LanguagesQueryResponse {
 pub struct UserData {
     pub name: Option<String>,
     pub login: String,
-    #[serde(rename = "contributionsCollection")]
-    pub contributions_collection: ContributionsCollection,
+    /// Account creation timestamp (ISO 8601)
+    #[serde(rename = "createdAt", default)]
+    pub created_at: Option<String>,
+    /// Null (or missing) for some edge accounts, counted as no contributions.
+    #[serde(rename = "contributionsCollection", default)]
+    pub contributions_collection: Option<ContributionsCollection>,
     #[serde(rename = "pullRequests")]
     pub pull_requests: CountableConnection,
     #[serde(rename = "mergedPullRequests")]
@@ -106,7 +408,7 @@ pub struct UserData {
     pub repositories: RepositoriesConnection,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Default, Deserialize)]
 pub struct ContributionsCollection {
     #[serde(rename = "totalCommitContributions")]
     pub total_commit_contributions: u32,
@@ -122,6 +424,9 @@ pub struct CountableConnection {
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct RepositoriesConnection {
+    /// Only requested by the organization query
+    #[serde(rename = "totalCount", default)]
+    pub total_count: u32,
     pub nodes: Vec<RepositoryNode>,
     #[serde(rename = "pageInfo")]
     pub page_info: PageInfo,
@@ -129,7 +434,15 @@ pub struct RepositoriesConnection {
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct RepositoryNode {
+    #[serde(default)]
+    pub name: String,
+    /// Only requested by the profile query
+    #[serde(rename = "isFork", default)]
+    pub is_fork: bool,
     pub stargazers: CountableConnection,
+    /// Only requested by the profile query
+    #[serde(default)]
+    pub languages: Option<LanguagesConnection>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -159,18 +472,94 @@ pub struct LanguageRepositoryNode {
     pub languages: LanguagesConnection,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct LanguagesConnection {
     pub edges: Vec<LanguageEdge>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct LanguageEdge {
     pub size: usize,
     pub node: LanguageNode,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct LanguageNode {
     pub name: String,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod fn_contributions {
+        use super::*;
+
+        fn stats() -> GitHubStats {
+            GitHubStats {
+                total_commits_ytd: 100,
+                total_prs: 20,
+                total_issues: 5,
+                total_reviews: 7,
+                total_merged_prs: 15,
+                ..Default::default()
+            }
+        }
+
+        #[test]
+        fn commits_only() {
+            assert_eq!(stats().contributions(&[ContributionType::Commits]), 100);
+        }
+
+        #[test]
+        fn all_types() {
+            assert_eq!(stats().contributions(&ContributionType::ALL), 132);
+        }
+
+        #[test]
+        fn combinations() {
+            use ContributionType::*;
+
+            assert_eq!(stats().contributions(&[Commits, PullRequests]), 120);
+            assert_eq!(stats().contributions(&[Issues, Reviews]), 12);
+            assert_eq!(stats().contributions(&[Reviews]), 7);
+            // Repeated types are counted once
+            assert_eq!(stats().contributions(&[Issues, Issues]), 5);
+            assert_eq!(stats().contributions(&[]), 0);
+        }
+
+        #[test]
+        fn saturates_instead_of_overflowing() {
+            let stats = GitHubStats {
+                total_commits_ytd: u32::MAX,
+                total_prs: 1,
+                ..Default::default()
+            };
+            assert_eq!(stats.contributions(&ContributionType::ALL), u32::MAX);
+        }
+    }
+
+    mod fn_graphql_list {
+        use super::*;
+
+        #[test]
+        fn lists_the_set_affiliations_in_order() {
+            assert_eq!(RepositoryAffiliations::default().graphql_list(), "[OWNER]");
+            let all = RepositoryAffiliations {
+                owner: true,
+                collaborator: true,
+                organization_member: true,
+            };
+            assert_eq!(
+                all.graphql_list(),
+                "[OWNER, COLLABORATOR, ORGANIZATION_MEMBER]"
+            );
+            let member = RepositoryAffiliations {
+                owner: false,
+                collaborator: false,
+                organization_member: true,
+            };
+            assert_eq!(member.graphql_list(), "[ORGANIZATION_MEMBER]");
+        }
+    }
+}
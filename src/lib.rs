@@ -1,3 +1,5 @@
 pub mod cards;
 pub mod github;
+pub mod logging;
+pub mod render;
 pub mod web;
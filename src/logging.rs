@@ -0,0 +1,92 @@
+use std::env;
+
+use tracing::Subscriber;
+use tracing_subscriber::{EnvFilter, Layer, registry::LookupSpan};
+
+/// Levels logged when `RUST_LOG` is unset or invalid
+const DEFAULT_LOG_FILTER: &str = "info";
+
+/// Format of the log lines written to stdout
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LogFormat {
+    /// Human-readable lines
+    #[default]
+    Pretty,
+    /// One JSON object per line, for log aggregation
+    Json,
+}
+
+impl LogFormat {
+    /// Load the log format from the `LOG_FORMAT` environment variable
+    pub fn from_env() -> Self {
+        Self::from_value(env::var("LOG_FORMAT").ok().as_deref())
+    }
+
+    /// Parses a `LOG_FORMAT` value, unset or unknown values fall back to [LogFormat::Pretty]
+    pub fn from_value(value: Option<&str>) -> Self {
+        match value.map(str::trim) {
+            Some(v) if v.eq_ignore_ascii_case("json") => Self::Json,
+            _ => Self::Pretty,
+        }
+    }
+}
+
+/// Builds the level filter from `RUST_LOG`-style directives, defaulting to `info`.
+pub fn env_filter(directives: Option<&str>) -> EnvFilter {
+    match directives.map(EnvFilter::try_new) {
+        Some(Ok(filter)) => filter,
+        Some(Err(e)) => {
+            // The subscriber is not set up yet, so this can't go through tracing
+            eprintln!("Invalid RUST_LOG, falling back to {DEFAULT_LOG_FILTER}: {e}");
+            EnvFilter::new(DEFAULT_LOG_FILTER)
+        }
+        None => EnvFilter::new(DEFAULT_LOG_FILTER),
+    }
+}
+
+/// Builds the stdout logging layer in the given format, filtered by `filter`.
+pub fn fmt_layer<S>(format: LogFormat, filter: EnvFilter) -> Box<dyn Layer<S> + Send + Sync>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    match format {
+        LogFormat::Pretty => tracing_subscriber::fmt::layer().with_filter(filter).boxed(),
+        LogFormat::Json => tracing_subscriber::fmt::layer()
+            .json()
+            .with_filter(filter)
+            .boxed(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing_subscriber::prelude::*;
+
+    #[test]
+    fn test_log_format_from_value() {
+        assert_eq!(LogFormat::from_value(None), LogFormat::Pretty);
+        assert_eq!(LogFormat::from_value(Some("pretty")), LogFormat::Pretty);
+        assert_eq!(LogFormat::from_value(Some("json")), LogFormat::Json);
+        assert_eq!(LogFormat::from_value(Some(" JSON ")), LogFormat::Json);
+        assert_eq!(LogFormat::from_value(Some("xml")), LogFormat::Pretty);
+    }
+
+    #[test]
+    fn test_env_filter_falls_back_to_default() {
+        assert_eq!(env_filter(None).to_string(), DEFAULT_LOG_FILTER);
+        assert_eq!(env_filter(Some("=[")).to_string(), DEFAULT_LOG_FILTER);
+        assert_eq!(env_filter(Some("debug")).to_string(), "debug");
+    }
+
+    #[test]
+    fn test_fmt_layer_builds_for_both_formats() {
+        for format in [LogFormat::Pretty, LogFormat::Json] {
+            let subscriber =
+                tracing_subscriber::registry().with(fmt_layer(format, env_filter(Some("info"))));
+            tracing::subscriber::with_default(subscriber, || {
+                tracing::info!(?format, "logging works");
+            });
+        }
+    }
+}
@@ -1,9 +1,6 @@
-mod cards;
-mod github;
-mod web;
-
 use anyhow::Result;
 use axum::{body::Body, http::Request};
+use github_statcrab::{github::prewarm, logging, web};
 use sentry::integrations::tower::{NewSentryLayer, SentryHttpLayer};
 use sentry::integrations::tracing::EventFilter;
 use std::net::SocketAddr;
@@ -48,14 +45,24 @@ fn main() -> Result<()> {
             // Capture everything else just as a log
             _ => EventFilter::Log,
         });
+    // Levels from RUST_LOG, format (pretty or json) from LOG_FORMAT
+    let log_filter = logging::env_filter(std::env::var("RUST_LOG").ok().as_deref());
     tracing_subscriber::registry()
-        .with(tracing_subscriber::fmt::layer())
+        .with(logging::fmt_layer(
+            logging::LogFormat::from_env(),
+            log_filter,
+        ))
         .with(sentry_layer)
         .init();
 
+    // Validate the web configuration before serving any requests
+    let web_config = web::config::init_web_config()?;
+
     // Build our application with some routes
     let app = web::app_router().layer(
         ServiceBuilder::new()
+            // Keep the per-request GitHub token out of the requests reported to Sentry
+            .layer(axum::middleware::from_fn(web::take_token_header))
             // Add Sentry tower layer for performance tracing
             .layer(sentry::integrations::tower::SentryLayer::new_from_top())
             // Bind a new Hub per request for error correlation
@@ -92,16 +99,19 @@ fn main() -> Result<()> {
 
             tracing::info!("Listening on http://{}", listener.local_addr().unwrap());
 
-            // Start the server with graceful shutdown
-            if let Err(e) = axum::serve(listener, app.into_make_service())
-                .with_graceful_shutdown(shutdown_signal())
-                .await
-            {
-                tracing::error!("Server error: {}", e);
-                sentry::capture_error(&e);
-                std::process::exit(1);
+            // Keep the cache of the configured profiles warm, if any
+            if let Some(config) = prewarm::PrewarmConfig::from_env() {
+                tracing::info!(
+                    "Prewarming {} usernames every {}s",
+                    config.usernames.len(),
+                    config.interval.as_secs()
+                );
+                prewarm::spawn(config);
             }
 
+            // Start the server with graceful shutdown
+            web::serve(listener, app, web_config, shutdown_signal()).await;
+
             tracing::info!("Server shutdown complete");
         });
 
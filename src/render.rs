@@ -0,0 +1,268 @@
+//! Rendering of the cards from already fetched statistics, for using the crate as a
+//! library without the HTTP server or the GitHub client.
+
+use std::{collections::HashSet, str::FromStr};
+
+use crate::cards::card::{CardError, CardSettings, Svg};
+use crate::cards::langs_card::{LangsCard, LanguageStat};
+use crate::cards::stats_card::StatsCard;
+use crate::github::GitHubStats;
+
+/// Fewest stats a stats card can be left with after hiding some.
+pub const MIN_VISIBLE_STATS: usize = 2;
+
+/// Reasons a card can't be rendered.
+#[derive(thiserror::Error, Debug)]
+pub enum RenderError {
+    #[error("hide would remove too many stats; at least {MIN_VISIBLE_STATS} must remain")]
+    TooFewStats,
+    #[error(transparent)]
+    Card(#[from] CardError),
+}
+
+/// A stat of the stats card that can be hidden.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HideStat {
+    StarsCount,
+    CommitsYtdCount,
+    IssuesCount,
+    PullRequestsCount,
+    MergeRequestsCount,
+    ReviewsCount,
+    StartedDiscussionsCount,
+    AnsweredDiscussionsCount,
+}
+
+impl HideStat {
+    /// Every stat, in the order the hide list is applied in.
+    pub const ALL: [HideStat; 8] = [
+        HideStat::StarsCount,
+        HideStat::CommitsYtdCount,
+        HideStat::IssuesCount,
+        HideStat::PullRequestsCount,
+        HideStat::MergeRequestsCount,
+        HideStat::ReviewsCount,
+        HideStat::StartedDiscussionsCount,
+        HideStat::AnsweredDiscussionsCount,
+    ];
+}
+
+impl FromStr for HideStat {
+    type Err = ();
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "stars_count" => Ok(HideStat::StarsCount),
+            "commits_ytd_count" => Ok(HideStat::CommitsYtdCount),
+            "issues_count" => Ok(HideStat::IssuesCount),
+            "pull_requests_count" => Ok(HideStat::PullRequestsCount),
+            "merge_requests_count" => Ok(HideStat::MergeRequestsCount),
+            "reviews_count" => Ok(HideStat::ReviewsCount),
+            "started_discussions_count" => Ok(HideStat::StartedDiscussionsCount),
+            "answered_discussions_count" => Ok(HideStat::AnsweredDiscussionsCount),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Parses a comma-separated list of stat names (e.g. `stars_count,issues_count`),
+/// returning the first invalid name on error.
+pub fn parse_hide_list(hide: &str) -> Result<HashSet<HideStat>, &str> {
+    hide.split(',')
+        .map(str::trim)
+        .filter(|token| !token.is_empty())
+        .map(|token| HideStat::from_str(token).map_err(|_| token))
+        .collect()
+}
+
+/// Hides the given stats of the card, going through [HideStat::ALL] in its fixed
+/// order rather than the order of `hide`.
+pub fn apply_hide(stats_card: &mut StatsCard, hide: &[HideStat]) {
+    for h in HideStat::ALL.into_iter().filter(|h| hide.contains(h)) {
+        match h {
+            HideStat::StarsCount => stats_card.stars_count = None,
+            HideStat::CommitsYtdCount => stats_card.commits_ytd_count = None,
+            HideStat::IssuesCount => stats_card.issues_count = None,
+            HideStat::PullRequestsCount => stats_card.pull_requests_count = None,
+            HideStat::MergeRequestsCount => stats_card.merge_requests_count = None,
+            HideStat::ReviewsCount => stats_card.reviews_count = None,
+            HideStat::StartedDiscussionsCount => stats_card.started_discussions_count = None,
+            HideStat::AnsweredDiscussionsCount => stats_card.answered_discussions_count = None,
+        }
+    }
+}
+
+/// Number of the user stats the card shows.
+fn visible_stats(stats_card: &StatsCard) -> usize {
+    [
+        stats_card.stars_count,
+        stats_card.commits_ytd_count,
+        stats_card.issues_count,
+        stats_card.pull_requests_count,
+        stats_card.merge_requests_count,
+        stats_card.reviews_count,
+        stats_card.started_discussions_count,
+        stats_card.answered_discussions_count,
+    ]
+    .iter()
+    .filter(|v| v.is_some())
+    .count()
+}
+
+/// Renders the stats card of `username`, without the `hide` stats.
+pub fn render_stats_card(
+    stats: &GitHubStats,
+    username: String,
+    settings: CardSettings,
+    hide: &[HideStat],
+) -> Result<Svg, RenderError> {
+    render_stats_card_with(stats.to_stats_card(username, settings), hide)
+}
+
+/// Renders a stats card built beforehand (e.g. with its rows sorted), without the
+/// `hide` stats. At least [MIN_VISIBLE_STATS] of them must remain.
+pub fn render_stats_card_with(
+    mut stats_card: StatsCard,
+    hide: &[HideStat],
+) -> Result<Svg, RenderError> {
+    apply_hide(&mut stats_card, hide);
+    if visible_stats(&stats_card) < MIN_VISIBLE_STATS {
+        return Err(RenderError::TooFewStats);
+    }
+    Ok(stats_card.render()?)
+}
+
+/// Renders the languages card of `stats`, laid out and ranked as set by `options`
+/// (whose own stats are ignored).
+pub fn render_langs_card(stats: Vec<LanguageStat>, options: LangsCard) -> Result<Svg, CardError> {
+    LangsCard { stats, ..options }.render()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn card() -> StatsCard {
+        StatsCard {
+            username: "octocat".to_string(),
+            stars_count: Some(1),
+            commits_ytd_count: Some(2),
+            issues_count: Some(3),
+            pull_requests_count: Some(4),
+            merge_requests_count: Some(5),
+            reviews_count: Some(6),
+            started_discussions_count: Some(7),
+            answered_discussions_count: Some(8),
+            ..Default::default()
+        }
+    }
+
+    fn hide_list(hide: &str) -> Vec<HideStat> {
+        parse_hide_list(hide).unwrap().into_iter().collect()
+    }
+
+    mod fn_apply_hide {
+        use super::*;
+
+        fn hidden(hide: &str) -> StatsCard {
+            let mut card = card();
+            apply_hide(&mut card, &hide_list(hide));
+            card
+        }
+
+        #[test]
+        fn scrambled_lists_give_identical_results() {
+            let expected = hidden("stars_count,issues_count,reviews_count")
+                .render()
+                .unwrap();
+            for hide in [
+                "reviews_count,stars_count,issues_count",
+                " issues_count , reviews_count,,stars_count",
+                "issues_count,stars_count,reviews_count,stars_count",
+            ] {
+                for _ in 0..10 {
+                    assert_eq!(hidden(hide).render().unwrap(), expected, "{hide}");
+                }
+            }
+
+            let card = hidden("reviews_count,stars_count,issues_count");
+            assert_eq!(card.stars_count, None);
+            assert_eq!(card.issues_count, None);
+            assert_eq!(card.reviews_count, None);
+            assert_eq!(card.commits_ytd_count, Some(2));
+        }
+    }
+
+    mod fn_parse_hide_list {
+        use super::*;
+
+        #[test]
+        fn invalid_token_is_reported() {
+            assert_eq!(
+                parse_hide_list("stars_count, bogus ,issues_count"),
+                Err("bogus")
+            );
+            assert_eq!(parse_hide_list(" , "), Ok(HashSet::new()));
+        }
+    }
+
+    mod fn_render_stats_card {
+        use super::*;
+
+        fn stats() -> GitHubStats {
+            GitHubStats {
+                login: "octocat".to_string(),
+                total_stars: 1234,
+                total_commits_ytd: 56,
+                ..Default::default()
+            }
+        }
+
+        #[test]
+        fn renders_without_the_hidden_stats() {
+            let hide = hide_list("issues_count,reviews_count");
+            let svg = render_stats_card(&stats(), "octocat".to_string(), Default::default(), &hide)
+                .unwrap();
+            assert!(svg.contains(">1.2k</text>"));
+            assert!(!svg.contains("Issues"));
+            assert!(!svg.contains("Reviews"));
+        }
+
+        #[test]
+        fn keeps_at_least_two_stats() {
+            let all_but = |kept: HideStat| -> Vec<HideStat> {
+                HideStat::ALL.into_iter().filter(|h| *h != kept).collect()
+            };
+            assert!(matches!(
+                render_stats_card_with(card(), &all_but(HideStat::StarsCount)),
+                Err(RenderError::TooFewStats)
+            ));
+
+            let hide = &all_but(HideStat::StarsCount)[1..];
+            assert!(render_stats_card_with(card(), hide).is_ok());
+        }
+    }
+
+    mod fn_render_langs_card {
+        use super::*;
+
+        #[test]
+        fn renders_the_given_stats() {
+            let stats = vec![LanguageStat {
+                name: "Rust".to_string(),
+                size_bytes: 1000,
+                repo_count: 1,
+            }];
+            let options = LangsCard {
+                stats: vec![LanguageStat {
+                    name: "Zig".to_string(),
+                    size_bytes: 1000,
+                    repo_count: 1,
+                }],
+                ..Default::default()
+            };
+            let svg = render_langs_card(stats, options).unwrap();
+            assert!(svg.contains("Rust"));
+            assert!(!svg.contains("Zig"));
+        }
+    }
+}
@@ -0,0 +1,381 @@
+use std::{collections::HashSet, env, str::FromStr, sync::OnceLock, time::Duration};
+
+use crate::cards::langs_card::LayoutType;
+use crate::github::cache::CacheConfig;
+use crate::render::{HideStat, parse_hide_list};
+
+/// Web configuration error types
+#[derive(thiserror::Error, Debug)]
+pub enum WebConfigError {
+    #[error("Invalid value for {name}: {value:?} ({reason})")]
+    InvalidValue {
+        name: &'static str,
+        value: String,
+        reason: &'static str,
+    },
+}
+
+/// Web layer configuration settings
+#[derive(Debug, Clone)]
+pub struct WebConfig {
+    /// `max-age` in seconds advertised for rendered cards, the user stats cache TTL by default
+    pub card_cache_max_age: u64,
+    /// Full `Cache-Control` value for rendered cards, overrides the derived one
+    pub card_cache_control: Option<String>,
+    /// Instance-wide defaults for the languages card
+    pub langs_defaults: LangsDefaults,
+    /// Stats shown when a request has no `hide` list. `None` shows all of them
+    pub default_stats: Option<HashSet<HideStat>>,
+    /// Maximum number of requests handled at once, the others wait for a free slot.
+    /// `None` disables the limit
+    pub max_concurrent_requests: Option<usize>,
+    /// Maximum accepted request body size in bytes
+    pub request_body_limit: usize,
+    /// Keep the HTTP/1.1 connections open between requests
+    pub keep_alive: bool,
+    /// Time a connection has to send the headers of a request, idle keep-alive
+    /// connections included
+    pub header_read_timeout: Duration,
+    /// Use the GitHub token of the `X-GitHub-Token` request header instead of the server's one
+    pub allow_token_header: bool,
+    /// Identical concurrent card requests wait for a single render
+    pub coalesce_requests: bool,
+}
+
+/// Defaults for the languages card, applied when a request omits the option.
+/// `None` keeps the card's built-in default.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LangsDefaults {
+    pub layout: Option<LayoutType>,
+    pub size_weight: Option<f64>,
+    pub count_weight: Option<f64>,
+    pub max_languages: Option<u64>,
+}
+
+impl Default for WebConfig {
+    fn default() -> Self {
+        Self {
+            card_cache_max_age: CacheConfig::default().user_stats_ttl.as_secs(),
+            card_cache_control: None,
+            langs_defaults: LangsDefaults::default(),
+            default_stats: None,
+            max_concurrent_requests: None,
+            request_body_limit: 64 * 1024, // 64 KiB
+            keep_alive: true,
+            header_read_timeout: Duration::from_secs(30),
+            allow_token_header: false,
+            coalesce_requests: false,
+        }
+    }
+}
+
+impl WebConfig {
+    /// Load web configuration from environment variables
+    pub fn from_env() -> Result<Self, WebConfigError> {
+        Self::from_vars(|key| env::var(key).ok())
+    }
+
+    /// Load web configuration using the given variable lookup
+    pub fn from_vars<F>(var: F) -> Result<Self, WebConfigError>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        let defaults = Self::default();
+
+        // The cards are as fresh as the cached stats they show
+        let card_cache_max_age = var("CARD_CACHE_MAX_AGE_SECONDS")
+            .and_then(|v| v.parse().ok())
+            .or_else(|| var("CACHE_USER_STATS_TTL_SECONDS").and_then(|v| v.parse().ok()))
+            .unwrap_or(defaults.card_cache_max_age);
+
+        let card_cache_control = var("CARD_CACHE_CONTROL")
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty());
+
+        let langs_defaults = LangsDefaults {
+            layout: parse_var(&var, "DEFAULT_LANGS_LAYOUT", |v| match v {
+                "vertical" => Ok(LayoutType::Vertical),
+                "horizontal" => Ok(LayoutType::Horizontal),
+                "donut" => Ok(LayoutType::Donut),
+                _ => Err("expected vertical, horizontal or donut"),
+            })?,
+            size_weight: parse_var(&var, "DEFAULT_SIZE_WEIGHT", parse_weight)?,
+            count_weight: parse_var(&var, "DEFAULT_COUNT_WEIGHT", parse_weight)?,
+            max_languages: parse_var(&var, "DEFAULT_MAX_LANGUAGES", |v| match u64::from_str(v) {
+                Ok(n) if n > 0 => Ok(n),
+                _ => Err("expected a positive integer"),
+            })?,
+        };
+
+        let default_stats = parse_var(&var, "DEFAULT_STATS", |v| match parse_hide_list(v) {
+            Ok(stats) if stats.len() >= 2 => Ok(stats),
+            Ok(_) => Err("expected at least 2 stats"),
+            Err(_) => Err("expected a comma-separated list of stat names"),
+        })?;
+
+        let max_concurrent_requests =
+            parse_var(&var, "MAX_CONCURRENT_REQUESTS", parse_positive_integer)?;
+        let request_body_limit =
+            parse_var(&var, "REQUEST_BODY_LIMIT_BYTES", parse_positive_integer)?
+                .unwrap_or(defaults.request_body_limit);
+        let keep_alive =
+            parse_var(&var, "HTTP_KEEP_ALIVE", parse_bool)?.unwrap_or(defaults.keep_alive);
+        let header_read_timeout =
+            parse_var(&var, "HEADER_READ_TIMEOUT_SECONDS", parse_positive_integer)?
+                .map_or(defaults.header_read_timeout, |secs| {
+                    Duration::from_secs(secs as u64)
+                });
+        let allow_token_header = parse_var(&var, "ALLOW_TOKEN_HEADER", parse_bool)?
+            .unwrap_or(defaults.allow_token_header);
+        let coalesce_requests =
+            parse_var(&var, "COALESCE_REQUESTS", parse_bool)?.unwrap_or(defaults.coalesce_requests);
+
+        Ok(Self {
+            card_cache_max_age,
+            card_cache_control,
+            langs_defaults,
+            default_stats,
+            max_concurrent_requests,
+            request_body_limit,
+            keep_alive,
+            header_read_timeout,
+            allow_token_header,
+            coalesce_requests,
+        })
+    }
+
+    /// `Cache-Control` header value for rendered cards.
+    ///
+    /// `no-transform` keeps proxies (e.g. GitHub's camo) from re-encoding the SVG.
+    pub fn card_cache_control(&self) -> String {
+        self.card_cache_control
+            .clone()
+            .unwrap_or_else(|| format!("public, max-age={}, no-transform", self.card_cache_max_age))
+    }
+}
+
+/// Parses an optional variable, ignoring it when unset or blank
+fn parse_var<F, T, P>(var: &F, name: &'static str, parse: P) -> Result<Option<T>, WebConfigError>
+where
+    F: Fn(&str) -> Option<String>,
+    P: Fn(&str) -> Result<T, &'static str>,
+{
+    let Some(value) = var(name) else {
+        return Ok(None);
+    };
+    let trimmed = value.trim();
+    if trimmed.is_empty() {
+        return Ok(None);
+    }
+
+    parse(trimmed)
+        .map(Some)
+        .map_err(|reason| WebConfigError::InvalidValue {
+            name,
+            value,
+            reason,
+        })
+}
+
+fn parse_weight(value: &str) -> Result<f64, &'static str> {
+    match f64::from_str(value) {
+        Ok(weight) if weight.is_finite() && weight >= 0.0 => Ok(weight),
+        _ => Err("expected a non-negative number"),
+    }
+}
+
+fn parse_positive_integer(value: &str) -> Result<usize, &'static str> {
+    match usize::from_str(value) {
+        Ok(n) if n > 0 => Ok(n),
+        _ => Err("expected a positive integer"),
+    }
+}
+
+fn parse_bool(value: &str) -> Result<bool, &'static str> {
+    match value.to_ascii_lowercase().as_str() {
+        "true" | "1" => Ok(true),
+        "false" | "0" => Ok(false),
+        _ => Err("expected true or false"),
+    }
+}
+
+// Global web configuration instance
+static WEB_CONFIG: OnceLock<WebConfig> = OnceLock::new();
+
+/// Load and validate the global web configuration, meant to be called once at startup
+pub fn init_web_config() -> Result<&'static WebConfig, WebConfigError> {
+    let config = WebConfig::from_env()?;
+    Ok(WEB_CONFIG.get_or_init(|| config))
+}
+
+/// Get or initialize the global web configuration.
+///
+/// Falls back to the defaults when the environment is invalid and
+/// [init_web_config] was not called.
+pub fn get_web_config() -> &'static WebConfig {
+    WEB_CONFIG.get_or_init(|| {
+        WebConfig::from_env().unwrap_or_else(|e| {
+            tracing::error!("{e}, falling back to the default web configuration");
+            WebConfig::default()
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn try_config_from(vars: &[(&str, &str)]) -> Result<WebConfig, WebConfigError> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        WebConfig::from_vars(|key| vars.get(key).cloned())
+    }
+
+    fn config_from(vars: &[(&str, &str)]) -> WebConfig {
+        try_config_from(vars).expect("valid config")
+    }
+
+    #[test]
+    fn test_default_card_cache_control() {
+        let config = WebConfig::default();
+        assert_eq!(
+            config.card_cache_control(),
+            "public, max-age=900, no-transform"
+        );
+    }
+
+    #[test]
+    fn test_card_cache_control_from_vars() {
+        let config = config_from(&[("CARD_CACHE_MAX_AGE_SECONDS", "60")]);
+        assert_eq!(
+            config.card_cache_control(),
+            "public, max-age=60, no-transform"
+        );
+
+        let config = config_from(&[
+            ("CARD_CACHE_MAX_AGE_SECONDS", "60"),
+            ("CARD_CACHE_CONTROL", "private, no-store"),
+        ]);
+        assert_eq!(config.card_cache_control(), "private, no-store");
+
+        let config = config_from(&[("CARD_CACHE_MAX_AGE_SECONDS", "not-a-number")]);
+        assert_eq!(config.card_cache_max_age, 900);
+    }
+
+    #[test]
+    fn test_card_cache_max_age_follows_the_stats_ttl() {
+        let config = config_from(&[("CACHE_USER_STATS_TTL_SECONDS", "1800")]);
+        assert_eq!(config.card_cache_max_age, 1800);
+
+        let config = config_from(&[
+            ("CACHE_USER_STATS_TTL_SECONDS", "1800"),
+            ("CARD_CACHE_MAX_AGE_SECONDS", "60"),
+        ]);
+        assert_eq!(config.card_cache_max_age, 60);
+    }
+
+    #[test]
+    fn test_langs_defaults_from_vars() {
+        assert_eq!(config_from(&[]).langs_defaults, LangsDefaults::default());
+
+        let config = config_from(&[
+            ("DEFAULT_LANGS_LAYOUT", "horizontal"),
+            ("DEFAULT_SIZE_WEIGHT", "0.5"),
+            ("DEFAULT_COUNT_WEIGHT", "1"),
+            ("DEFAULT_MAX_LANGUAGES", " 6 "),
+        ]);
+        assert_eq!(
+            config.langs_defaults,
+            LangsDefaults {
+                layout: Some(LayoutType::Horizontal),
+                size_weight: Some(0.5),
+                count_weight: Some(1.0),
+                max_languages: Some(6),
+            }
+        );
+
+        let config = config_from(&[("DEFAULT_LANGS_LAYOUT", "donut")]);
+        assert_eq!(config.langs_defaults.layout, Some(LayoutType::Donut));
+    }
+
+    #[test]
+    fn test_default_stats_from_vars() {
+        assert_eq!(config_from(&[]).default_stats, None);
+
+        let config = config_from(&[(
+            "DEFAULT_STATS",
+            "stars_count, commits_ytd_count,pull_requests_count,issues_count",
+        )]);
+        assert_eq!(
+            config.default_stats,
+            Some(HashSet::from([
+                HideStat::StarsCount,
+                HideStat::CommitsYtdCount,
+                HideStat::PullRequestsCount,
+                HideStat::IssuesCount,
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_server_limits_from_vars() {
+        let config = config_from(&[]);
+        assert_eq!(config.max_concurrent_requests, None);
+        assert_eq!(config.request_body_limit, 64 * 1024);
+        assert!(config.keep_alive);
+        assert_eq!(config.header_read_timeout, Duration::from_secs(30));
+
+        let config = config_from(&[
+            ("MAX_CONCURRENT_REQUESTS", "128"),
+            ("REQUEST_BODY_LIMIT_BYTES", "1024"),
+            ("HTTP_KEEP_ALIVE", "false"),
+            ("HEADER_READ_TIMEOUT_SECONDS", "5"),
+        ]);
+        assert_eq!(config.max_concurrent_requests, Some(128));
+        assert_eq!(config.request_body_limit, 1024);
+        assert!(!config.keep_alive);
+        assert_eq!(config.header_read_timeout, Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_allow_token_header_from_vars() {
+        assert!(!config_from(&[]).allow_token_header);
+        assert!(config_from(&[("ALLOW_TOKEN_HEADER", "true")]).allow_token_header);
+        assert!(config_from(&[("ALLOW_TOKEN_HEADER", "1")]).allow_token_header);
+        assert!(!config_from(&[("ALLOW_TOKEN_HEADER", "FALSE")]).allow_token_header);
+    }
+
+    #[test]
+    fn test_coalesce_requests_from_vars() {
+        assert!(!config_from(&[]).coalesce_requests);
+        assert!(config_from(&[("COALESCE_REQUESTS", "true")]).coalesce_requests);
+    }
+
+    #[test]
+    fn test_invalid_langs_defaults_are_rejected() {
+        for (name, value) in [
+            ("DEFAULT_LANGS_LAYOUT", "diagonal"),
+            ("DEFAULT_SIZE_WEIGHT", "heavy"),
+            ("DEFAULT_COUNT_WEIGHT", "-1"),
+            ("DEFAULT_MAX_LANGUAGES", "0"),
+            ("DEFAULT_STATS", "stars_count,followers"),
+            ("DEFAULT_STATS", "stars_count"),
+            ("MAX_CONCURRENT_REQUESTS", "0"),
+            ("REQUEST_BODY_LIMIT_BYTES", "1k"),
+            ("HTTP_KEEP_ALIVE", "sometimes"),
+            ("HEADER_READ_TIMEOUT_SECONDS", "0"),
+            ("ALLOW_TOKEN_HEADER", "yes"),
+            ("COALESCE_REQUESTS", "on"),
+        ] {
+            match try_config_from(&[(name, value)]) {
+                Err(WebConfigError::InvalidValue { name: invalid, .. }) => {
+                    assert_eq!(invalid, name)
+                }
+                Ok(_) => panic!("{name}={value} should be rejected"),
+            }
+        }
+    }
+}
@@ -1,16 +1,179 @@
+pub mod config;
 pub mod routes;
 
 use axum::{
     Router,
-    http::Request,
+    extract::DefaultBodyLimit,
+    http::{HeaderValue, Request, header},
     middleware::{self, Next},
     response::Response,
 };
+use hyper::server::conn::http1;
+use hyper_util::{
+    rt::{TokioIo, TokioTimer},
+    server::graceful::GracefulShutdown,
+    service::TowerToHyperService,
+};
+use std::{
+    future::Future,
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, Instant},
+};
+use tokio::net::TcpListener;
+use tower::limit::GlobalConcurrencyLimitLayer;
+
+use crate::github::GitHubToken;
+use config::{WebConfig, get_web_config};
+
+/// Prefix of the current API version.
+const API_V1: &str = "/api/v1";
+
+/// Request header carrying a GitHub token to use instead of the server's one,
+/// only when `ALLOW_TOKEN_HEADER` is set.
+pub const TOKEN_HEADER: &str = "x-github-token";
+
+/// Request header identifying a request in the access log, generated when missing
+/// and echoed in the response.
+const REQUEST_ID_HEADER: &str = "x-request-id";
 
 pub fn app_router() -> Router {
-    Router::new()
-        .nest("/api", routes::api_router())
+    let router = Router::new()
+        .nest(API_V1, routes::api_router())
+        // The unversioned routes predate v1 and are kept for the existing README links
+        .nest(
+            "/api",
+            routes::api_router().layer(middleware::from_fn(deprecation_headers)),
+        )
         .layer(middleware::from_fn(error_handling_middleware))
+        .layer(middleware::from_fn(access_log))
+        .layer(middleware::from_fn(take_token_header));
+    with_limits(router, get_web_config())
+}
+
+/// Marks a response of an unversioned `/api/...` route as deprecated, linking to
+/// the same route under [API_V1].
+async fn deprecation_headers(request: Request<axum::body::Body>, next: Next) -> Response {
+    let successor = match request.uri().path_and_query() {
+        Some(path) => format!("<{API_V1}{path}>; rel=\"successor-version\""),
+        None => format!("<{API_V1}>; rel=\"successor-version\""),
+    };
+
+    let mut response = next.run(request).await;
+    let headers = response.headers_mut();
+    headers.insert("deprecation", HeaderValue::from_static("true"));
+    if let Ok(link) = HeaderValue::from_str(&successor) {
+        headers.insert(header::LINK, link);
+    }
+    response
+}
+
+/// Takes the [TOKEN_HEADER] out of the request before anything can log it or report it
+/// to Sentry, keeping its token as a [GitHubToken] request extension when allowed.
+///
+/// Meant to be the outermost layer, wrapping the Sentry ones too.
+pub async fn take_token_header(mut request: Request<axum::body::Body>, next: Next) -> Response {
+    take_token(&mut request, get_web_config().allow_token_header);
+    next.run(request).await
+}
+
+/// Removes the [TOKEN_HEADER] of the request, moving its token to the extensions if `allowed`.
+fn take_token<B>(request: &mut Request<B>, allowed: bool) {
+    let Some(value) = request.headers_mut().remove(TOKEN_HEADER) else {
+        return;
+    };
+    let token = value.to_str().map(str::trim).unwrap_or_default();
+    if allowed && !token.is_empty() {
+        let token = GitHubToken::new(token.to_string());
+        request.extensions_mut().insert(token);
+    }
+}
+
+/// Logs a line per request under the `access_log` target, with structured fields
+/// so the JSON log format gets one clean record per request.
+async fn access_log(mut request: Request<axum::body::Body>, next: Next) -> Response {
+    static NEXT_REQUEST_ID: AtomicU64 = AtomicU64::new(1);
+
+    let started = Instant::now();
+    let method = request.method().clone();
+    let path = request.uri().path().to_string();
+    let request_id = match request.headers().get(REQUEST_ID_HEADER) {
+        Some(id) => id.clone(),
+        None => {
+            let id = HeaderValue::from(NEXT_REQUEST_ID.fetch_add(1, Ordering::Relaxed));
+            request.headers_mut().insert(REQUEST_ID_HEADER, id.clone());
+            id
+        }
+    };
+
+    let mut response = next.run(request).await;
+
+    tracing::info!(
+        target: "access_log",
+        method = %method,
+        path = %path,
+        status = response.status().as_u16(),
+        latency_ms = started.elapsed().as_millis() as u64,
+        stale = response.headers().contains_key(routes::STALE_HEADER),
+        request_id = request_id.to_str().unwrap_or_default(),
+        "request"
+    );
+    response.headers_mut().insert(REQUEST_ID_HEADER, request_id);
+    response
+}
+
+/// Applies the configured request body size and concurrency limits to the router.
+///
+/// The concurrency limit is global (shared by all routes), requests beyond it are
+/// queued until a slot frees up rather than rejected.
+fn with_limits(router: Router, config: &WebConfig) -> Router {
+    let router = router.layer(DefaultBodyLimit::max(config.request_body_limit));
+    match config.max_concurrent_requests {
+        Some(max) => router.layer(GlobalConcurrencyLimitLayer::new(max)),
+        None => router,
+    }
+}
+
+/// Serves `app` over HTTP/1.1 on `listener` with the keep-alive and header read timeout
+/// of `config`, until `shutdown` completes and the open connections are done.
+pub async fn serve(
+    listener: TcpListener,
+    app: Router,
+    config: &WebConfig,
+    shutdown: impl Future<Output = ()>,
+) {
+    let mut builder = http1::Builder::new();
+    builder
+        .timer(TokioTimer::new())
+        .keep_alive(config.keep_alive)
+        .header_read_timeout(config.header_read_timeout);
+    let graceful = GracefulShutdown::new();
+    let mut shutdown = std::pin::pin!(shutdown);
+
+    loop {
+        let stream = tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok((stream, _)) => stream,
+                Err(e) => {
+                    // e.g. out of file descriptors, give the open connections time to close
+                    tracing::warn!("Failed to accept a connection: {e}");
+                    tokio::time::sleep(Duration::from_secs(1)).await;
+                    continue;
+                }
+            },
+            () = &mut shutdown => break,
+        };
+
+        let service = TowerToHyperService::new(app.clone());
+        let connection = graceful.watch(builder.serve_connection(TokioIo::new(stream), service));
+        tokio::spawn(async move {
+            if let Err(e) = connection.await {
+                tracing::debug!("Connection closed with an error: {e}");
+            }
+        });
+    }
+
+    drop(listener);
+    graceful.shutdown().await;
 }
 
 /// Global error handling middleware to catch any unhandled errors
@@ -52,3 +215,393 @@ async fn error_handling_middleware(
 
     Ok(response)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::StatusCode, routing::get, routing::post};
+    use std::sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    };
+    use std::time::Duration;
+    use tower::ServiceExt;
+
+    mod fn_with_limits {
+        use super::*;
+
+        /// Sends `requests` concurrent requests to a slow handler and
+        /// returns the highest number of them being handled at once.
+        async fn max_in_flight(config: &WebConfig, requests: usize) -> usize {
+            let in_flight = Arc::new(AtomicUsize::new(0));
+            let max_seen = Arc::new(AtomicUsize::new(0));
+
+            let handler = {
+                let (in_flight, max_seen) = (in_flight.clone(), max_seen.clone());
+                move || async move {
+                    let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                    max_seen.fetch_max(now, Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(50)).await;
+                    in_flight.fetch_sub(1, Ordering::SeqCst);
+                }
+            };
+            let app = with_limits(Router::new().route("/slow", get(handler)), config);
+
+            let handles: Vec<_> = (0..requests)
+                .map(|_| {
+                    let req = Request::builder().uri("/slow").body(Body::empty()).unwrap();
+                    tokio::spawn(app.clone().oneshot(req))
+                })
+                .collect();
+            for handle in handles {
+                let resp = handle.await.unwrap().unwrap();
+                assert_eq!(resp.status(), StatusCode::OK);
+            }
+
+            max_seen.load(Ordering::SeqCst)
+        }
+
+        #[tokio::test]
+        async fn concurrency_limit_queues_requests_beyond_the_limit() {
+            let config = WebConfig {
+                max_concurrent_requests: Some(2),
+                ..Default::default()
+            };
+            assert_eq!(max_in_flight(&config, 6).await, 2);
+        }
+
+        #[tokio::test]
+        async fn without_concurrency_limit_requests_run_at_once() {
+            assert!(max_in_flight(&WebConfig::default(), 6).await > 2);
+        }
+
+        #[tokio::test]
+        async fn body_over_limit_returns_413() {
+            let config = WebConfig {
+                request_body_limit: 16,
+                ..Default::default()
+            };
+            let app = with_limits(
+                Router::new().route("/echo", post(|body: String| async { body })),
+                &config,
+            );
+
+            let req = Request::builder()
+                .method("POST")
+                .uri("/echo")
+                .body(Body::from("a".repeat(17)))
+                .unwrap();
+            let resp = app.clone().oneshot(req).await.unwrap();
+            assert_eq!(resp.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+            let req = Request::builder()
+                .method("POST")
+                .uri("/echo")
+                .body(Body::from("a".repeat(16)))
+                .unwrap();
+            let resp = app.oneshot(req).await.unwrap();
+            assert_eq!(resp.status(), StatusCode::OK);
+        }
+    }
+
+    mod fn_serve {
+        use super::*;
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        use tokio::net::TcpStream;
+
+        /// Serves a hello route with `config` in the background, returning its address.
+        async fn spawn_server(config: WebConfig) -> std::net::SocketAddr {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let addr = listener.local_addr().unwrap();
+            let app = Router::new().route("/", get(|| async { "hello" }));
+            tokio::spawn(async move {
+                serve(listener, app, &config, std::future::pending()).await;
+            });
+            addr
+        }
+
+        /// Sends a GET request over `stream` and returns the head of the response.
+        async fn get_head(stream: &mut TcpStream) -> String {
+            stream
+                .write_all(b"GET / HTTP/1.1\r\nhost: localhost\r\n\r\n")
+                .await
+                .unwrap();
+            let mut buf = vec![0; 1024];
+            let read = stream.read(&mut buf).await.unwrap();
+            String::from_utf8_lossy(&buf[..read]).to_lowercase()
+        }
+
+        /// Reads `stream` until the server closes it, failing after `limit`.
+        async fn closed_within(stream: &mut TcpStream, limit: Duration) -> bool {
+            let mut rest = Vec::new();
+            tokio::time::timeout(limit, stream.read_to_end(&mut rest))
+                .await
+                .is_ok()
+        }
+
+        #[tokio::test]
+        async fn keeps_the_connection_alive_between_requests() {
+            let addr = spawn_server(WebConfig::default()).await;
+            let mut stream = TcpStream::connect(addr).await.unwrap();
+
+            for _ in 0..2 {
+                let head = get_head(&mut stream).await;
+                assert!(head.starts_with("http/1.1 200"), "{head}");
+                assert!(!head.contains("connection: close"), "{head}");
+            }
+        }
+
+        #[tokio::test]
+        async fn closes_the_connection_without_keep_alive() {
+            let addr = spawn_server(WebConfig {
+                keep_alive: false,
+                ..Default::default()
+            })
+            .await;
+            let mut stream = TcpStream::connect(addr).await.unwrap();
+
+            let head = get_head(&mut stream).await;
+            assert!(head.contains("connection: close"), "{head}");
+            assert!(closed_within(&mut stream, Duration::from_secs(1)).await);
+        }
+
+        #[tokio::test]
+        async fn closes_idle_connections_after_the_header_read_timeout() {
+            let addr = spawn_server(WebConfig {
+                header_read_timeout: Duration::from_millis(100),
+                ..Default::default()
+            })
+            .await;
+
+            // Neither a request that never sends its headers nor an idle
+            // keep-alive connection can hold a connection open
+            let mut silent = TcpStream::connect(addr).await.unwrap();
+            silent.write_all(b"GET / HTTP/1.1\r\n").await.unwrap();
+            assert!(closed_within(&mut silent, Duration::from_secs(1)).await);
+
+            let mut idle = TcpStream::connect(addr).await.unwrap();
+            assert!(get_head(&mut idle).await.starts_with("http/1.1 200"));
+            assert!(closed_within(&mut idle, Duration::from_secs(1)).await);
+        }
+    }
+
+    mod fn_access_log {
+        use super::*;
+        use std::io;
+        use std::sync::Mutex;
+        use tracing_subscriber::fmt::MakeWriter;
+
+        /// Log writer keeping the written lines in memory.
+        #[derive(Clone, Default)]
+        struct CapturedLogs(Arc<Mutex<Vec<u8>>>);
+
+        impl CapturedLogs {
+            fn contents(&self) -> String {
+                String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+            }
+        }
+
+        impl io::Write for CapturedLogs {
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                self.0.lock().unwrap().extend_from_slice(buf);
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        impl<'a> MakeWriter<'a> for CapturedLogs {
+            type Writer = Self;
+
+            fn make_writer(&'a self) -> Self::Writer {
+                self.clone()
+            }
+        }
+
+        /// Sends the request to the app and returns the response with the
+        /// JSON access log lines it produced.
+        async fn send(request: Request<Body>) -> (Response, Vec<serde_json::Value>) {
+            let logs = CapturedLogs::default();
+            let subscriber = tracing_subscriber::fmt()
+                .json()
+                .with_writer(logs.clone())
+                .with_max_level(tracing::Level::INFO)
+                .finish();
+            let _guard = tracing::subscriber::set_default(subscriber);
+
+            let resp = app_router().oneshot(request).await.unwrap();
+            let events = logs
+                .contents()
+                .lines()
+                .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+                .filter(|event| event["target"] == "access_log")
+                .collect();
+            (resp, events)
+        }
+
+        #[tokio::test]
+        async fn logs_the_path_and_status() {
+            let req = Request::builder()
+                .uri("/api/v1/health")
+                .body(Body::empty())
+                .unwrap();
+            let (resp, events) = send(req).await;
+            assert_eq!(resp.status(), StatusCode::OK);
+
+            assert_eq!(events.len(), 1);
+            let fields = &events[0]["fields"];
+            assert_eq!(fields["method"], "GET");
+            assert_eq!(fields["path"], "/api/v1/health");
+            assert_eq!(fields["status"], 200);
+            assert_eq!(fields["stale"], false);
+            assert!(fields["latency_ms"].is_u64());
+            assert_eq!(
+                fields["request_id"],
+                resp.headers()[REQUEST_ID_HEADER].to_str().unwrap()
+            );
+        }
+
+        #[tokio::test]
+        async fn never_logs_the_token_header() {
+            let req = Request::builder()
+                .uri("/api/v1/stats-card?username=octocat&format=webp")
+                .header(TOKEN_HEADER, "ghp_secret")
+                .body(Body::empty())
+                .unwrap();
+            let logs = CapturedLogs::default();
+            let subscriber = tracing_subscriber::fmt()
+                .json()
+                .with_writer(logs.clone())
+                .with_max_level(tracing::Level::TRACE)
+                .finish();
+            let _guard = tracing::subscriber::set_default(subscriber);
+
+            app_router().oneshot(req).await.unwrap();
+            assert!(!logs.contents().is_empty());
+            assert!(!logs.contents().contains("ghp_secret"));
+        }
+
+        #[tokio::test]
+        async fn keeps_the_request_id_of_the_client() {
+            let req = Request::builder()
+                .uri("/api/v1/stats-card?offset_x=500")
+                .header(REQUEST_ID_HEADER, "abc-123")
+                .body(Body::empty())
+                .unwrap();
+            let (resp, events) = send(req).await;
+            assert_eq!(resp.headers()[REQUEST_ID_HEADER], "abc-123");
+
+            let fields = &events[0]["fields"];
+            assert_eq!(fields["request_id"], "abc-123");
+            assert_eq!(fields["status"], resp.status().as_u16());
+        }
+    }
+
+    mod fn_take_token {
+        use super::*;
+
+        fn request_with_token() -> Request<()> {
+            Request::builder()
+                .uri("/api/v1/health")
+                .header(TOKEN_HEADER, "ghp_secret")
+                .body(())
+                .unwrap()
+        }
+
+        #[test]
+        fn keeps_the_token_when_allowed() {
+            let mut request = request_with_token();
+            take_token(&mut request, true);
+            assert!(request.headers().get(TOKEN_HEADER).is_none());
+            assert_eq!(
+                request.extensions().get::<GitHubToken>(),
+                Some(&GitHubToken::new("ghp_secret".to_string()))
+            );
+        }
+
+        #[test]
+        fn drops_the_token_when_not_allowed() {
+            let mut request = request_with_token();
+            take_token(&mut request, false);
+            assert!(request.headers().get(TOKEN_HEADER).is_none());
+            assert!(request.extensions().get::<GitHubToken>().is_none());
+        }
+
+        #[test]
+        fn ignores_a_blank_token() {
+            let mut request = Request::builder()
+                .header(TOKEN_HEADER, "  ")
+                .body(())
+                .unwrap();
+            take_token(&mut request, true);
+            assert!(request.extensions().get::<GitHubToken>().is_none());
+        }
+
+        #[tokio::test]
+        async fn inner_layers_never_see_the_header() {
+            // Stands for the Sentry layers, which report the request headers
+            let app = Router::new()
+                .route(
+                    "/headers",
+                    get(|headers: axum::http::HeaderMap| async move { format!("{headers:?}") }),
+                )
+                .layer(middleware::from_fn(take_token_header));
+
+            let req = Request::builder()
+                .uri("/headers")
+                .header(TOKEN_HEADER, "ghp_secret")
+                .body(Body::empty())
+                .unwrap();
+            let resp = app.oneshot(req).await.unwrap();
+            let body = http_body_util::BodyExt::collect(resp.into_body())
+                .await
+                .unwrap()
+                .to_bytes();
+            assert!(!String::from_utf8_lossy(&body).contains("ghp_secret"));
+        }
+    }
+
+    mod fn_app_router {
+        use super::*;
+        use http_body_util::BodyExt as _;
+
+        async fn get(uri: &str) -> Response {
+            let req = Request::builder().uri(uri).body(Body::empty()).unwrap();
+            app_router().oneshot(req).await.unwrap()
+        }
+
+        #[tokio::test]
+        async fn versioned_and_unversioned_routes_respond_the_same() {
+            for path in ["/stats-card?hide=stars_count", "/langs-card?username=%20"] {
+                let unversioned = get(&format!("/api{path}")).await;
+                let versioned = get(&format!("/api/v1{path}")).await;
+                assert_eq!(unversioned.status(), versioned.status());
+                assert_eq!(
+                    unversioned.into_body().collect().await.unwrap().to_bytes(),
+                    versioned.into_body().collect().await.unwrap().to_bytes()
+                );
+            }
+
+            assert_eq!(get("/api/v1/health").await.status(), StatusCode::OK);
+        }
+
+        #[tokio::test]
+        async fn unversioned_routes_are_deprecated() {
+            let resp = get("/api/stats-card?username=octocat&offset_x=500").await;
+            assert_eq!(resp.headers()["deprecation"], "true");
+            assert_eq!(
+                resp.headers()[header::LINK],
+                "</api/v1/stats-card?username=octocat&offset_x=500>; rel=\"successor-version\""
+            );
+        }
+
+        #[tokio::test]
+        async fn versioned_routes_are_not_deprecated() {
+            let resp = get("/api/v1/health").await;
+            assert!(resp.headers().get("deprecation").is_none());
+            assert!(resp.headers().get(header::LINK).is_none());
+        }
+    }
+}
@@ -1,17 +1,36 @@
 use axum::{
     Json, Router,
-    extract::Query,
+    body::Body,
+    extract::{Extension, Path, Query, Request},
     http::{HeaderMap, StatusCode, header},
+    middleware::{self, Next},
     response::{IntoResponse, Response},
     routing::get,
 };
+use base64::{Engine as _, prelude::BASE64_STANDARD};
 use serde::Deserialize;
-use std::{collections::HashSet, str::FromStr};
+use std::{
+    collections::{HashMap, HashSet},
+    time::Duration,
+};
 
-use crate::cards::card::{CardSettings, CardTheme};
-use crate::cards::error_card::ErrorCard;
-use crate::cards::langs_card::{LangsCard, LayoutType};
-use crate::github::{GitHubApi, GitHubApiError, get_github_cache, get_github_rate_limit};
+use crate::cards::accent::accent_css;
+use crate::cards::card::{Canvas, Card, CardError, CardSettings, CardTheme};
+use crate::cards::error_card::{ErrorCard, ErrorStyle};
+use crate::cards::helpers::{MIN_CONTRAST_RATIO, ensure_contrast, gel_language_color};
+use crate::cards::langs_card::{LangsCard, LangsSort, LanguageStat, LayoutType};
+use crate::cards::raster;
+use crate::cards::stats_card::{StatsCard, StatsSort};
+use crate::cards::streak_card::{StreakCard, StreakStats};
+use crate::github::{
+    Clock, ContributionType, GitHubApi, GitHubApiError, GitHubStats, GitHubToken,
+    RepositoryAffiliations, StatsOptions,
+    api::{current_utc_year_with, is_token_configured, rate_limit_threshold},
+    cache::{CacheConfig, Cached},
+    get_github_cache, get_github_rate_limit,
+};
+use crate::render::{self, HideStat, RenderError, parse_hide_list};
+use crate::web::config::{LangsDefaults, WebConfig, get_web_config};
 
 use card_theme_macros::build_theme_query;
 
@@ -19,7 +38,16 @@ pub fn api_router() -> Router {
     Router::new()
         .route("/stats-card", get(get_stats_card))
         .route("/langs-card", get(get_langs_card))
+        .route("/langs-card/top", get(get_langs_summary))
+        .route("/org-stats-card", get(get_org_stats_card))
+        .route("/org-langs-card", get(get_org_langs_card))
+        .route("/streak-card", get(get_streak_card))
+        .route_layer(middleware::from_fn(coalesce_requests))
+        // Outside of the coalescing, each waiting request is answered after its own `If-None-Match`
+        .route_layer(middleware::from_fn(not_modified))
         .route("/health", get(get_health))
+        .route("/themes/{name}/colors", get(get_theme_colors))
+        .layer(middleware::from_fn(apply_error_style))
 }
 
 #[derive(Debug, Deserialize)]
@@ -31,10 +59,45 @@ pub struct StatsCardQuery {
     settings: CardSettingsQuery,
     // comma-separated array: e.g. ?hide=stars_count,commits_ytd_count
     hide: Option<String>,
+    // optional ordering of the rows by value
+    sort: Option<StatsSortQuery>,
+    // row icon size, clamped to a readable range
+    icon_size: Option<String>,
+    // space between the row icons and labels, clamped
+    label_gap: Option<String>,
+    // space between the labels and values, clamped
+    value_gap: Option<String>,
+    // rows wrapping: down (default) or columns, with at most max_rows rows per column
+    overflow: Option<String>,
+    max_rows: Option<String>,
+    // count private contributions, only allowed for the token owner
+    include_private: Option<String>,
+    // calendar year the commits and reviews are counted in, the current one by default
+    year: Option<String>,
+    // all to count the commits of every year since the account was created
+    commits_year: Option<String>,
+    // most starred repositories counted in the stars total, all of them by default
+    max_repos: Option<String>,
+    // comma-separated relations to the counted repositories: owner (default), collaborator, organization_member
+    affiliations: Option<String>,
+    // contribution types summed up in the commits row: commits (default), all or a list
+    contribution_types: Option<String>,
+    // accent color of the title and icons: avatar for the dominant color of the user's avatar
+    accent: Option<String>,
+    // title shown instead of `@username: GitHub Stats`
+    title: Option<String>,
+    // response format: the SVG itself, a data URI, a PNG or a WebP, negotiated from `Accept` when omitted
+    format: Option<String>,
+    // size of the PNG or WebP relative to the SVG, clamped
+    scale: Option<String>,
 }
 
-#[tracing::instrument(name = "stats_card_request", fields(username = %q.username))]
-async fn get_stats_card(Query(q): Query<StatsCardQuery>) -> impl IntoResponse {
+#[tracing::instrument(name = "stats_card_request", skip(headers, token), fields(username = %q.username))]
+async fn get_stats_card(
+    headers: HeaderMap,
+    token: Option<Extension<GitHubToken>>,
+    Query(q): Query<StatsCardQuery>,
+) -> impl IntoResponse {
     // Add user context to Sentry
     sentry::configure_scope(|scope| {
         scope.set_user(Some(sentry::User {
@@ -58,154 +121,239 @@ async fn get_stats_card(Query(q): Query<StatsCardQuery>) -> impl IntoResponse {
         );
     });
 
-    // Validate username
-    if let Err(e) = validate_username(&q.username) {
-        return error_response(StatusCode::BAD_REQUEST, &e);
+    // Create GitHub API client
+    let github_api = github_api(token);
+    let mut request = match stats_card_from_query(q, &headers, github_api.clock()) {
+        Ok(request) => request,
+        Err(response) => return *response,
+    };
+
+    if request.avatar_accent {
+        apply_avatar_accent(&github_api, &request.username, &mut request.settings).await;
+    }
+
+    // Fetch real stats from GitHub
+    let Cached {
+        value: github_stats,
+        mut stale,
+        ..
+    } = match github_api
+        .fetch_cached_user_stats(&request.username, request.options)
+        .await
+    {
+        Ok(cached) => cached,
+        Err(e) => {
+            return github_error_response(e, &request.username, "statistics", github_api.clock());
+        }
+    };
+    // The commits of every year are cached apart from the stats of the current one
+    let lifetime_commits = if request.commits_all_years {
+        match github_api
+            .fetch_cached_lifetime_commits(&request.username, request.options.include_private)
+            .await
+        {
+            Ok(cached) => {
+                stale |= cached.stale;
+                Some(cached.value)
+            }
+            Err(e) => {
+                return github_error_response(e, &request.username, "commits", github_api.clock());
+            }
+        }
+    } else {
+        None
+    };
+
+    let (format, scale) = (request.format, request.scale);
+    let settings_header = request.settings_header.take();
+    let svg = match request.render(&github_stats, lifetime_commits) {
+        Ok(svg) => svg,
+        Err(response) => return *response,
+    };
+
+    let mut response = card_response(svg, format, scale).await;
+    if let Some(value) = settings_header {
+        response.headers_mut().insert(CARD_SETTINGS_HEADER, value);
     }
+    if stale {
+        response
+            .headers_mut()
+            .insert(STALE_HEADER, header::HeaderValue::from_static("true"));
+    }
+    response
+}
+
+/// A stats card query, parsed and validated before anything is fetched from GitHub.
+struct StatsCardRequest {
+    username: String,
+    format: ResponseFormat,
+    scale: f32,
+    settings: CardSettings,
+    settings_header: Option<header::HeaderValue>,
+    options: StatsOptions,
+    commits_all_years: bool,
+    contribution_types: Vec<ContributionType>,
+    avatar_accent: bool,
+    to_hide: Vec<HideStat>,
+    sort: Option<StatsSort>,
+    icon_size: u32,
+    label_gap: u32,
+    value_gap: u32,
+    max_rows: Option<u32>,
+    custom_title: Option<String>,
+}
+
+/// Parses the query of the stats card, failing with the response to send back.
+fn stats_card_from_query(
+    q: StatsCardQuery,
+    headers: &HeaderMap,
+    clock: &dyn Clock,
+) -> Result<StatsCardRequest, Box<Response>> {
+    let bad_request = |e: String| Box::new(error_response(StatusCode::BAD_REQUEST, &e));
+
+    // Validate username
+    validate_username(&q.username).map_err(bad_request)?;
 
     // Check if username is allowed to use the API
     if !is_username_allowed(&q.username) {
-        return error_response(
+        return Err(Box::new(error_response(
             StatusCode::FORBIDDEN,
             "Username not authorized to access this service",
-        );
+        )));
     }
 
-    // Build card settings from query (with defaults applied)
-    let settings = q.settings.into_settings();
+    let format = negotiate_format(q.format.as_deref(), headers)
+        .map_err(|e| Box::new(error_response(StatusCode::NOT_ACCEPTABLE, &e)))?;
 
-    // Create GitHub API client
-    let github_api = GitHubApi::new();
+    // Build card settings from query (with defaults applied)
+    let settings = q.settings.into_settings().map_err(bad_request)?;
+    let settings_header = card_settings_header(&settings, &[]);
 
-    // Fetch real stats from GitHub
-    let github_stats = match github_api.fetch_user_stats(&q.username).await {
-        Ok(stats) => stats,
-        Err(GitHubApiError::UserNotFound) => {
-            return error_response(StatusCode::NOT_FOUND, "User not found");
-        }
-        Err(GitHubApiError::InvalidUsername(msg)) => {
-            return error_response(StatusCode::BAD_REQUEST, &msg);
-        }
-        Err(GitHubApiError::MissingToken) => {
-            return error_response(
-                StatusCode::SERVICE_UNAVAILABLE,
-                "GitHub API token not configured",
-            );
-        }
-        Err(GitHubApiError::RateLimitExceeded) => {
-            // Report rate limit exceeded to Sentry as it's an operational issue
-            sentry::capture_message(
-                &format!("GitHub API rate limit exceeded for user: {}", q.username),
-                sentry::Level::Warning,
-            );
-            return error_response(
-                StatusCode::TOO_MANY_REQUESTS,
-                "GitHub API rate limit exceeded",
-            );
-        }
-        Err(GitHubApiError::RateLimitProtection(remaining, reset_time)) => {
-            // Calculate seconds until reset
-            let current_time = std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .map(|d| d.as_secs())
-                .unwrap_or(0);
-            let retry_after = reset_time.saturating_sub(current_time);
+    let year = q
+        .year
+        .as_deref()
+        .map(|year| parse_commits_year(year, clock))
+        .transpose()
+        .map_err(bad_request)?;
+    let commits_all_years = q
+        .commits_year
+        .as_deref()
+        .map(parse_commits_all_years)
+        .transpose()
+        .map_err(bad_request)?
+        .unwrap_or_default();
+    if commits_all_years && year.is_some() {
+        return Err(bad_request(
+            "commits_year=all cannot be combined with year".to_string(),
+        ));
+    }
+    let max_repos = q
+        .max_repos
+        .as_deref()
+        .map(parse_max_repos)
+        .transpose()
+        .map_err(bad_request)?;
+    let affiliations = q
+        .affiliations
+        .as_deref()
+        .map(parse_affiliations)
+        .transpose()
+        .map_err(bad_request)?
+        .unwrap_or_default();
 
-            let message = format!(
-                "Rate limit protection active: {} requests remaining, reset at {}",
-                remaining, reset_time
-            );
-            let error_card = ErrorCard::new(message);
-            let svg = error_card.render();
+    let contribution_types = q
+        .contribution_types
+        .as_deref()
+        .map(parse_contribution_types)
+        .transpose()
+        .map_err(bad_request)?
+        .unwrap_or_else(|| vec![ContributionType::Commits]);
+    if commits_all_years && contribution_types != [ContributionType::Commits] {
+        return Err(bad_request(
+            "commits_year=all cannot be combined with contribution_types".to_string(),
+        ));
+    }
 
-            let mut headers = axum::http::HeaderMap::new();
-            headers.insert(
-                header::CONTENT_TYPE,
-                header::HeaderValue::from_static("image/svg+xml"),
-            );
-            if let Ok(retry_header) = axum::http::HeaderValue::from_str(&retry_after.to_string()) {
-                headers.insert("retry-after", retry_header);
-            }
+    let avatar_accent = q
+        .accent
+        .as_deref()
+        .map(parse_accent)
+        .transpose()
+        .map_err(bad_request)?
+        == Some(Accent::Avatar);
+    let max_rows =
+        parse_overflow(q.overflow.as_deref(), q.max_rows.as_deref()).map_err(bad_request)?;
+    let custom_title = q
+        .title
+        .as_deref()
+        .map(parse_title)
+        .transpose()
+        .map_err(bad_request)?;
 
-            return (StatusCode::TOO_MANY_REQUESTS, headers, svg).into_response();
-        }
-        Err(e) => {
-            // Report all other unexpected errors to Sentry
-            sentry::capture_error(&e);
-            tracing::error!("GitHub API error: {e}");
-            return error_response(
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "Failed to fetch user statistics",
-            );
-        }
-    };
+    // Parse hide list before fetching, falling back to the configured default stats
+    let to_hide = stats_to_hide(q.hide.as_deref(), get_web_config().default_stats.as_ref())
+        .map_err(|value| bad_request(format!("invalid hide value: {value}")))?;
 
-    // Create StatsCard directly from GitHub stats
-    let mut stats_card = github_stats.to_stats_card(q.username.clone(), settings);
+    Ok(StatsCardRequest {
+        format,
+        scale: parse_scale(q.scale.as_deref()),
+        settings,
+        settings_header,
+        options: StatsOptions {
+            include_private: parse_toggle(q.include_private.as_deref()),
+            year,
+            max_repos,
+            affiliations,
+        },
+        commits_all_years,
+        contribution_types,
+        avatar_accent,
+        to_hide: Vec::from_iter(to_hide),
+        sort: q.sort.map(Into::into),
+        icon_size: parse_icon_size(q.icon_size.as_deref()),
+        label_gap: parse_label_gap(q.label_gap.as_deref()),
+        value_gap: parse_value_gap(q.value_gap.as_deref()),
+        max_rows,
+        custom_title,
+        username: q.username,
+    })
+}
 
-    // Parse and apply hide list
-    if let Some(hide_str) = q.hide.as_deref() {
-        let mut to_hide: HashSet<HideStat> = HashSet::new();
-        if !hide_str.trim().is_empty() {
-            for token in hide_str.split(',') {
-                let token = token.trim();
-                if token.is_empty() {
-                    continue;
-                }
-                match HideStat::from_str(token) {
-                    Ok(v) => {
-                        to_hide.insert(v);
-                    }
-                    Err(_) => {
-                        return (
-                            StatusCode::BAD_REQUEST,
-                            Json(serde_json::json!({"error": format!("invalid hide value: {}", token)})),
-                        )
-                            .into_response();
-                    }
-                }
-            }
+impl StatsCardRequest {
+    /// Renders the card of the fetched `stats`, with the commits of every year when
+    /// `lifetime_commits` were fetched too.
+    fn render(
+        self,
+        stats: &GitHubStats,
+        lifetime_commits: Option<u32>,
+    ) -> Result<String, Box<Response>> {
+        // Create StatsCard directly from GitHub stats
+        let mut stats_card = stats.to_stats_card(self.username, self.settings);
+        stats_card.sort = self.sort;
+        stats_card.icon_size = self.icon_size;
+        stats_card.label_gap = self.label_gap;
+        stats_card.value_gap = self.value_gap;
+        stats_card.max_rows = self.max_rows;
+        stats_card.custom_title = self.custom_title;
+        if let Some(commits) = lifetime_commits {
+            stats_card.commits_ytd_count = Some(commits);
+            stats_card.commits_all_years = true;
         }
-
-        for h in to_hide {
-            match h {
-                HideStat::StarsCount => stats_card.stars_count = None,
-                HideStat::CommitsYtdCount => stats_card.commits_ytd_count = None,
-                HideStat::IssuesCount => stats_card.issues_count = None,
-                HideStat::PullRequestsCount => stats_card.pull_requests_count = None,
-                HideStat::MergeRequestsCount => stats_card.merge_requests_count = None,
-                HideStat::ReviewsCount => stats_card.reviews_count = None,
-                HideStat::StartedDiscussionsCount => stats_card.started_discussions_count = None,
-                HideStat::AnsweredDiscussionsCount => stats_card.answered_discussions_count = None,
-            }
+        if self.contribution_types != [ContributionType::Commits] {
+            stats_card.commits_ytd_count = Some(stats.contributions(&self.contribution_types));
+            stats_card.counts_contributions = true;
         }
-    }
-
-    // Ensure at least two visible stats remain
-    let visible = [
-        &stats_card.stars_count,
-        &stats_card.commits_ytd_count,
-        &stats_card.issues_count,
-        &stats_card.pull_requests_count,
-        &stats_card.merge_requests_count,
-        &stats_card.reviews_count,
-        &stats_card.started_discussions_count,
-        &stats_card.answered_discussions_count,
-    ]
-    .iter()
-    .filter(|v| v.is_some())
-    .count();
 
-    if visible < 2 {
-        return error_response(
-            StatusCode::BAD_REQUEST,
-            "hide would remove too many stats; at least 2 must remain",
-        );
+        // Apply hide list, at least two visible stats must remain
+        render::render_stats_card_with(stats_card, &self.to_hide).map_err(|e| {
+            Box::new(match e {
+                e @ RenderError::TooFewStats => {
+                    error_response(StatusCode::BAD_REQUEST, &e.to_string())
+                }
+                RenderError::Card(e) => card_error_response(e, "stats"),
+            })
+        })
     }
-
-    let svg = stats_card.render();
-
-    svg_response(svg)
 }
 
 #[derive(Debug, Deserialize)]
@@ -220,12 +368,48 @@ pub struct LangsCardQuery {
     size_weight: Option<f64>,
     count_weight: Option<f64>,
     max_languages: Option<u64>,
+    // blend size and count shares instead of multiplying powers of the weights
+    normalize_weights: Option<String>,
+    // percentages relative to the displayed languages only, so they sum up to 100%
+    normalize: Option<String>,
     // comma-separated list of repositories to exclude
     exclude_repo: Option<String>,
+    // comma-separated relations to the counted repositories: owner (default), collaborator, organization_member
+    affiliations: Option<String>,
+    // comma-separated color overrides: e.g. ?lang_colors=Rust:ff0000,Go:00ffff
+    lang_colors: Option<String>,
+    // semicolon-separated language groups: e.g. ?group_langs=JS/TS=JavaScript,TypeScript;C/C++=C,C++
+    group_langs: Option<String>,
+    // text shown when the user has no languages to rank (e.g. only forks)
+    empty_note: Option<String>,
+    // darken or lighten the language colors too close to the theme background
+    auto_contrast: Option<String>,
+    // order of the displayed languages, the selection is always by rank
+    sort: Option<LangsSortQuery>,
+    // minimum width of a language bar or segment in pixels, clamped
+    min_bar_px: Option<String>,
+    // only the legend of the languages, without bars
+    legend_only: Option<String>,
+    // percentage under which the languages are summed up into "Other"
+    combine_below: Option<String>,
+    // color of the combined "Other" language: e.g. ?other_color=ededed
+    other_color: Option<String>,
+    // percentage under which the languages are dropped, after ranking
+    min_percent: Option<String>,
+    // accent color of the title and icons: avatar for the dominant color of the user's avatar
+    accent: Option<String>,
+    // response format: the SVG itself, a data URI, a PNG or a WebP, negotiated from `Accept` when omitted
+    format: Option<String>,
+    // size of the PNG or WebP relative to the SVG, clamped
+    scale: Option<String>,
 }
 
-#[tracing::instrument(name = "langs_card_request", fields(username = %q.username))]
-async fn get_langs_card(Query(q): Query<LangsCardQuery>) -> impl IntoResponse {
+#[tracing::instrument(name = "langs_card_request", skip(headers, token), fields(username = %q.username))]
+async fn get_langs_card(
+    headers: HeaderMap,
+    token: Option<Extension<GitHubToken>>,
+    Query(q): Query<LangsCardQuery>,
+) -> impl IntoResponse {
     // Add user context to Sentry
     sentry::configure_scope(|scope| {
         scope.set_user(Some(sentry::User {
@@ -247,15 +431,258 @@ async fn get_langs_card(Query(q): Query<LangsCardQuery>) -> impl IntoResponse {
                 if let Some(layout) = &q.layout {
                     map.insert("layout".to_string(), format!("{:?}", layout).into());
                 }
-                map.insert(
-                    "max_languages".to_string(),
-                    q.max_languages.unwrap_or(8).into(),
-                );
+                // The number of languages the card shows, the default depends on the layout
+                let options = langs_card_options(&q, &get_web_config().langs_defaults);
+                map.insert("max_languages".to_string(), options.max_languages.into());
                 map
             }),
         );
     });
 
+    let mut request = match langs_card_from_query(q, &headers) {
+        Ok(request) => request,
+        Err(response) => return *response,
+    };
+
+    // Create GitHub API client
+    let github_api = github_api(token);
+    if request.avatar_accent {
+        apply_avatar_accent(
+            &github_api,
+            &request.username,
+            &mut request.card.card_settings,
+        )
+        .await;
+    }
+
+    // Fetch real language stats from GitHub
+    let Cached {
+        value: language_stats,
+        stale,
+        ..
+    } = match github_api
+        .fetch_cached_user_languages(
+            &request.username,
+            &request.exclude_repos,
+            request.affiliations,
+        )
+        .await
+    {
+        Ok(cached) => cached,
+        Err(e) => {
+            return github_error_response(e, &request.username, "languages", github_api.clock());
+        }
+    };
+
+    let settings_header = card_settings_header(
+        &request.card.card_settings,
+        &[("layout", request.card.layout.name())],
+    );
+
+    let (format, scale) = (request.format, request.scale);
+    let svg = match request.render(language_stats) {
+        Ok(svg) => svg,
+        Err(response) => return *response,
+    };
+
+    let mut response = card_response(svg, format, scale).await;
+    if let Some(value) = settings_header {
+        response.headers_mut().insert(CARD_SETTINGS_HEADER, value);
+    }
+    if stale {
+        response
+            .headers_mut()
+            .insert(STALE_HEADER, header::HeaderValue::from_static("true"));
+    }
+    response
+}
+
+/// A languages card query, parsed and validated before anything is fetched from GitHub.
+struct LangsCardRequest {
+    username: String,
+    format: ResponseFormat,
+    scale: f32,
+    /// Options of the card, its stats are the fetched ones
+    card: LangsCard,
+    lang_groups: Vec<LangGroup>,
+    exclude_repos: Vec<String>,
+    affiliations: RepositoryAffiliations,
+    avatar_accent: bool,
+}
+
+/// Parses the query of the languages card, failing with the response to send back.
+fn langs_card_from_query(
+    q: LangsCardQuery,
+    headers: &HeaderMap,
+) -> Result<LangsCardRequest, Box<Response>> {
+    let bad_request = |e: String| Box::new(error_response(StatusCode::BAD_REQUEST, &e));
+
+    // Validate username
+    validate_username(&q.username).map_err(bad_request)?;
+
+    // Check if username is allowed to use the API
+    if !is_username_allowed(&q.username) {
+        return Err(Box::new(error_response(
+            StatusCode::FORBIDDEN,
+            "Username not authorized to access this service",
+        )));
+    }
+
+    let format = negotiate_format(q.format.as_deref(), headers)
+        .map_err(|e| Box::new(error_response(StatusCode::NOT_ACCEPTABLE, &e)))?;
+
+    // Resolve layout and ranking options (with instance-wide defaults applied)
+    let options = langs_card_options(&q, &get_web_config().langs_defaults);
+
+    // Build card settings from query (with defaults applied)
+    let settings = q.settings.into_settings().map_err(bad_request)?;
+
+    // Parse custom language colors
+    let color_overrides = q
+        .lang_colors
+        .as_deref()
+        .map(parse_lang_colors)
+        .transpose()
+        .map_err(bad_request)?
+        .unwrap_or_default();
+
+    // Text shown when there are no languages to rank
+    let empty_note = q
+        .empty_note
+        .as_deref()
+        .map(parse_empty_note)
+        .transpose()
+        .map_err(bad_request)?
+        .unwrap_or_else(|| LangsCard::DEFAULT_EMPTY_NOTE.to_string());
+
+    // Languages summed up into "Other"
+    let combine_below = q
+        .combine_below
+        .as_deref()
+        .map(|value| parse_percent("combine_below", value))
+        .transpose()
+        .map_err(bad_request)?;
+    let other_color = q
+        .other_color
+        .as_deref()
+        .map(parse_other_color)
+        .transpose()
+        .map_err(bad_request)?
+        .unwrap_or_else(|| LangsCard::DEFAULT_OTHER_COLOR.to_string());
+    let min_percent = q
+        .min_percent
+        .as_deref()
+        .map(|value| parse_percent("min_percent", value))
+        .transpose()
+        .map_err(bad_request)?
+        .unwrap_or_default();
+
+    let avatar_accent = q
+        .accent
+        .as_deref()
+        .map(parse_accent)
+        .transpose()
+        .map_err(bad_request)?
+        == Some(Accent::Avatar);
+
+    // Parse language groups
+    let lang_groups = q
+        .group_langs
+        .as_deref()
+        .map(parse_lang_groups)
+        .transpose()
+        .map_err(bad_request)?
+        .unwrap_or_default();
+
+    // Parse excluded repositories
+    let exclude_repos = q
+        .exclude_repo
+        .as_deref()
+        .map(parse_exclude_repos)
+        .transpose()
+        .map_err(bad_request)?
+        .unwrap_or_default();
+    let affiliations = q
+        .affiliations
+        .as_deref()
+        .map(parse_affiliations)
+        .transpose()
+        .map_err(bad_request)?
+        .unwrap_or_default();
+
+    Ok(LangsCardRequest {
+        format,
+        scale: parse_scale(q.scale.as_deref()),
+        card: LangsCard {
+            card_settings: settings,
+            color_overrides,
+            empty_note,
+            combine_below,
+            other_color,
+            min_percent,
+            ..options
+        },
+        lang_groups,
+        exclude_repos,
+        affiliations,
+        avatar_accent,
+        username: q.username,
+    })
+}
+
+impl LangsCardRequest {
+    /// Renders the card of the fetched `stats`, grouped as requested.
+    fn render(self, stats: Vec<LanguageStat>) -> Result<String, Box<Response>> {
+        let mut card = self.card;
+        let stats = group_languages(stats, &self.lang_groups, &mut card.color_overrides);
+        render::render_langs_card(stats, card)
+            .map_err(|e| Box::new(card_error_response(e, "languages")))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LangsSummaryQuery {
+    // required
+    username: String,
+    // number of top languages to list
+    n: Option<u64>,
+    // same ranking options as the languages card
+    size_weight: Option<f64>,
+    count_weight: Option<f64>,
+    normalize_weights: Option<String>,
+    normalize: Option<String>,
+    // comma-separated list of repositories to exclude
+    exclude_repo: Option<String>,
+    // comma-separated relations to the counted repositories: owner (default), collaborator, organization_member
+    affiliations: Option<String>,
+    // response format: plain text (default) or JSON
+    format: Option<SummaryFormatQuery>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+enum SummaryFormatQuery {
+    #[serde(rename = "text")]
+    Text,
+    #[serde(rename = "json")]
+    Json,
+}
+
+/// Number of languages listed by the summary when `n` is omitted.
+const DEFAULT_SUMMARY_LANGUAGES: u64 = 3;
+
+#[tracing::instrument(name = "langs_summary_request", skip(token), fields(username = %q.username))]
+async fn get_langs_summary(
+    token: Option<Extension<GitHubToken>>,
+    Query(q): Query<LangsSummaryQuery>,
+) -> impl IntoResponse {
+    sentry::configure_scope(|scope| {
+        scope.set_user(Some(sentry::User {
+            username: Some(q.username.clone()),
+            ..Default::default()
+        }));
+        scope.set_tag("card_type", "languages_summary");
+    });
+
     // Validate username
     if let Err(e) = validate_username(&q.username) {
         return error_response(StatusCode::BAD_REQUEST, &e);
@@ -269,329 +696,3876 @@ async fn get_langs_card(Query(q): Query<LangsCardQuery>) -> impl IntoResponse {
         );
     }
 
-    // Build card settings from query (with defaults applied)
-    let settings = q.settings.into_settings();
-
-    // Parse excluded repositories
-    let exclude_repos: Vec<String> = if let Some(exclude_str) = q.exclude_repo.as_deref() {
-        exclude_str
-            .split(',')
-            .map(|s| s.trim().to_string())
-            .filter(|s| !s.is_empty())
-            .collect()
-    } else {
-        Vec::new()
+    let exclude_repos = match q.exclude_repo.as_deref().map(parse_exclude_repos) {
+        Some(Ok(repos)) => repos,
+        Some(Err(e)) => return error_response(StatusCode::BAD_REQUEST, &e),
+        None => Vec::new(),
+    };
+    let affiliations = match q.affiliations.as_deref().map(parse_affiliations) {
+        Some(Ok(affiliations)) => affiliations,
+        Some(Err(e)) => return error_response(StatusCode::BAD_REQUEST, &e),
+        None => RepositoryAffiliations::default(),
     };
 
-    // Create GitHub API client
-    let github_api = GitHubApi::new();
-
-    // Fetch real language stats from GitHub
-    let language_stats = match github_api
-        .fetch_user_languages(&q.username, &exclude_repos)
+    let github_api = github_api(token);
+    let Cached {
+        value: language_stats,
+        stale,
+        age,
+    } = match github_api
+        .fetch_cached_user_languages(&q.username, &exclude_repos, affiliations)
         .await
     {
-        Ok(stats) => stats,
-        Err(GitHubApiError::UserNotFound) => {
-            return error_response(StatusCode::NOT_FOUND, "User not found");
-        }
-        Err(GitHubApiError::InvalidUsername(msg)) => {
-            return error_response(StatusCode::BAD_REQUEST, &msg);
-        }
-        Err(GitHubApiError::MissingToken) => {
-            return error_response(
-                StatusCode::SERVICE_UNAVAILABLE,
-                "GitHub API token not configured",
-            );
-        }
-        Err(GitHubApiError::RateLimitExceeded) => {
-            // Report rate limit exceeded to Sentry as it's an operational issue
-            sentry::capture_message(
-                &format!(
-                    "GitHub API rate limit exceeded for user: {} (languages)",
-                    q.username
-                ),
-                sentry::Level::Warning,
-            );
-            return error_response(
-                StatusCode::TOO_MANY_REQUESTS,
-                "GitHub API rate limit exceeded",
-            );
+        Ok(cached) => cached,
+        Err(e) => return github_error_response(e, &q.username, "languages", github_api.clock()),
+    };
+
+    let percentages = langs_summary_card(&q, &get_web_config().langs_defaults, language_stats)
+        .computed_percentages();
+
+    match q.format.unwrap_or(SummaryFormatQuery::Text) {
+        SummaryFormatQuery::Text => (
+            StatusCode::OK,
+            [(header::CONTENT_TYPE, "text/plain; charset=utf-8")],
+            format_langs_summary(&percentages),
+        )
+            .into_response(),
+        SummaryFormatQuery::Json => {
+            Json(langs_summary_json(&q.username, &percentages, stale, age)).into_response()
         }
-        Err(GitHubApiError::RateLimitProtection(remaining, reset_time)) => {
-            // Calculate seconds until reset
-            let current_time = std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .map(|d| d.as_secs())
-                .unwrap_or(0);
-            let retry_after = reset_time.saturating_sub(current_time);
+    }
+}
 
-            let mut headers = axum::http::HeaderMap::new();
-            if let Ok(retry_header) = axum::http::HeaderValue::from_str(&retry_after.to_string()) {
-                headers.insert("retry-after", retry_header);
-            }
+/// Builds the JSON summary, telling how old the underlying data is so consumers
+/// can judge its freshness: `age_seconds` since it was fetched from GitHub, and
+/// `stale` once it outlived the cache TTL.
+fn langs_summary_json(
+    username: &str,
+    percentages: &[(LanguageStat, f64)],
+    stale: bool,
+    age: Duration,
+) -> serde_json::Value {
+    serde_json::json!({
+        "username": username,
+        "languages": percentages
+            .iter()
+            .map(|(stat, value)| serde_json::json!({
+                "name": stat.name,
+                "percent": (value * 100.0).round() / 100.0,
+            }))
+            .collect::<Vec<_>>(),
+        "age_seconds": age.as_secs(),
+        "stale": stale,
+    })
+}
 
-            let message = format!(
-                "Rate limit protection active: {} requests remaining, reset at {}",
-                remaining, reset_time
-            );
-            let error_card = ErrorCard::new(message);
-            let svg = error_card.render();
+/// Builds the [LangsCard] ranking the summary languages, with the languages card
+/// defaults applied to the omitted weights.
+fn langs_summary_card(
+    q: &LangsSummaryQuery,
+    defaults: &LangsDefaults,
+    stats: Vec<LanguageStat>,
+) -> LangsCard {
+    let (size_weight, count_weight) = ranking_weights(q.size_weight, q.count_weight, defaults);
+    LangsCard {
+        stats,
+        size_weight,
+        count_weight,
+        max_languages: Some(q.n.unwrap_or(DEFAULT_SUMMARY_LANGUAGES)),
+        normalize_weights: parse_toggle(q.normalize_weights.as_deref()),
+        normalize_percentages: parse_toggle(q.normalize.as_deref()),
+        ..Default::default()
+    }
+}
 
-            headers.insert(
-                header::CONTENT_TYPE,
-                header::HeaderValue::from_static("image/svg+xml"),
-            );
+/// Resolves the ranking weights of a request. A single weight ranks by that measure
+/// only, so `count_weight=1` alone ranks by repository count, while a request with
+/// neither keeps the instance-wide defaults.
+fn ranking_weights(
+    size_weight: Option<f64>,
+    count_weight: Option<f64>,
+    defaults: &LangsDefaults,
+) -> (Option<f64>, Option<f64>) {
+    match (size_weight, count_weight) {
+        (None, None) => (defaults.size_weight, defaults.count_weight),
+        (Some(size_weight), None) => (Some(size_weight), Some(0.0)),
+        (None, Some(count_weight)) => (Some(0.0), Some(count_weight)),
+        weights => weights,
+    }
+}
 
-            return (StatusCode::TOO_MANY_REQUESTS, headers, svg).into_response();
-        }
+/// Formats the ranked languages as `Rust 40%, Go 30%, Python 15%`.
+fn format_langs_summary(percentages: &[(LanguageStat, f64)]) -> String {
+    percentages
+        .iter()
+        .map(|(stat, value)| format!("{} {value:.0}%", stat.name))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Builds a [LangsCard] holding the layout and ranking options of the request,
+/// falling back to the instance-wide defaults for the omitted ones, and to the
+/// layout's own default for `max_languages`.
+fn langs_card_options(q: &LangsCardQuery, defaults: &LangsDefaults) -> LangsCard {
+    let layout = q
+        .layout
+        .map(LayoutType::from)
+        .or(defaults.layout)
+        .unwrap_or(LayoutType::Vertical);
+
+    let (size_weight, count_weight) = ranking_weights(q.size_weight, q.count_weight, defaults);
+    LangsCard {
+        layout,
+        size_weight,
+        count_weight,
+        max_languages: Some(
+            q.max_languages
+                .or(defaults.max_languages)
+                .unwrap_or(layout.default_max_languages()),
+        ),
+        normalize_weights: parse_toggle(q.normalize_weights.as_deref()),
+        normalize_percentages: parse_toggle(q.normalize.as_deref()),
+        auto_contrast: parse_toggle(q.auto_contrast.as_deref()),
+        sort: q.sort.map(LangsSort::from).unwrap_or_default(),
+        min_bar_px: parse_min_bar_px(q.min_bar_px.as_deref()),
+        legend_only: parse_toggle(q.legend_only.as_deref()),
+        ..Default::default()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StreakCardQuery {
+    // required
+    username: String,
+    // flattened common settings
+    #[serde(flatten)]
+    settings: CardSettingsQuery,
+    // response format: the SVG itself, a data URI, a PNG or a WebP, negotiated from `Accept` when omitted
+    format: Option<String>,
+    // size of the PNG or WebP relative to the SVG, clamped
+    scale: Option<String>,
+}
+
+/// Current streak, longest streak and total contributions of the user over the
+/// contribution calendar of the last year.
+#[tracing::instrument(name = "streak_card_request", skip(headers, token), fields(username = %q.username))]
+async fn get_streak_card(
+    headers: HeaderMap,
+    token: Option<Extension<GitHubToken>>,
+    Query(q): Query<StreakCardQuery>,
+) -> impl IntoResponse {
+    sentry::configure_scope(|scope| scope.set_tag("card_type", "streak"));
+
+    if let Err(e) = validate_username(&q.username) {
+        return error_response(StatusCode::BAD_REQUEST, &e);
+    }
+    if !is_username_allowed(&q.username) {
+        return error_response(
+            StatusCode::FORBIDDEN,
+            "Username not authorized to access this service",
+        );
+    }
+
+    let format = match negotiate_format(q.format.as_deref(), &headers) {
+        Ok(format) => format,
+        Err(e) => return error_response(StatusCode::NOT_ACCEPTABLE, &e),
+    };
+    let settings = match q.settings.into_settings() {
+        Ok(settings) => settings,
+        Err(e) => return error_response(StatusCode::BAD_REQUEST, &e),
+    };
+    let settings_header = card_settings_header(&settings, &[]);
+
+    // Create GitHub API client
+    let github_api = github_api(token);
+    let Cached {
+        value: days, stale, ..
+    } = match github_api
+        .fetch_cached_contribution_calendar(&q.username)
+        .await
+    {
+        Ok(cached) => cached,
         Err(e) => {
-            // Report all other unexpected errors to Sentry
-            sentry::capture_error(&e);
-            tracing::error!("GitHub API error: {e}");
-            return error_response(
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "Failed to fetch user languages",
+            return github_error_response(
+                e,
+                &q.username,
+                "contribution calendar",
+                github_api.clock(),
             );
         }
     };
 
-    let svg = LangsCard {
+    let streak_card = StreakCard {
         card_settings: settings,
-        layout: q.layout.unwrap_or(LayoutTypeQuery::Vertical).into(),
-        stats: language_stats,
-        size_weight: q.size_weight,
-        count_weight: q.count_weight,
-        max_languages: q.max_languages,
+        username: q.username,
+        stats: StreakStats::from_days(&days),
+    };
+    let svg = match streak_card.render() {
+        Ok(svg) => svg,
+        Err(e) => return card_error_response(e, "streak"),
+    };
+
+    let mut response = card_response(svg, format, parse_scale(q.scale.as_deref())).await;
+    if let Some(value) = settings_header {
+        response.headers_mut().insert(CARD_SETTINGS_HEADER, value);
     }
-    .render();
+    if stale {
+        response
+            .headers_mut()
+            .insert(STALE_HEADER, header::HeaderValue::from_static("true"));
+    }
+    response
+}
 
-    svg_response(svg)
+#[derive(Debug, Deserialize)]
+pub struct OrgStatsCardQuery {
+    // required
+    org: String,
+    // flattened common settings
+    #[serde(flatten)]
+    settings: CardSettingsQuery,
+    // response format: the SVG itself, a data URI, a PNG or a WebP, negotiated from `Accept` when omitted
+    format: Option<String>,
+    // size of the PNG or WebP relative to the SVG, clamped
+    scale: Option<String>,
 }
 
-#[tracing::instrument(level = "trace")]
-async fn get_health() -> impl IntoResponse {
-    let rate_limit = get_github_rate_limit();
-    let cache = get_github_cache();
-    let cache_stats = cache.stats();
+#[tracing::instrument(name = "org_stats_card_request", skip(headers, token), fields(org = %q.org))]
+async fn get_org_stats_card(
+    headers: HeaderMap,
+    token: Option<Extension<GitHubToken>>,
+    Query(q): Query<OrgStatsCardQuery>,
+) -> impl IntoResponse {
+    sentry::configure_scope(|scope| scope.set_tag("card_type", "org_stats"));
 
-    let health_data = serde_json::json!({
-        "status": "OK",
-        "app_version": env!("CARGO_PKG_VERSION"),
-        "github_ratelimit": {
-            "limit": rate_limit.limit,
-            "remaining": rate_limit.remaining,
-            "used": rate_limit.used,
-            "reset": rate_limit.reset
-        },
-        "cache": {
-            "total_entries": cache_stats.entry_count,
-            "total_size_bytes": cache_stats.weighted_size,
+    if let Err(e) = validate_username(&q.org) {
+        return error_response(StatusCode::BAD_REQUEST, &e);
+    }
+    if !is_username_allowed(&q.org) {
+        return error_response(
+            StatusCode::FORBIDDEN,
+            "Organization not authorized to access this service",
+        );
+    }
+
+    let format = match negotiate_format(q.format.as_deref(), &headers) {
+        Ok(format) => format,
+        Err(e) => return error_response(StatusCode::NOT_ACCEPTABLE, &e),
+    };
+    let settings = match q.settings.into_settings() {
+        Ok(settings) => settings,
+        Err(e) => return error_response(StatusCode::BAD_REQUEST, &e),
+    };
+    let settings_header = card_settings_header(&settings, &[]);
+
+    // Create GitHub API client
+    let github_api = github_api(token);
+    let Cached {
+        value: org_stats,
+        stale,
+        ..
+    } = match github_api.fetch_cached_org_stats(&q.org).await {
+        Ok(cached) => cached,
+        Err(e) => {
+            return github_error_response(e, &q.org, "organization statistics", github_api.clock());
+        }
+    };
+
+    let svg = match org_stats.to_stats_card(settings).render() {
+        Ok(svg) => svg,
+        Err(e) => return card_error_response(e, "organization stats"),
+    };
+
+    let mut response = card_response(svg, format, parse_scale(q.scale.as_deref())).await;
+    if let Some(value) = settings_header {
+        response.headers_mut().insert(CARD_SETTINGS_HEADER, value);
+    }
+    if stale {
+        response
+            .headers_mut()
+            .insert(STALE_HEADER, header::HeaderValue::from_static("true"));
+    }
+    response
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OrgLangsCardQuery {
+    // required
+    org: String,
+    // flattened common settings
+    #[serde(flatten)]
+    settings: CardSettingsQuery,
+    layout: Option<LayoutTypeQuery>,
+    size_weight: Option<f64>,
+    count_weight: Option<f64>,
+    max_languages: Option<u64>,
+    // response format: the SVG itself, a data URI, a PNG or a WebP, negotiated from `Accept` when omitted
+    format: Option<String>,
+    // size of the PNG or WebP relative to the SVG, clamped
+    scale: Option<String>,
+}
+
+/// Languages of the organization repositories, fetched along with the organization
+/// statistics and sharing their cache entry.
+#[tracing::instrument(name = "org_langs_card_request", skip(headers, token), fields(org = %q.org))]
+async fn get_org_langs_card(
+    headers: HeaderMap,
+    token: Option<Extension<GitHubToken>>,
+    Query(q): Query<OrgLangsCardQuery>,
+) -> impl IntoResponse {
+    sentry::configure_scope(|scope| scope.set_tag("card_type", "org_languages"));
+
+    if let Err(e) = validate_username(&q.org) {
+        return error_response(StatusCode::BAD_REQUEST, &e);
+    }
+    if !is_username_allowed(&q.org) {
+        return error_response(
+            StatusCode::FORBIDDEN,
+            "Organization not authorized to access this service",
+        );
+    }
+
+    let format = match negotiate_format(q.format.as_deref(), &headers) {
+        Ok(format) => format,
+        Err(e) => return error_response(StatusCode::NOT_ACCEPTABLE, &e),
+    };
+    let settings = match q.settings.into_settings() {
+        Ok(settings) => settings,
+        Err(e) => return error_response(StatusCode::BAD_REQUEST, &e),
+    };
+
+    let defaults = &get_web_config().langs_defaults;
+    let layout = q
+        .layout
+        .map(LayoutType::from)
+        .or(defaults.layout)
+        .unwrap_or(LayoutType::Vertical);
+    let (size_weight, count_weight) = ranking_weights(q.size_weight, q.count_weight, defaults);
+    let settings_header = card_settings_header(&settings, &[("layout", layout.name())]);
+
+    // Create GitHub API client
+    let github_api = github_api(token);
+    let Cached {
+        value: org_stats,
+        stale,
+        ..
+    } = match github_api.fetch_cached_org_stats(&q.org).await {
+        Ok(cached) => cached,
+        Err(e) => {
+            return github_error_response(e, &q.org, "organization languages", github_api.clock());
+        }
+    };
+
+    let langs_card = LangsCard {
+        card_settings: settings,
+        layout,
+        stats: org_stats.languages,
+        size_weight,
+        count_weight,
+        max_languages: Some(
+            q.max_languages
+                .or(defaults.max_languages)
+                .unwrap_or(layout.default_max_languages()),
+        ),
+        ..Default::default()
+    };
+    let svg = match langs_card.render() {
+        Ok(svg) => svg,
+        Err(e) => return card_error_response(e, "organization languages"),
+    };
+
+    let mut response = card_response(svg, format, parse_scale(q.scale.as_deref())).await;
+    if let Some(value) = settings_header {
+        response.headers_mut().insert(CARD_SETTINGS_HEADER, value);
+    }
+    if stale {
+        response
+            .headers_mut()
+            .insert(STALE_HEADER, header::HeaderValue::from_static("true"));
+    }
+    response
+}
+
+#[tracing::instrument(level = "trace")]
+async fn get_theme_colors(Path(name): Path<String>) -> impl IntoResponse {
+    match CardTheme::from_name(&name) {
+        Some(theme) => Json(serde_json::json!({
+            "theme": theme.name(),
+            "colors": theme.colors(),
+        }))
+        .into_response(),
+        None => error_response(StatusCode::NOT_FOUND, &format!("unknown theme: {name}")),
+    }
+}
+
+#[tracing::instrument(level = "trace")]
+async fn get_health() -> impl IntoResponse {
+    let rate_limit = get_github_rate_limit();
+    let cache = get_github_cache();
+    let cache_stats = cache.stats();
+
+    let health_data = serde_json::json!({
+        "status": "OK",
+        "app_version": env!("CARGO_PKG_VERSION"),
+        "github_ratelimit": {
+            "limit": rate_limit.limit,
+            "remaining": rate_limit.remaining,
+            "used": rate_limit.used,
+            "reset": rate_limit.reset
+        },
+        "cache": {
+            "total_entries": cache_stats.entry_count,
+            "total_size_bytes": cache_stats.weighted_size,
             "stats_entries": cache_stats.stats_cache_entries,
             "stats_size_bytes": cache_stats.stats_cache_size,
             "languages_entries": cache_stats.languages_cache_entries,
-            "languages_size_bytes": cache_stats.languages_cache_size
+            "languages_size_bytes": cache_stats.languages_cache_size,
+            "profiles_entries": cache_stats.profiles_cache_entries,
+            "profiles_size_bytes": cache_stats.profiles_cache_size,
+            "orgs_entries": cache_stats.orgs_cache_entries,
+            "orgs_size_bytes": cache_stats.orgs_cache_size,
+            "avatar_colors_entries": cache_stats.avatar_colors_cache_entries,
+            "avatar_colors_size_bytes": cache_stats.avatar_colors_cache_size,
+            "calendars_entries": cache_stats.calendars_cache_entries,
+            "calendars_size_bytes": cache_stats.calendars_cache_size,
+            "lifetime_commits_entries": cache_stats.lifetime_commits_cache_entries,
+            "lifetime_commits_size_bytes": cache_stats.lifetime_commits_cache_size
+        },
+        "config": health_config(
+            get_web_config(),
+            &CacheConfig::from_env(),
+            is_token_configured()
+        )
+    });
+
+    Json(health_data)
+}
+
+/// Summarizes the effective runtime configuration for the health JSON.
+/// Secrets are only reported as present or not, never with their values.
+fn health_config(
+    web_config: &WebConfig,
+    cache_config: &CacheConfig,
+    token_configured: bool,
+) -> serde_json::Value {
+    serde_json::json!({
+        "cache": {
+            "max_capacity_mb": cache_config.max_capacity_mb,
+            "user_stats_ttl_seconds": cache_config.user_stats_ttl.as_secs(),
+            "user_languages_ttl_seconds": cache_config.user_languages_ttl.as_secs(),
+            "swr_seconds": cache_config.swr_window.as_secs(),
+            "fail_open_seconds": cache_config.fail_open_window.as_secs()
+        },
+        "card_cache_control": web_config.card_cache_control(),
+        "rate_limit_threshold": rate_limit_threshold(),
+        "default_theme": CardSettings::default().theme.name(),
+        "max_concurrent_requests": web_config.max_concurrent_requests,
+        "request_body_limit_bytes": web_config.request_body_limit,
+        "keep_alive": web_config.keep_alive,
+        "header_read_timeout_seconds": web_config.header_read_timeout.as_secs(),
+        "coalesce_requests": web_config.coalesce_requests,
+        "github_token_configured": token_configured
+    })
+}
+
+/// Response header echoing the effective settings a card was rendered with.
+const CARD_SETTINGS_HEADER: &str = "x-card-settings";
+
+/// Response header set when the card is rendered from expired cached data,
+/// either while it's being refreshed or because GitHub could not be reached.
+pub(crate) const STALE_HEADER: &str = "x-stale";
+
+/// Helper function to build the compact `key=value` value of the [CARD_SETTINGS_HEADER]
+/// from the effective (post-defaults) settings and any card specific `extra` pairs.
+fn card_settings_header(
+    settings: &CardSettings,
+    extra: &[(&str, &str)],
+) -> Option<header::HeaderValue> {
+    let mut pairs = vec![
+        format!("theme={}", settings.theme.name()),
+        format!("offset_x={}", settings.offset_x),
+        format!("offset_y={}", settings.offset_y),
+        format!("hide_title={}", settings.hide_title),
+        format!("hide_background={}", settings.hide_background),
+        format!("hide_background_stroke={}", settings.hide_background_stroke),
+        format!("title_size={}", settings.title_size),
+    ];
+    pairs.extend(extra.iter().map(|(key, value)| format!("{key}={value}")));
+
+    header::HeaderValue::from_str(&pairs.join("; ")).ok()
+}
+
+/// Helper function to create a response with SVG content and appropriate headers
+fn svg_response(svg: String) -> Response {
+    image_response(svg.into_bytes(), "image/svg+xml")
+}
+
+/// Helper function to create a response with a rendered card of the given content type
+fn image_response(body: Vec<u8>, content_type: &'static str) -> Response {
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        header::CONTENT_TYPE,
+        header::HeaderValue::from_static(content_type),
+    );
+    headers.insert(
+        header::CONTENT_LENGTH,
+        header::HeaderValue::from(body.len()),
+    );
+    insert_cache_headers(&mut headers, &body);
+    (StatusCode::OK, headers, body).into_response()
+}
+
+/// Helper function to create a response text with the SVG encoded as a `data:` URI
+fn datauri_response(svg: String) -> Response {
+    let uri = format!("data:image/svg+xml;base64,{}", BASE64_STANDARD.encode(svg));
+
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        header::CONTENT_TYPE,
+        header::HeaderValue::from_static("text/plain; charset=utf-8"),
+    );
+    headers.insert(header::CONTENT_LENGTH, header::HeaderValue::from(uri.len()));
+    insert_cache_headers(&mut headers, uri.as_bytes());
+    (StatusCode::OK, headers, uri).into_response()
+}
+
+/// Adds the `Cache-Control` of the rendered cards and the weak `ETag` of their `body`.
+fn insert_cache_headers(headers: &mut HeaderMap, body: &[u8]) {
+    if let Ok(cache_control) = header::HeaderValue::from_str(&get_web_config().card_cache_control())
+    {
+        headers.insert(header::CACHE_CONTROL, cache_control);
+    }
+    if let Ok(etag) = header::HeaderValue::from_str(&weak_etag(body)) {
+        headers.insert(header::ETAG, etag);
+    }
+}
+
+/// Weak `ETag` of a card body (`W/"<hash>"`): the bytes are compared, not their encoding.
+fn weak_etag(body: &[u8]) -> String {
+    use std::hash::{DefaultHasher, Hasher};
+
+    let mut hasher = DefaultHasher::new();
+    hasher.write(body);
+    format!("W/\"{:016x}\"", hasher.finish())
+}
+
+/// Tells whether the `If-None-Match` value lists `etag`, compared weakly (`W/` ignored).
+fn etag_matches(if_none_match: &str, etag: &str) -> bool {
+    let opaque = |tag: &str| tag.trim().trim_start_matches("W/").to_string();
+    let etag = opaque(etag);
+    if_none_match
+        .split(',')
+        .any(|tag| tag.trim() == "*" || opaque(tag) == etag)
+}
+
+/// Helper function to respond with a rendered card in the negotiated format
+async fn card_response(svg: String, format: ResponseFormat, scale: f32) -> Response {
+    let mut response = match format {
+        ResponseFormat::Svg => svg_response(svg),
+        ResponseFormat::DataUri => datauri_response(svg),
+        ResponseFormat::Png => raster_response(svg, scale, raster::svg_to_png, "image/png").await,
+        ResponseFormat::WebP => {
+            raster_response(svg, scale, raster::svg_to_webp, "image/webp").await
+        }
+    };
+    // The format may come from the Accept header, caches must tell them apart
+    response
+        .headers_mut()
+        .insert(header::VARY, header::HeaderValue::from_static("accept"));
+    response
+}
+
+/// Responds with the card rasterized by `rasterize` into `content_type`, or with the SVG
+/// when it can't be.
+///
+/// Rasterizing takes milliseconds of CPU, even more at high scales, so it runs on the
+/// blocking pool instead of stalling the other requests of the worker thread.
+async fn raster_response(
+    svg: String,
+    scale: f32,
+    rasterize: fn(&str, f32) -> Result<Vec<u8>, raster::RasterError>,
+    content_type: &'static str,
+) -> Response {
+    let rendered = tokio::task::spawn_blocking(move || {
+        let image = rasterize(&svg, scale);
+        (svg, image)
+    })
+    .await;
+
+    match rendered {
+        Ok((_, Ok(image))) => image_response(image, content_type),
+        Ok((svg, Err(e))) => {
+            tracing::warn!("Serving the card as SVG, it can't be rendered as {content_type}: {e}");
+            svg_response(svg)
+        }
+        // Rasterizing panicked, as it would have without the blocking pool
+        Err(e) => std::panic::resume_unwind(e.into_panic()),
+    }
+}
+
+/// Output format of the card endpoints.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ResponseFormat {
+    /// The SVG itself
+    Svg,
+    /// The SVG as a base64 `data:` URI in a plain text body
+    DataUri,
+    /// The SVG rasterized, served as SVG when it can't be
+    Png,
+    /// The SVG rasterized to lossless WebP, served as SVG when it can't be
+    WebP,
+}
+
+impl ResponseFormat {
+    /// Format requested by a media range of the `Accept` header, if supported.
+    fn from_media_range(range: &str) -> Option<Self> {
+        match range {
+            "image/svg+xml" | "image/*" | "*/*" => Some(ResponseFormat::Svg),
+            "text/plain" => Some(ResponseFormat::DataUri),
+            _ => None,
+        }
+    }
+
+    /// Rasterized format requested by a media range of the `Accept` header, if supported.
+    fn raster_from_media_range(range: &str) -> Option<Self> {
+        match range {
+            "image/png" => Some(ResponseFormat::Png),
+            "image/webp" => Some(ResponseFormat::WebP),
+            _ => None,
+        }
+    }
+}
+
+/// Decides the format of a card response from the `format` query parameter,
+/// falling back to the `Accept` header and then to SVG.
+///
+/// Fails (for a 406 response) on formats the cards can't be rendered in yet,
+/// or when the `Accept` header allows none of the supported ones.
+fn negotiate_format(format: Option<&str>, headers: &HeaderMap) -> Result<ResponseFormat, String> {
+    match format.map(str::trim) {
+        Some("svg") => return Ok(ResponseFormat::Svg),
+        Some("datauri") => return Ok(ResponseFormat::DataUri),
+        Some("png") => return Ok(ResponseFormat::Png),
+        Some("webp") => return Ok(ResponseFormat::WebP),
+        Some(other @ "json") => {
+            return Err(format!(
+                "format {other} is not supported, expected svg, datauri, png or webp"
+            ));
+        }
+        Some(other) => {
+            return Err(format!(
+                "unknown format: {other}, expected svg, datauri, png or webp"
+            ));
+        }
+        None => {}
+    }
+
+    let Some(accept) = headers
+        .get(header::ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .filter(|v| !v.trim().is_empty())
+    else {
+        return Ok(ResponseFormat::Svg);
+    };
+
+    // Media ranges by decreasing quality, the header order breaking ties
+    let mut ranges: Vec<(String, f32)> = accept
+        .split(',')
+        .filter_map(|item| {
+            let mut parts = item.split(';');
+            let range = parts.next()?.trim().to_ascii_lowercase();
+            let quality = parts
+                .filter_map(|param| param.trim().strip_prefix("q="))
+                .find_map(|q| q.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+            (!range.is_empty() && quality > 0.0).then_some((range, quality))
+        })
+        .collect();
+    ranges.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    // Browsers accept image/webp or image/png before SVG for any image, a rasterized
+    // card is only picked when the client can't take the SVG
+    ranges
+        .iter()
+        .find_map(|(range, _)| ResponseFormat::from_media_range(range))
+        .or_else(|| {
+            ranges
+                .iter()
+                .find_map(|(range, _)| ResponseFormat::raster_from_media_range(range))
+        })
+        .ok_or_else(|| {
+            "no acceptable format, the cards are served as image/svg+xml, text/plain, image/png or image/webp"
+                .to_string()
+        })
+}
+
+/// Message of an ErrorCard response, kept to render it again in the requested [ErrorStyle].
+#[derive(Debug, Clone)]
+struct ErrorMessage(String);
+
+/// Helper function to create an error response with ErrorCard SVG and appropriate status code
+fn error_response(status: StatusCode, message: &str) -> Response {
+    let mut response = error_card_response(
+        status,
+        HeaderMap::new(),
+        ErrorCard::new(message.to_string()),
+    );
+    response
+        .extensions_mut()
+        .insert(ErrorMessage(message.to_string()));
+    response
+}
+
+/// Renders the ErrorCard into a response with the given status and headers.
+fn error_card_response(status: StatusCode, mut headers: HeaderMap, card: ErrorCard) -> Response {
+    let svg = match card.render() {
+        Ok(svg) => svg,
+        Err(e) => {
+            // The error card uses fixed settings, so this only happens on a layout bug
+            sentry::capture_error(&e);
+            tracing::error!("Failed to render error card: {e}");
+            return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response();
+        }
+    };
+
+    headers.insert(
+        header::CONTENT_TYPE,
+        header::HeaderValue::from_static("image/svg+xml"),
+    );
+    headers.insert(header::CONTENT_LENGTH, header::HeaderValue::from(svg.len()));
+    (status, headers, svg).into_response()
+}
+
+/// Parses `error_style`: `full` (default) or `compact`.
+fn parse_error_style(value: &str) -> Result<ErrorStyle, String> {
+    match value {
+        "full" => Ok(ErrorStyle::Full),
+        "compact" => Ok(ErrorStyle::Compact),
+        _ => Err(format!(
+            "unknown error_style: {value}, expected full or compact"
+        )),
+    }
+}
+
+/// How the errors are reported, set by the `on_error` parameter.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum OnError {
+    /// An ErrorCard SVG, shown in place of the card by the READMEs embedding it
+    #[default]
+    Card,
+    /// A `{"error": message}` JSON body, for programmatic callers
+    Json,
+}
+
+/// Parses `on_error`: `card` (default) or `json`.
+fn parse_on_error(value: &str) -> Result<OnError, String> {
+    match value {
+        "card" => Ok(OnError::Card),
+        "json" => Ok(OnError::Json),
+        _ => Err(format!("unknown on_error: {value}, expected card or json")),
+    }
+}
+
+/// Renders the ErrorCard responses again with the `error_style` of the query, or as
+/// JSON with `on_error=json`, keeping their status and headers.
+async fn apply_error_style(request: Request<Body>, next: Next) -> Response {
+    #[derive(Default, Deserialize)]
+    struct ErrorStyleQuery {
+        error_style: Option<String>,
+        on_error: Option<String>,
+    }
+
+    // Malformed queries are left to the route extractor to reject
+    let query = Query::<ErrorStyleQuery>::try_from_uri(request.uri())
+        .map(|Query(q)| q)
+        .unwrap_or_default();
+    let style = match query.error_style.as_deref().map(parse_error_style) {
+        Some(Ok(style)) => style,
+        Some(Err(e)) => return error_response(StatusCode::BAD_REQUEST, &e),
+        None => ErrorStyle::default(),
+    };
+    let on_error = match query.on_error.as_deref().map(parse_on_error) {
+        Some(Ok(on_error)) => on_error,
+        Some(Err(e)) => return error_response(StatusCode::BAD_REQUEST, &e),
+        None => OnError::default(),
+    };
+
+    let response = next.run(request).await;
+    if style != ErrorStyle::Compact && on_error != OnError::Json {
+        return response;
+    }
+    let Some(ErrorMessage(message)) = response.extensions().get::<ErrorMessage>().cloned() else {
+        return response;
+    };
+
+    let (mut parts, _) = response.into_parts();
+    match on_error {
+        OnError::Card => {
+            error_card_response(parts.status, parts.headers, ErrorCard::compact(message))
+        }
+        OnError::Json => {
+            // Set again for the JSON body
+            parts.headers.remove(header::CONTENT_TYPE);
+            parts.headers.remove(header::CONTENT_LENGTH);
+            let body = Json(serde_json::json!({ "error": message }));
+            (parts.status, parts.headers, body).into_response()
+        }
+    }
+}
+
+/// Longest a coalesced response is kept, should the request rendering it be dropped
+/// between the render and its removal.
+const COALESCED_RESPONSE_TTL: Duration = Duration::from_secs(30);
+
+/// A rendered response, copied to the requests waiting for it.
+#[derive(Debug, Clone)]
+struct CoalescedResponse {
+    status: StatusCode,
+    headers: HeaderMap,
+    body: axum::body::Bytes,
+    error: Option<ErrorMessage>,
+}
+
+impl CoalescedResponse {
+    async fn from_response(response: Response) -> Self {
+        let (parts, body) = response.into_parts();
+        let error = parts.extensions.get::<ErrorMessage>().cloned();
+        match axum::body::to_bytes(body, usize::MAX).await {
+            Ok(body) => Self {
+                status: parts.status,
+                headers: parts.headers,
+                body,
+                error,
+            },
+            Err(e) => {
+                tracing::error!("Failed to read the response to coalesce: {e}");
+                let message = "Failed to render the card".to_string();
+                Self {
+                    status: StatusCode::INTERNAL_SERVER_ERROR,
+                    headers: HeaderMap::new(),
+                    body: message.clone().into(),
+                    error: Some(ErrorMessage(message)),
+                }
+            }
+        }
+    }
+
+    fn into_response(self) -> Response {
+        let mut response = (self.status, self.headers, self.body).into_response();
+        if let Some(error) = self.error {
+            response.extensions_mut().insert(error);
+        }
+        response
+    }
+}
+
+/// Responses being rendered, by the normalized request they answer.
+type InFlight = moka::future::Cache<String, CoalescedResponse>;
+
+fn in_flight() -> &'static InFlight {
+    static IN_FLIGHT: std::sync::OnceLock<InFlight> = std::sync::OnceLock::new();
+    IN_FLIGHT.get_or_init(|| {
+        moka::future::Cache::builder()
+            .time_to_live(COALESCED_RESPONSE_TTL)
+            .build()
+    })
+}
+
+/// Identifies the response of a request: its path, sorted query pairs and `Accept`
+/// header, which the format is negotiated from.
+fn coalescing_key(request: &Request<Body>) -> String {
+    let mut pairs = Query::<Vec<(String, String)>>::try_from_uri(request.uri())
+        .map(|Query(pairs)| pairs)
+        .unwrap_or_default();
+    pairs.sort();
+    let query: Vec<String> = pairs
+        .iter()
+        .map(|(name, value)| format!("{name:?}={value:?}"))
+        .collect();
+    let accept = request
+        .headers()
+        .get(header::ACCEPT)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    format!("{} {} {accept:?}", request.uri().path(), query.join("&"))
+}
+
+/// Runs `render` for the first of the concurrent requests with the same `key`, the
+/// others await its response rather than rendering it again.
+async fn coalesce<F>(in_flight: &InFlight, key: String, render: F) -> Response
+where
+    F: Future<Output = Response>,
+{
+    let entry = in_flight
+        .entry_by_ref(&key)
+        .or_insert_with(async { CoalescedResponse::from_response(render.await).await })
+        .await;
+    // Only in flight until rendered, later requests render again
+    if entry.is_fresh() {
+        in_flight.invalidate(&key).await;
+    }
+    entry.into_value().into_response()
+}
+
+/// Coalesces the identical concurrent card requests when enabled. Requests with their
+/// own GitHub token are rendered apart, their data depends on the token.
+async fn coalesce_requests(request: Request<Body>, next: Next) -> Response {
+    if !get_web_config().coalesce_requests || request.extensions().get::<GitHubToken>().is_some() {
+        return next.run(request).await;
+    }
+    let key = coalescing_key(&request);
+    coalesce(in_flight(), key, next.run(request)).await
+}
+
+/// Answers `304 Not Modified` when the `If-None-Match` of the request lists the `ETag` of
+/// the rendered card, keeping its caching headers but not the body.
+async fn not_modified(request: Request<Body>, next: Next) -> Response {
+    let if_none_match = request
+        .headers()
+        .get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+    let response = next.run(request).await;
+
+    let (Some(if_none_match), Some(etag)) = (
+        if_none_match,
+        response
+            .headers()
+            .get(header::ETAG)
+            .and_then(|v| v.to_str().ok()),
+    ) else {
+        return response;
+    };
+    if response.status() != StatusCode::OK || !etag_matches(&if_none_match, etag) {
+        return response;
+    }
+
+    let mut headers = HeaderMap::new();
+    for name in [
+        header::ETAG,
+        header::CACHE_CONTROL,
+        header::VARY,
+        header::HeaderName::from_static(STALE_HEADER),
+    ] {
+        if let Some(value) = response.headers().get(&name) {
+            headers.insert(name, value.clone());
+        }
+    }
+    (StatusCode::NOT_MODIFIED, headers).into_response()
+}
+
+/// Helper function to map a [CardError] of a card that could not be rendered
+/// with the requested settings into an ErrorCard response.
+fn card_error_response(error: CardError, card_type: &str) -> Response {
+    sentry::capture_error(&error);
+    tracing::error!("Failed to render {card_type} card: {error}");
+    error_response(
+        StatusCode::INTERNAL_SERVER_ERROR,
+        &format!("Failed to render the card: {error}"),
+    )
+}
+
+/// Creates the GitHub API client of a request, using the token of its `X-GitHub-Token`
+/// header when allowed (see [crate::web::take_token_header]) or the server's one.
+fn github_api(token: Option<Extension<GitHubToken>>) -> GitHubApi {
+    match token {
+        Some(Extension(token)) => GitHubApi::with_token(token),
+        None => GitHubApi::new(),
+    }
+}
+
+/// Gives the title and icons of the card the dominant color of the avatar of `username`,
+/// keeping the theme colors when the avatar can't be fetched or has no color.
+async fn apply_avatar_accent(github_api: &GitHubApi, username: &str, settings: &mut CardSettings) {
+    match github_api.fetch_cached_avatar_color(username).await {
+        Ok(Cached {
+            value: Some(color), ..
+        }) => apply_accent(settings, &color),
+        Ok(_) => {}
+        Err(e) => tracing::warn!("Keeping the theme accent of {username}: {e}"),
+    }
+}
+
+/// Gives the title and icons of the card the accent `color`, made readable on the theme
+/// background. The `custom_css` still comes after it.
+fn apply_accent(settings: &mut CardSettings, color: &str) {
+    let color = match settings.theme.colors().background {
+        Some(background) if !settings.hide_background => {
+            ensure_contrast(color, &background, MIN_CONTRAST_RATIO)
+        }
+        _ => color.to_string(),
+    };
+
+    let accent = accent_css(&color);
+    settings.custom_css = Some(match settings.custom_css.take() {
+        Some(custom_css) => format!("{accent}\n{custom_css}"),
+        None => accent,
+    });
+}
+
+/// Seconds left until the rate limit resets at `reset_time` (unix seconds), for `Retry-After`.
+fn retry_after_secs(reset_time: u64, clock: &dyn Clock) -> u64 {
+    reset_time.saturating_sub(clock.now())
+}
+
+/// Helper function to map a [GitHubApiError] into an ErrorCard response with a fitting status code.
+///
+/// `resource` names what was being fetched (e.g. "statistics") for messages and Sentry reports.
+fn github_error_response(
+    error: GitHubApiError,
+    username: &str,
+    resource: &str,
+    clock: &dyn Clock,
+) -> Response {
+    match error {
+        GitHubApiError::UserNotFound => error_response(StatusCode::NOT_FOUND, "User not found"),
+        GitHubApiError::IsOrganization(_) => error_response(
+            StatusCode::NOT_FOUND,
+            "This is an organization; use /org-stats-card",
+        ),
+        GitHubApiError::OrgNotFound => {
+            error_response(StatusCode::NOT_FOUND, "Organization not found")
+        }
+        GitHubApiError::InvalidUsername(msg) => error_response(StatusCode::BAD_REQUEST, &msg),
+        GitHubApiError::MissingToken => error_response(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Server not configured with a GitHub token",
+        ),
+        GitHubApiError::PrivateContributionsForbidden(_) => error_response(
+            StatusCode::FORBIDDEN,
+            "include_private is only available for the token owner",
+        ),
+        GitHubApiError::RateLimitExceeded => {
+            // Report rate limit exceeded to Sentry as it's an operational issue
+            sentry::capture_message(
+                &format!("GitHub API rate limit exceeded for user: {username} ({resource})"),
+                sentry::Level::Warning,
+            );
+            error_response(
+                StatusCode::TOO_MANY_REQUESTS,
+                "GitHub API rate limit exceeded",
+            )
+        }
+        GitHubApiError::SecondaryRateLimit(retry_after) => {
+            sentry::capture_message(
+                &format!(
+                    "GitHub API secondary rate limit exceeded for user: {username} ({resource})"
+                ),
+                sentry::Level::Warning,
+            );
+            let mut response = error_response(
+                StatusCode::TOO_MANY_REQUESTS,
+                "GitHub API secondary rate limit exceeded",
+            );
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, retry_after.into());
+            response
+        }
+        GitHubApiError::RateLimitProtection(remaining, reset_time) => {
+            let retry_after = retry_after_secs(reset_time, clock);
+
+            let message = format!(
+                "Rate limit protection active: {} requests remaining, reset at {}",
+                remaining, reset_time
+            );
+            let mut response = error_response(StatusCode::TOO_MANY_REQUESTS, &message);
+            if let Ok(retry_header) = header::HeaderValue::from_str(&retry_after.to_string()) {
+                response
+                    .headers_mut()
+                    .insert(header::RETRY_AFTER, retry_header);
+            }
+            response
+        }
+        e => {
+            // Report all other unexpected errors to Sentry
+            sentry::capture_error(&e);
+            tracing::error!("GitHub API error: {e}");
+            error_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                &format!("Failed to fetch user {resource}"),
+            )
+        }
+    }
+}
+
+fn validate_username(username: &str) -> Result<(), String> {
+    if username.trim().is_empty() {
+        return Err("Username cannot be empty".to_string());
+    }
+    if username.contains(' ') {
+        return Err("Username cannot contain spaces".to_string());
+    }
+    Ok(())
+}
+
+/// Longest `empty_note` accepted from the query (in characters).
+const MAX_EMPTY_NOTE_LEN: usize = 60;
+
+/// Checks the `empty_note` is short plain text, as it's written into the SVG unescaped.
+fn parse_empty_note(value: &str) -> Result<String, String> {
+    let note = value.trim();
+    let valid = !note.is_empty()
+        && note.chars().count() <= MAX_EMPTY_NOTE_LEN
+        && note
+            .chars()
+            .all(|c| c.is_alphanumeric() || " .,:;!?'()-_/+#".contains(c));
+    if !valid {
+        return Err(format!(
+            "empty_note must be up to {MAX_EMPTY_NOTE_LEN} characters of plain text"
+        ));
+    }
+    Ok(note.to_string())
+}
+
+/// Longest stats card `title` accepted from the query (in characters).
+const MAX_TITLE_LEN: usize = 60;
+
+/// Checks the stats card `title` is a short line of text, the card escapes it.
+fn parse_title(value: &str) -> Result<String, String> {
+    let title = value.trim();
+    if title.is_empty() || title.chars().count() > MAX_TITLE_LEN {
+        return Err(format!(
+            "title must be between 1 and {MAX_TITLE_LEN} characters"
+        ));
+    }
+    if title.chars().any(char::is_control) {
+        return Err("title must not contain control characters".to_string());
+    }
+    Ok(title.to_string())
+}
+
+/// Most repositories accepted in `exclude_repo`.
+const MAX_EXCLUDE_REPOS: usize = 200;
+/// Longest repository name accepted in `exclude_repo` (GitHub's own limit).
+const MAX_REPO_NAME_LEN: usize = 100;
+
+/// Parses the comma-separated `exclude_repo` list, bounded so a huge list can't
+/// bloat the cache keys.
+fn parse_exclude_repos(value: &str) -> Result<Vec<String>, String> {
+    let repos: Vec<String> = value
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect();
+
+    if repos.len() > MAX_EXCLUDE_REPOS {
+        return Err(format!(
+            "exclude_repo accepts at most {MAX_EXCLUDE_REPOS} repositories, got {}",
+            repos.len()
+        ));
+    }
+    if let Some(repo) = repos
+        .iter()
+        .find(|repo| repo.chars().count() > MAX_REPO_NAME_LEN)
+    {
+        return Err(format!(
+            "exclude_repo names must be at most {MAX_REPO_NAME_LEN} characters, got {}",
+            repo.chars().count()
+        ));
+    }
+
+    Ok(repos)
+}
+
+/// Checks a language (or language group) name supplied in the query.
+fn is_valid_language_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= 64
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || " +#-._'*/".contains(c))
+}
+
+/// Parses `lang_colors` (e.g. `Rust:ff0000,Go:#00ffff`) into a map of lowercase
+/// language names to `#`-prefixed hex colors.
+fn parse_lang_colors(value: &str) -> Result<HashMap<String, String>, String> {
+    let mut overrides = HashMap::new();
+
+    for entry in value.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        let invalid = || format!("invalid lang_colors entry: {entry}");
+
+        let (name, color) = entry.rsplit_once(':').ok_or_else(invalid)?;
+        let name = name.trim();
+        let hex = color.trim().trim_start_matches('#');
+
+        let color = parse_hex_color(hex).ok_or_else(invalid)?;
+        if !is_valid_language_name(name) {
+            return Err(invalid());
+        }
+
+        overrides.insert(name.to_lowercase(), color);
+    }
+
+    Ok(overrides)
+}
+
+/// Normalizes a 3 or 6 digits hex color, without its `#`, to its lowercase `#` form.
+fn parse_hex_color(hex: &str) -> Option<String> {
+    let valid = matches!(hex.len(), 3 | 6) && hex.chars().all(|c| c.is_ascii_hexdigit());
+    valid.then(|| format!("#{}", hex.to_lowercase()))
+}
+
+/// Parses `other_color` (e.g. `ededed` or `#ededed`).
+fn parse_other_color(value: &str) -> Result<String, String> {
+    parse_hex_color(value.trim().trim_start_matches('#'))
+        .ok_or_else(|| format!("invalid other_color: {value}, expected a hex color"))
+}
+
+/// Source of the accent color of a card.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Accent {
+    /// Dominant color of the user's avatar
+    Avatar,
+}
+
+/// Parses `accent`, only `avatar` is supported for now.
+fn parse_accent(value: &str) -> Result<Accent, String> {
+    match value.trim() {
+        "avatar" => Ok(Accent::Avatar),
+        _ => Err(format!("invalid accent: {value}, expected avatar")),
+    }
+}
+
+/// Parses the `name` parameter, a percentage between 0 and 100 (e.g. `combine_below`).
+fn parse_percent(name: &str, value: &str) -> Result<f64, String> {
+    match value.trim().parse::<f64>() {
+        Ok(percent) if (0.0..=100.0).contains(&percent) => Ok(percent),
+        _ => Err(format!(
+            "invalid {name}: {value}, expected a percentage between 0 and 100"
+        )),
+    }
+}
+
+/// Largest decoded `custom_css` accepted from the query (in bytes).
+const MAX_CUSTOM_CSS_BYTES: usize = 2048;
+
+/// Constructs rejected in `custom_css`, compared case-insensitively. At-rules pull in
+/// other resources or fonts, `url(` and `expression` load or run things, and `<`/`&`
+/// could end the `<style>` element or inject entities. Backslash escapes are rejected
+/// as they can spell any of those in disguise.
+const FORBIDDEN_CSS: [&str; 8] = [
+    "@",
+    "url(",
+    "expression",
+    "javascript:",
+    "-moz-binding",
+    "<",
+    "&",
+    "\\",
+];
+
+/// Decodes the base64 `custom_css` parameter and checks it only holds plain CSS rules.
+fn parse_custom_css(value: &str) -> Result<String, String> {
+    // Leave some room for the base64 overhead before decoding anything
+    if value.len() > MAX_CUSTOM_CSS_BYTES.div_ceil(3) * 4 {
+        return Err(format!(
+            "custom_css must be at most {MAX_CUSTOM_CSS_BYTES} bytes"
+        ));
+    }
+
+    // An unescaped `+` of the base64 alphabet arrives as a space
+    let decoded = BASE64_STANDARD
+        .decode(value.replace(' ', "+").trim())
+        .map_err(|_| "custom_css must be base64-encoded".to_string())?;
+    let css = String::from_utf8(decoded).map_err(|_| "custom_css must be UTF-8".to_string())?;
+    if css.len() > MAX_CUSTOM_CSS_BYTES {
+        return Err(format!(
+            "custom_css must be at most {MAX_CUSTOM_CSS_BYTES} bytes"
+        ));
+    }
+
+    let lowercase = css.to_lowercase();
+    if let Some(forbidden) = FORBIDDEN_CSS.iter().find(|f| lowercase.contains(*f)) {
+        return Err(format!("custom_css must not contain {forbidden}"));
+    }
+    if css.chars().any(|c| c.is_control() && !c.is_whitespace()) {
+        return Err("custom_css must not contain control characters".to_string());
+    }
+
+    Ok(css.trim().to_string())
+}
+
+/// A named group of languages shown as a single entry on the languages card.
+#[derive(Debug, Clone, PartialEq)]
+struct LangGroup {
+    name: String,
+    /// Lowercase names of the grouped languages, in the order given.
+    members: Vec<String>,
+}
+
+/// Parses `group_langs` (e.g. `JS/TS=JavaScript,TypeScript;C/C++=C,C++`) into
+/// language groups. A language can only belong to one group.
+fn parse_lang_groups(value: &str) -> Result<Vec<LangGroup>, String> {
+    let mut groups: Vec<LangGroup> = Vec::new();
+
+    for spec in value.split(';').map(str::trim).filter(|s| !s.is_empty()) {
+        let invalid = || format!("invalid group_langs entry: {spec}");
+
+        let (name, members) = spec.split_once('=').ok_or_else(invalid)?;
+        let name = name.trim();
+        let members: Vec<String> = members
+            .split(',')
+            .map(|m| m.trim().to_lowercase())
+            .filter(|m| !m.is_empty())
+            .collect();
+
+        if !is_valid_language_name(name) || members.is_empty() {
+            return Err(invalid());
+        }
+        if let Some(member) = members.iter().find(|m| {
+            groups.iter().any(|g| g.members.contains(m))
+                || members.iter().filter(|other| other == m).count() > 1
+        }) {
+            return Err(format!("language {member} is in more than one group"));
+        }
+
+        groups.push(LangGroup {
+            name: name.to_string(),
+            members,
+        });
+    }
+
+    Ok(groups)
+}
+
+/// Merges the stats of the grouped languages into a single entry per group, summing
+/// their sizes and repository counts. A group without a color override of its own
+/// takes the color of its first listed member.
+fn group_languages(
+    stats: Vec<LanguageStat>,
+    groups: &[LangGroup],
+    color_overrides: &mut HashMap<String, String>,
+) -> Vec<LanguageStat> {
+    if groups.is_empty() {
+        return stats;
+    }
+
+    let mut grouped: Vec<LanguageStat> = Vec::with_capacity(stats.len());
+    // The merged entry of every group along with the name of its first listed member
+    let mut merged: Vec<Option<(LanguageStat, String)>> = vec![None; groups.len()];
+
+    for stat in stats {
+        let lowercase = stat.name.to_lowercase();
+        let Some(index) = groups.iter().position(|g| g.members.contains(&lowercase)) else {
+            grouped.push(stat);
+            continue;
+        };
+
+        let is_first_member = groups[index].members[0] == lowercase;
+        match &mut merged[index] {
+            Some((group, first_member)) => {
+                group.size_bytes += stat.size_bytes;
+                group.repo_count += stat.repo_count;
+                if is_first_member {
+                    *first_member = stat.name;
+                }
+            }
+            None => {
+                let first_member = stat.name.clone();
+                merged[index] = Some((stat, first_member));
+            }
+        }
+    }
+
+    for (group, entry) in groups.iter().zip(merged) {
+        let Some((mut stat, first_member)) = entry else {
+            continue;
+        };
+
+        let key = group.name.to_lowercase();
+        if !color_overrides.contains_key(&key) {
+            let color = color_overrides
+                .get(&first_member.to_lowercase())
+                .cloned()
+                .unwrap_or_else(|| gel_language_color(&first_member));
+            color_overrides.insert(key, color);
+        }
+
+        stat.name = group.name.clone();
+        grouped.push(stat);
+    }
+
+    grouped
+}
+
+/// Earliest `year` the commits can be counted in, GitHub launched in 2008
+const MIN_COMMITS_YEAR: i32 = 2008;
+
+/// Parses the `year` parameter, which can't be later than the current year
+/// told by the `clock`.
+fn parse_commits_year(value: &str, clock: &dyn Clock) -> Result<i32, String> {
+    let current_year = current_utc_year_with(clock);
+    match value.trim().parse::<i32>() {
+        Ok(year) if (MIN_COMMITS_YEAR..=current_year).contains(&year) => Ok(year),
+        _ => Err(format!(
+            "year must be between {MIN_COMMITS_YEAR} and {current_year}, got {value}"
+        )),
+    }
+}
+
+/// Parses `commits_year`, only `all` is supported: a single year is set with `year`.
+fn parse_commits_all_years(value: &str) -> Result<bool, String> {
+    match value.trim() {
+        "all" => Ok(true),
+        _ => Err(format!("invalid commits_year: {value}, expected all")),
+    }
+}
+
+/// Parses `affiliations`, a comma-separated list of `owner`, `collaborator` and
+/// `organization_member`, at least one of them.
+fn parse_affiliations(value: &str) -> Result<RepositoryAffiliations, String> {
+    let mut affiliations = RepositoryAffiliations {
+        owner: false,
+        collaborator: false,
+        organization_member: false,
+    };
+    for token in value.split(',').map(str::trim).filter(|t| !t.is_empty()) {
+        match token {
+            "owner" => affiliations.owner = true,
+            "collaborator" => affiliations.collaborator = true,
+            "organization_member" => affiliations.organization_member = true,
+            _ => {
+                return Err(format!(
+                    "invalid affiliation: {token}, expected owner, collaborator or organization_member"
+                ));
+            }
+        }
+    }
+    if !(affiliations.owner || affiliations.collaborator || affiliations.organization_member) {
+        return Err(
+            "affiliations must list at least one of owner, collaborator or organization_member"
+                .to_string(),
+        );
+    }
+    Ok(affiliations)
+}
+
+/// Parses the `max_repos` parameter, at least one repository has to be counted.
+fn parse_max_repos(value: &str) -> Result<u32, String> {
+    match value.trim().parse::<u32>() {
+        Ok(max_repos) if max_repos > 0 => Ok(max_repos),
+        _ => Err(format!("max_repos must be a positive number, got {value}")),
+    }
+}
+
+/// Parses `contribution_types`: `all` or a comma-separated list of
+/// [ContributionType] names (e.g. `commits,reviews`).
+fn parse_contribution_types(value: &str) -> Result<Vec<ContributionType>, String> {
+    if value.trim() == "all" {
+        return Ok(ContributionType::ALL.to_vec());
+    }
+
+    let mut types = Vec::new();
+    for token in value.split(',').map(str::trim).filter(|t| !t.is_empty()) {
+        let contribution_type = ContributionType::ALL
+            .into_iter()
+            .find(|t| t.name() == token)
+            .ok_or_else(|| format!("unknown contribution type: {token}"))?;
+        if !types.contains(&contribution_type) {
+            types.push(contribution_type);
+        }
+    }
+
+    if types.is_empty() {
+        return Err("contribution_types must name at least one type".to_string());
+    }
+    Ok(types)
+}
+
+fn is_username_allowed(username: &str) -> bool {
+    // Get allowed usernames from environment variable
+    let allowed_usernames = std::env::var("ALLOWED_USERNAMES").unwrap_or_default();
+
+    // If empty or not set, allow all users
+    if allowed_usernames.trim().is_empty() {
+        return true;
+    }
+
+    // Check if username is in the allowed list (case-insensitive)
+    allowed_usernames
+        .split(',')
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+        .any(|allowed| allowed.eq_ignore_ascii_case(username))
+}
+
+// Build the ThemeQuery enum from the macro
+build_theme_query!();
+
+/// Common query parameters for building [CardSettings] reused across card endpoints.
+#[derive(Debug, Default, Deserialize)]
+struct CardSettingsQuery {
+    // common optional visuals
+    offset_x: Option<String>,
+    offset_y: Option<String>,
+    theme: Option<ThemeQuery>,
+    hide_title: Option<String>,
+    hide_background: Option<String>,
+    hide_background_stroke: Option<String>,
+    // alias of hide_background_stroke, which wins when both are supplied
+    hide_border: Option<String>,
+    // shorthand for hide_background=true&hide_background_stroke=true, works with any theme
+    transparent: Option<String>,
+    // clamp the rendered card width
+    min_width: Option<String>,
+    max_width: Option<String>,
+    // title font size, clamped to a readable range
+    title_size: Option<String>,
+    // overlay layout guides, meant for working on the card geometry
+    debug: Option<String>,
+    // base64-encoded CSS rules appended after the theme style
+    custom_css: Option<String>,
+    // center the card in a fixed size canvas: `aspect=og` or explicit dimensions
+    aspect: Option<String>,
+    canvas_width: Option<String>,
+    canvas_height: Option<String>,
+    // parameters unknown to the card, to reject misspelled visual toggles
+    #[serde(flatten)]
+    unknown: HashMap<String, String>,
+}
+
+/// Boolean visual toggles shared by the cards, parsed with [parse_toggle].
+/// Other `hide_*`/`show_*` parameters are rejected instead of silently ignored.
+const VISUAL_TOGGLES: [&str; 6] = [
+    "hide_title",
+    "hide_background",
+    "hide_background_stroke",
+    "hide_border",
+    "transparent",
+    "debug",
+];
+
+/// Parses a boolean toggle, only `true` enables it.
+fn parse_toggle(value: Option<&str>) -> bool {
+    value == Some("true")
+}
+
+/// Fails with a message naming the first unknown `hide_*`/`show_*` toggle among `params`.
+fn reject_unknown_toggles<'a>(params: impl IntoIterator<Item = &'a String>) -> Result<(), String> {
+    match params
+        .into_iter()
+        .filter(|name| name.starts_with("hide_") || name.starts_with("show_"))
+        .min()
+    {
+        Some(name) => Err(format!(
+            "unknown toggle: {name}, expected one of: {}",
+            VISUAL_TOGGLES.join(", ")
+        )),
+        None => Ok(()),
+    }
+}
+
+impl CardSettingsQuery {
+    /// Largest `offset_x`/`offset_y` accepted from the query (in pixels).
+    const MAX_OFFSET: i64 = 100;
+    /// Largest `canvas_width`/`canvas_height` accepted from the query (in pixels).
+    const MAX_CANVAS_SIZE: u32 = 4096;
+
+    /// Builds the [CardSettings], failing with a message for out-of-range offsets,
+    /// invalid widths or unknown toggles.
+    fn into_settings(self) -> Result<CardSettings, String> {
+        reject_unknown_toggles(self.unknown.keys())?;
+        let canvas = self.canvas()?;
+        let min_width = Self::parse_width("min_width", self.min_width.as_deref())?;
+        let max_width = Self::parse_width("max_width", self.max_width.as_deref())?;
+        if let (Some(min), Some(max)) = (min_width, max_width)
+            && min > max
+        {
+            return Err(format!(
+                "min_width can't be greater than max_width, got {min} and {max}"
+            ));
+        }
+        let theme = self.theme.map(|t| t.into()).unwrap_or(CardTheme::Light);
+        // Transparent themes are meant to be used without a background, unless asked otherwise
+        let transparent = match self.transparent.as_deref() {
+            Some(value) => parse_toggle(Some(value)),
+            None => theme.is_transparent(),
+        };
+
+        Ok(CardSettings {
+            offset_x: Self::parse_offset("offset_x", self.offset_x.as_deref())?,
+            offset_y: Self::parse_offset("offset_y", self.offset_y.as_deref())?,
+            theme,
+            hide_title: parse_toggle(self.hide_title.as_deref()),
+            hide_background: transparent || parse_toggle(self.hide_background.as_deref()),
+            hide_background_stroke: transparent
+                || parse_toggle(
+                    self.hide_background_stroke
+                        .as_deref()
+                        .or(self.hide_border.as_deref()),
+                ),
+            min_width,
+            max_width,
+            title_size: Self::parse_title_size(self.title_size.as_deref())?,
+            debug: parse_toggle(self.debug.as_deref()),
+            custom_css: self
+                .custom_css
+                .as_deref()
+                .map(parse_custom_css)
+                .transpose()?,
+            canvas,
+        })
+    }
+
+    /// Resolves the canvas from `aspect` or the explicit `canvas_width`/`canvas_height`,
+    /// which take precedence.
+    fn canvas(&self) -> Result<Option<Canvas>, String> {
+        let dimension = |name: &str, value: &str| match value.parse::<u32>() {
+            Ok(size @ 1..=Self::MAX_CANVAS_SIZE) => Ok(size),
+            _ => Err(format!(
+                "{name} must be between 1 and {}, got {value}",
+                Self::MAX_CANVAS_SIZE
+            )),
+        };
+
+        match (
+            self.canvas_width.as_deref(),
+            self.canvas_height.as_deref(),
+            self.aspect.as_deref(),
+        ) {
+            (Some(width), Some(height), _) => Ok(Some(Canvas {
+                width: dimension("canvas_width", width)?,
+                height: dimension("canvas_height", height)?,
+            })),
+            (Some(_), None, _) | (None, Some(_), _) => {
+                Err("canvas_width and canvas_height must be set together".to_string())
+            }
+            (None, None, Some("og")) => Ok(Some(Canvas::OPEN_GRAPH)),
+            (None, None, Some(aspect)) => Err(format!("unknown aspect: {aspect}")),
+            (None, None, None) => Ok(None),
+        }
+    }
+
+    /// Parses an offset, non-numeric values fall back to the default one.
+    /// Parses a `min_width`/`max_width`, a positive number of pixels.
+    fn parse_width(name: &str, value: Option<&str>) -> Result<Option<u32>, String> {
+        match value {
+            None => Ok(None),
+            Some(value) => match value.parse::<u32>() {
+                Ok(width @ 1..) => Ok(Some(width)),
+                _ => Err(format!("{name} must be a positive number, got {value}")),
+            },
+        }
+    }
+
+    /// Parses the `title_size`, clamped to the readable range.
+    fn parse_title_size(value: Option<&str>) -> Result<u32, String> {
+        match value {
+            None => Ok(Card::TITLE_FONT_SIZE),
+            Some(value) => value
+                .parse::<i64>()
+                .map(|size| {
+                    size.clamp(
+                        Card::MIN_TITLE_FONT_SIZE.into(),
+                        Card::MAX_TITLE_FONT_SIZE.into(),
+                    ) as u32
+                })
+                .map_err(|_| format!("title_size must be a number, got {value}")),
+        }
+    }
+
+    fn parse_offset(name: &str, value: Option<&str>) -> Result<u32, String> {
+        match value.and_then(|s| s.parse::<i64>().ok()) {
+            Some(offset @ 0..=Self::MAX_OFFSET) => Ok(offset as u32),
+            Some(offset) => Err(format!(
+                "{name} must be between 0 and {}, got {offset}",
+                Self::MAX_OFFSET
+            )),
+            None => Ok(12),
+        }
+    }
+}
+
+/// Parses a number of pixels clamped to `min..=max`. Non-numeric values fall back to `default`.
+fn parse_clamped(value: Option<&str>, min: u32, max: u32, default: u32) -> u32 {
+    value
+        .and_then(|s| s.parse::<u32>().ok())
+        .map(|v| v.clamp(min, max))
+        .unwrap_or(default)
+}
+
+/// Parses the PNG `scale`, clamped to the supported range. Non-numeric values fall
+/// back to the SVG size.
+fn parse_scale(value: Option<&str>) -> f32 {
+    value
+        .and_then(|s| s.trim().parse::<f32>().ok())
+        .filter(|v| v.is_finite())
+        .map(|v| v.clamp(raster::MIN_SCALE, raster::MAX_SCALE))
+        .unwrap_or(1.0)
+}
+
+/// Parses the stats card `icon_size`, clamped to the supported range. Non-numeric
+/// values fall back to the default size.
+fn parse_icon_size(value: Option<&str>) -> u32 {
+    parse_clamped(
+        value,
+        StatsCard::MIN_ICON_SIZE,
+        StatsCard::MAX_ICON_SIZE,
+        StatsCard::DEFAULT_ICON_SIZE,
+    )
+}
+
+/// Parses the stats card `overflow` into [StatsCard::max_rows]: `columns` wraps the
+/// rows after `max_rows` (clamped, like [parse_icon_size]) into the next column, `down`
+/// keeps them all in one.
+fn parse_overflow(overflow: Option<&str>, max_rows: Option<&str>) -> Result<Option<u32>, String> {
+    match overflow.map(str::trim) {
+        None | Some("down") => Ok(None),
+        Some("columns") => Ok(Some(parse_clamped(
+            max_rows,
+            1,
+            StatsCard::MAX_MAX_ROWS,
+            StatsCard::DEFAULT_MAX_ROWS,
+        ))),
+        Some(other) => Err(format!(
+            "invalid overflow: {other}, expected down or columns"
+        )),
+    }
+}
+
+/// Parses the languages card `min_bar_px`, clamped like [parse_icon_size].
+fn parse_min_bar_px(value: Option<&str>) -> u32 {
+    parse_clamped(
+        value,
+        0,
+        LangsCard::MAX_MIN_BAR_PX,
+        LangsCard::DEFAULT_MIN_BAR_PX,
+    )
+}
+
+/// Parses the stats card `label_gap`, clamped like [parse_icon_size].
+fn parse_label_gap(value: Option<&str>) -> u32 {
+    parse_clamped(
+        value,
+        StatsCard::MIN_LABEL_GAP,
+        StatsCard::MAX_LABEL_GAP,
+        StatsCard::DEFAULT_LABEL_GAP,
+    )
+}
+
+/// Parses the stats card `value_gap`, clamped like [parse_icon_size].
+fn parse_value_gap(value: Option<&str>) -> u32 {
+    parse_clamped(
+        value,
+        StatsCard::MIN_VALUE_GAP,
+        StatsCard::MAX_VALUE_GAP,
+        StatsCard::DEFAULT_VALUE_GAP,
+    )
+}
+
+/// Resolves the stats to hide from the `hide` list of the request. Without one,
+/// every stat outside the configured `default_stats` is hidden.
+fn stats_to_hide<'a>(
+    hide: Option<&'a str>,
+    default_stats: Option<&HashSet<HideStat>>,
+) -> Result<HashSet<HideStat>, &'a str> {
+    match (hide, default_stats) {
+        (Some(hide), _) => parse_hide_list(hide),
+        (None, Some(default_stats)) => Ok(HideStat::ALL
+            .into_iter()
+            .filter(|stat| !default_stats.contains(stat))
+            .collect()),
+        (None, None) => Ok(HashSet::new()),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+enum StatsSortQuery {
+    #[serde(rename = "value_desc")]
+    ValueDesc,
+    #[serde(rename = "value_asc")]
+    ValueAsc,
+}
+
+impl From<StatsSortQuery> for StatsSort {
+    fn from(sort: StatsSortQuery) -> Self {
+        match sort {
+            StatsSortQuery::ValueDesc => StatsSort::ValueDesc,
+            StatsSortQuery::ValueAsc => StatsSort::ValueAsc,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+enum LangsSortQuery {
+    #[serde(rename = "rank")]
+    Rank,
+    #[serde(rename = "alpha")]
+    Alpha,
+}
+
+impl From<LangsSortQuery> for LangsSort {
+    fn from(sort: LangsSortQuery) -> Self {
+        match sort {
+            LangsSortQuery::Rank => LangsSort::Rank,
+            LangsSortQuery::Alpha => LangsSort::Alpha,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+enum LayoutTypeQuery {
+    #[serde(rename = "vertical")]
+    Vertical,
+    #[serde(rename = "horizontal")]
+    Horizontal,
+    #[serde(rename = "donut")]
+    Donut,
+}
+
+impl From<LayoutTypeQuery> for LayoutType {
+    fn from(layout: LayoutTypeQuery) -> Self {
+        match layout {
+            LayoutTypeQuery::Vertical => LayoutType::Vertical,
+            LayoutTypeQuery::Horizontal => LayoutType::Horizontal,
+            LayoutTypeQuery::Donut => LayoutType::Donut,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode},
+    };
+    use http_body_util::BodyExt as _; // for collect()
+    use tower::ServiceExt; // for oneshot()
+
+    // Tests for the helper function that builds an SVG response
+    mod fn_svg_response {
+        use super::*;
+
+        #[tokio::test]
+        async fn returns_svg_with_correct_headers_and_body() {
+            let svg = "<svg xmlns=\"http://www.w3.org/2000/svg\"></svg>".to_string();
+            let resp = svg_response(svg.clone());
+
+            assert_eq!(resp.status(), StatusCode::OK);
+            let content_type = resp
+                .headers()
+                .get(header::CONTENT_TYPE)
+                .and_then(|v| v.to_str().ok())
+                .unwrap_or("");
+            assert_eq!(content_type, "image/svg+xml");
+
+            let bytes = resp.into_body().collect().await.unwrap().to_bytes();
+            assert_eq!(bytes, svg);
+        }
+
+        #[tokio::test]
+        async fn sets_cache_hints_for_proxies() {
+            let svg = "<svg xmlns=\"http://www.w3.org/2000/svg\"></svg>".to_string();
+            let resp = svg_response(svg.clone());
+
+            let cache_control = resp
+                .headers()
+                .get(header::CACHE_CONTROL)
+                .and_then(|v| v.to_str().ok())
+                .unwrap_or("");
+            assert!(cache_control.contains("public"));
+            assert!(cache_control.contains("no-transform"));
+
+            let content_length = resp
+                .headers()
+                .get(header::CONTENT_LENGTH)
+                .and_then(|v| v.to_str().ok())
+                .unwrap_or("");
+            assert_eq!(content_length, svg.len().to_string());
+        }
+    }
+
+    // Tests for the helper function that builds a data URI response
+    mod fn_datauri_response {
+        use super::*;
+
+        #[tokio::test]
+        async fn returns_base64_data_uri_of_the_svg() {
+            let svg = "<svg xmlns=\"http://www.w3.org/2000/svg\"></svg>".to_string();
+            let resp = card_response(svg.clone(), ResponseFormat::DataUri, 1.0).await;
+
+            assert_eq!(resp.status(), StatusCode::OK);
+            assert_eq!(resp.headers()[header::VARY], "accept");
+            let content_type = resp
+                .headers()
+                .get(header::CONTENT_TYPE)
+                .and_then(|v| v.to_str().ok())
+                .unwrap_or("");
+            assert!(content_type.starts_with("text/plain"));
+
+            let body = resp.into_body().collect().await.unwrap().to_bytes();
+            let body_str = String::from_utf8(body.to_vec()).unwrap();
+            let encoded = body_str
+                .strip_prefix("data:image/svg+xml;base64,")
+                .expect("data URI prefix");
+            let decoded = BASE64_STANDARD.decode(encoded).unwrap();
+            assert_eq!(decoded, svg.as_bytes());
+        }
+    }
+
+    // Tests for the helper function that rasterizes the PNG cards
+    mod fn_card_response {
+        use super::*;
+
+        #[tokio::test]
+        async fn png_falls_back_to_svg_when_it_cant_be_rasterized() {
+            let svg = "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"10\" height=\"10\"><style>rect { fill: var(--c); }</style><rect width=\"10\" height=\"10\"/></svg>".to_string();
+            let resp = card_response(svg.clone(), ResponseFormat::Png, 1.0).await;
+
+            assert_eq!(resp.status(), StatusCode::OK);
+            assert_eq!(resp.headers()[header::CONTENT_TYPE], "image/svg+xml");
+            assert_eq!(resp.headers()[header::VARY], "accept");
+            let bytes = resp.into_body().collect().await.unwrap().to_bytes();
+            assert_eq!(bytes, svg);
+        }
+
+        /// Pixel area of the PNG response of `svg` rasterized at `scale`
+        async fn png_area(svg: &str, scale: f32) -> u64 {
+            let resp = card_response(svg.to_string(), ResponseFormat::Png, scale).await;
+            assert_eq!(resp.headers()[header::CONTENT_TYPE], "image/png");
+            let png = resp.into_body().collect().await.unwrap().to_bytes();
+            let dimension =
+                |at: usize| u64::from(u32::from_be_bytes(png[at..at + 4].try_into().unwrap()));
+            dimension(16) * dimension(20)
+        }
+
+        #[tokio::test]
+        async fn webp_is_served_as_image_webp() {
+            let svg = crate::cards::examples::stats_example(CardTheme::Dark).unwrap();
+            let resp = card_response(svg, ResponseFormat::WebP, 1.0).await;
+
+            assert_eq!(resp.status(), StatusCode::OK);
+            assert_eq!(resp.headers()[header::CONTENT_TYPE], "image/webp");
+            let webp = resp.into_body().collect().await.unwrap().to_bytes();
+            assert_eq!(&webp[..4], b"RIFF");
+            assert_eq!(&webp[8..12], b"WEBP");
+        }
+
+        #[tokio::test]
+        async fn rasterizes_on_the_blocking_pool() {
+            fn thread_id(_: &str, _: f32) -> Result<Vec<u8>, raster::RasterError> {
+                Ok(format!("{:?}", std::thread::current().id()).into_bytes())
+            }
+
+            let resp = raster_response(String::new(), 1.0, thread_id, "image/png").await;
+            let rasterized_on = resp.into_body().collect().await.unwrap().to_bytes();
+            let handled_on = format!("{:?}", std::thread::current().id());
+            assert_ne!(rasterized_on, handled_on.as_bytes());
+        }
+
+        #[tokio::test]
+        async fn png_of_scale_2_has_4_times_the_pixels() {
+            let svg = crate::cards::examples::stats_example(CardTheme::Dark).unwrap();
+            let area = png_area(&svg, 1.0).await;
+            let scaled = png_area(&svg, 2.0).await;
+            assert!(scaled.abs_diff(4 * area) <= area / 100, "{area} {scaled}");
+        }
+    }
+
+    // Tests for the helper function parsing the PNG scale
+    mod fn_parse_scale {
+        use super::*;
+
+        #[test]
+        fn defaults_to_the_svg_size() {
+            for value in [None, Some("big"), Some("NaN"), Some("inf")] {
+                assert_eq!(parse_scale(value), 1.0, "{value:?}");
+            }
+        }
+
+        #[test]
+        fn clamps_to_the_supported_range() {
+            assert_eq!(parse_scale(Some("2.5")), 2.5);
+            assert_eq!(parse_scale(Some("0.1")), raster::MIN_SCALE);
+            assert_eq!(parse_scale(Some("10")), raster::MAX_SCALE);
+        }
+    }
+
+    // Tests for the helper function that decides the format of the card responses
+    mod fn_negotiate_format {
+        use super::*;
+
+        fn accept(value: &str) -> HeaderMap {
+            HeaderMap::from_iter([(
+                header::ACCEPT,
+                header::HeaderValue::from_str(value).unwrap(),
+            )])
+        }
+
+        #[test]
+        fn defaults_to_svg() {
+            assert_eq!(
+                negotiate_format(None, &HeaderMap::new()),
+                Ok(ResponseFormat::Svg)
+            );
+            assert_eq!(
+                negotiate_format(None, &accept(" ")),
+                Ok(ResponseFormat::Svg)
+            );
+        }
+
+        #[test]
+        fn format_param_selects_the_format() {
+            assert_eq!(
+                negotiate_format(Some("svg"), &HeaderMap::new()),
+                Ok(ResponseFormat::Svg)
+            );
+            assert_eq!(
+                negotiate_format(Some("datauri"), &HeaderMap::new()),
+                Ok(ResponseFormat::DataUri)
+            );
+            assert_eq!(
+                negotiate_format(Some("png"), &HeaderMap::new()),
+                Ok(ResponseFormat::Png)
+            );
+        }
+
+        #[test]
+        fn format_param_overrides_accept() {
+            assert_eq!(
+                negotiate_format(Some("datauri"), &accept("image/svg+xml")),
+                Ok(ResponseFormat::DataUri)
+            );
+            assert_eq!(
+                negotiate_format(Some("svg"), &accept("image/png")),
+                Ok(ResponseFormat::Svg)
+            );
+        }
+
+        #[test]
+        fn webp_format_param_is_supported() {
+            assert_eq!(
+                negotiate_format(Some("webp"), &accept("image/svg+xml")),
+                Ok(ResponseFormat::WebP)
+            );
+        }
+
+        #[test]
+        fn unsupported_format_param_fails() {
+            assert_eq!(
+                negotiate_format(Some("json"), &HeaderMap::new()),
+                Err("format json is not supported, expected svg, datauri, png or webp".to_string())
+            );
+            assert_eq!(
+                negotiate_format(Some("gif"), &accept("*/*")),
+                Err("unknown format: gif, expected svg, datauri, png or webp".to_string())
+            );
+        }
+
+        #[test]
+        fn accept_selects_the_format() {
+            for value in [
+                "image/svg+xml",
+                "image/*",
+                "*/*",
+                "IMAGE/SVG+XML; charset=utf-8",
+            ] {
+                assert_eq!(
+                    negotiate_format(None, &accept(value)),
+                    Ok(ResponseFormat::Svg),
+                    "{value}"
+                );
+            }
+            assert_eq!(
+                negotiate_format(None, &accept("text/plain")),
+                Ok(ResponseFormat::DataUri)
+            );
+        }
+
+        #[test]
+        fn accept_prefers_the_highest_quality() {
+            assert_eq!(
+                negotiate_format(None, &accept("image/svg+xml;q=0.5, text/plain")),
+                Ok(ResponseFormat::DataUri)
+            );
+            assert_eq!(
+                negotiate_format(None, &accept("text/plain, image/svg+xml")),
+                Ok(ResponseFormat::DataUri)
+            );
+            // Browsers loading an <img>
+            assert_eq!(
+                negotiate_format(None, &accept("image/avif,image/webp,image/apng,*/*;q=0.8")),
+                Ok(ResponseFormat::Svg)
+            );
+        }
+
+        #[test]
+        fn accept_selects_a_raster_format_only_without_svg() {
+            assert_eq!(
+                negotiate_format(None, &accept("image/png")),
+                Ok(ResponseFormat::Png)
+            );
+            assert_eq!(
+                negotiate_format(None, &accept("image/webp, image/png;q=0.5")),
+                Ok(ResponseFormat::WebP)
+            );
+            assert_eq!(
+                negotiate_format(None, &accept("image/webp;q=0.5, image/png")),
+                Ok(ResponseFormat::Png)
+            );
+            // Safari loading an <img>
+            assert_eq!(
+                negotiate_format(
+                    None,
+                    &accept("image/webp,image/png,image/svg+xml;q=0.8,image/*;q=0.8,*/*;q=0.5")
+                ),
+                Ok(ResponseFormat::Svg)
+            );
+        }
+
+        #[test]
+        fn accept_without_a_supported_format_fails() {
+            for value in ["image/avif, application/json", "image/svg+xml;q=0"] {
+                assert!(negotiate_format(None, &accept(value)).is_err(), "{value}");
+            }
+        }
+    }
+
+    mod fn_github_api {
+        use super::*;
+
+        #[test]
+        fn uses_the_request_token_when_given() {
+            let token = GitHubToken::new("ghp_secret".to_string());
+            assert!(github_api(Some(Extension(token))).uses_request_token());
+            assert!(!github_api(None).uses_request_token());
+        }
+    }
+
+    mod fn_retry_after_secs {
+        use super::*;
+        use crate::github::FixedClock;
+
+        #[test]
+        fn counts_down_to_the_reset() {
+            let clock = FixedClock(1_700_000_000);
+            assert_eq!(retry_after_secs(1_700_000_060, &clock), 60);
+            assert_eq!(retry_after_secs(1_700_000_000, &clock), 0);
+            // A reset in the past doesn't underflow
+            assert_eq!(retry_after_secs(1_699_999_000, &clock), 0);
+        }
+    }
+
+    // Tests for the helper function that maps GitHub API errors into responses
+    mod fn_github_error_response {
+        use super::*;
+        use crate::github::{FixedClock, SystemClock};
+
+        #[tokio::test]
+        async fn missing_token_returns_503_error_card() {
+            let resp = github_error_response(
+                GitHubApiError::MissingToken,
+                "octocat",
+                "statistics",
+                &SystemClock,
+            );
+
+            assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+            let content_type = resp
+                .headers()
+                .get(header::CONTENT_TYPE)
+                .and_then(|v| v.to_str().ok())
+                .unwrap_or("");
+            assert_eq!(content_type, "image/svg+xml");
+
+            let body = resp.into_body().collect().await.unwrap().to_bytes();
+            let body_str = String::from_utf8(body.to_vec()).unwrap();
+            assert!(body_str.starts_with("<svg"));
+            assert!(body_str.contains("Server not configured with a GitHub"));
+        }
+
+        #[tokio::test]
+        async fn organization_returns_404_error_card() {
+            let resp = github_error_response(
+                GitHubApiError::IsOrganization("rust-lang".to_string()),
+                "rust-lang",
+                "statistics",
+                &SystemClock,
+            );
+
+            assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+            let body = resp.into_body().collect().await.unwrap().to_bytes();
+            let body_str = String::from_utf8(body.to_vec()).unwrap();
+            assert!(body_str.contains("This is an organization; use"));
+        }
+
+        #[tokio::test]
+        async fn rate_limit_protection_sets_retry_after() {
+            let resp = github_error_response(
+                GitHubApiError::RateLimitProtection(42, 1_700_000_060),
+                "octocat",
+                "languages",
+                &FixedClock(1_700_000_000),
+            );
+
+            assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+            assert_eq!(resp.headers()[header::RETRY_AFTER], "60");
+        }
+
+        #[tokio::test]
+        async fn secondary_rate_limit_sets_retry_after() {
+            let resp = github_error_response(
+                GitHubApiError::SecondaryRateLimit(30),
+                "octocat",
+                "statistics",
+                &SystemClock,
+            );
+
+            assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+            assert_eq!(resp.headers()[header::RETRY_AFTER], "30");
+        }
+
+        #[tokio::test]
+        async fn private_contributions_for_other_user_returns_403() {
+            let resp = github_error_response(
+                GitHubApiError::PrivateContributionsForbidden("octocat".to_string()),
+                "octocat",
+                "statistics",
+                &SystemClock,
+            );
+
+            assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+        }
+    }
+
+    // Tests for the helper function that builds the x-card-settings header
+    mod fn_card_error_response {
+        use super::*;
+
+        #[tokio::test]
+        async fn returns_500_error_card() {
+            let error = Card::new(
+                10,
+                10,
+                String::new(),
+                String::new(),
+                String::new(),
+                String::new(),
+                CardSettings::default(),
+            )
+            .err()
+            .expect("tiny card should fail validation");
+            let resp = card_error_response(error, "stats");
+
+            assert_eq!(resp.status(), StatusCode::INTERNAL_SERVER_ERROR);
+            let content_type = resp
+                .headers()
+                .get(header::CONTENT_TYPE)
+                .and_then(|v| v.to_str().ok())
+                .unwrap_or("");
+            assert_eq!(content_type, "image/svg+xml");
+
+            let body = resp.into_body().collect().await.unwrap().to_bytes();
+            let body_str = String::from_utf8(body.to_vec()).unwrap();
+            assert!(body_str.starts_with("<svg"));
+            assert!(body_str.contains("Failed to render the card"));
+        }
+    }
+
+    mod fn_stats_to_hide {
+        use super::*;
+
+        fn card() -> StatsCard {
+            StatsCard {
+                username: "octocat".to_string(),
+                stars_count: Some(1),
+                commits_ytd_count: Some(2),
+                issues_count: Some(3),
+                pull_requests_count: Some(4),
+                merge_requests_count: Some(5),
+                reviews_count: Some(6),
+                started_discussions_count: Some(7),
+                answered_discussions_count: Some(8),
+                ..Default::default()
+            }
+        }
+
+        #[test]
+        fn default_stats_render_without_hide_list() {
+            let defaults = HashSet::from([
+                HideStat::StarsCount,
+                HideStat::CommitsYtdCount,
+                HideStat::PullRequestsCount,
+                HideStat::IssuesCount,
+            ]);
+            let to_hide = Vec::from_iter(stats_to_hide(None, Some(&defaults)).unwrap());
+            let svg = render::render_stats_card_with(card(), &to_hide).unwrap();
+
+            for label in ["Stars", "Commits YTD", "Issues", "Pull Requests"] {
+                assert!(svg.contains(&format!(">{label}:</text>")), "{label}");
+            }
+            for label in ["Merge Requests", "Reviews", "Discussions"] {
+                assert!(!svg.contains(label), "{label}");
+            }
+        }
+
+        #[test]
+        fn hide_list_overrides_default_stats() {
+            let defaults = HashSet::from([HideStat::StarsCount, HideStat::IssuesCount]);
+            assert_eq!(
+                stats_to_hide(Some("reviews_count"), Some(&defaults)),
+                Ok(HashSet::from([HideStat::ReviewsCount]))
+            );
+            // An empty list shows every stat
+            assert_eq!(stats_to_hide(Some(""), Some(&defaults)), Ok(HashSet::new()));
+            assert_eq!(stats_to_hide(None, None), Ok(HashSet::new()));
+        }
+    }
+
+    mod fn_card_settings_header {
+        use super::*;
+
+        #[test]
+        fn reflects_effective_settings() {
+            let settings = CardSettingsQuery {
+                offset_x: None,
+                offset_y: Some("20".to_string()),
+                theme: Some(ThemeQuery::Dark),
+                hide_title: Some("true".to_string()),
+                ..Default::default()
+            }
+            .into_settings()
+            .unwrap();
+
+            let value = card_settings_header(&settings, &[("layout", "horizontal")]).unwrap();
+            let value = value.to_str().unwrap();
+
+            assert!(value.contains("theme=dark"));
+            assert!(value.contains("hide_title=true"));
+            assert!(value.contains("hide_background=false"));
+            assert!(value.contains("offset_x=12"));
+            assert!(value.contains("offset_y=20"));
+            assert!(value.ends_with("layout=horizontal"));
+        }
+    }
+
+    // Tests for turning the common query parameters into card settings
+    mod fn_into_settings {
+        use super::*;
+
+        fn settings(params: &str) -> CardSettings {
+            let uri: axum::http::Uri = format!("/?{params}").parse().unwrap();
+            Query::<CardSettingsQuery>::try_from_uri(&uri)
+                .unwrap()
+                .0
+                .into_settings()
+                .unwrap()
+        }
+
+        fn settings_error(params: &str) -> String {
+            let uri: axum::http::Uri = format!("/?{params}").parse().unwrap();
+            Query::<CardSettingsQuery>::try_from_uri(&uri)
+                .unwrap()
+                .0
+                .into_settings()
+                .unwrap_err()
+        }
+
+        #[test]
+        fn theme_is_case_insensitive_and_trimmed() {
+            assert_eq!(
+                settings("theme=Transparent_Blue").theme.name(),
+                "transparent_blue"
+            );
+            assert_eq!(settings("theme=%20dark%20").theme.name(), "dark");
+            assert_eq!(settings("theme=%20DRACULA").theme.name(), "dracula");
+        }
+
+        #[test]
+        fn unknown_theme_is_still_rejected() {
+            let uri: axum::http::Uri = "/?theme=Unknown_Theme".parse().unwrap();
+            let err = Query::<CardSettingsQuery>::try_from_uri(&uri).unwrap_err();
+            assert!(err.body_text().contains("unknown variant `Unknown_Theme`"));
+        }
+
+        #[test]
+        fn offsets_within_bounds_are_accepted() {
+            let settings = settings("offset_x=0&offset_y=100");
+            assert_eq!(settings.offset_x, 0);
+            assert_eq!(settings.offset_y, 100);
+        }
+
+        #[test]
+        fn out_of_range_offsets_are_rejected() {
+            assert_eq!(
+                settings_error("offset_x=101"),
+                "offset_x must be between 0 and 100, got 101"
+            );
+            assert_eq!(
+                settings_error("offset_y=-5"),
+                "offset_y must be between 0 and 100, got -5"
+            );
+        }
+
+        #[test]
+        fn transparent_hides_background_for_any_theme() {
+            let settings = settings("theme=dark&transparent=true");
+            assert!(settings.hide_background);
+            assert!(settings.hide_background_stroke);
+            assert_eq!(settings.theme.name(), "dark");
+
+            let svg = StatsCard {
+                card_settings: settings,
+                username: "octocat".to_string(),
+                stars_count: Some(1),
+                commits_ytd_count: Some(2),
+                ..Default::default()
+            }
+            .render()
+            .unwrap();
+            assert!(svg.starts_with("<svg"));
+            assert!(!svg.contains("<rect class=\"background\""));
+        }
+
+        #[test]
+        fn debug_overlay_only_with_debug_true() {
+            let render = |params: &str| {
+                StatsCard {
+                    card_settings: settings(params),
+                    stars_count: Some(1),
+                    commits_ytd_count: Some(2),
+                    ..Default::default()
+                }
+                .render()
+                .unwrap()
+            };
+
+            assert!(render("debug=true").contains("<g class=\"debug\""));
+            for params in ["", "debug=false", "debug=1"] {
+                assert!(!render(params).contains("class=\"debug\""), "{params}");
+            }
+        }
+
+        #[test]
+        fn hide_border_is_an_alias_of_hide_background_stroke() {
+            let render = |settings| {
+                StatsCard {
+                    card_settings: settings,
+                    stars_count: Some(1),
+                    ..Default::default()
+                }
+                .render()
+                .unwrap()
+            };
+
+            let border = render(settings("hide_border=true"));
+            let stroke = render(settings("hide_background_stroke=true"));
+            assert!(border.contains("stroke-opacity=\"0\""));
+            assert_eq!(border, stroke);
+
+            // The explicit hide_background_stroke is honored over the alias
+            assert!(
+                !settings("hide_background_stroke=false&hide_border=true").hide_background_stroke
+            );
+            assert!(
+                settings("hide_background_stroke=true&hide_border=false").hide_background_stroke
+            );
+        }
+
+        #[test]
+        fn widths_must_be_positive_numbers() {
+            assert_eq!(settings("").min_width, None);
+            let widths = settings("min_width=300&max_width=400");
+            assert_eq!((widths.min_width, widths.max_width), (Some(300), Some(400)));
+            assert_eq!(settings("min_width=400&max_width=400").max_width, Some(400));
+
+            assert_eq!(
+                settings_error("min_width=wide"),
+                "min_width must be a positive number, got wide"
+            );
+            assert_eq!(
+                settings_error("max_width=0"),
+                "max_width must be a positive number, got 0"
+            );
+            assert_eq!(
+                settings_error("max_width=-300"),
+                "max_width must be a positive number, got -300"
+            );
+            assert_eq!(
+                settings_error("min_width=500&max_width=300"),
+                "min_width can't be greater than max_width, got 500 and 300"
+            );
+        }
+
+        #[test]
+        fn title_size_is_clamped() {
+            assert_eq!(settings("").title_size, Card::TITLE_FONT_SIZE);
+            assert_eq!(settings("title_size=24").title_size, 24);
+            assert_eq!(
+                settings("title_size=2").title_size,
+                Card::MIN_TITLE_FONT_SIZE
+            );
+            assert_eq!(
+                settings("title_size=500").title_size,
+                Card::MAX_TITLE_FONT_SIZE
+            );
+            assert_eq!(
+                settings("title_size=-4").title_size,
+                Card::MIN_TITLE_FONT_SIZE
+            );
+            assert_eq!(
+                settings_error("title_size=big"),
+                "title_size must be a number, got big"
+            );
+        }
+
+        #[test]
+        fn canvas_from_aspect_or_dimensions() {
+            assert_eq!(settings("").canvas, None);
+            assert_eq!(settings("aspect=og").canvas, Some(Canvas::OPEN_GRAPH));
+            assert_eq!(
+                settings("canvas_width=800&canvas_height=400").canvas,
+                Some(Canvas {
+                    width: 800,
+                    height: 400
+                })
+            );
+            // Explicit dimensions take precedence over the aspect
+            assert_eq!(
+                settings("aspect=og&canvas_width=800&canvas_height=400").canvas,
+                Some(Canvas {
+                    width: 800,
+                    height: 400
+                })
+            );
+        }
+
+        #[test]
+        fn invalid_canvas_is_rejected() {
+            assert_eq!(settings_error("aspect=square"), "unknown aspect: square");
+            assert_eq!(
+                settings_error("canvas_width=800"),
+                "canvas_width and canvas_height must be set together"
+            );
+            assert_eq!(
+                settings_error("canvas_width=0&canvas_height=400"),
+                "canvas_width must be between 1 and 4096, got 0"
+            );
+            assert_eq!(
+                settings_error("canvas_width=800&canvas_height=5000"),
+                "canvas_height must be between 1 and 4096, got 5000"
+            );
+        }
+
+        #[test]
+        fn known_toggles_are_parsed() {
+            let enabled = settings(
+                "hide_title=true&hide_background=true&hide_background_stroke=true&debug=true",
+            );
+            assert!(enabled.hide_title);
+            assert!(enabled.hide_background);
+            assert!(enabled.hide_background_stroke);
+            assert!(enabled.debug);
+
+            let disabled = settings("hide_title=false&hide_background=yes");
+            assert!(!disabled.hide_title);
+            assert!(!disabled.hide_background);
+        }
+
+        #[test]
+        fn unknown_toggles_are_rejected() {
+            assert_eq!(
+                settings_error("hide_icons=true"),
+                "unknown toggle: hide_icons, expected one of: hide_title, hide_background, \
+                 hide_background_stroke, hide_border, transparent, debug"
+            );
+            assert!(
+                settings_error("show_summary=true").starts_with("unknown toggle: show_summary")
+            );
+            // Only the first one is reported
+            assert!(
+                settings_error("hide_titel=true&hide_backgroud=true")
+                    .starts_with("unknown toggle: hide_backgroud,")
+            );
+        }
+
+        #[test]
+        fn other_unknown_params_are_ignored() {
+            assert!(!settings("utm_source=readme").hide_title);
+        }
+
+        #[test]
+        fn transparent_theme_hides_background_by_default() {
+            let transparent = settings("theme=transparent_blue");
+            assert!(transparent.hide_background);
+            assert!(transparent.hide_background_stroke);
+
+            let dark = settings("theme=dark");
+            assert!(!dark.hide_background);
+            assert!(!dark.hide_background_stroke);
+        }
+
+        #[test]
+        fn transparent_false_keeps_background() {
+            let settings = settings("theme=transparent_blue&transparent=false");
+            assert!(!settings.hide_background);
+            assert!(!settings.hide_background_stroke);
+        }
+    }
+
+    // Tests for applying the instance-wide languages card defaults
+    mod fn_langs_card_options {
+        use super::*;
+
+        fn query(params: &str) -> LangsCardQuery {
+            let uri: axum::http::Uri = format!("/langs-card?username=octocat{params}")
+                .parse()
+                .unwrap();
+            Query::<LangsCardQuery>::try_from_uri(&uri).unwrap().0
+        }
+
+        fn defaults() -> LangsDefaults {
+            LangsDefaults {
+                layout: Some(LayoutType::Horizontal),
+                size_weight: Some(0.5),
+                count_weight: Some(0.25),
+                max_languages: Some(4),
+            }
+        }
+
+        #[test]
+        fn omitted_params_use_configured_defaults() {
+            let card = langs_card_options(&query(""), &defaults());
+            assert_eq!(card.layout, LayoutType::Horizontal);
+            assert_eq!(card.size_weight, Some(0.5));
+            assert_eq!(card.count_weight, Some(0.25));
+            assert_eq!(card.max_languages, Some(4));
+        }
+
+        #[test]
+        fn sort_defaults_to_rank() {
+            assert_eq!(
+                langs_card_options(&query(""), &defaults()).sort,
+                LangsSort::Rank
+            );
+            assert_eq!(
+                langs_card_options(&query("&sort=alpha"), &defaults()).sort,
+                LangsSort::Alpha
+            );
+        }
+
+        #[test]
+        fn explicit_params_override_defaults() {
+            let card = langs_card_options(
+                &query("&layout=vertical&size_weight=1&count_weight=0&max_languages=10"),
+                &defaults(),
+            );
+            assert_eq!(card.layout, LayoutType::Vertical);
+            assert_eq!(card.size_weight, Some(1.0));
+            assert_eq!(card.count_weight, Some(0.0));
+            assert_eq!(card.max_languages, Some(10));
+        }
+
+        #[test]
+        fn single_weight_zeroes_the_other_one() {
+            let card = langs_card_options(&query("&count_weight=1"), &defaults());
+            assert_eq!(card.size_weight, Some(0.0));
+            assert_eq!(card.count_weight, Some(1.0));
+
+            let card = langs_card_options(&query("&size_weight=2"), &defaults());
+            assert_eq!(card.size_weight, Some(2.0));
+            assert_eq!(card.count_weight, Some(0.0));
+        }
+
+        #[test]
+        fn only_count_weight_ranks_by_count() {
+            let ranked = |params: &str| {
+                LangsCard {
+                    stats: vec![
+                        LanguageStat {
+                            name: "Rust".to_string(),
+                            size_bytes: 8_000,
+                            repo_count: 2,
+                        },
+                        LanguageStat {
+                            name: "Go".to_string(),
+                            size_bytes: 2_000,
+                            repo_count: 8,
+                        },
+                    ],
+                    ..langs_card_options(&query(params), &LangsDefaults::default())
+                }
+                .computed_percentages()
+                .into_iter()
+                .map(|(stat, percent)| (stat.name, percent.round()))
+                .collect::<Vec<_>>()
+            };
+
+            assert_eq!(
+                ranked("&count_weight=1"),
+                [("Go".to_string(), 80.0), ("Rust".to_string(), 20.0)]
+            );
+            // Neither weight keeps ranking by size only
+            assert_eq!(
+                ranked(""),
+                [("Rust".to_string(), 80.0), ("Go".to_string(), 20.0)]
+            );
+        }
+
+        #[test]
+        fn without_defaults_keeps_card_defaults() {
+            let card = langs_card_options(&query(""), &LangsDefaults::default());
+            assert_eq!(card.layout, LayoutType::Vertical);
+            assert_eq!(card.size_weight, None);
+            assert_eq!(card.count_weight, None);
+            assert_eq!(card.max_languages, Some(8));
+            assert!(!card.normalize_weights);
+        }
+
+        #[test]
+        fn max_languages_defaults_by_layout() {
+            let vertical =
+                langs_card_options(&query("&layout=vertical"), &LangsDefaults::default());
+            let horizontal =
+                langs_card_options(&query("&layout=horizontal"), &LangsDefaults::default());
+            let donut = langs_card_options(&query("&layout=donut"), &LangsDefaults::default());
+            assert_eq!(vertical.max_languages, Some(8));
+            assert_eq!(horizontal.max_languages, Some(6));
+            assert_eq!(donut.layout, LayoutType::Donut);
+            assert_eq!(donut.max_languages, Some(6));
+
+            // Explicit odd counts are honored for the horizontal layout
+            let explicit = langs_card_options(
+                &query("&layout=horizontal&max_languages=5"),
+                &LangsDefaults::default(),
+            );
+            assert_eq!(explicit.max_languages, Some(5));
+        }
+
+        #[test]
+        fn default_row_count_differs_by_layout() {
+            let stats: Vec<LanguageStat> = (0..10)
+                .map(|i| LanguageStat {
+                    name: format!("Lang{i}"),
+                    size_bytes: 1000 * (10 - i),
+                    repo_count: 1,
+                })
+                .collect();
+            let render = |layout: &str| {
+                let card = LangsCard {
+                    stats: stats.clone(),
+                    ..langs_card_options(&query(layout), &LangsDefaults::default())
+                };
+                card.render().unwrap()
+            };
+
+            let vertical = render("&layout=vertical");
+            let horizontal = render("&layout=horizontal");
+
+            assert_eq!(vertical.matches("<g class=\"row\">").count(), 8);
+            // Two languages per row
+            assert_eq!(horizontal.matches("<g class=\"row\">").count(), 3);
+            assert!(horizontal.contains(">Lang5 "));
+            assert!(!horizontal.contains(">Lang6 "));
+        }
+
+        #[test]
+        fn normalize_flags() {
+            let card = langs_card_options(&query("&normalize_weights=true"), &defaults());
+            assert!(card.normalize_weights);
+            assert!(!card.normalize_percentages);
+
+            let card = langs_card_options(&query("&normalize=true"), &defaults());
+            assert!(!card.normalize_weights);
+            assert!(card.normalize_percentages);
+        }
+
+        #[test]
+        fn legend_only_flag() {
+            assert!(!langs_card_options(&query(""), &defaults()).legend_only);
+            let card = langs_card_options(&query("&layout=donut&legend_only=true"), &defaults());
+            assert!(card.legend_only);
+            assert_eq!(card.layout, LayoutType::Donut);
+        }
+    }
+
+    // Tests for the lang_colors query parser
+    mod fn_parse_icon_size {
+        use super::*;
+
+        #[test]
+        fn clamps_and_defaults() {
+            assert_eq!(parse_icon_size(None), StatsCard::DEFAULT_ICON_SIZE);
+            assert_eq!(parse_icon_size(Some("20")), 20);
+            assert_eq!(parse_icon_size(Some("2")), StatsCard::MIN_ICON_SIZE);
+            assert_eq!(parse_icon_size(Some("100")), StatsCard::MAX_ICON_SIZE);
+            assert_eq!(parse_icon_size(Some("big")), StatsCard::DEFAULT_ICON_SIZE);
+        }
+    }
+
+    mod fn_parse_overflow {
+        use super::*;
+
+        #[test]
+        fn columns_take_the_clamped_max_rows() {
+            assert_eq!(parse_overflow(None, Some("3")), Ok(None));
+            assert_eq!(parse_overflow(Some("down"), None), Ok(None));
+            assert_eq!(
+                parse_overflow(Some("columns"), None),
+                Ok(Some(StatsCard::DEFAULT_MAX_ROWS))
+            );
+            assert_eq!(parse_overflow(Some("columns"), Some("3")), Ok(Some(3)));
+            assert_eq!(parse_overflow(Some("columns"), Some("0")), Ok(Some(1)));
+            assert_eq!(
+                parse_overflow(Some("columns"), Some("99")),
+                Ok(Some(StatsCard::MAX_MAX_ROWS))
+            );
+            assert_eq!(
+                parse_overflow(Some("wrap"), None),
+                Err("invalid overflow: wrap, expected down or columns".to_string())
+            );
+        }
+    }
+
+    mod fn_parse_gaps {
+        use super::*;
+
+        #[test]
+        fn clamps_to_the_supported_range() {
+            assert_eq!(parse_label_gap(None), StatsCard::DEFAULT_LABEL_GAP);
+            assert_eq!(parse_label_gap(Some("12")), 12);
+            assert_eq!(parse_label_gap(Some("0")), StatsCard::MIN_LABEL_GAP);
+            assert_eq!(parse_value_gap(Some("500")), StatsCard::MAX_VALUE_GAP);
+            assert_eq!(parse_value_gap(Some("wide")), StatsCard::DEFAULT_VALUE_GAP);
+        }
+    }
+
+    mod fn_parse_min_bar_px {
+        use super::*;
+
+        #[test]
+        fn clamps_to_the_supported_range() {
+            assert_eq!(parse_min_bar_px(None), LangsCard::DEFAULT_MIN_BAR_PX);
+            assert_eq!(parse_min_bar_px(Some("abc")), LangsCard::DEFAULT_MIN_BAR_PX);
+            assert_eq!(parse_min_bar_px(Some("0")), 0);
+            assert_eq!(parse_min_bar_px(Some("6")), 6);
+            assert_eq!(parse_min_bar_px(Some("500")), LangsCard::MAX_MIN_BAR_PX);
+        }
+    }
+
+    mod fn_parse_commits_year {
+        use super::*;
+
+        use crate::github::FixedClock;
+
+        /// 2024-06-01
+        const CLOCK: FixedClock = FixedClock(1_717_200_000);
+
+        #[test]
+        fn accepts_past_and_current_years() {
+            assert_eq!(parse_commits_year("2008", &CLOCK), Ok(2008));
+            assert_eq!(parse_commits_year(" 2020 ", &CLOCK), Ok(2020));
+            assert_eq!(parse_commits_year("2024", &CLOCK), Ok(2024));
+        }
+
+        #[test]
+        fn rejects_out_of_range_years() {
+            for value in ["2007", "2025", "last"] {
+                assert_eq!(
+                    parse_commits_year(value, &CLOCK),
+                    Err(format!("year must be between 2008 and 2024, got {value}"))
+                );
+            }
+        }
+    }
+
+    mod fn_parse_commits_all_years {
+        use super::*;
+
+        #[test]
+        fn accepts_all_only() {
+            assert_eq!(parse_commits_all_years("all"), Ok(true));
+            assert_eq!(parse_commits_all_years(" all "), Ok(true));
+            for value in ["2024", "ALL", ""] {
+                assert_eq!(
+                    parse_commits_all_years(value),
+                    Err(format!("invalid commits_year: {value}, expected all"))
+                );
+            }
+        }
+    }
+
+    mod fn_parse_affiliations {
+        use super::*;
+
+        #[test]
+        fn parses_the_listed_affiliations() {
+            assert_eq!(
+                parse_affiliations("owner"),
+                Ok(RepositoryAffiliations::OWNER)
+            );
+            assert_eq!(
+                parse_affiliations(" organization_member ,collaborator,"),
+                Ok(RepositoryAffiliations {
+                    owner: false,
+                    collaborator: true,
+                    organization_member: true,
+                })
+            );
+        }
+
+        #[test]
+        fn rejects_unknown_and_empty_lists() {
+            assert_eq!(
+                parse_affiliations("owner,member"),
+                Err("invalid affiliation: member, expected owner, collaborator or organization_member".to_string())
+            );
+            assert!(parse_affiliations(" , ").is_err());
+        }
+    }
+
+    mod fn_parse_contribution_types {
+        use super::*;
+        use ContributionType::*;
+
+        #[test]
+        fn all_selects_every_type() {
+            assert_eq!(
+                parse_contribution_types("all").unwrap(),
+                ContributionType::ALL
+            );
+        }
+
+        #[test]
+        fn parses_a_list_once_per_type() {
+            assert_eq!(
+                parse_contribution_types("reviews, commits,reviews,").unwrap(),
+                [Reviews, Commits]
+            );
+            assert_eq!(
+                parse_contribution_types("pull_requests,issues").unwrap(),
+                [PullRequests, Issues]
+            );
+        }
+
+        #[test]
+        fn rejects_unknown_or_empty_lists() {
+            assert_eq!(
+                parse_contribution_types("commits,stars").unwrap_err(),
+                "unknown contribution type: stars"
+            );
+            assert_eq!(
+                parse_contribution_types(" , ").unwrap_err(),
+                "contribution_types must name at least one type"
+            );
+        }
+    }
+
+    mod fn_parse_max_repos {
+        use super::*;
+
+        #[test]
+        fn accepts_positive_numbers() {
+            assert_eq!(parse_max_repos("1"), Ok(1));
+            assert_eq!(parse_max_repos(" 200 "), Ok(200));
+        }
+
+        #[test]
+        fn rejects_zero_and_non_numbers() {
+            for value in ["0", "-5", "all"] {
+                assert_eq!(
+                    parse_max_repos(value),
+                    Err(format!("max_repos must be a positive number, got {value}"))
+                );
+            }
+        }
+    }
+
+    mod fn_langs_summary {
+        use super::*;
+
+        fn query(params: &str) -> LangsSummaryQuery {
+            let uri: axum::http::Uri = format!("/langs-card/top?username=octocat{params}")
+                .parse()
+                .unwrap();
+            Query::<LangsSummaryQuery>::try_from_uri(&uri).unwrap().0
+        }
+
+        fn stats() -> Vec<LanguageStat> {
+            [
+                ("Python", 1500),
+                ("Rust", 4000),
+                ("Go", 3000),
+                ("C", 1000),
+                ("Zig", 500),
+            ]
+            .into_iter()
+            .map(|(name, size_bytes)| LanguageStat {
+                name: name.to_string(),
+                size_bytes,
+                repo_count: 1,
+            })
+            .collect()
+        }
+
+        fn summary(params: &str) -> String {
+            let card = langs_summary_card(&query(params), &LangsDefaults::default(), stats());
+            format_langs_summary(&card.computed_percentages())
+        }
+
+        #[tokio::test]
+        async fn json_reports_the_age_of_the_data() {
+            let cache = crate::github::cache::GitHubCache::new(CacheConfig::default());
+            let fetch = || async { Ok(stats()) };
+
+            // A fresh fetch is as young as it gets
+            let cached = cache
+                .get_or_insert_user_languages("octocat".into(), &[], Default::default(), fetch)
+                .await
+                .unwrap();
+            let card = langs_summary_card(&query(""), &LangsDefaults::default(), cached.value);
+            let json = langs_summary_json(
+                "octocat",
+                &card.computed_percentages(),
+                cached.stale,
+                cached.age,
+            );
+            assert_eq!(json["age_seconds"], 0);
+            assert_eq!(json["stale"], false);
+            assert_eq!(json["languages"][0]["name"], "Rust");
+
+            // A cache hit reports the time since that fetch
+            let cached = cache
+                .get_or_insert_user_languages("octocat".into(), &[], Default::default(), fetch)
+                .await
+                .unwrap();
+            let json = langs_summary_json("octocat", &[], cached.stale, cached.age);
+            assert!(json["age_seconds"].as_u64().is_some());
+            assert_eq!(json["stale"], false);
+        }
+
+        #[test]
+        fn lists_top_three_in_ranked_order() {
+            assert_eq!(summary(""), "Rust 40%, Go 30%, Python 15%");
+        }
+
+        #[test]
+        fn honors_n_and_normalize() {
+            assert_eq!(summary("&n=2"), "Rust 40%, Go 30%");
+            assert_eq!(summary("&n=2&normalize=true"), "Rust 57%, Go 43%");
+        }
+
+        #[test]
+        fn empty_stats_give_empty_summary() {
+            let card = langs_summary_card(&query(""), &LangsDefaults::default(), Vec::new());
+            assert_eq!(format_langs_summary(&card.computed_percentages()), "");
+        }
+
+        #[tokio::test]
+        async fn requires_username_param() {
+            let req = Request::builder()
+                .uri("/langs-card/top")
+                .body(Body::empty())
+                .unwrap();
+            let resp = api_router().oneshot(req).await.unwrap();
+            assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        }
+    }
+
+    mod fn_parse_custom_css {
+        use super::*;
+
+        fn encode(css: &str) -> String {
+            BASE64_STANDARD.encode(css)
+        }
+
+        #[test]
+        fn decodes_benign_rules() {
+            let css = ".title { fill: #ff0000; font-weight: 700; }\n.row > text { opacity: 0.8; }";
+            assert_eq!(parse_custom_css(&encode(css)).unwrap(), css);
+        }
+
+        #[test]
+        fn accepts_plus_decoded_as_space() {
+            let css = ".title{fill:#fff}>";
+            let encoded = encode(css);
+            assert!(encoded.contains('+'));
+            assert_eq!(parse_custom_css(&encoded.replace('+', " ")).unwrap(), css);
+        }
+
+        #[test]
+        fn rejects_forbidden_constructs() {
+            for css in [
+                "@import 'https://example.com/x.css';",
+                "@font-face { font-family: x; src: local(x); }",
+                ".title { background: URL(https://example.com/x.png); }",
+                ".title { width: expression(alert(1)); }",
+                ".title { fill: red; }</style><script>alert(1)</script>",
+                ".title { content: '&amp;'; }",
+                ".title { background: u\\72l(https://example.com); }",
+                ".title { -moz-binding: none; }",
+                ".title { fill: red; }\u{0}",
+            ] {
+                assert!(parse_custom_css(&encode(css)).is_err(), "accepted: {css}");
+            }
+        }
+
+        #[test]
+        fn rejects_invalid_encoding() {
+            assert_eq!(
+                parse_custom_css("not base64!"),
+                Err("custom_css must be base64-encoded".to_string())
+            );
+            assert_eq!(
+                parse_custom_css(&BASE64_STANDARD.encode([0xff, 0xfe])),
+                Err("custom_css must be UTF-8".to_string())
+            );
+        }
+
+        #[test]
+        fn rejects_oversized_css() {
+            let css = format!(
+                ".title {{ fill: red; }}{}",
+                " ".repeat(MAX_CUSTOM_CSS_BYTES)
+            );
+            assert_eq!(
+                parse_custom_css(&encode(&css)),
+                Err(format!(
+                    "custom_css must be at most {MAX_CUSTOM_CSS_BYTES} bytes"
+                ))
+            );
+        }
+    }
+
+    mod fn_parse_empty_note {
+        use super::*;
+
+        #[test]
+        fn accepts_plain_text() {
+            assert_eq!(
+                parse_empty_note(" Only forks here, for now! ").unwrap(),
+                "Only forks here, for now!"
+            );
+        }
+
+        #[test]
+        fn rejects_markup_and_long_notes() {
+            assert!(parse_empty_note("<script>").is_err());
+            assert!(parse_empty_note("Tom &amp; Jerry").is_err());
+            assert!(parse_empty_note("   ").is_err());
+            assert!(parse_empty_note(&"a".repeat(MAX_EMPTY_NOTE_LEN + 1)).is_err());
+        }
+    }
+
+    mod fn_parse_title {
+        use super::*;
+
+        #[test]
+        fn keeps_special_characters_for_the_card_to_escape() {
+            assert_eq!(
+                parse_title(" My Open Source Work ").unwrap(),
+                "My Open Source Work"
+            );
+            assert_eq!(parse_title("<Tom & Jerry>").unwrap(), "<Tom & Jerry>");
+        }
+
+        #[test]
+        fn rejects_empty_long_and_multiline_titles() {
+            assert!(parse_title("   ").is_err());
+            assert!(parse_title(&"a".repeat(MAX_TITLE_LEN + 1)).is_err());
+            assert!(parse_title(&"é".repeat(MAX_TITLE_LEN)).is_ok());
+            assert!(parse_title("line\nbreak").is_err());
+        }
+    }
+
+    mod fn_parse_lang_groups {
+        use super::*;
+
+        #[test]
+        fn parses_named_groups() {
+            let groups = parse_lang_groups("JS/TS=JavaScript, TypeScript; C/C++=C,C++").unwrap();
+            assert_eq!(
+                groups,
+                [
+                    LangGroup {
+                        name: "JS/TS".to_string(),
+                        members: vec!["javascript".to_string(), "typescript".to_string()],
+                    },
+                    LangGroup {
+                        name: "C/C++".to_string(),
+                        members: vec!["c".to_string(), "c++".to_string()],
+                    },
+                ]
+            );
+        }
+
+        #[test]
+        fn rejects_invalid_entries() {
+            assert!(parse_lang_groups("JavaScript,TypeScript").is_err());
+            assert!(parse_lang_groups("JS/TS=").is_err());
+            assert!(parse_lang_groups("=JavaScript").is_err());
+            assert!(parse_lang_groups("<b>=JavaScript").is_err());
+            assert_eq!(
+                parse_lang_groups("Web=JavaScript;Scripts=Shell,javascript"),
+                Err("language javascript is in more than one group".to_string())
+            );
+        }
+    }
+
+    mod fn_group_languages {
+        use super::*;
+
+        fn stat(name: &str, size_bytes: usize, repo_count: u64) -> LanguageStat {
+            LanguageStat {
+                name: name.to_string(),
+                size_bytes,
+                repo_count,
+            }
+        }
+
+        #[test]
+        fn merges_members_into_named_group() {
+            let stats = vec![
+                stat("TypeScript", 3000, 2),
+                stat("Rust", 5000, 4),
+                stat("JavaScript", 1000, 3),
+            ];
+            let groups = parse_lang_groups("JS/TS=JavaScript,TypeScript").unwrap();
+            let mut colors = HashMap::new();
+
+            let grouped = group_languages(stats, &groups, &mut colors);
+            assert_eq!(grouped.len(), 2);
+            assert_eq!(grouped[0].name, "Rust");
+            assert_eq!(grouped[1].name, "JS/TS");
+            assert_eq!(grouped[1].size_bytes, 4000);
+            assert_eq!(grouped[1].repo_count, 5);
+
+            // The group is colored like its first listed member
+            assert_eq!(colors.get("js/ts"), Some(&gel_language_color("JavaScript")));
+        }
+
+        #[test]
+        fn keeps_provided_group_color() {
+            let stats = vec![stat("C", 1000, 1), stat("C++", 2000, 1)];
+            let groups = parse_lang_groups("C/C++=C,C++").unwrap();
+            let mut colors = parse_lang_colors("C/C++:ff0000,C:00ff00").unwrap();
+
+            group_languages(stats, &groups, &mut colors);
+            assert_eq!(colors.get("c/c++").map(String::as_str), Some("#ff0000"));
+        }
+
+        #[test]
+        fn uses_member_color_override() {
+            let stats = vec![stat("C", 1000, 1), stat("C++", 2000, 1)];
+            let groups = parse_lang_groups("C/C++=C,C++").unwrap();
+            let mut colors = parse_lang_colors("C:00ff00").unwrap();
+
+            group_languages(stats, &groups, &mut colors);
+            assert_eq!(colors.get("c/c++").map(String::as_str), Some("#00ff00"));
+        }
+
+        #[test]
+        fn groups_without_members_present_are_skipped() {
+            let stats = vec![stat("Rust", 5000, 4)];
+            let groups = parse_lang_groups("JS/TS=JavaScript,TypeScript").unwrap();
+            let mut colors = HashMap::new();
+
+            let grouped = group_languages(stats, &groups, &mut colors);
+            assert_eq!(grouped.len(), 1);
+            assert_eq!(grouped[0].name, "Rust");
+            assert!(colors.is_empty());
+        }
+    }
+
+    mod fn_parse_exclude_repos {
+        use super::*;
+
+        #[test]
+        fn trims_and_skips_empty_entries() {
+            assert_eq!(
+                parse_exclude_repos(" dotfiles, ,blog,").unwrap(),
+                ["dotfiles", "blog"]
+            );
+        }
+
+        #[test]
+        fn accepts_a_list_at_the_cap() {
+            let repos: Vec<String> = (0..MAX_EXCLUDE_REPOS)
+                .map(|i| format!("repo-{i}"))
+                .collect();
+            assert_eq!(parse_exclude_repos(&repos.join(",")).unwrap(), repos);
+        }
+
+        #[test]
+        fn rejects_a_list_over_the_cap() {
+            let value = vec!["repo"; MAX_EXCLUDE_REPOS + 1].join(",");
+            assert_eq!(
+                parse_exclude_repos(&value).unwrap_err(),
+                "exclude_repo accepts at most 200 repositories, got 201"
+            );
+        }
+
+        #[test]
+        fn rejects_an_overlong_name() {
+            let value = format!("blog,{}", "x".repeat(MAX_REPO_NAME_LEN + 1));
+            assert_eq!(
+                parse_exclude_repos(&value).unwrap_err(),
+                "exclude_repo names must be at most 100 characters, got 101"
+            );
+            assert!(parse_exclude_repos(&"x".repeat(MAX_REPO_NAME_LEN)).is_ok());
+        }
+    }
+
+    mod fn_parse_lang_colors {
+        use super::*;
+
+        #[test]
+        fn parses_names_and_hex_colors() {
+            let overrides = parse_lang_colors("Rust:ff0000, Go:#00FFFF,C++:abc").unwrap();
+            assert_eq!(overrides.len(), 3);
+            assert_eq!(overrides.get("rust").map(String::as_str), Some("#ff0000"));
+            assert_eq!(overrides.get("go").map(String::as_str), Some("#00ffff"));
+            assert_eq!(overrides.get("c++").map(String::as_str), Some("#abc"));
+        }
+
+        #[test]
+        fn rejects_invalid_entries() {
+            assert!(parse_lang_colors("Rust").is_err());
+            assert!(parse_lang_colors("Rust:red").is_err());
+            assert!(parse_lang_colors("Rust:ff00").is_err());
+            assert!(parse_lang_colors(":ff0000").is_err());
+            assert!(parse_lang_colors("<script>:ff0000").is_err());
+        }
+    }
+
+    // Tests for the combine_below, min_percent and other_color query parsers
+    mod fn_parse_percent {
+        use super::*;
+
+        #[test]
+        fn parses_a_percentage() {
+            assert_eq!(parse_percent("combine_below", "2.5"), Ok(2.5));
+            assert_eq!(parse_percent("combine_below", "0"), Ok(0.0));
+            assert_eq!(parse_percent("combine_below", "100"), Ok(100.0));
+            assert_eq!(parse_percent("min_percent", " 0.5 "), Ok(0.5));
+        }
+
+        #[test]
+        fn rejects_out_of_range_values() {
+            for value in ["-1", "100.5", "NaN", "inf", "few"] {
+                assert!(parse_percent("combine_below", value).is_err(), "{value}");
+            }
+        }
+
+        #[test]
+        fn names_the_parameter_in_the_error() {
+            assert_eq!(
+                parse_percent("min_percent", "101"),
+                Err(
+                    "invalid min_percent: 101, expected a percentage between 0 and 100".to_string()
+                )
+            );
+        }
+
+        #[test]
+        fn parses_other_color() {
+            assert_eq!(parse_other_color("ABCDEF"), Ok("#abcdef".to_string()));
+            assert_eq!(parse_other_color("#ccc"), Ok("#ccc".to_string()));
+            assert!(parse_other_color("gray").is_err());
+            assert!(parse_other_color("#ff00").is_err());
+        }
+    }
+
+    mod fn_parse_accent {
+        use super::*;
+
+        #[test]
+        fn accent_comes_before_the_custom_css() {
+            let mut settings = CardSettings {
+                theme: CardTheme::Dark,
+                custom_css: Some(".title { fill: red; }".to_string()),
+                ..Default::default()
+            };
+            apply_accent(&mut settings, "#2f81f7");
+            assert_eq!(
+                settings.custom_css.as_deref(),
+                Some(".title, .icon { fill: #2f81f7; }\n.title { fill: red; }")
+            );
+        }
+
+        #[test]
+        fn accent_is_readable_on_the_background() {
+            let mut settings = CardSettings {
+                theme: CardTheme::Light,
+                ..Default::default()
+            };
+            apply_accent(&mut settings, "#fafafa");
+            let css = settings.custom_css.unwrap();
+            assert!(!css.contains("#fafafa"), "{css}");
+
+            // A card drawn without background keeps the avatar color as is
+            let mut settings = CardSettings {
+                theme: CardTheme::Light,
+                hide_background: true,
+                ..Default::default()
+            };
+            apply_accent(&mut settings, "#fafafa");
+            assert_eq!(
+                settings.custom_css.as_deref(),
+                Some(".title, .icon { fill: #fafafa; }")
+            );
+        }
+
+        #[test]
+        fn only_the_avatar_is_supported() {
+            assert_eq!(parse_accent("avatar"), Ok(Accent::Avatar));
+            assert_eq!(parse_accent(" avatar "), Ok(Accent::Avatar));
+            assert_eq!(
+                parse_accent("gravatar"),
+                Err("invalid accent: gravatar, expected avatar".to_string())
+            );
+        }
+    }
+
+    // Tests for the coalescing of identical concurrent requests
+    mod fn_coalesce {
+        use super::*;
+        use std::sync::{
+            Arc,
+            atomic::{AtomicUsize, Ordering},
+        };
+
+        fn request(uri: &str, accept: Option<&str>) -> Request<Body> {
+            let mut builder = Request::builder().uri(uri);
+            if let Some(accept) = accept {
+                builder = builder.header(header::ACCEPT, accept);
+            }
+            builder.body(Body::empty()).unwrap()
+        }
+
+        /// Counts its calls, answering after a delay so the concurrent requests overlap.
+        async fn render(renders: Arc<AtomicUsize>) -> Response {
+            let n = renders.fetch_add(1, Ordering::SeqCst) + 1;
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            svg_response(format!("<svg>{n}</svg>"))
+        }
+
+        async fn body(response: Response) -> String {
+            let body = response.into_body().collect().await.unwrap().to_bytes();
+            String::from_utf8(body.to_vec()).unwrap()
+        }
+
+        #[tokio::test]
+        async fn renders_identical_concurrent_requests_once() {
+            let in_flight = InFlight::new(10);
+            let renders = Arc::new(AtomicUsize::new(0));
+            let key = || "/stats-card username=octocat".to_string();
+
+            let (a, b, c) = tokio::join!(
+                coalesce(&in_flight, key(), render(Arc::clone(&renders))),
+                coalesce(&in_flight, key(), render(Arc::clone(&renders))),
+                coalesce(&in_flight, key(), render(Arc::clone(&renders))),
+            );
+            assert_eq!(renders.load(Ordering::SeqCst), 1);
+            for response in [a, b, c] {
+                assert_eq!(response.status(), StatusCode::OK);
+                assert_eq!(body(response).await, "<svg>1</svg>");
+            }
+        }
+
+        #[tokio::test]
+        async fn renders_other_and_later_requests_again() {
+            let in_flight = InFlight::new(10);
+            let renders = Arc::new(AtomicUsize::new(0));
+
+            let (a, b) = tokio::join!(
+                coalesce(&in_flight, "a".into(), render(Arc::clone(&renders))),
+                coalesce(&in_flight, "b".into(), render(Arc::clone(&renders))),
+            );
+            assert_ne!(body(a).await, body(b).await);
+
+            let later = coalesce(&in_flight, "a".into(), render(Arc::clone(&renders))).await;
+            assert_eq!(body(later).await, "<svg>3</svg>");
+        }
+
+        #[tokio::test]
+        async fn keeps_the_error_message() {
+            let in_flight = InFlight::new(10);
+            let response = coalesce(&in_flight, "a".into(), async {
+                error_response(StatusCode::BAD_REQUEST, "invalid theme")
+            })
+            .await;
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+            assert!(response.extensions().get::<ErrorMessage>().is_some());
+        }
+
+        #[test]
+        fn key_ignores_the_query_order() {
+            assert_eq!(
+                coalescing_key(&request("/stats-card?username=a&theme=dark", None)),
+                coalescing_key(&request("/stats-card?theme=dark&username=a", None))
+            );
+        }
+
+        #[test]
+        fn key_tells_the_requests_apart() {
+            let keys: HashSet<String> = [
+                request("/stats-card?username=a", None),
+                request("/stats-card?username=b", None),
+                request("/langs-card?username=a", None),
+                request("/stats-card?username=a", Some("image/png")),
+                request("/stats-card?username=a&theme=dark", None),
+            ]
+            .iter()
+            .map(coalescing_key)
+            .collect();
+            assert_eq!(keys.len(), 5);
+        }
+    }
+
+    // Tests for GET /api/stats-card route behavior
+    mod route_get_stats_card {
+        use super::*;
+
+        fn app() -> Router {
+            // Reuse only the API router which mounts /stats-card
+            api_router()
+        }
+
+        #[tokio::test]
+        async fn requires_username_param() {
+            let app = app();
+            let req = Request::builder()
+                .uri("/stats-card")
+                .body(Body::empty())
+                .unwrap();
+            let resp = app.oneshot(req).await.unwrap();
+            assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        }
+
+        #[tokio::test]
+        async fn out_of_range_offset_returns_400() {
+            let app = app();
+            let req = Request::builder()
+                .uri("/stats-card?username=alice&offset_x=500")
+                .body(Body::empty())
+                .unwrap();
+            let resp = app.oneshot(req).await.unwrap();
+            assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+            let body = resp.into_body().collect().await.unwrap().to_bytes();
+            let body_str = String::from_utf8(body.to_vec()).unwrap_or_default();
+            assert!(body_str.contains("offset_x must be between 0 and 100, got 500"));
+        }
+
+        #[tokio::test]
+        async fn forbidden_custom_css_returns_400() {
+            let app = app();
+            let css = BASE64_STANDARD.encode("@import 'https://example.com/x.css';");
+            let req = Request::builder()
+                .uri(format!(
+                    "/stats-card?username=alice&custom_css={}",
+                    css.replace('+', "%2B").replace('=', "%3D")
+                ))
+                .body(Body::empty())
+                .unwrap();
+            let resp = app.oneshot(req).await.unwrap();
+            assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+            let body = resp.into_body().collect().await.unwrap().to_bytes();
+            let body_str = String::from_utf8(body.to_vec()).unwrap_or_default();
+            assert!(body_str.contains("custom_css must not contain @"));
+        }
+
+        #[tokio::test]
+        async fn invalid_commits_year_returns_400() {
+            for (query, message) in [
+                (
+                    "commits_year=2024",
+                    "invalid commits_year: 2024, expected all",
+                ),
+                (
+                    "commits_year=all&year=2024",
+                    "commits_year=all cannot be combined with year",
+                ),
+                // The longer message wraps over two lines of the error card
+                (
+                    "commits_year=all&contribution_types=all",
+                    "contribution_types",
+                ),
+            ] {
+                let req = Request::builder()
+                    .uri(format!("/stats-card?username=alice&{query}"))
+                    .body(Body::empty())
+                    .unwrap();
+                let resp = app().oneshot(req).await.unwrap();
+                assert_eq!(resp.status(), StatusCode::BAD_REQUEST, "{query}");
+                let body = resp.into_body().collect().await.unwrap().to_bytes();
+                let body_str = String::from_utf8(body.to_vec()).unwrap_or_default();
+                assert!(body_str.contains(message), "{query}: {body_str}");
+            }
+        }
+
+        #[tokio::test]
+        async fn invalid_accent_returns_400() {
+            for uri in [
+                "/stats-card?username=alice&accent=red",
+                "/langs-card?username=alice&accent=red",
+            ] {
+                let req = Request::builder().uri(uri).body(Body::empty()).unwrap();
+                let resp = app().oneshot(req).await.unwrap();
+                assert_eq!(resp.status(), StatusCode::BAD_REQUEST, "{uri}");
+                let body = resp.into_body().collect().await.unwrap().to_bytes();
+                let body_str = String::from_utf8(body.to_vec()).unwrap_or_default();
+                assert!(body_str.contains("invalid accent: red"), "{uri}");
+            }
+        }
+
+        #[tokio::test]
+        async fn unknown_toggle_returns_400() {
+            let app = app();
+            let req = Request::builder()
+                .uri("/stats-card?username=alice&hide_rank=true")
+                .body(Body::empty())
+                .unwrap();
+            let resp = app.oneshot(req).await.unwrap();
+            assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+            let body = resp.into_body().collect().await.unwrap().to_bytes();
+            let body_str = String::from_utf8(body.to_vec()).unwrap_or_default();
+            assert!(body_str.contains("unknown toggle: hide_rank"));
+        }
+
+        #[tokio::test]
+        async fn compact_error_style_renders_a_single_line_error() {
+            let req = Request::builder()
+                .uri("/stats-card?username=alice&offset_x=500&error_style=compact")
+                .body(Body::empty())
+                .unwrap();
+            let resp = app().oneshot(req).await.unwrap();
+            assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+            let length = resp.headers()[header::CONTENT_LENGTH].clone();
+            let body = resp.into_body().collect().await.unwrap().to_bytes();
+            assert_eq!(length, body.len().to_string());
+            let body_str = String::from_utf8(body.to_vec()).unwrap_or_default();
+            assert_eq!(body_str.matches("class=\"error-message\"").count(), 1);
+            assert!(body_str.contains(&format!("height=\"{}\"", Card::MIN_HEIGHT)));
+            assert!(!body_str.contains("Readme: samgozman/github-statcrab"));
+            assert!(body_str.contains("offset_x must be between 0 and 100, got 500</desc>"));
+        }
+
+        #[tokio::test]
+        async fn invalid_hide_value_returns_an_error_card() {
+            let req = Request::builder()
+                .uri("/stats-card?username=alice&hide=invalid_stat")
+                .body(Body::empty())
+                .unwrap();
+            let resp = app().oneshot(req).await.unwrap();
+            assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+            assert_eq!(resp.headers()[header::CONTENT_TYPE], "image/svg+xml");
+            let body = resp.into_body().collect().await.unwrap().to_bytes();
+            let body_str = String::from_utf8(body.to_vec()).unwrap_or_default();
+            assert!(body_str.contains("invalid hide value: invalid_stat"));
         }
-    });
-
-    Json(health_data)
-}
 
-/// Helper function to create a response with SVG content and appropriate headers
-fn svg_response(svg: String) -> Response {
-    let mut headers = HeaderMap::new();
-    headers.insert(
-        header::CONTENT_TYPE,
-        header::HeaderValue::from_static("image/svg+xml"),
-    );
-    (StatusCode::OK, headers, svg).into_response()
-}
+        #[tokio::test]
+        async fn json_on_error_returns_the_message_as_json() {
+            let req = Request::builder()
+                .uri("/stats-card?username=alice&offset_x=500&on_error=json")
+                .body(Body::empty())
+                .unwrap();
+            let resp = app().oneshot(req).await.unwrap();
+            assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+            assert_eq!(resp.headers()[header::CONTENT_TYPE], "application/json");
+            let body = resp.into_body().collect().await.unwrap().to_bytes();
+            let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            assert_eq!(json["error"], "offset_x must be between 0 and 100, got 500");
+        }
 
-/// Helper function to create an error response with ErrorCard SVG and appropriate status code
-fn error_response(status: StatusCode, message: &str) -> Response {
-    let error_card = ErrorCard::new(message.to_string());
-    let svg = error_card.render();
+        #[tokio::test]
+        async fn card_on_error_keeps_the_error_card() {
+            let req = Request::builder()
+                .uri("/stats-card?username=alice&offset_x=500&on_error=card")
+                .body(Body::empty())
+                .unwrap();
+            let resp = app().oneshot(req).await.unwrap();
+            assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+            assert_eq!(resp.headers()[header::CONTENT_TYPE], "image/svg+xml");
+        }
 
-    let mut headers = HeaderMap::new();
-    headers.insert(
-        header::CONTENT_TYPE,
-        header::HeaderValue::from_static("image/svg+xml"),
-    );
-    (status, headers, svg).into_response()
-}
+        #[tokio::test]
+        async fn unknown_on_error_returns_400() {
+            let req = Request::builder()
+                .uri("/stats-card?username=alice&on_error=html")
+                .body(Body::empty())
+                .unwrap();
+            let resp = app().oneshot(req).await.unwrap();
+            assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+            let body = resp.into_body().collect().await.unwrap().to_bytes();
+            let body_str = String::from_utf8(body.to_vec()).unwrap_or_default();
+            assert!(body_str.contains("unknown on_error: html"));
+        }
 
-fn validate_username(username: &str) -> Result<(), String> {
-    if username.trim().is_empty() {
-        return Err("Username cannot be empty".to_string());
-    }
-    if username.contains(' ') {
-        return Err("Username cannot contain spaces".to_string());
-    }
-    Ok(())
-}
+        #[tokio::test]
+        async fn unsupported_format_returns_406() {
+            let req = Request::builder()
+                .uri("/stats-card?username=alice&format=json")
+                .body(Body::empty())
+                .unwrap();
+            let resp = app().oneshot(req).await.unwrap();
+            assert_eq!(resp.status(), StatusCode::NOT_ACCEPTABLE);
+            let body = resp.into_body().collect().await.unwrap().to_bytes();
+            let body_str = String::from_utf8(body.to_vec()).unwrap_or_default();
+            assert!(body_str.contains("format json is not supported"));
+        }
 
-fn is_username_allowed(username: &str) -> bool {
-    // Get allowed usernames from environment variable
-    let allowed_usernames = std::env::var("ALLOWED_USERNAMES").unwrap_or_default();
+        #[tokio::test]
+        async fn unsupported_accept_returns_406() {
+            let req = Request::builder()
+                .uri("/stats-card?username=alice")
+                .header(header::ACCEPT, "application/json")
+                .body(Body::empty())
+                .unwrap();
+            let resp = app().oneshot(req).await.unwrap();
+            assert_eq!(resp.status(), StatusCode::NOT_ACCEPTABLE);
+        }
 
-    // If empty or not set, allow all users
-    if allowed_usernames.trim().is_empty() {
-        return true;
+        #[tokio::test]
+        async fn unknown_error_style_returns_400() {
+            let req = Request::builder()
+                .uri("/stats-card?username=alice&error_style=tiny")
+                .body(Body::empty())
+                .unwrap();
+            let resp = app().oneshot(req).await.unwrap();
+            assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+            let body = resp.into_body().collect().await.unwrap().to_bytes();
+            let body_str = String::from_utf8(body.to_vec()).unwrap_or_default();
+            assert!(body_str.contains("unknown error_style: tiny"));
+        }
     }
 
-    // Check if username is in the allowed list (case-insensitive)
-    allowed_usernames
-        .split(',')
-        .map(|s| s.trim())
-        .filter(|s| !s.is_empty())
-        .any(|allowed| allowed.eq_ignore_ascii_case(username))
-}
-
-// Build the ThemeQuery enum from the macro
-build_theme_query!();
-
-/// Common query parameters for building [CardSettings] reused across card endpoints.
-#[derive(Debug, Deserialize)]
-struct CardSettingsQuery {
-    // common optional visuals
-    offset_x: Option<String>,
-    offset_y: Option<String>,
-    theme: Option<ThemeQuery>,
-    hide_title: Option<String>,
-    hide_background: Option<String>,
-    hide_background_stroke: Option<String>,
-}
+    // Tests for GET /api/org-stats-card and /api/org-langs-card route behavior
+    mod route_get_org_cards {
+        use super::*;
+        use crate::github::SystemClock;
 
-impl CardSettingsQuery {
-    fn into_settings(self) -> CardSettings {
-        CardSettings {
-            offset_x: self
-                .offset_x
-                .as_deref()
-                .and_then(|s| s.parse::<u32>().ok())
-                .unwrap_or(12),
-            offset_y: self
-                .offset_y
-                .as_deref()
-                .and_then(|s| s.parse::<u32>().ok())
-                .unwrap_or(12),
-            theme: self.theme.map(|t| t.into()).unwrap_or(CardTheme::Light),
-            hide_title: self
-                .hide_title
-                .as_deref()
-                .map(|s| s == "true")
-                .unwrap_or(false),
-            hide_background: self
-                .hide_background
-                .as_deref()
-                .map(|s| s == "true")
-                .unwrap_or(false),
-            hide_background_stroke: self
-                .hide_background_stroke
-                .as_deref()
-                .map(|s| s == "true")
-                .unwrap_or(false),
+        fn app() -> Router {
+            api_router()
         }
-    }
-}
 
-#[allow(clippy::enum_variant_names)]
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-enum HideStat {
-    StarsCount,
-    CommitsYtdCount,
-    IssuesCount,
-    PullRequestsCount,
-    MergeRequestsCount,
-    ReviewsCount,
-    StartedDiscussionsCount,
-    AnsweredDiscussionsCount,
-}
+        #[tokio::test]
+        async fn requires_org_param() {
+            for uri in ["/org-stats-card", "/org-langs-card?username=rust-lang"] {
+                let req = Request::builder().uri(uri).body(Body::empty()).unwrap();
+                let resp = app().oneshot(req).await.unwrap();
+                assert_eq!(resp.status(), StatusCode::BAD_REQUEST, "{uri}");
+            }
+        }
 
-impl FromStr for HideStat {
-    type Err = ();
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
-            "stars_count" => Ok(HideStat::StarsCount),
-            "commits_ytd_count" => Ok(HideStat::CommitsYtdCount),
-            "issues_count" => Ok(HideStat::IssuesCount),
-            "pull_requests_count" => Ok(HideStat::PullRequestsCount),
-            "merge_requests_count" => Ok(HideStat::MergeRequestsCount),
-            "reviews_count" => Ok(HideStat::ReviewsCount),
-            "started_discussions_count" => Ok(HideStat::StartedDiscussionsCount),
-            "answered_discussions_count" => Ok(HideStat::AnsweredDiscussionsCount),
-            _ => Err(()),
+        #[tokio::test]
+        async fn invalid_org_returns_400() {
+            for uri in ["/org-stats-card?org=-rust", "/org-langs-card?org=rust_lang"] {
+                let req = Request::builder().uri(uri).body(Body::empty()).unwrap();
+                let resp = app().oneshot(req).await.unwrap();
+                assert_eq!(resp.status(), StatusCode::BAD_REQUEST, "{uri}");
+            }
         }
-    }
-}
 
-#[derive(Debug, Deserialize)]
-enum LayoutTypeQuery {
-    #[serde(rename = "vertical")]
-    Vertical,
-    #[serde(rename = "horizontal")]
-    Horizontal,
-}
+        #[tokio::test]
+        async fn unsupported_format_returns_406() {
+            for uri in [
+                "/org-stats-card?org=rust-lang&format=gif",
+                "/org-langs-card?org=rust-lang&format=json",
+            ] {
+                let req = Request::builder().uri(uri).body(Body::empty()).unwrap();
+                let resp = app().oneshot(req).await.unwrap();
+                assert_eq!(resp.status(), StatusCode::NOT_ACCEPTABLE, "{uri}");
+            }
+        }
 
-impl From<LayoutTypeQuery> for LayoutType {
-    fn from(layout: LayoutTypeQuery) -> Self {
-        match layout {
-            LayoutTypeQuery::Vertical => LayoutType::Vertical,
-            LayoutTypeQuery::Horizontal => LayoutType::Horizontal,
+        #[tokio::test]
+        async fn org_not_found_returns_404() {
+            let resp = github_error_response(
+                GitHubApiError::OrgNotFound,
+                "ghost",
+                "statistics",
+                &SystemClock,
+            );
+            assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+            let body = resp.into_body().collect().await.unwrap().to_bytes();
+            let body_str = String::from_utf8(body.to_vec()).unwrap();
+            assert!(body_str.contains("Organization not found"));
         }
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use axum::{
-        body::Body,
-        http::{Request, StatusCode},
-    };
-    use http_body_util::BodyExt as _; // for collect()
-    use tower::ServiceExt; // for oneshot()
 
-    // Tests for the helper function that builds an SVG response
-    mod fn_svg_response {
+    // Tests for the ETag / If-None-Match handling of the card routes
+    mod route_conditional_requests {
         use super::*;
 
+        /// Requests the stats card of a user whose stats are cached beforehand, so the
+        /// card renders without calling GitHub.
+        async fn stats_card(if_none_match: Option<&str>) -> Response {
+            let stats = crate::github::GitHubStats {
+                login: "etag-octocat".to_string(),
+                total_stars: 42,
+                ..Default::default()
+            };
+            get_github_cache()
+                .insert_user_stats("etag-octocat".into(), StatsOptions::default(), stats)
+                .await;
+
+            let mut req = Request::builder().uri("/stats-card?username=etag-octocat");
+            if let Some(if_none_match) = if_none_match {
+                req = req.header(header::IF_NONE_MATCH, if_none_match);
+            }
+            let req = req.body(Body::empty()).unwrap();
+            api_router().oneshot(req).await.unwrap().into_response()
+        }
+
         #[tokio::test]
-        async fn returns_svg_with_correct_headers_and_body() {
-            let svg = "<svg xmlns=\"http://www.w3.org/2000/svg\"></svg>".to_string();
-            let resp = svg_response(svg.clone());
+        async fn matching_if_none_match_returns_304() {
+            let resp = stats_card(None).await;
+            assert_eq!(resp.status(), StatusCode::OK);
+            let etag = resp.headers()[header::ETAG].to_str().unwrap().to_string();
+            assert!(etag.starts_with("W/\""), "{etag}");
+
+            let resp = stats_card(Some(&format!("\"other\", {etag}"))).await;
+            assert_eq!(resp.status(), StatusCode::NOT_MODIFIED);
+            assert_eq!(resp.headers()[header::ETAG], etag.as_str());
+            assert!(resp.headers().contains_key(header::CACHE_CONTROL));
+            let body = resp.into_body().collect().await.unwrap().to_bytes();
+            assert!(body.is_empty());
 
+            let resp = stats_card(Some("W/\"0000000000000000\"")).await;
             assert_eq!(resp.status(), StatusCode::OK);
-            let content_type = resp
-                .headers()
-                .get(header::CONTENT_TYPE)
-                .and_then(|v| v.to_str().ok())
-                .unwrap_or("");
-            assert_eq!(content_type, "image/svg+xml");
+        }
 
-            let bytes = resp.into_body().collect().await.unwrap().to_bytes();
-            assert_eq!(bytes, svg);
+        #[test]
+        fn etags_are_compared_weakly() {
+            let etag = weak_etag(b"<svg/>");
+            assert_eq!(etag, weak_etag(b"<svg/>"));
+            assert_ne!(etag, weak_etag(b"<svg></svg>"));
+
+            let strong = etag.trim_start_matches("W/");
+            assert!(etag_matches(strong, &etag));
+            assert!(etag_matches(&format!("W/\"a\" , {etag}"), &etag));
+            assert!(etag_matches("*", &etag));
+            assert!(!etag_matches("W/\"a\"", &etag));
         }
     }
 
-    // Tests for GET /api/stats-card route behavior
-    mod route_get_stats_card {
+    // Tests for GET /api/streak-card route behavior
+    mod route_get_streak_card {
         use super::*;
 
         fn app() -> Router {
-            // Reuse only the API router which mounts /stats-card
             api_router()
         }
 
         #[tokio::test]
-        async fn requires_username_param() {
-            let app = app();
-            let req = Request::builder()
-                .uri("/stats-card")
-                .body(Body::empty())
-                .unwrap();
-            let resp = app.oneshot(req).await.unwrap();
-            assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        async fn rejects_invalid_requests() {
+            for (uri, status) in [
+                ("/streak-card", StatusCode::BAD_REQUEST),
+                ("/streak-card?username=-octocat", StatusCode::BAD_REQUEST),
+                (
+                    "/streak-card?username=octocat&format=json",
+                    StatusCode::NOT_ACCEPTABLE,
+                ),
+            ] {
+                let req = Request::builder().uri(uri).body(Body::empty()).unwrap();
+                let resp = app().oneshot(req).await.unwrap();
+                assert_eq!(resp.status(), status, "{uri}");
+            }
         }
     }
 
@@ -614,6 +4588,22 @@ mod tests {
             assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
         }
 
+        #[tokio::test]
+        async fn unsupported_format_returns_406() {
+            for (uri, accept) in [
+                ("/langs-card?username=alice&format=json", "*/*"),
+                ("/langs-card?username=alice", "image/avif"),
+            ] {
+                let req = Request::builder()
+                    .uri(uri)
+                    .header(header::ACCEPT, accept)
+                    .body(Body::empty())
+                    .unwrap();
+                let resp = app().oneshot(req).await.unwrap();
+                assert_eq!(resp.status(), StatusCode::NOT_ACCEPTABLE, "{uri} {accept}");
+            }
+        }
+
         #[tokio::test]
         async fn invalid_username_returns_400() {
             let app = app();
@@ -625,6 +4615,19 @@ mod tests {
             assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
         }
 
+        #[tokio::test]
+        async fn json_on_error_returns_the_message_as_json() {
+            let req = Request::builder()
+                .uri("/langs-card?username=bad%20user&on_error=json")
+                .body(Body::empty())
+                .unwrap();
+            let resp = app().oneshot(req).await.unwrap();
+            assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+            let body = resp.into_body().collect().await.unwrap().to_bytes();
+            let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            assert!(json["error"].as_str().unwrap().contains("Username"));
+        }
+
         #[tokio::test]
         async fn with_unknown_theme_returns_400() {
             let app = app();
@@ -638,6 +4641,58 @@ mod tests {
             let body_str = String::from_utf8(body.to_vec()).unwrap_or_default();
             assert!(body_str.contains("unknown variant `unknown_theme`"));
         }
+
+        #[tokio::test]
+        async fn over_cap_exclude_repo_returns_400() {
+            let repos = vec!["repo"; MAX_EXCLUDE_REPOS + 1].join(",");
+            for route in ["/langs-card", "/langs-card/top"] {
+                let req = Request::builder()
+                    .uri(format!("{route}?username=alice&exclude_repo={repos}"))
+                    .body(Body::empty())
+                    .unwrap();
+                let resp = app().oneshot(req).await.unwrap();
+                assert_eq!(resp.status(), StatusCode::BAD_REQUEST, "{route}");
+                let body = resp.into_body().collect().await.unwrap().to_bytes();
+                let body_str = String::from_utf8(body.to_vec()).unwrap_or_default();
+                assert!(body_str.contains("exclude_repo accepts at most 200"));
+            }
+        }
+    }
+
+    // Tests for GET /api/themes/{name}/colors route behavior
+    mod route_get_theme_colors {
+        use super::*;
+
+        #[tokio::test]
+        async fn returns_the_key_colors_of_the_theme() {
+            let req = Request::builder()
+                .uri("/themes/dracula/colors")
+                .body(Body::empty())
+                .unwrap();
+            let resp = api_router().oneshot(req).await.unwrap();
+            assert_eq!(resp.status(), StatusCode::OK);
+
+            let body = resp.into_body().collect().await.unwrap().to_bytes();
+            let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            assert_eq!(json["theme"], "dracula");
+            let colors = json["colors"].as_object().unwrap();
+            let mut keys: Vec<_> = colors.keys().map(String::as_str).collect();
+            keys.sort();
+            assert_eq!(keys, ["background", "border", "text", "title", "value"]);
+            for color in colors.values() {
+                assert!(color.as_str().unwrap().starts_with('#'), "{color}");
+            }
+        }
+
+        #[tokio::test]
+        async fn unknown_theme_returns_404() {
+            let req = Request::builder()
+                .uri("/themes/unknown_theme/colors")
+                .body(Body::empty())
+                .unwrap();
+            let resp = api_router().oneshot(req).await.unwrap();
+            assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+        }
     }
 
     // Tests for GET /api/health route behavior
@@ -709,6 +4764,230 @@ mod tests {
             assert!(cache.get("stats_size_bytes").is_some());
             assert!(cache.get("languages_entries").is_some());
             assert!(cache.get("languages_size_bytes").is_some());
+            assert!(cache.get("profiles_entries").is_some());
+            assert!(cache.get("profiles_size_bytes").is_some());
+
+            // Check config object exists
+            let config = json.get("config").expect("config should be present");
+            assert!(config.get("github_token_configured").unwrap().is_boolean());
+        }
+
+        #[test]
+        fn config_reports_settings_without_secrets() {
+            let config = health_config(&WebConfig::default(), &CacheConfig::default(), true);
+
+            for key in [
+                "cache",
+                "card_cache_control",
+                "rate_limit_threshold",
+                "default_theme",
+                "max_concurrent_requests",
+                "request_body_limit_bytes",
+                "keep_alive",
+                "header_read_timeout_seconds",
+                "github_token_configured",
+            ] {
+                assert!(config.get(key).is_some(), "{key} should be present");
+            }
+            for key in [
+                "max_capacity_mb",
+                "user_stats_ttl_seconds",
+                "user_languages_ttl_seconds",
+                "swr_seconds",
+                "fail_open_seconds",
+            ] {
+                assert!(
+                    config["cache"].get(key).is_some(),
+                    "cache.{key} should be present"
+                );
+            }
+            assert_eq!(config["cache"]["user_stats_ttl_seconds"], 900);
+            assert_eq!(config["rate_limit_threshold"], 100);
+            assert_eq!(config["default_theme"], "light");
+            assert_eq!(config["github_token_configured"], true);
+
+            // The token itself never shows up
+            if let Ok(token) = std::env::var("GITHUB_TOKEN")
+                && !token.is_empty()
+            {
+                assert!(!config.to_string().contains(&token));
+            }
+            assert!(config.get("github_token").is_none());
+        }
+    }
+
+    // Randomized query strings fed through the query parsing and validation helpers,
+    // which must never panic: a malformed request ends up as a clean error
+    mod fuzz_query_parsing {
+        use super::*;
+        use crate::github::SystemClock;
+        use proptest::prelude::*;
+
+        const STATS_KEYS: &[&str] = &[
+            "username",
+            "hide",
+            "sort",
+            "icon_size",
+            "label_gap",
+            "value_gap",
+            "include_private",
+            "year",
+            "commits_year",
+            "max_repos",
+            "affiliations",
+            "contribution_types",
+            "accent",
+            "title",
+            "overflow",
+            "max_rows",
+            "format",
+            "scale",
+        ];
+        const LANGS_KEYS: &[&str] = &[
+            "username",
+            "layout",
+            "size_weight",
+            "count_weight",
+            "max_languages",
+            "normalize_weights",
+            "normalize",
+            "exclude_repo",
+            "affiliations",
+            "lang_colors",
+            "group_langs",
+            "empty_note",
+            "auto_contrast",
+            "sort",
+            "min_bar_px",
+            "legend_only",
+            "combine_below",
+            "min_percent",
+            "other_color",
+            "accent",
+            "format",
+            "scale",
+        ];
+        const SETTINGS_KEYS: &[&str] = &[
+            "offset_x",
+            "offset_y",
+            "theme",
+            "hide_title",
+            "hide_background",
+            "hide_background_stroke",
+            "hide_border",
+            "transparent",
+            "min_width",
+            "max_width",
+            "title_size",
+            "debug",
+            "custom_css",
+            "aspect",
+            "canvas_width",
+            "canvas_height",
+        ];
+
+        /// A query parameter value: numbers of any magnitude, lists and arbitrary text.
+        fn value() -> impl Strategy<Value = String> {
+            prop_oneof![
+                any::<i64>().prop_map(|n| n.to_string()),
+                any::<f64>().prop_map(|n| n.to_string()),
+                "(true|false|dark|og|horizontal|stars_count|Rust:ff0000|A=Rust,Go)",
+                "[a-zA-Z0-9_,;:=/#+. -]{0,40}",
+                ".{0,40}",
+            ]
+        }
+
+        /// A percent-encoded query string made of known and random keys.
+        fn query(keys: &'static [&'static str]) -> impl Strategy<Value = String> {
+            let key = prop_oneof![
+                proptest::sample::select(keys).prop_map(str::to_string),
+                proptest::sample::select(SETTINGS_KEYS).prop_map(str::to_string),
+                "(hide|show)_[a-z]{1,8}",
+                "[a-z_]{1,12}",
+            ];
+            proptest::collection::vec((key, value()), 0..12).prop_map(|params| {
+                params
+                    .iter()
+                    .map(|(key, value)| format!("{}={}", encode(key), encode(value)))
+                    .collect::<Vec<_>>()
+                    .join("&")
+            })
+        }
+
+        fn encode(text: &str) -> String {
+            text.bytes()
+                .map(|b| match b {
+                    b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                        (b as char).to_string()
+                    }
+                    _ => format!("%{b:02X}"),
+                })
+                .collect()
+        }
+
+        fn uri(path: &str, query: &str) -> axum::http::Uri {
+            format!("{path}?{query}").parse().unwrap()
+        }
+
+        fn stats_card(q: StatsCardQuery) -> Result<String, Box<Response>> {
+            let stats = GitHubStats {
+                total_stars: u32::MAX,
+                total_commits_ytd: 1,
+                ..Default::default()
+            };
+            stats_card_from_query(q, &HeaderMap::new(), &SystemClock)?
+                .render(&stats, Some(u32::MAX))
+        }
+
+        fn langs_card(q: LangsCardQuery) -> Result<String, Box<Response>> {
+            let stats = ["Rust", "Go", "TypeScript", "Jupyter Notebook"]
+                .iter()
+                .enumerate()
+                .map(|(i, name)| LanguageStat {
+                    name: name.to_string(),
+                    size_bytes: 1000 * (i + 1),
+                    repo_count: i as u64,
+                })
+                .collect();
+            langs_card_from_query(q, &HeaderMap::new())?.render(stats)
+        }
+
+        proptest! {
+            #[test]
+            fn stats_card_query_never_panics(query in query(STATS_KEYS)) {
+                if let Ok(Query(q)) = Query::<StatsCardQuery>::try_from_uri(&uri("/stats-card", &query)) {
+                    let _ = stats_card(q);
+                }
+            }
+
+            #[test]
+            fn langs_card_query_never_panics(query in query(LANGS_KEYS)) {
+                if let Ok(Query(q)) = Query::<LangsCardQuery>::try_from_uri(&uri("/langs-card", &query)) {
+                    let _ = langs_card(q);
+                }
+            }
+
+            #[test]
+            fn list_helpers_never_panic(value in value()) {
+                let _ = parse_hide_list(&value);
+                let _ = parse_lang_colors(&value);
+                let _ = parse_lang_groups(&value);
+                let _ = parse_custom_css(&value);
+                let _ = parse_empty_note(&value);
+                let _ = parse_commits_year(&value, &SystemClock);
+                let _ = parse_commits_all_years(&value);
+                let _ = parse_max_repos(&value);
+                let _ = parse_affiliations(&value);
+                let _ = parse_contribution_types(&value);
+                let _ = validate_username(&value);
+                let _ = parse_icon_size(Some(&value));
+                let _ = parse_scale(Some(&value));
+                let _ = negotiate_format(Some(&value), &HeaderMap::new());
+                if let Ok(accept) = header::HeaderValue::from_str(&value) {
+                    let headers = HeaderMap::from_iter([(header::ACCEPT, accept)]);
+                    let _ = negotiate_format(None, &headers);
+                }
+            }
         }
     }
 }
@@ -0,0 +1,518 @@
+//! GitHub API Tests With Canned Responses
+//!
+//! These tests answer the GraphQL requests with a `MockTransport`, they need neither
+//! a GitHub token nor network access.
+//!
+//! Run with: cargo test --test github_api_mock
+
+use std::sync::Arc;
+
+use github_statcrab::github::transport::MockTransport;
+use github_statcrab::github::{
+    FixedClock, GitHubApi, GitHubApiError, GitHubToken, StatsOptions, get_github_cache,
+};
+use serde_json::{Value, json};
+
+/// A client answered by `transport`. It uses a request token, so nothing it fetches
+/// goes into the shared cache.
+fn mocked_api(transport: &Arc<MockTransport>) -> GitHubApi {
+    GitHubApi::with_token(GitHubToken::new("mock-token".to_string()))
+        .with_transport(transport.clone())
+}
+
+/// 2024-06-01, the time told by the clock of the time-dependent tests
+const NOW: u64 = 1_717_200_000;
+
+fn page_info(end_cursor: Option<&str>) -> Value {
+    json!({ "hasNextPage": end_cursor.is_some(), "endCursor": end_cursor })
+}
+
+fn starred_repositories(stars: &[u32], end_cursor: Option<&str>) -> Value {
+    let nodes: Vec<Value> = stars
+        .iter()
+        .enumerate()
+        .map(|(i, stars)| json!({ "name": format!("repo-{i}"), "stargazers": { "totalCount": stars } }))
+        .collect();
+    json!({ "totalCount": stars.len(), "nodes": nodes, "pageInfo": page_info(end_cursor) })
+}
+
+fn user_stats(login: &str, repositories: Value) -> Value {
+    json!({ "data": { "user": {
+        "name": "The Mock",
+        "login": login,
+        "createdAt": "2015-01-01T00:00:00Z",
+        "contributionsCollection": {
+            "totalCommitContributions": 120,
+            "totalPullRequestReviewContributions": 7
+        },
+        "pullRequests": { "totalCount": 30 },
+        "mergedPullRequests": { "totalCount": 25 },
+        "openIssues": { "totalCount": 2 },
+        "closedIssues": { "totalCount": 8 },
+        "repositoryDiscussions": { "totalCount": 1 },
+        "repositoryDiscussionComments": { "totalCount": 3 },
+        "repositories": repositories
+    } } })
+}
+
+fn language_repositories(
+    repositories: &[(&str, &[(&str, usize)])],
+    end_cursor: Option<&str>,
+) -> Value {
+    let nodes: Vec<Value> = repositories
+        .iter()
+        .map(|(name, languages)| {
+            let edges: Vec<Value> = languages
+                .iter()
+                .map(|(language, size)| json!({ "size": size, "node": { "name": language, "color": null } }))
+                .collect();
+            json!({ "name": name, "languages": { "edges": edges } })
+        })
+        .collect();
+    json!({ "data": { "user": { "repositories": { "nodes": nodes, "pageInfo": page_info(end_cursor) } } } })
+}
+
+#[tokio::test]
+async fn test_fetch_user_stats_follows_the_repository_pages() {
+    let transport = Arc::new(
+        MockTransport::new()
+            .respond(
+                "GetUserStats",
+                user_stats("mock-stats", starred_repositories(&[50, 20], Some("page-2"))),
+            )
+            .respond(
+                "GetUserRepos",
+                json!({ "data": { "user": { "repositories": starred_repositories(&[5, 1], None) } } }),
+            ),
+    );
+
+    let stats = mocked_api(&transport)
+        .fetch_user_stats("mock-stats")
+        .await
+        .unwrap();
+
+    assert_eq!(stats.login, "mock-stats");
+    assert_eq!(stats.total_stars, 76);
+    assert_eq!(stats.total_commits_ytd, 120);
+    assert_eq!(stats.total_prs, 30);
+    assert_eq!(stats.total_merged_prs, 25);
+    assert_eq!(stats.total_reviews, 7);
+    assert_eq!(stats.total_issues, 10);
+    assert_eq!(stats.total_discussions_started, 1);
+    assert_eq!(stats.total_discussions_answered, 3);
+
+    // The second page was asked for after the cursor of the first one
+    let pages = transport.variables("GetUserRepos");
+    assert_eq!(pages.len(), 1);
+    assert_eq!(pages[0]["login"], "mock-stats");
+    assert_eq!(pages[0]["after"], "page-2");
+}
+
+#[tokio::test]
+async fn test_fetch_user_languages_follows_the_repository_pages() {
+    let transport = Arc::new(
+        MockTransport::new()
+            .respond(
+                "GetUserLanguages",
+                language_repositories(
+                    &[
+                        ("api", &[("Rust", 3000), ("Shell", 100)]),
+                        ("web", &[("TypeScript", 2000)]),
+                    ],
+                    Some("page-2"),
+                ),
+            )
+            .respond(
+                "GetUserLanguages",
+                language_repositories(
+                    &[("cli", &[("Rust", 1000)]), ("old", &[("Perl", 500)])],
+                    None,
+                ),
+            ),
+    );
+    let api = mocked_api(&transport);
+
+    let mut languages = api
+        .fetch_user_languages("mock-languages", &["old".to_string()])
+        .await
+        .unwrap();
+    languages.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let summary: Vec<(&str, usize, u64)> = languages
+        .iter()
+        .map(|stat| (stat.name.as_str(), stat.size_bytes, stat.repo_count))
+        .collect();
+    assert_eq!(
+        summary,
+        [
+            ("Rust", 4000, 2),
+            ("Shell", 100, 1),
+            ("TypeScript", 2000, 1)
+        ]
+    );
+
+    let cursors: Vec<Value> = transport
+        .variables("GetUserLanguages")
+        .into_iter()
+        .map(|variables| variables["after"].clone())
+        .collect();
+    assert_eq!(cursors, [Value::Null, json!("page-2")]);
+}
+
+#[tokio::test]
+async fn test_fetch_user_stats_of_an_organization() {
+    let transport = Arc::new(
+        MockTransport::new()
+            .respond("GetUserStats", json!({ "data": { "user": null } }))
+            .respond(
+                "GetOrganization",
+                json!({ "data": { "organization": { "login": "mock-org" } } }),
+            ),
+    );
+
+    let result = mocked_api(&transport).fetch_user_stats("mock-org").await;
+    assert!(matches!(result, Err(GitHubApiError::IsOrganization(login)) if login == "mock-org"));
+}
+
+#[tokio::test]
+async fn test_fetch_user_stats_retries_server_errors() {
+    let transport = Arc::new(
+        MockTransport::new()
+            .respond_with_status("GetUserStats", 502, json!({ "message": "Bad Gateway" }))
+            .respond(
+                "GetUserStats",
+                user_stats("mock-retried", starred_repositories(&[3], None)),
+            ),
+    );
+
+    let stats = mocked_api(&transport)
+        .fetch_user_stats("mock-retried")
+        .await
+        .unwrap();
+    assert_eq!(stats.total_stars, 3);
+    assert_eq!(transport.requests().len(), 2);
+}
+
+#[tokio::test]
+async fn test_fetch_avatar_color_downloads_the_avatar() {
+    let url = "https://avatars.githubusercontent.com/u/1?s=64&v=4";
+    let mut avatar = resvg::tiny_skia::Pixmap::new(8, 8).unwrap();
+    avatar.fill(resvg::tiny_skia::Color::from_rgba8(0x2f, 0x81, 0xf7, 255));
+    let avatar_url = json!({ "data": { "user": { "avatarUrl": url } } });
+    let transport = Arc::new(
+        MockTransport::new()
+            .respond("GetUserAvatar", avatar_url.clone())
+            .respond("GetUserAvatar", avatar_url)
+            .serve(url, avatar.encode_png().unwrap()),
+    );
+    let api = mocked_api(&transport);
+
+    let color = api.fetch_cached_avatar_color("mock-avatar").await.unwrap();
+    assert_eq!(color.value.as_deref(), Some("#2f81f7"));
+
+    // The color fetched with a request token is never cached
+    let color = api.fetch_cached_avatar_color("mock-avatar").await.unwrap();
+    assert_eq!(color.value.as_deref(), Some("#2f81f7"));
+    assert_eq!(transport.downloads(), [url, url]);
+}
+
+#[tokio::test]
+async fn test_request_tokens_never_fill_the_shared_cache() {
+    let transport = Arc::new(
+        MockTransport::new()
+            .respond(
+                "GetUserStats",
+                user_stats("mock-private", starred_repositories(&[42], None)),
+            )
+            .respond(
+                "GetUserLanguages",
+                language_repositories(&[("secret", &[("Rust", 100)])], None),
+            ),
+    );
+    let api = mocked_api(&transport);
+
+    let stats = api
+        .fetch_cached_user_stats("mock-private", Default::default())
+        .await
+        .unwrap();
+    assert_eq!(stats.value.total_stars, 42);
+    let languages = api
+        .fetch_cached_user_languages("mock-private", &[], Default::default())
+        .await
+        .unwrap();
+    assert_eq!(languages.value[0].name, "Rust");
+
+    // The shared entries are still empty, so their fetch runs
+    let cache = get_github_cache();
+    let stats = cache
+        .get_or_insert_user_stats("mock-private".to_string(), Default::default(), || async {
+            Err(GitHubApiError::UserNotFound)
+        })
+        .await;
+    assert!(matches!(stats, Err(GitHubApiError::UserNotFound)));
+    let languages = cache
+        .get_or_insert_user_languages(
+            "mock-private".to_string(),
+            &[],
+            Default::default(),
+            || async { Err(GitHubApiError::UserNotFound) },
+        )
+        .await;
+    assert!(matches!(languages, Err(GitHubApiError::UserNotFound)));
+}
+
+#[tokio::test]
+async fn test_fetch_avatar_color_ignores_other_hosts() {
+    let url = "https://example.com/avatar.png";
+    let transport = Arc::new(MockTransport::new().respond(
+        "GetUserAvatar",
+        json!({ "data": { "user": { "avatarUrl": url } } }),
+    ));
+
+    let color = mocked_api(&transport)
+        .fetch_cached_avatar_color("mock-avatar-elsewhere")
+        .await
+        .unwrap();
+    assert_eq!(color.value, None);
+    assert!(transport.downloads().is_empty());
+}
+
+#[tokio::test]
+async fn test_fetch_contribution_calendar_flattens_the_weeks() {
+    let week = |days: &[(&str, u32)]| {
+        let days: Vec<Value> = days
+            .iter()
+            .map(|(date, count)| json!({ "date": date, "contributionCount": count }))
+            .collect();
+        json!({ "contributionDays": days })
+    };
+    let transport = Arc::new(MockTransport::new().respond(
+        "GetContributionCalendar",
+        json!({ "data": { "user": { "contributionsCollection": { "contributionCalendar": {
+            "weeks": [
+                week(&[("2024-01-06", 0)]),
+                week(&[("2024-01-07", 2), ("2024-01-08", 1)])
+            ]
+        } } } } }),
+    ));
+
+    let days = mocked_api(&transport)
+        .fetch_contribution_calendar("mock-calendar")
+        .await
+        .unwrap();
+
+    let summary: Vec<(&str, u32)> = days
+        .iter()
+        .map(|day| (day.date.as_str(), day.contribution_count))
+        .collect();
+    assert_eq!(
+        summary,
+        [("2024-01-06", 0), ("2024-01-07", 2), ("2024-01-08", 1)]
+    );
+    assert_eq!(
+        transport.variables("GetContributionCalendar")[0]["login"],
+        "mock-calendar"
+    );
+}
+
+#[tokio::test]
+async fn test_rate_limit_protection_waits_for_the_reset_by_the_client_clock() {
+    let reset = (NOW + 60).to_string();
+    let transport = Arc::new(
+        MockTransport::new()
+            .respond_with_headers(
+                "GetUserStats",
+                200,
+                &[
+                    ("x-ratelimit-remaining", "10"),
+                    ("x-ratelimit-reset", &reset),
+                ],
+                user_stats("mock-clock", starred_repositories(&[1], None)),
+            )
+            .respond(
+                "GetUserStats",
+                user_stats("mock-clock", starred_repositories(&[1], None)),
+            ),
+    );
+    // A token of its own, the rate limit state is shared by the clients of a token
+    let api = |now: u64| {
+        GitHubApi::with_token(GitHubToken::new("mock-clock-token".to_string()))
+            .with_transport(transport.clone())
+            .with_clock(Arc::new(FixedClock(now)))
+    };
+
+    api(NOW).fetch_user_stats("mock-clock").await.unwrap();
+    assert!(matches!(
+        api(NOW).fetch_user_stats("mock-clock").await,
+        Err(GitHubApiError::RateLimitProtection(10, _))
+    ));
+    assert_eq!(transport.requests().len(), 1);
+
+    // Past the reset
+    api(NOW + 60).fetch_user_stats("mock-clock").await.unwrap();
+    assert_eq!(transport.requests().len(), 2);
+}
+
+#[tokio::test]
+async fn test_fetch_user_stats_counts_the_commits_of_the_clock_year() {
+    let transport = Arc::new(MockTransport::new().respond(
+        "GetUserStats",
+        user_stats("mock-year", starred_repositories(&[1], None)),
+    ));
+
+    let stats = mocked_api(&transport)
+        .with_clock(Arc::new(FixedClock(NOW)))
+        .fetch_user_stats("mock-year")
+        .await
+        .unwrap();
+    assert_eq!(stats.commits_year, Some(2024));
+    let query = transport.requests()[0]["query"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    assert!(query.contains(r#"from: "2024-01-01T00:00:00Z""#), "{query}");
+}
+
+#[tokio::test]
+async fn test_private_commits_of_the_owner_are_the_commit_contributions() {
+    let mut response = user_stats("mock-owner", starred_repositories(&[1], None));
+    response["data"]["viewer"] = json!({ "login": "mock-owner" });
+    // Restricted contributions of every type, hidden from other viewers only
+    response["data"]["user"]["contributionsCollection"]["restrictedContributionsCount"] = json!(40);
+    let transport = Arc::new(MockTransport::new().respond("GetUserStats", response));
+
+    let options = StatsOptions {
+        include_private: true,
+        ..Default::default()
+    };
+    let stats = mocked_api(&transport)
+        .fetch_user_stats_with_options("mock-owner", options)
+        .await
+        .unwrap();
+    assert_eq!(stats.total_commits_ytd, 120);
+}
+
+#[tokio::test]
+async fn test_secondary_rate_limit_is_not_retried() {
+    let transport = Arc::new(
+        MockTransport::new()
+            .respond_with_headers(
+                "GetUserStats",
+                403,
+                &[("retry-after", "45")],
+                json!({
+                    "message": "You have exceeded a secondary rate limit.",
+                    "documentation_url": "https://docs.github.com/graphql/overview/rate-limits-and-node-limits-for-the-graphql-api#secondary-rate-limits"
+                }),
+            )
+            .respond(
+                "GetUserStats",
+                user_stats("mock-secondary", starred_repositories(&[1], None)),
+            ),
+    );
+
+    let result = mocked_api(&transport)
+        .fetch_user_stats("mock-secondary")
+        .await;
+    assert!(matches!(
+        result,
+        Err(GitHubApiError::SecondaryRateLimit(45))
+    ));
+    assert_eq!(transport.requests().len(), 1);
+}
+
+#[tokio::test]
+async fn test_fetch_lifetime_commits_sums_every_year() {
+    let year_commits = |commits: u32| {
+        json!({ "data": { "user": { "contributionsCollection": {
+            "totalCommitContributions": commits
+        } } } })
+    };
+    let mut transport = MockTransport::new().respond(
+        "GetUserCreatedAt",
+        json!({ "data": { "user": { "createdAt": "2022-03-01T00:00:00Z" } } }),
+    );
+    for commits in [100, 20, 3] {
+        transport = transport.respond("GetYearCommits", year_commits(commits));
+    }
+    let transport = Arc::new(transport);
+    let api = mocked_api(&transport).with_clock(Arc::new(FixedClock(NOW)));
+
+    let commits = api
+        .fetch_lifetime_commits("mock-lifetime", false)
+        .await
+        .unwrap();
+    assert_eq!(commits, 123);
+
+    // A query per calendar year, from the year the account was created
+    let years: Vec<(Value, Value)> = transport
+        .variables("GetYearCommits")
+        .into_iter()
+        .map(|variables| (variables["from"].clone(), variables["to"].clone()))
+        .collect();
+    let expected: Vec<(Value, Value)> = (2022..=2024)
+        .map(|year| {
+            (
+                json!(format!("{year}-01-01T00:00:00Z")),
+                json!(format!("{year}-12-31T23:59:59Z")),
+            )
+        })
+        .collect();
+    assert_eq!(years, expected);
+    assert_eq!(transport.requests().len(), 4);
+}
+
+/// The per-client request budget of [GitHubApi], retries included
+const REQUEST_BUDGET: usize = 50;
+
+/// Answers the first page of stats of `login` and `pages - 1` more repository pages,
+/// each one preceded by `failures_per_page` 503s
+fn paginated_stats(login: &str, pages: usize, failures_per_page: usize) -> MockTransport {
+    let mut transport = MockTransport::new().respond(
+        "GetUserStats",
+        user_stats(login, starred_repositories(&[1], Some("page-1"))),
+    );
+    for page in 1..pages {
+        for _ in 0..failures_per_page {
+            transport = transport.respond_with_status(
+                "GetUserRepos",
+                503,
+                json!({ "message": "Service Unavailable" }),
+            );
+        }
+        let end_cursor = (page + 1 < pages).then(|| format!("page-{}", page + 1));
+        transport = transport.respond(
+            "GetUserRepos",
+            json!({ "data": { "user": {
+                "repositories": starred_repositories(&[1], end_cursor.as_deref())
+            } } }),
+        );
+    }
+    transport
+}
+
+#[tokio::test]
+async fn test_fetch_user_stats_stops_paginating_once_the_request_budget_is_spent() {
+    let transport = Arc::new(paginated_stats("mock-many-pages", 60, 0));
+
+    let result = mocked_api(&transport)
+        .fetch_user_stats("mock-many-pages")
+        .await;
+
+    assert!(matches!(result, Err(GitHubApiError::RequestBudgetExceeded)));
+    assert_eq!(transport.requests().len(), REQUEST_BUDGET);
+}
+
+#[tokio::test]
+async fn test_retried_server_errors_consume_the_request_budget() {
+    let transport = Arc::new(paginated_stats("mock-flaky-pages", 60, 1));
+
+    let result = mocked_api(&transport)
+        .fetch_user_stats("mock-flaky-pages")
+        .await;
+
+    // Every page costs a 503 and its retry, the 503 of the page reached with the last
+    // request of the budget can't be retried
+    assert!(matches!(result, Err(GitHubApiError::ServerError(503))));
+    assert_eq!(transport.requests().len(), REQUEST_BUDGET);
+}
@@ -189,7 +189,7 @@ async fn test_invalid_hide_value() {
     let username = common::get_test_username();
     let req = Request::builder()
         .uri(format!(
-            "/stats-card?username={}&hide=invalid_stat",
+            "/stats-card?username={}&hide=invalid_stat&on_error=json",
             username
         ))
         .body(Body::empty())